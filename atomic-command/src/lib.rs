@@ -20,7 +20,11 @@
 //! This library contains the definition for an atomic command. This is used by `atomic_bgp`, as
 //! well as `bgpsim_web` with the feature `atomic_bgp`.
 
-use std::{collections::BTreeSet, iter::once};
+use std::{
+    collections::BTreeSet,
+    iter::once,
+    time::{Duration, Instant},
+};
 
 use bgpsim::{
     bgp::BgpRibEntry,
@@ -34,6 +38,12 @@ use itertools::Itertools;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "serde")]
+pub mod test_util;
+
+#[cfg(test)]
+mod test;
+
 /// Atomic command, along with its pre and postconditions.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -145,7 +155,14 @@ impl<P: Prefix> From<AtomicModifier<P>> for Vec<ConfigModifier<P>> {
 }
 
 impl<P: Prefix> AtomicModifier<P> {
-    /// Get the router(s) that are affected by the modifier.
+    /// Get the router(s) that are affected by the modifier, in the order in which they must be
+    /// configured when the command is applied on each router separately (as in the lab).
+    ///
+    /// For [`AtomicModifier::AddTempSession`], the `neighbor` is configured before `router`. The
+    /// session can only be established once both sides are configured, so configuring `router`
+    /// last ensures that `router` (whose routing depends on the session) is the one that brings
+    /// it up. For [`AtomicModifier::RemoveTempSession`], the order is reversed, such that `router`
+    /// tears down the session before `neighbor` does.
     pub fn routers(&self) -> Vec<RouterId> {
         match self {
             AtomicModifier::Raw(raw) => raw.routers(),
@@ -155,8 +172,8 @@ impl<P: Prefix> AtomicModifier<P> {
             | AtomicModifier::IgnoreTempSession { router, .. } => vec![*router],
             AtomicModifier::AddTempSession {
                 router, neighbor, ..
-            }
-            | AtomicModifier::RemoveTempSession {
+            } => vec![*neighbor, *router],
+            AtomicModifier::RemoveTempSession {
                 router, neighbor, ..
             } => vec![*router, *neighbor],
         }
@@ -286,6 +303,9 @@ pub enum AtomicCondition<P: Prefix> {
         weight: Option<u32>,
         /// The selected route has a next hop via x
         next_hop: Option<RouterId>,
+        /// The selected route carries the given originator-id. If `None`, then the originator-id
+        /// is ignored.
+        originator_id: Option<RouterId>,
     },
     /// Condition on the availability of a given route. It implies that there exists at least one
     /// route that is from either one of the given neighbors. If `neighbors` is  `None`, then it
@@ -303,6 +323,16 @@ pub enum AtomicCondition<P: Prefix> {
         /// The selected route has a next hop via x
         next_hop: Option<RouterId>,
     },
+    /// Condition on all known routes for a given prefix. It asserts that every route the router
+    /// has learned for this prefix satisfies `cond`. Routes for other prefixes are ignored.
+    AllRoutesMatch {
+        /// Which router should be checked
+        router: RouterId,
+        /// Which prefix should be checked
+        prefix: P,
+        /// Condition that all routes for this prefix must satisfy.
+        cond: RibCond<P>,
+    },
     /// The BGP session with a given neighbor is established.
     BgpSessionEstablished {
         /// Which router should be checked
@@ -323,6 +353,15 @@ pub enum AtomicCondition<P: Prefix> {
         /// preferred than this one.
         route: BgpRibEntry<P>,
     },
+    /// The inner condition must hold continuously for the given duration. In the simulation, this
+    /// is equivalent to the inner condition, as the network is checked only once it has
+    /// converged. In the lab, use a [`StabilityTracker`] to require sustained truth.
+    Stable {
+        /// The condition that must be stable.
+        inner: Box<AtomicCondition<P>>,
+        /// How long the inner condition must hold without interruption.
+        for_duration: Duration,
+    },
 }
 
 impl<P: Prefix> AtomicCondition<P> {
@@ -335,6 +374,38 @@ impl<P: Prefix> AtomicCondition<P> {
     pub fn check<Q>(&self, net: &Network<P, Q>) -> Result<bool, NetworkError> {
         AtomicConditionExt::from(self.clone()).check(net)
     }
+
+    /// Get the condition without any stability requirement, i.e., the innermost condition of
+    /// nested [`AtomicCondition::Stable`].
+    pub fn without_stability(&self) -> &Self {
+        match self {
+            AtomicCondition::Stable { inner, .. } => inner.without_stability(),
+            c => c,
+        }
+    }
+
+    /// Create a [`StabilityTracker`] if the condition must hold for some duration. Nested
+    /// [`AtomicCondition::Stable`] are flattened, i.e., the innermost condition must hold for the
+    /// longest of all durations.
+    pub fn stability_tracker(&self) -> Option<StabilityTracker> {
+        self.stable_duration().map(StabilityTracker::new)
+    }
+
+    /// Get the longest duration of all nested [`AtomicCondition::Stable`], or `None` if the
+    /// condition has no stability requirement.
+    fn stable_duration(&self) -> Option<Duration> {
+        match self {
+            AtomicCondition::Stable {
+                inner,
+                for_duration,
+            } => Some(
+                inner
+                    .stable_duration()
+                    .map_or(*for_duration, |d| d.max(*for_duration)),
+            ),
+            _ => None,
+        }
+    }
 }
 
 impl<P: Prefix> From<AtomicCondition<P>> for AtomicConditionExt<P> {
@@ -347,6 +418,7 @@ impl<P: Prefix> From<AtomicCondition<P>> for AtomicConditionExt<P> {
                 neighbor,
                 weight,
                 next_hop,
+                originator_id,
             } => AtomicConditionExt::CurrentRib {
                 router,
                 prefix,
@@ -357,6 +429,7 @@ impl<P: Prefix> From<AtomicCondition<P>> for AtomicConditionExt<P> {
                         .chain(once(RibCond::Prefix(prefix)))
                         .chain(weight.iter().map(|x| RibCond::Weight(*x)))
                         .chain(next_hop.iter().map(|x| RibCond::NextHop(*x)))
+                        .chain(originator_id.iter().map(|x| RibCond::OriginatorId(*x)))
                         .collect(),
                 )),
             },
@@ -378,6 +451,14 @@ impl<P: Prefix> From<AtomicCondition<P>> for AtomicConditionExt<P> {
                         .collect(),
                 ),
             },
+            AtomicCondition::AllRoutesMatch {
+                router,
+                prefix,
+                cond,
+            } => AtomicConditionExt::AllKnownRoutes {
+                router,
+                cond: RibCond::Or(vec![RibCond::Not(Box::new(RibCond::Prefix(prefix))), cond]),
+            },
             AtomicCondition::BgpSessionEstablished { router, neighbor } => {
                 AtomicConditionExt::BgpSessionEstablished { router, neighbor }
             }
@@ -392,6 +473,7 @@ impl<P: Prefix> From<AtomicCondition<P>> for AtomicConditionExt<P> {
                 good_neighbors,
                 route,
             },
+            AtomicCondition::Stable { inner, .. } => AtomicConditionExt::from(*inner),
         }
     }
 }
@@ -408,6 +490,7 @@ impl<'a, 'n, P: Prefix, Q> NetworkFormatter<'a, 'n, P, Q> for AtomicCondition<P>
                 neighbor,
                 weight,
                 next_hop,
+                originator_id,
             } => {
                 let from = neighbor
                     .as_ref()
@@ -419,8 +502,11 @@ impl<'a, 'n, P: Prefix, Q> NetworkFormatter<'a, 'n, P, Q> for AtomicCondition<P>
                 let nh = next_hop
                     .map(|x| format!(" via {}", x.fmt(net)))
                     .unwrap_or_default();
+                let originator = originator_id
+                    .map(|x| format!(" originated by {}", x.fmt(net)))
+                    .unwrap_or_default();
                 format!(
-                    "{} selects route for {prefix}{from}{nh}{weight}",
+                    "{} selects route for {prefix}{from}{nh}{weight}{originator}",
                     router.fmt(net)
                 )
             }
@@ -446,6 +532,15 @@ impl<'a, 'n, P: Prefix, Q> NetworkFormatter<'a, 'n, P, Q> for AtomicCondition<P>
                     router.fmt(net)
                 )
             }
+            AtomicCondition::AllRoutesMatch {
+                router,
+                prefix,
+                cond,
+            } => format!(
+                "All routes at {} for {prefix} match {}",
+                router.fmt(net),
+                cond.fmt(net)
+            ),
             AtomicCondition::BgpSessionEstablished { router, neighbor } => {
                 format!(
                     "BGP session berween {} and {} is established.",
@@ -465,6 +560,43 @@ impl<'a, 'n, P: Prefix, Q> NetworkFormatter<'a, 'n, P, Q> for AtomicCondition<P>
                     good_neighbors.iter().map(|n| n.fmt(net)).join(" and ")
                 )
             }
+            AtomicCondition::Stable {
+                inner,
+                for_duration,
+            } => format!("{} (stable for {for_duration:?})", inner.fmt(net)),
+        }
+    }
+}
+
+/// Tracks whether a condition has held continuously for a given duration. Every time the
+/// condition is polled, call [`StabilityTracker::update`] with its current value. The timer is
+/// reset as soon as the condition is violated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StabilityTracker {
+    /// How long the condition must hold without interruption.
+    for_duration: Duration,
+    /// Since when the condition holds, or `None` if it was violated at the last update.
+    since: Option<Instant>,
+}
+
+impl StabilityTracker {
+    /// Create a new tracker that requires the condition to hold for `for_duration`.
+    pub fn new(for_duration: Duration) -> Self {
+        Self {
+            for_duration,
+            since: None,
+        }
+    }
+
+    /// Update the tracker with the value of the condition at time `now`. Returns `true` if the
+    /// condition has held continuously for at least the required duration.
+    pub fn update(&mut self, holds: bool, now: Instant) -> bool {
+        if holds {
+            let since = *self.since.get_or_insert(now);
+            now.saturating_duration_since(since) >= self.for_duration
+        } else {
+            self.since = None;
+            false
         }
     }
 }
@@ -610,6 +742,8 @@ pub enum RibCond<P: Prefix> {
     Or(Vec<RibCond<P>>),
     /// Check the prefix
     Prefix(P),
+    /// Check that the prefix is equal to, or an aggregate covering the given prefix.
+    PrefixCovers(P),
     /// Check the learned-from attribute
     LearnedFrom(RouterId),
     /// Check that a specific community is set
@@ -618,6 +752,10 @@ pub enum RibCond<P: Prefix> {
     Weight(u32),
     /// Check that the route has the given next-hop
     NextHop(RouterId),
+    /// Check that the route carries the given originator-id
+    OriginatorId(RouterId),
+    /// Check that the cluster list of the route has at most the given length
+    ClusterListLenLe(usize),
 }
 
 impl<P: Prefix> RibCond<P> {
@@ -628,10 +766,13 @@ impl<P: Prefix> RibCond<P> {
             RibCond::And(cs) => cs.iter().all(|c| c.check(rib)),
             RibCond::Or(cs) => cs.iter().any(|c| c.check(rib)),
             RibCond::Prefix(p) => rib.route.prefix == *p,
+            RibCond::PrefixCovers(p) => rib.route.prefix.contains(p),
             RibCond::LearnedFrom(r) => rib.from_id == *r,
             RibCond::CommunityContains(c) => rib.route.community.contains(c),
             RibCond::Weight(w) => rib.weight == *w,
             RibCond::NextHop(nh) => rib.route.next_hop == *nh,
+            RibCond::OriginatorId(r) => rib.route.originator_id == Some(*r),
+            RibCond::ClusterListLenLe(l) => rib.route.cluster_list.len() <= *l,
         }
     }
 }
@@ -695,10 +836,13 @@ impl<'a, 'n, P: Prefix, Q> NetworkFormatter<'a, 'n, P, Q> for RibCond<P> {
             RibCond::And(cs) => format!("({})", cs.iter().map(|x| x.fmt(net)).join(" && ")),
             RibCond::Or(cs) => format!("({})", cs.iter().map(|x| x.fmt(net)).join(" || ")),
             RibCond::Prefix(p) => p.to_string(),
+            RibCond::PrefixCovers(p) => format!("covers {p}"),
             RibCond::LearnedFrom(r) => format!("from {}", r.fmt(net)),
             RibCond::CommunityContains(c) => format!("Community {c}"),
             RibCond::Weight(w) => format!("Weight {w}"),
             RibCond::NextHop(x) => format!("nh {}", x.fmt(net)),
+            RibCond::OriginatorId(r) => format!("originator {}", r.fmt(net)),
+            RibCond::ClusterListLenLe(l) => format!("cluster-list length <= {l}"),
         }
     }
 }
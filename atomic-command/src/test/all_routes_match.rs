@@ -0,0 +1,105 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the condition that all known routes for a prefix must match.
+
+use bgpsim::{
+    bgp::BgpSessionType,
+    event::BasicEventQueue,
+    network::Network,
+    types::{AsId, RouterId, SimplePrefix},
+};
+
+use crate::{AtomicCondition, RibCond};
+
+/// # Test network
+///
+/// ```text
+/// E1 ---- R0 ---- E2
+/// ```
+///
+/// Both external routers advertise prefix 0, and only `E2` advertises prefix 1.
+fn get_test_net() -> (
+    Network<SimplePrefix, BasicEventQueue<SimplePrefix>>,
+    RouterId,
+    RouterId,
+    RouterId,
+) {
+    let mut net = Network::default();
+    let r0 = net.add_router("R0");
+    let e1 = net.add_external_router("E1", AsId(65101));
+    let e2 = net.add_external_router("E2", AsId(65102));
+    net.add_link(r0, e1);
+    net.add_link(r0, e2);
+    for e in [e1, e2] {
+        net.set_link_weight(r0, e, 1.0).unwrap();
+        net.set_link_weight(e, r0, 1.0).unwrap();
+        net.set_bgp_session(r0, e, Some(BgpSessionType::EBgp))
+            .unwrap();
+    }
+    net.advertise_external_route(e1, 0, [65101], None, [])
+        .unwrap();
+    net.advertise_external_route(e2, 0, [65102], None, [])
+        .unwrap();
+    net.advertise_external_route(e2, 1, [65102], None, [])
+        .unwrap();
+    (net, r0, e1, e2)
+}
+
+#[test]
+fn all_routes_match() {
+    let (mut net, r0, e1, e2) = get_test_net();
+    net.retract_external_route(e2, 0).unwrap();
+
+    // all routes for prefix 0 are from E1. The route for prefix 1 from E2 must be ignored.
+    let cond = AtomicCondition::AllRoutesMatch {
+        router: r0,
+        prefix: 0.into(),
+        cond: RibCond::NextHop(e1),
+    };
+    assert!(cond.check(&net).unwrap());
+
+    let cond = AtomicCondition::AllRoutesMatch {
+        router: r0,
+        prefix: 1.into(),
+        cond: RibCond::NextHop(e2),
+    };
+    assert!(cond.check(&net).unwrap());
+}
+
+#[test]
+fn all_routes_match_one_mismatch() {
+    let (net, r0, e1, e2) = get_test_net();
+
+    // R0 knows a route for prefix 0 from both E1 and E2.
+    for nh in [e1, e2] {
+        let cond = AtomicCondition::AllRoutesMatch {
+            router: r0,
+            prefix: 0.into(),
+            cond: RibCond::NextHop(nh),
+        };
+        assert!(!cond.check(&net).unwrap());
+    }
+
+    // a condition that holds for both routes is satisfied.
+    let cond = AtomicCondition::AllRoutesMatch {
+        router: r0,
+        prefix: 0.into(),
+        cond: RibCond::Or(vec![RibCond::NextHop(e1), RibCond::NextHop(e2)]),
+    };
+    assert!(cond.check(&net).unwrap());
+}
@@ -0,0 +1,58 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the order in which atomic modifiers are applied on the individual routers.
+
+use bgpsim::{
+    bgp::BgpSessionType,
+    config::{ConfigExpr, ConfigModifier},
+    types::{RouterId, SimplePrefix},
+};
+
+use crate::AtomicModifier;
+
+fn session(router: RouterId, neighbor: RouterId) -> ConfigModifier<SimplePrefix> {
+    ConfigModifier::Insert(ConfigExpr::BgpSession {
+        source: neighbor,
+        target: router,
+        session_type: BgpSessionType::IBgpClient,
+    })
+}
+
+#[test]
+fn add_temp_session_configures_neighbor_first() {
+    let router: RouterId = 0.into();
+    let neighbor: RouterId = 1.into();
+    let cmd = AtomicModifier::AddTempSession {
+        router,
+        neighbor,
+        raw: vec![session(router, neighbor)],
+    };
+    assert_eq!(cmd.routers(), vec![neighbor, router]);
+}
+
+#[test]
+fn remove_temp_session_configures_router_first() {
+    let router: RouterId = 0.into();
+    let neighbor: RouterId = 1.into();
+    let cmd = AtomicModifier::RemoveTempSession {
+        router,
+        neighbor,
+        raw: vec![session(router, neighbor).reverse()],
+    };
+    assert_eq!(cmd.routers(), vec![router, neighbor]);
+}
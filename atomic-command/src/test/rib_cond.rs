@@ -0,0 +1,107 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the evaluation of conditions on RIB entries.
+
+use bgpsim::{
+    bgp::{BgpRibEntry, BgpRoute, BgpSessionType},
+    types::{AsId, Ipv4Prefix, RouterId},
+};
+
+use crate::RibCond;
+
+fn rib_entry(prefix: Ipv4Prefix) -> BgpRibEntry<Ipv4Prefix> {
+    let r: RouterId = 1.into();
+    BgpRibEntry {
+        route: BgpRoute::new(r, prefix, [AsId(100)], None, []),
+        from_type: BgpSessionType::EBgp,
+        from_id: r,
+        to_id: None,
+        igp_cost: None,
+        weight: 100,
+    }
+}
+
+#[test]
+fn prefix_covers_aggregate() {
+    let aggregate: Ipv4Prefix = "10.0.0.0/8".parse().unwrap();
+    let specific: Ipv4Prefix = "10.1.0.0/16".parse().unwrap();
+    let other: Ipv4Prefix = "11.1.0.0/16".parse().unwrap();
+
+    // the router selects the aggregate instead of the specific prefix.
+    let rib = rib_entry(aggregate);
+    assert!(!RibCond::Prefix(specific).check(&rib));
+    assert!(RibCond::PrefixCovers(specific).check(&rib));
+    assert!(RibCond::PrefixCovers(aggregate).check(&rib));
+    assert!(!RibCond::PrefixCovers(other).check(&rib));
+
+    // a more-specific route does not cover the aggregate.
+    let rib = rib_entry(specific);
+    assert!(RibCond::PrefixCovers(specific).check(&rib));
+    assert!(!RibCond::PrefixCovers(aggregate).check(&rib));
+}
+
+#[test]
+fn originator_id_of_reflected_route() {
+    let prefix: Ipv4Prefix = "10.0.0.0/8".parse().unwrap();
+    let originator: RouterId = 1.into();
+    let reflector: RouterId = 2.into();
+    let other: RouterId = 3.into();
+
+    // a route learned directly from the originator carries no originator-id.
+    let rib = rib_entry(prefix);
+    assert!(!RibCond::OriginatorId(originator).check(&rib));
+
+    // the route reflector sets the originator-id when reflecting the route.
+    let mut rib = rib_entry(prefix);
+    rib.route.originator_id = Some(originator);
+    rib.route.cluster_list = vec![reflector];
+    rib.from_type = BgpSessionType::IBgpPeer;
+    rib.from_id = reflector;
+    assert!(RibCond::OriginatorId(originator).check(&rib));
+    assert!(!RibCond::OriginatorId(reflector).check(&rib));
+    assert!(!RibCond::OriginatorId(other).check(&rib));
+    assert!(RibCond::And(vec![
+        RibCond::LearnedFrom(reflector),
+        RibCond::OriginatorId(originator)
+    ])
+    .check(&rib));
+}
+
+#[test]
+fn cluster_list_len_bound() {
+    let prefix: Ipv4Prefix = "10.0.0.0/8".parse().unwrap();
+
+    // a route that was not reflected has an empty cluster list.
+    let rib = rib_entry(prefix);
+    assert!(RibCond::ClusterListLenLe(0).check(&rib));
+    assert!(RibCond::ClusterListLenLe(1).check(&rib));
+
+    // a route that was reflected once.
+    let mut rib = rib_entry(prefix);
+    rib.route.cluster_list = vec![2.into()];
+    assert!(!RibCond::ClusterListLenLe(0).check(&rib));
+    assert!(RibCond::ClusterListLenLe(1).check(&rib));
+    assert!(RibCond::ClusterListLenLe(2).check(&rib));
+
+    // a route that was reflected three times.
+    let mut rib = rib_entry(prefix);
+    rib.route.cluster_list = vec![2.into(), 3.into(), 4.into()];
+    assert!(!RibCond::ClusterListLenLe(1).check(&rib));
+    assert!(!RibCond::ClusterListLenLe(2).check(&rib));
+    assert!(RibCond::ClusterListLenLe(3).check(&rib));
+}
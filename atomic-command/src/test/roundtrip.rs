@@ -0,0 +1,167 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test that all atomic commands and conditions survive a serde round-trip, for all prefix types.
+
+use std::{collections::BTreeSet, time::Duration};
+
+use bgpsim::{
+    bgp::{BgpRibEntry, BgpRoute, BgpSessionType},
+    config::{ConfigExpr, ConfigModifier},
+    types::{AsId, Ipv4Prefix, Prefix, RouterId, SimplePrefix, SinglePrefix},
+};
+
+use crate::{test_util::assert_roundtrip, AtomicCommand, AtomicCondition, AtomicModifier, RibCond};
+
+fn roundtrip_all<P: Prefix>() {
+    let r0: RouterId = 0.into();
+    let r1: RouterId = 1.into();
+    let p = P::from(0);
+
+    let raw = ConfigModifier::Insert(ConfigExpr::BgpSession {
+        source: r0,
+        target: r1,
+        session_type: BgpSessionType::IBgpPeer,
+    });
+
+    let modifiers = vec![
+        AtomicModifier::Raw(raw.clone()),
+        AtomicModifier::UseTempSession {
+            router: r0,
+            neighbor: r1,
+            prefix: p,
+            raw: raw.clone(),
+        },
+        AtomicModifier::IgnoreTempSession {
+            router: r0,
+            neighbor: r1,
+            prefix: p,
+            raw: raw.clone(),
+        },
+        AtomicModifier::AddTempSession {
+            router: r0,
+            neighbor: r1,
+            raw: vec![raw.clone()],
+        },
+        AtomicModifier::RemoveTempSession {
+            router: r0,
+            neighbor: r1,
+            raw: vec![raw.clone()],
+        },
+        AtomicModifier::ChangePreference {
+            router: r0,
+            prefix: p,
+            neighbor: r1,
+            raw: vec![raw.clone()],
+        },
+        AtomicModifier::ClearPreference {
+            router: r0,
+            prefix: p,
+            raw: vec![raw],
+        },
+    ];
+
+    let conditions = vec![
+        AtomicCondition::None,
+        AtomicCondition::SelectedRoute {
+            router: r0,
+            prefix: p,
+            neighbor: Some(r1),
+            weight: Some(100),
+            next_hop: None,
+            originator_id: Some(r1),
+        },
+        AtomicCondition::AvailableRoute {
+            router: r0,
+            prefix: p,
+            neighbor: None,
+            weight: None,
+            next_hop: Some(r1),
+        },
+        AtomicCondition::AllRoutesMatch {
+            router: r0,
+            prefix: p,
+            cond: RibCond::NextHop(r1),
+        },
+        AtomicCondition::Stable {
+            inner: Box::new(AtomicCondition::BgpSessionEstablished {
+                router: r0,
+                neighbor: r1,
+            }),
+            for_duration: Duration::from_millis(1500),
+        },
+        AtomicCondition::BgpSessionEstablished {
+            router: r0,
+            neighbor: r1,
+        },
+        AtomicCondition::RoutesLessPreferred {
+            router: r0,
+            prefix: p,
+            good_neighbors: BTreeSet::from([r1]),
+            route: BgpRibEntry {
+                route: BgpRoute::new(r1, p, [AsId(100), AsId(200)], Some(10), [1, 2]),
+                from_type: BgpSessionType::EBgp,
+                from_id: r1,
+                to_id: None,
+                igp_cost: None,
+                weight: 100,
+            },
+        },
+    ];
+
+    for modifier in modifiers.iter() {
+        assert_roundtrip(modifier);
+    }
+    for condition in conditions.iter() {
+        assert_roundtrip(condition);
+    }
+    for (command, condition) in modifiers.into_iter().zip(conditions) {
+        assert_roundtrip(&AtomicCommand {
+            command,
+            precondition: condition.clone(),
+            postcondition: condition,
+        });
+    }
+
+    assert_roundtrip(&RibCond::Not(Box::new(RibCond::And(vec![
+        RibCond::Prefix(p),
+        RibCond::PrefixCovers(p),
+        RibCond::LearnedFrom(r1),
+        RibCond::Or(vec![
+            RibCond::CommunityContains(10),
+            RibCond::Weight(100),
+            RibCond::NextHop(r0),
+            RibCond::OriginatorId(r1),
+            RibCond::ClusterListLenLe(2),
+        ]),
+    ]))));
+}
+
+#[test]
+fn roundtrip_single_prefix() {
+    roundtrip_all::<SinglePrefix>();
+}
+
+#[test]
+fn roundtrip_simple_prefix() {
+    roundtrip_all::<SimplePrefix>();
+}
+
+#[test]
+fn roundtrip_ipv4_prefix() {
+    roundtrip_all::<Ipv4Prefix>();
+}
@@ -0,0 +1,120 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the stability requirement of conditions, while the simulated network flaps.
+
+use std::time::{Duration, Instant};
+
+use bgpsim::{
+    bgp::BgpSessionType,
+    event::BasicEventQueue,
+    network::Network,
+    types::{AsId, SimplePrefix},
+};
+
+use crate::{AtomicCondition, StabilityTracker};
+
+#[test]
+fn stable_requires_sustained_truth() {
+    let mut net: Network<SimplePrefix, BasicEventQueue<SimplePrefix>> = Network::default();
+    let r0 = net.add_router("R0");
+    let e1 = net.add_external_router("E1", AsId(65101));
+    net.add_link(r0, e1);
+    net.set_link_weight(r0, e1, 1.0).unwrap();
+    net.set_link_weight(e1, r0, 1.0).unwrap();
+    net.set_bgp_session(r0, e1, Some(BgpSessionType::EBgp))
+        .unwrap();
+
+    let cond = AtomicCondition::Stable {
+        inner: Box::new(AtomicCondition::SelectedRoute {
+            router: r0,
+            prefix: 0.into(),
+            neighbor: Some(e1),
+            weight: None,
+            next_hop: None,
+            originator_id: None,
+        }),
+        for_duration: Duration::from_secs(10),
+    };
+    let mut tracker = cond.stability_tracker().unwrap();
+    let t0 = Instant::now();
+    let t = |secs: u64| t0 + Duration::from_secs(secs);
+
+    // the inner condition is not yet satisfied.
+    assert!(!cond.check(&net).unwrap());
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(0)));
+
+    // the inner condition holds, but not yet for long enough.
+    net.advertise_external_route(e1, 0, [65101], None, [])
+        .unwrap();
+    assert!(cond.check(&net).unwrap());
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(1)));
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(6)));
+
+    // the route flaps, which resets the timer.
+    net.retract_external_route(e1, 0).unwrap();
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(7)));
+    net.advertise_external_route(e1, 0, [65101], None, [])
+        .unwrap();
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(8)));
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(12)));
+    assert!(!tracker.update(cond.check(&net).unwrap(), t(17)));
+
+    // only after 10 seconds of sustained truth is the condition stable.
+    assert!(tracker.update(cond.check(&net).unwrap(), t(18)));
+    assert!(tracker.update(cond.check(&net).unwrap(), t(20)));
+}
+
+#[test]
+fn stability_tracker_without_requirement() {
+    let cond: AtomicCondition<SimplePrefix> = AtomicCondition::None;
+    assert!(cond.stability_tracker().is_none());
+    assert_eq!(cond.without_stability(), &cond);
+
+    let stable = AtomicCondition::Stable {
+        inner: Box::new(cond.clone()),
+        for_duration: Duration::ZERO,
+    };
+    assert_eq!(stable.without_stability(), &cond);
+
+    // a zero duration is satisfied as soon as the condition holds.
+    let mut tracker = StabilityTracker::new(Duration::ZERO);
+    assert!(!tracker.update(false, Instant::now()));
+    assert!(tracker.update(true, Instant::now()));
+}
+
+#[test]
+fn stability_tracker_nested() {
+    let cond: AtomicCondition<SimplePrefix> = AtomicCondition::None;
+    let nested = |outer: u64, inner: u64| AtomicCondition::Stable {
+        inner: Box::new(AtomicCondition::Stable {
+            inner: Box::new(cond.clone()),
+            for_duration: Duration::from_secs(inner),
+        }),
+        for_duration: Duration::from_secs(outer),
+    };
+
+    // the innermost condition must hold for the longest duration, no matter where it is nested.
+    for (outer, inner) in [(5, 10), (10, 5)] {
+        let stable = nested(outer, inner);
+        assert_eq!(
+            stable.stability_tracker(),
+            Some(StabilityTracker::new(Duration::from_secs(10)))
+        );
+        assert_eq!(stable.without_stability(), &cond);
+    }
+}
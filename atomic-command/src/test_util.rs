@@ -0,0 +1,39 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Utilities to test the (de-)serialization of atomic commands and conditions.
+
+use std::fmt::Debug;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Assert that `value` does not change when serializing it to JSON and deserializing it again.
+///
+/// # Panics
+/// Panics if either serialization or deserialization fails, or if the deserialized value differs
+/// from `value`.
+pub fn assert_roundtrip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let json = serde_json::to_string(value).expect("Cannot serialize the value");
+    let parsed: T = serde_json::from_str(&json).expect("Cannot deserialize the value");
+    assert_eq!(
+        &parsed, value,
+        "Value changed during the round-trip: {json}"
+    );
+}
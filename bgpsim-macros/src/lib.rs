@@ -37,6 +37,10 @@ use syn::parse_macro_input;
 ///   in both directions. However, you can also set the link weight in the opposite direction by
 ///   writing `DST -> SRC: WEIGHT`.
 ///
+/// - `areas`: An enumeration of OSPF areas of links (optional). Each entry is written as `SRC -> DST:
+///   AREA`, where `SRC -> DST` is a link (in any direction), and `AREA` is the number of the OSPF
+///   area. Links that are not mentioned remain in the backbone area (area 0).
+///
 /// - `sessions`: An enumeration of all BGP sessions in the network. Each session is written as `SRC
 ///   -> DST[: TYPE]`, where both `SRC` and `DST` are identifiers of a node. The `TYPE` is optiona,
 ///   and can be omitted. If the type is omitted, then it will be a `BgpSessionType::IBgpPeer` for
@@ -47,6 +51,11 @@ use syn::parse_macro_input;
 ///   - `peer`, which maps to `BgpSessionType::IBgpPeer`,
 ///   - `client`, which maps to `BgpSessionType::IBgpClient`.
 ///
+///   For `client` sessions, you can additionally set the cluster ID of the route reflector (`SRC`)
+///   by writing `SRC -> DST: client, cluster: N`, where `N` is a number. This expands to
+///   `Network::set_bgp_cluster_id`. All `client` sessions of the same route reflector must use the
+///   same cluster ID.
+///
 ///   This macro will **automatically add links between nodes for external sessions** if they are
 ///   not already defined in `links`.
 ///
@@ -81,6 +90,12 @@ use syn::parse_macro_input;
 /// where `AS_ID` is a literal number. In that case, this node will be trned into an external router
 /// that uses the given AS number. You only need to annotate an external router once!
 ///
+/// # Node names
+/// Instead of an identifier, a node can also be written as a string literal, e.g., `"New York" ->
+/// "Houston": 1`. The string is used as the name of the router, and it may contain characters
+/// that are not valid in Rust identifiers. Such nodes can also be returned by writing the string
+/// literal in the `return` tuple.
+///
 /// # Example
 /// ```rust, ignore
 /// use bgpsim::prelude::*;
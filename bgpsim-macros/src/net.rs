@@ -38,8 +38,11 @@ pub(crate) struct Net {
     queue_ty: Option<Type>,
     queue: Option<Expr>,
     nodes: HashMap<Ident, Option<(u32, Span)>>,
+    names: HashMap<Ident, String>,
     links: HashMap<(Ident, Ident), (f64, Span)>,
+    areas: HashMap<(Ident, Ident), (u32, Span)>,
     sessions: HashMap<(Ident, Ident), Option<Ident>>,
+    clusters: HashMap<Ident, (u32, Span)>,
     routes: Vec<Route<Ident>>,
     returns: Option<Returns>,
 }
@@ -52,8 +55,11 @@ impl Parse for Net {
             queue_ty: Default::default(),
             queue: Default::default(),
             nodes: Default::default(),
+            names: Default::default(),
             links: Default::default(),
+            areas: Default::default(),
             sessions: Default::default(),
+            clusters: Default::default(),
             routes: Default::default(),
             returns: Default::default(),
         };
@@ -65,6 +71,10 @@ impl Parse for Net {
                     let _: Token![=] = input.parse()?;
                     net.parse_links(input)?;
                 }
+                "areas" => {
+                    let _: Token![=] = input.parse()?;
+                    net.parse_areas(input)?;
+                }
                 "sessions" => {
                     let _: Token![=] = input.parse()?;
                     net.parse_sessions(input)?;
@@ -122,7 +132,7 @@ impl Parse for Net {
                 _ => return Err(Error::new(
                     block.span(),
                     format!(
-                        "Unexpected identifier! Expected `links`, `sessions`, `routes`, `queue`, `Queue`, `Prefix`, `Type`, or `return`, but found `{block}`"
+                        "Unexpected identifier! Expected `links`, `areas`, `sessions`, `routes`, `queue`, `Queue`, `Prefix`, `Type`, or `return`, but found `{block}`"
                     )
                 )),
             }
@@ -133,6 +143,7 @@ impl Parse for Net {
 
         net.check_input()?;
         net.add_external_links();
+        net.check_areas()?;
 
         Ok(net)
     }
@@ -144,7 +155,11 @@ impl Net {
             .nodes
             .iter()
             .map(|(ident, ext)| {
-                let router_name = ident.to_string();
+                let router_name = self
+                    .names
+                    .get(ident)
+                    .cloned()
+                    .unwrap_or_else(|| ident.to_string());
                 if let Some((as_id, _)) = ext.as_ref() {
                     quote! { let #ident = _net.add_external_router(#router_name, #as_id); }
                 } else {
@@ -178,6 +193,16 @@ impl Net {
             })
             .collect::<Vec<_>>();
 
+        let areas = self
+            .areas
+            .iter()
+            .map(|((src, dst), (area, _))| {
+                quote! {
+                    _net.set_ospf_area(#src, #dst, #area).unwrap();
+                }
+            })
+            .collect::<Vec<_>>();
+
         let sessions = self
             .sessions
             .iter()
@@ -202,6 +227,16 @@ impl Net {
             })
             .collect::<Vec<_>>();
 
+        let clusters = self
+            .clusters
+            .iter()
+            .map(|(rr, (cluster_id, _))| {
+                quote! {
+                    _net.set_bgp_cluster_id(#rr, Some(::bgpsim::types::RouterId::from(#cluster_id))).unwrap();
+                }
+            })
+            .collect::<Vec<_>>();
+
         let routes = self
             .routes
             .iter()
@@ -253,6 +288,8 @@ impl Net {
                 let mut _net: #ty = ::bgpsim::prelude::Network::new(#queue);
                 #(#nodes)*
                 #(#links)*
+                #(#areas)*
+                #(#clusters)*
                 #(#sessions)*
                 #(#routes)*
                 #returns
@@ -295,13 +332,58 @@ impl Net {
         Ok(())
     }
 
+    fn parse_areas(&mut self, input: ParseStream) -> Result<()> {
+        // must start with a paren.
+        let areas;
+        braced!(areas in input);
+        let areas: Punctuated<Area, Token![;]> = areas.parse_terminated(Area::parse)?;
+
+        for Area {
+            src,
+            dst,
+            area,
+            area_span,
+        } in areas.into_iter()
+        {
+            let src = self.register_node(src)?;
+            let dst = self.register_node(dst)?;
+            let key = if self.areas.contains_key(&(dst.clone(), src.clone())) {
+                (dst, src)
+            } else {
+                (src, dst)
+            };
+            match self.areas.entry(key) {
+                Entry::Occupied(e) if e.get().0 == area => {}
+                Entry::Occupied(e) => {
+                    let mut err = Error::new(
+                        area_span,
+                        "The same link was assigned to a different OSPF area earlier!",
+                    );
+                    err.combine(Error::new(e.get().1, "The OSPF area was assigned here"));
+                    return Err(err);
+                }
+                Entry::Vacant(e) => {
+                    e.insert((area, area_span));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn parse_sessions(&mut self, input: ParseStream) -> Result<()> {
         // must start with a paren.
         let sessions;
         braced!(sessions in input);
         let sessions: Punctuated<_, Token![;]> = sessions.parse_terminated(BgpSession::parse)?;
 
-        for BgpSession { src, dst, ty } in sessions.into_iter() {
+        for BgpSession {
+            src,
+            dst,
+            ty,
+            cluster,
+        } in sessions.into_iter()
+        {
             let src = self.register_node(src)?;
             let dst = self.register_node(dst)?;
             if let Some(((a, b), c)) = self
@@ -325,6 +407,31 @@ impl Net {
                 ));
                 return Err(err);
             }
+            if let Some((cluster_id, cluster_span)) = cluster {
+                if !matches!(SessionType::try_from(&ty)?, SessionType::IBgpClient) {
+                    return Err(Error::new(
+                        cluster_span,
+                        "A cluster ID can only be set on `client` sessions!",
+                    ));
+                }
+                match self.clusters.entry(src.clone()) {
+                    Entry::Occupied(e) if e.get().0 == cluster_id => {}
+                    Entry::Occupied(e) => {
+                        let mut err = Error::new(
+                            cluster_span,
+                            "Declared a different cluster ID for the same route reflector!",
+                        );
+                        err.combine(Error::new(
+                            e.get().1,
+                            "The cluster ID was originally defined here.",
+                        ));
+                        return Err(err);
+                    }
+                    Entry::Vacant(e) => {
+                        e.insert((cluster_id, cluster_span));
+                    }
+                }
+            }
             self.sessions.insert((src, dst), ty);
         }
 
@@ -359,6 +466,9 @@ impl Net {
     }
 
     fn register_node(&mut self, node: Node) -> Result<Ident> {
+        if let Some(name) = node.name {
+            self.names.insert(node.ident.clone(), name);
+        }
         let entry = self.nodes.entry(node.ident.clone());
         if let Some((as_id, span)) = node.ext {
             match entry {
@@ -440,6 +550,21 @@ impl Net {
         Ok(())
     }
 
+    fn check_areas(&self) -> Result<()> {
+        if let Some(((src, dst), (_, span))) = self.areas.iter().find(|((src, dst), _)| {
+            !(self.links.contains_key(&(src.clone(), dst.clone()))
+                || self.links.contains_key(&(dst.clone(), src.clone())))
+        }) {
+            return Err(Error::new(
+                *span,
+                format!(
+                    "Cannot set the OSPF area of the link `{src} -> {dst}` that does not exist!"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     fn add_external_links(&mut self) {
         let external_sessions = self
             .sessions
@@ -470,6 +595,7 @@ struct BgpSession {
     src: Node,
     dst: Node,
     ty: Option<Ident>,
+    cluster: Option<(u32, Span)>,
 }
 
 impl Parse for BgpSession {
@@ -483,7 +609,27 @@ impl Parse for BgpSession {
         } else {
             None
         };
-        Ok(BgpSession { src, dst, ty })
+        let cluster = if ty.is_some() && input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            let key: Ident = input.parse()?;
+            if key != "cluster" {
+                return Err(Error::new(
+                    key.span(),
+                    format!("Unexpected identifier! Expected `cluster`, but found `{key}`"),
+                ));
+            }
+            let _: Token![:] = input.parse()?;
+            let elem: LitInt = input.parse()?;
+            Some((elem.base10_parse()?, elem.span()))
+        } else {
+            None
+        };
+        Ok(BgpSession {
+            src,
+            dst,
+            ty,
+            cluster,
+        })
     }
 }
 
@@ -519,6 +665,29 @@ impl Parse for Link {
     }
 }
 
+struct Area {
+    src: Node,
+    dst: Node,
+    area: u32,
+    area_span: Span,
+}
+
+impl Parse for Area {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let src: Node = input.parse()?;
+        let _: Token![->] = input.parse()?;
+        let dst: Node = input.parse()?;
+        let _: Token![:] = input.parse()?;
+        let elem: LitInt = input.parse()?;
+        Ok(Area {
+            src,
+            dst,
+            area: elem.base10_parse()?,
+            area_span: elem.span(),
+        })
+    }
+}
+
 enum MaybeExpr<T> {
     Expr(Expr),
     Other(T),
@@ -676,12 +845,18 @@ impl Parse for Prefix {
 
 struct Node {
     ident: Ident,
+    name: Option<String>,
     ext: Option<(u32, Span)>,
 }
 
 impl Parse for Node {
     fn parse(input: ParseStream) -> Result<Self> {
-        let ident: Ident = input.parse()?;
+        let (ident, name) = if input.peek(LitStr) {
+            let name: LitStr = input.parse()?;
+            (name_ident(&name), Some(name.value()))
+        } else {
+            (input.parse()?, None)
+        };
         if input.peek(Token![!]) {
             let _: Token![!] = input.parse()?;
             let paren_parser;
@@ -689,12 +864,32 @@ impl Parse for Node {
             let as_id: LitInt = paren_parser.parse()?;
             Ok(Self {
                 ident,
+                name,
                 ext: Some((as_id.base10_parse()?, as_id.span())),
             })
         } else {
-            Ok(Self { ident, ext: None })
+            Ok(Self {
+                ident,
+                name,
+                ext: None,
+            })
+        }
+    }
+}
+
+/// Generate the identifier of a node that is referred to by a string literal. All characters
+/// that are not alphanumeric are escaped, such that different names result in different
+/// identifiers.
+fn name_ident(name: &LitStr) -> Ident {
+    let mut ident = String::from("_node_");
+    for c in name.value().chars() {
+        if c.is_ascii_alphanumeric() {
+            ident.push(c);
+        } else {
+            ident.push_str(&format!("_{:x}_", c as u32));
         }
     }
+    Ident::new(&ident, name.span())
 }
 
 enum Returns {
@@ -718,6 +913,10 @@ impl Returns {
                 }
                 Ok(Returns::Node(p.segments.into_iter().next().unwrap().ident))
             }
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(name),
+                ..
+            }) => Ok(Returns::Node(name_ident(&name))),
             Expr::Tuple(t) => t
                 .elems
                 .into_iter()
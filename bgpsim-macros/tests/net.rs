@@ -0,0 +1,140 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use bgpsim::{ospf::OspfArea, prelude::*, types::RouterId};
+use bgpsim_macros::*;
+
+#[test]
+fn cluster_id() {
+    let (net, (r0, r1, b1)) = net! {
+        Prefix = SimplePrefix;
+        links = {
+            b0 -> r0: 1;
+            r0 -> b1: 1;
+            r0 -> r1: 1;
+        };
+        sessions = {
+            b0 -> e0!(1);
+            r0 -> b0: client, cluster: 42;
+            r0 -> b1: client, cluster: 42;
+            r0 -> r1: peer;
+        };
+        routes = {
+            e0 -> "10.0.0.0/8" as {path: [1, 2]};
+        };
+        return (r0, r1, b1)
+    };
+
+    let p: SimplePrefix = prefix!("10.0.0.0/8" as);
+    let r0 = net.get_device(r0).unwrap_internal();
+    let r1 = net.get_device(r1).unwrap_internal();
+    assert_eq!(r0.get_cluster_id(), RouterId::from(42));
+    assert_eq!(r1.get_cluster_id(), r1.router_id());
+
+    // the route reflected by r0 to b1 carries the cluster ID.
+    let entry = net
+        .get_device(b1)
+        .unwrap_internal()
+        .get_selected_bgp_route(p)
+        .unwrap();
+    assert_eq!(entry.route.cluster_list, vec![RouterId::from(42)]);
+}
+
+#[test]
+fn ospf_areas() {
+    let (net, (r0, r1, r2, r3)) = net! {
+        Prefix = SimplePrefix;
+        links = {
+            r0 -> r1: 1;
+            r1 -> r2: 1;
+            r2 -> r3: 1;
+        };
+        areas = {
+            r1 -> r2: 1;
+            r3 -> r2: 1;
+        };
+        return (r0, r1, r2, r3)
+    };
+
+    assert_eq!(net.get_ospf_area(r0, r1).unwrap(), OspfArea::BACKBONE);
+    assert_eq!(net.get_ospf_area(r1, r2).unwrap(), OspfArea::from(1u32));
+    assert_eq!(net.get_ospf_area(r2, r1).unwrap(), OspfArea::from(1u32));
+    assert_eq!(net.get_ospf_area(r2, r3).unwrap(), OspfArea::from(1u32));
+}
+
+#[test]
+fn quoted_names() {
+    let (net, (ny, hs, ext)) = net! {
+        Prefix = SimplePrefix;
+        links = {
+            "New York" -> "Houston": 1;
+            "Houston" -> "Los Angeles": 2;
+        };
+        sessions = {
+            "Houston" -> "External Router"!(100);
+            "New York" -> "Houston": client;
+            "New York" -> "Los Angeles": client;
+        };
+        return ("New York", "Houston", "External Router")
+    };
+
+    assert_eq!(net.get_router_name(ny).unwrap(), "New York");
+    assert_eq!(net.get_router_name(hs).unwrap(), "Houston");
+    assert!(net.get_router_id("Los Angeles").is_ok());
+    assert!(net.get_device(ext).is_external());
+    assert_eq!(net.get_router_name(ext).unwrap(), "External Router");
+    assert_eq!(
+        net.get_device(ny)
+            .unwrap_internal()
+            .get_bgp_session_type(hs),
+        Some(BgpSessionType::IBgpClient)
+    );
+}
+
+#[test]
+fn cluster_list_loop() {
+    let (net, (r0, r1, b0)) = net! {
+        Prefix = SimplePrefix;
+        links = {
+            b0 -> r0: 1;
+            b0 -> r1: 1;
+            r0 -> r1: 1;
+        };
+        sessions = {
+            b0 -> e0!(1);
+            r0 -> b0: client, cluster: 42;
+            r1 -> b0: client, cluster: 42;
+            r0 -> r1: peer;
+        };
+        routes = {
+            e0 -> "10.0.0.0/8" as {path: [1, 2]};
+        };
+        return (r0, r1, b0)
+    };
+
+    let p: SimplePrefix = prefix!("10.0.0.0/8" as);
+
+    // both route reflectors belong to the same cluster. Hence, they drop the routes reflected by
+    // each other, as their CLUSTER_LIST already contains the cluster ID.
+    for (rr, other) in [(r0, r1), (r1, r0)] {
+        let rr = net.get_device(rr).unwrap_internal();
+        let rib_in = rr.get_bgp_rib_in().get(&p).unwrap();
+        assert!(rib_in.contains_key(&b0));
+        assert!(!rib_in.contains_key(&other));
+        assert_eq!(rr.get_selected_bgp_route(p).unwrap().from_id, b0);
+    }
+}
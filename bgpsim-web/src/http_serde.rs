@@ -167,6 +167,12 @@ pub fn export_json_str(compact: bool) -> String {
 }
 
 fn interpret_json_str(s: &str) -> Result<(Net, Settings), String> {
+    // A bundle exported by Chameleon contains the network, the spec, and both the atomic and the
+    // naive migration. Import the network with the atomic migration.
+    if let Some(s) = unbundle_json_str(s) {
+        return interpret_json_str(&s);
+    }
+
     // first, try to deserialize the network. If that works, ignore the config
     let net = Network::from_json_str(s, Queue::default).map_err(|x| x.to_string())?;
     let content: Value =
@@ -243,6 +249,21 @@ fn interpret_json_str(s: &str) -> Result<(Net, Settings), String> {
     Ok((imported_net, settings))
 }
 
+/// If the json string is a bundle (with the keys `net`, `spec`, `atomic` and `naive`), then
+/// transform it into a regular network export that uses the `atomic` migration.
+fn unbundle_json_str(s: &str) -> Option<String> {
+    let mut content: Value = serde_json::from_str(s).ok()?;
+    let bundle = content.as_object_mut()?;
+    if !(bundle.contains_key("atomic") && bundle.contains_key("naive")) {
+        return None;
+    }
+    let mut net = bundle.remove("net")?;
+    let obj = net.as_object_mut()?;
+    obj.insert("spec".to_string(), bundle.remove("spec")?);
+    obj.insert("migration".to_string(), bundle.remove("atomic")?);
+    serde_json::to_string(&net).ok()
+}
+
 fn rand_uniform() -> f64 {
     let mut bytes = [0, 0, 0, 0];
     getrandom(&mut bytes).unwrap();
@@ -24,7 +24,17 @@ use crate::types::{AsId, LinkWeight, Prefix, RouterId};
 
 use ordered_float::NotNan;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, collections::BTreeSet, hash::Hash};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+    net::Ipv4Addr,
+};
+
+/// Effective BGP router-IDs of routers in the network, used for the final tie-breaks of the BGP
+/// decision process. Routers without an entry use their [`RouterId`] (see
+/// [`crate::network::Network::set_bgp_router_id`]).
+pub type BgpRouterIds = HashMap<RouterId, Ipv4Addr>;
 
 /// Bgp Route
 /// The following attributes are omitted
@@ -284,75 +294,147 @@ impl<P: Prefix> PartialEq for BgpRibEntry<P> {
 
 impl<P: Prefix> PartialOrd for BgpRibEntry<P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.compare(other).0)
+    }
+}
+
+impl<P: Prefix> BgpRibEntry<P> {
+    /// Explain the comparison between `self` and `other` by returning the step of the BGP decision
+    /// process that decided which of the two routes is preferred, using the effective `router_ids`
+    /// of the network (see [`crate::network::Network::get_bgp_router_ids`]). Use
+    /// [`BgpRibEntry::compare_with_router_ids`] to figure out which of the two routes is preferred.
+    /// If both routes are equally preferred, [`PreferenceReason::Equal`] is returned.
+    pub fn explain_preference(&self, other: &Self, router_ids: &BgpRouterIds) -> PreferenceReason {
+        self.compare_with_router_ids(other, router_ids).1
+    }
+
+    /// Compare `self` and `other` according to the BGP decision process, using the effective
+    /// `router_ids` for the final tie-breaks (next-hop, router-id, and neighbor ID), and return the
+    /// step that decided the comparison. Routers with a configured router-ID are compared by that
+    /// ID, and are preferred over all routers without one. Routers without a configured router-ID
+    /// are compared by their [`RouterId`]. Without any configured router-ID, this is equal to
+    /// [`Ord::cmp`].
+    pub fn compare_with_router_ids(
+        &self,
+        other: &Self,
+        router_ids: &BgpRouterIds,
+    ) -> (Ordering, PreferenceReason) {
+        if router_ids.is_empty() {
+            return self.compare(other);
+        }
+        self.compare_with(other, |r| match router_ids.get(&r) {
+            Some(id) => (false, u32::from(*id)),
+            None => (true, r.index() as u32),
+        })
+    }
+
+    /// Compare `self` and `other` according to the BGP decision process, and return the step that
+    /// decided the comparison.
+    fn compare(&self, other: &Self) -> (Ordering, PreferenceReason) {
+        self.compare_with(other, |r| r)
+    }
+
+    /// Compare `self` and `other` according to the BGP decision process, and return the step that
+    /// decided the comparison. The final tie-breaks (next-hop, router-id, and neighbor ID) compare
+    /// the key returned by `router_id` instead of the [`RouterId`] itself, where lower keys are
+    /// preferred.
+    fn compare_with<F, K>(&self, other: &Self, router_id: F) -> (Ordering, PreferenceReason)
+    where
+        F: Fn(RouterId) -> K,
+        K: Ord,
+    {
         let s = self.route.clone_default();
         let o = other.route.clone_default();
 
         match self.weight.cmp(&other.weight) {
             Ordering::Equal => {}
-            o => return Some(o),
+            o => return (o, PreferenceReason::Weight),
         }
 
         match s.local_pref.unwrap().cmp(&o.local_pref.unwrap()) {
             Ordering::Equal => {}
-            o => return Some(o),
+            o => return (o, PreferenceReason::LocalPref),
         }
 
         match s.as_path.len().cmp(&o.as_path.len()) {
             Ordering::Equal => {}
-            Ordering::Greater => return Some(Ordering::Less),
-            Ordering::Less => return Some(Ordering::Greater),
+            o => return (o.reverse(), PreferenceReason::AsPathLength),
         }
 
         if s.as_path.first() == o.as_path.first() {
             match s.med.unwrap().cmp(&o.med.unwrap()) {
                 Ordering::Equal => {}
-                Ordering::Greater => return Some(Ordering::Less),
-                Ordering::Less => return Some(Ordering::Greater),
+                o => return (o.reverse(), PreferenceReason::Med),
             }
         }
 
         if self.from_type.is_ebgp() && other.from_type.is_ibgp() {
-            return Some(Ordering::Greater);
+            return (Ordering::Greater, PreferenceReason::SessionType);
         } else if self.from_type.is_ibgp() && other.from_type.is_ebgp() {
-            return Some(Ordering::Less);
+            return (Ordering::Less, PreferenceReason::SessionType);
         }
 
         match self.igp_cost.unwrap().partial_cmp(&other.igp_cost.unwrap()) {
             Some(Ordering::Equal) | None => {}
-            Some(Ordering::Greater) => return Some(Ordering::Less),
-            Some(Ordering::Less) => return Some(Ordering::Greater),
+            Some(o) => return (o.reverse(), PreferenceReason::IgpCost),
         }
 
-        match s.next_hop.cmp(&o.next_hop) {
+        match router_id(s.next_hop).cmp(&router_id(o.next_hop)) {
             Ordering::Equal => {}
-            Ordering::Greater => return Some(Ordering::Less),
-            Ordering::Less => return Some(Ordering::Greater),
+            o => return (o.reverse(), PreferenceReason::NextHop),
         }
 
         let s_from = s.originator_id.unwrap_or(self.from_id);
         let o_from = o.originator_id.unwrap_or(other.from_id);
-        match s_from.cmp(&o_from) {
+        match router_id(s_from).cmp(&router_id(o_from)) {
             Ordering::Equal => {}
-            Ordering::Greater => return Some(Ordering::Less),
-            Ordering::Less => return Some(Ordering::Greater),
+            o => return (o.reverse(), PreferenceReason::RouterId),
         }
 
         match s.cluster_list.len().cmp(&o.cluster_list.len()) {
             Ordering::Equal => {}
-            Ordering::Greater => return Some(Ordering::Less),
-            Ordering::Less => return Some(Ordering::Greater),
+            o => return (o.reverse(), PreferenceReason::ClusterListLength),
         }
 
-        match self.from_id.cmp(&other.from_id) {
+        match router_id(self.from_id).cmp(&router_id(other.from_id)) {
             Ordering::Equal => {}
-            Ordering::Greater => return Some(Ordering::Less),
-            Ordering::Less => return Some(Ordering::Greater),
+            o => return (o.reverse(), PreferenceReason::NeighborId),
         }
 
-        Some(Ordering::Equal)
+        (Ordering::Equal, PreferenceReason::Equal)
     }
 }
 
+/// The step of the BGP decision process that decided which of two routes is preferred. See
+/// [`BgpRibEntry::explain_preference`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PreferenceReason {
+    /// The route with the higher local weight is preferred.
+    Weight,
+    /// The route with the higher local preference is preferred.
+    LocalPref,
+    /// The route with the shorter AS path is preferred.
+    AsPathLength,
+    /// The route with the lower MED is preferred (only compared if both routes are learned from
+    /// the same neighboring AS).
+    Med,
+    /// Routes learned via eBGP are preferred over routes learned via iBGP.
+    SessionType,
+    /// The route with the lower IGP cost towards the next-hop is preferred.
+    IgpCost,
+    /// The route with the lower next-hop is preferred.
+    NextHop,
+    /// The route with the lower router-id (originator-id, or the neighbor's ID if no
+    /// originator-id is set) is preferred.
+    RouterId,
+    /// The route with the shorter cluster list is preferred.
+    ClusterListLength,
+    /// The route learned from the neighbor with the lower ID is preferred.
+    NeighborId,
+    /// Both routes are equally preferred.
+    Equal,
+}
+
 impl<P: Prefix> PartialEq<Option<&BgpRibEntry<P>>> for BgpRibEntry<P> {
     fn eq(&self, other: &Option<&BgpRibEntry<P>>) -> bool {
         match other {
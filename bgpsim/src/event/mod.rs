@@ -26,7 +26,7 @@ pub use queue::{BasicEventQueue, EventQueue, FmtPriority};
 #[cfg(feature = "rand_queue")]
 mod rand_queue;
 #[cfg(feature = "rand_queue")]
-pub use rand_queue::{GeoTimingModel, ModelParams, SimpleTimingModel};
+pub use rand_queue::{GeoTimingModel, MeasuredTimingModel, ModelParams, SimpleTimingModel};
 
 use crate::{
     bgp::BgpEvent,
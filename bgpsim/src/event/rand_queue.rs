@@ -33,6 +33,7 @@ use std::{
     cmp::Reverse,
     collections::{HashMap, HashSet},
     iter::zip,
+    time::Duration,
 };
 
 use super::{Event, EventQueue};
@@ -158,6 +159,130 @@ impl<P: Prefix> PartialEq for SimpleTimingModel<P> {
     }
 }
 
+/// Deterministic timing model based on measured delays. This timing model uses seconds as time
+/// unit.
+///
+/// A BGP message from `a` to `b` is processed exactly after the delay given for the pair `(a, b)`.
+/// If no delay is given for that pair, then the `default_delay` is used. This allows the
+/// convergence in the simulation to match a specific measurement, e.g., from the lab. Messages of
+/// the same session are always processed in the order in which they were sent. If two messages
+/// of the same session would be processed at the same time, the later one is delayed by 1us.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "P: for<'a> serde::Deserialize<'a>"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand_queue")))]
+pub struct MeasuredTimingModel<P: Prefix> {
+    q: PriorityQueue<Event<P, NotNan<f64>>, Reverse<NotNan<f64>>>,
+    messages: HashMap<(RouterId, RouterId), (usize, NotNan<f64>)>,
+    delays: HashMap<(RouterId, RouterId), Duration>,
+    default_delay: Duration,
+    current_time: NotNan<f64>,
+}
+
+impl<P: Prefix> MeasuredTimingModel<P> {
+    /// Create a new, empty model queue with the given delays for each pair of routers.
+    pub fn new(delays: HashMap<(RouterId, RouterId), Duration>, default_delay: Duration) -> Self {
+        Self {
+            q: PriorityQueue::new(),
+            messages: HashMap::new(),
+            delays,
+            default_delay,
+            current_time: NotNan::default(),
+        }
+    }
+
+    /// Set the delay of messages from `src` to `dst`.
+    pub fn set_delay(&mut self, src: RouterId, dst: RouterId, delay: Duration) {
+        self.delays.insert((src, dst), delay);
+    }
+}
+
+impl<P: Prefix> EventQueue<P> for MeasuredTimingModel<P> {
+    type Priority = NotNan<f64>;
+
+    fn push(
+        &mut self,
+        mut event: Event<P, Self::Priority>,
+        _routers: &HashMap<RouterId, Router<P>>,
+        _net: &IgpNetwork,
+    ) {
+        let mut next_time = self.current_time;
+        // match on the event
+        match event {
+            Event::Bgp(ref mut t, src, dst, _) => {
+                let key = (src, dst);
+                // compute the next time
+                let delay = self.delays.get(&key).unwrap_or(&self.default_delay);
+                next_time += NotNan::new(delay.as_secs_f64()).unwrap();
+                // check if there is already something enqueued for this session
+                if let Some((ref mut num, ref mut time)) = self.messages.get_mut(&key) {
+                    if *num > 0 && *time >= next_time {
+                        next_time = *time + NotNan::new(1e-6).unwrap();
+                    }
+                    *num += 1;
+                    *time = next_time;
+                } else {
+                    self.messages.insert(key, (1, next_time));
+                }
+                *t = next_time;
+            }
+        }
+        // enqueue with the computed time
+        self.q.push(event, Reverse(next_time));
+    }
+
+    fn pop(&mut self) -> Option<Event<P, Self::Priority>> {
+        let (event, _) = self.q.pop()?;
+        self.current_time = *event.priority();
+        match event {
+            Event::Bgp(_, src, dst, _) => {
+                if let Some((num, _)) = self.messages.get_mut(&(src, dst)) {
+                    *num -= 1;
+                }
+            }
+        }
+        Some(event)
+    }
+
+    fn peek(&self) -> Option<&Event<P, Self::Priority>> {
+        self.q.peek().map(|(e, _)| e)
+    }
+
+    fn len(&self) -> usize {
+        self.q.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.q.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.q.clear();
+        self.messages.clear();
+        self.current_time = NotNan::default();
+    }
+
+    fn get_time(&self) -> Option<f64> {
+        Some(self.current_time.into_inner())
+    }
+
+    fn update_params(&mut self, _: &HashMap<RouterId, Router<P>>, _: &IgpNetwork) {}
+
+    unsafe fn clone_events(&self, conquered: Self) -> Self {
+        MeasuredTimingModel {
+            q: self.q.clone(),
+            messages: self.messages.clone(),
+            current_time: self.current_time,
+            ..conquered
+        }
+    }
+}
+
+impl<P: Prefix> PartialEq for MeasuredTimingModel<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.q.iter().collect::<Vec<_>>() == other.q.iter().collect::<Vec<_>>()
+    }
+}
+
 /// Timing model based on geological information. This timing model uses seconds as time unit.
 ///
 /// The delay of a message from `a` to `b` is computed as follows: First, we compute the message's
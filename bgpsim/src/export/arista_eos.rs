@@ -0,0 +1,147 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! This module contains methods and functions for exporting configurations for Arista EOS.
+
+use crate::{
+    bgp::BgpRoute,
+    config::ConfigModifier,
+    network::Network,
+    ospf::OspfArea,
+    types::{Prefix, RouterId},
+};
+
+use super::{
+    cisco_frr_generators::Target, Addressor, CiscoFrrCfgGen, ExportError, ExternalCfgGen,
+    InternalCfgGen,
+};
+
+/// Configuration generator for Arista EOS. The generated configuration follows the same structure
+/// as the one of [`CiscoFrrCfgGen`], but uses the EOS syntax (see [`Target::AristaEos`]).
+#[derive(Debug)]
+pub struct AristaEosCfgGen<P: Prefix> {
+    inner: CiscoFrrCfgGen<P>,
+}
+
+impl<P: Prefix> AristaEosCfgGen<P> {
+    /// Create a new config generator for the specified router.
+    pub fn new<Q>(
+        net: &Network<P, Q>,
+        router: RouterId,
+        ifaces: Vec<String>,
+    ) -> Result<Self, ExportError> {
+        Ok(Self {
+            inner: CiscoFrrCfgGen::new(net, router, Target::AristaEos, ifaces)?,
+        })
+    }
+
+    /// Get the local OSPF area of the router. This is equal to the OSPF area with the lowest ID
+    /// which is adjacent to that router.
+    ///
+    /// *Warning*: This field is only computed after generating the configuration!.
+    pub fn local_area(&self) -> Option<OspfArea> {
+        self.inner.local_area()
+    }
+
+    /// Get the interface name at the given index
+    pub fn iface_name(&self, idx: usize) -> Result<&str, ExportError> {
+        self.inner.iface_name(idx)
+    }
+
+    /// Get the interface index given an interface name.
+    pub fn iface_idx(&self, name: impl AsRef<str>) -> Result<usize, ExportError> {
+        self.inner.iface_idx(name)
+    }
+
+    /// Set the MAC Address of a specific interface.
+    pub fn set_mac_address(&mut self, iface_name: impl AsRef<str>, mac_address: [u8; 6]) {
+        self.inner.set_mac_address(iface_name, mac_address)
+    }
+
+    /// Set the OSPF interval parameters on all routers. Both the `hello-interval` and
+    /// `dead-interval` are measured in seconds. By default, the `hello_interval = Some(1)` and
+    /// `dead_interval = Some(5)`.
+    pub fn set_ospf_parameters(&mut self, hello_interval: Option<u16>, dead_interval: Option<u16>) {
+        self.inner
+            .set_ospf_parameters(hello_interval, dead_interval)
+    }
+}
+
+impl<P: Prefix, A: Addressor<P>, Q> InternalCfgGen<P, Q, A> for AristaEosCfgGen<P> {
+    fn generate_config(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+    ) -> Result<String, ExportError> {
+        InternalCfgGen::generate_config(&mut self.inner, net, addressor)
+    }
+
+    fn generate_command(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+        cmd: ConfigModifier<P>,
+    ) -> Result<String, ExportError> {
+        self.inner.generate_command(net, addressor, cmd)
+    }
+}
+
+impl<P: Prefix, A: Addressor<P>, Q> ExternalCfgGen<P, Q, A> for AristaEosCfgGen<P> {
+    fn generate_config(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+    ) -> Result<String, ExportError> {
+        ExternalCfgGen::generate_config(&mut self.inner, net, addressor)
+    }
+
+    fn advertise_route(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+        route: &BgpRoute<P>,
+    ) -> Result<String, ExportError> {
+        self.inner.advertise_route(net, addressor, route)
+    }
+
+    fn withdraw_route(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+        prefix: P,
+    ) -> Result<String, ExportError> {
+        self.inner.withdraw_route(net, addressor, prefix)
+    }
+
+    fn establish_ebgp_session(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+        neighbor: RouterId,
+    ) -> Result<String, ExportError> {
+        self.inner.establish_ebgp_session(net, addressor, neighbor)
+    }
+
+    fn teardown_ebgp_session(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+        neighbor: RouterId,
+    ) -> Result<String, ExportError> {
+        self.inner.teardown_ebgp_session(net, addressor, neighbor)
+    }
+}
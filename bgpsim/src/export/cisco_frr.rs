@@ -45,9 +45,9 @@ use crate::{
 
 use super::{
     cisco_frr_generators::{
-        enable_bgp, enable_ospf, loopback_iface, AsPathList, CommunityList, Interface, PrefixList,
-        RouteMapItem, RouterBgp, RouterBgpNeighbor, RouterOspf, StaticRoute as StaticRouteGen,
-        Target,
+        enable_bfd, enable_bgp, enable_ospf, loopback_iface, AsPathList, Bfd, CommunityList,
+        Interface, PrefixList, RouteMapItem, RouterBgp, RouterBgpNeighbor, RouterOspf,
+        StaticRoute as StaticRouteGen, Target,
     },
     Addressor, ExportError, ExternalCfgGen, InternalCfgGen, INTERNAL_AS,
 };
@@ -55,6 +55,8 @@ use super::{
 /// constant for the internal AS number
 const EXTERNAL_RM_IN: &str = "neighbor-in";
 const EXTERNAL_RM_OUT: &str = "neighbor-out";
+/// Name of the BFD profile used by all BGP neighbors on FRR.
+const BFD_PROFILE: &str = "bgp";
 
 /// Configuration generator for Cisco IOS. This was tested on the nexus 7000 series.
 #[derive(Debug)]
@@ -72,6 +74,8 @@ pub struct CiscoFrrCfgGen<P: Prefix> {
     mac_addresses: HashMap<String, [u8; 6]>,
     /// OSPF parameters
     ospf_params: (Option<u16>, Option<u16>),
+    /// BFD parameters `(min_tx, min_rx, multiplier)`, or `None` if BFD is disabled.
+    bfd: Option<(u16, u16, u8)>,
     /// List of route map indices,
     route_maps: HashMap<(RouterId, RmDir), Vec<(i16, RouteMapState)>>,
     /// list of routes (external) that are advertised
@@ -125,6 +129,7 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
             local_area: Default::default(),
             mac_addresses: Default::default(),
             ospf_params: (Some(1), Some(5)),
+            bfd: None,
             route_maps,
             advertised_external_routes: Default::default(),
         })
@@ -147,6 +152,11 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
         }
     }
 
+    /// Get the list of all interface names. The interface at position `i` has index `i`.
+    pub fn ifaces(&self) -> &[String] {
+        &self.ifaces
+    }
+
     /// Get the interface index given an interface name.
     pub fn iface_idx(&self, name: impl AsRef<str>) -> Result<usize, ExportError> {
         let name = name.as_ref();
@@ -172,6 +182,85 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
         self.ospf_params = (hello_interval, dead_interval);
     }
 
+    /// Enable or disable BFD on all BGP sessions (intervals in milliseconds). On Cisco, this
+    /// enables `feature bfd` and sets the timers on each interface and for multi-hop sessions. On
+    /// FRR, the timers are configured in a BFD profile used by all neighbors. iBGP sessions between
+    /// loopback addresses use multi-hop BFD. BFD is disabled by default. If `enable` is `false`,
+    /// all other arguments are ignored.
+    pub fn set_bfd(&mut self, enable: bool, min_rx: u16, min_tx: u16, multiplier: u8) {
+        self.bfd = enable.then_some((min_tx, min_rx, multiplier));
+    }
+
+    /// Check a generated configuration for obvious issues that would cause the router to reject
+    /// it. This is not a full syntax checker; it only detects the following problems:
+    ///
+    /// - An interface is used as `update-source` before it is defined.
+    /// - A BGP neighbor is configured before its `remote-as` is set.
+    /// - A route-map is applied to a BGP neighbor, but it is never defined.
+    ///
+    /// The warnings are ordered by the line on which they occur.
+    pub fn lint(config: &str) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        let mut ifaces: HashSet<&str> = HashSet::new();
+        let mut neighbors: HashSet<&str> = HashSet::new();
+        let mut route_maps: HashSet<&str> = HashSet::new();
+        let mut applied_route_maps: Vec<(usize, &str)> = Vec::new();
+
+        for (i, line) in config.lines().enumerate() {
+            let line_nr = i + 1;
+            let tokens = line.split_whitespace().collect_vec();
+            match tokens.as_slice() {
+                // removing something never references undefined objects
+                ["no", ..] => continue,
+                ["interface", name, ..] => {
+                    ifaces.insert(*name);
+                }
+                ["route-map", name, "permit" | "deny", ..] => {
+                    route_maps.insert(*name);
+                }
+                ["neighbor", addr, "remote-as", ..] => {
+                    neighbors.insert(*addr);
+                }
+                ["neighbor", addr, ..] => {
+                    if neighbors.insert(*addr) {
+                        warnings.push(LintWarning::NeighborWithoutRemoteAs {
+                            line: line_nr,
+                            neighbor: addr.to_string(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+
+            for (pos, token) in tokens.iter().enumerate() {
+                match (*token, tokens.get(pos + 1), tokens.get(pos + 2)) {
+                    ("update-source", Some(iface), _) if !ifaces.contains(iface) => {
+                        warnings.push(LintWarning::InterfaceNotDefined {
+                            line: line_nr,
+                            iface: iface.to_string(),
+                        })
+                    }
+                    ("route-map", Some(name), Some(&"in" | &"out")) => {
+                        applied_route_maps.push((line_nr, *name))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        warnings.extend(
+            applied_route_maps
+                .into_iter()
+                .filter(|(_, name)| !route_maps.contains(name))
+                .map(|(line, name)| LintWarning::RouteMapNotDefined {
+                    line,
+                    route_map: name.to_string(),
+                }),
+        );
+        warnings.sort_by_key(LintWarning::line);
+        warnings
+    }
+
     /// Get the interface name of this router that is connected to either `a` or `b`. This function
     /// will also make sure that either `a` or `b` is `self.router`. If not, this function will
     /// return `Err(ExportError::ModifierDoesNotAffectRouter)`. We use `a` and `b`, instead of only
@@ -218,6 +307,19 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
         config
     }
 
+    /// Create the global BFD configuration, or an empty string if BFD is disabled.
+    fn bfd_config(&self) -> String {
+        match self.bfd {
+            Some((min_tx, min_rx, multiplier)) => format!(
+                "!\n! BFD\n!\n{}",
+                Bfd::new(BFD_PROFILE)
+                    .interval(min_tx, min_rx, multiplier)
+                    .build(self.target)
+            ),
+            None => String::new(),
+        }
+    }
+
     /// Create all the interface configuration
     fn iface_config<A: Addressor<P>, Q>(
         &mut self,
@@ -242,6 +344,10 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
                 iface.mac_address(*mac);
             }
 
+            if let Some((min_tx, min_rx, multiplier)) = self.bfd {
+                iface.bfd(min_tx, min_rx, multiplier);
+            }
+
             if is_internal {
                 iface.cost(*edge.weight());
                 if let Some(hello) = self.ospf_params.0 {
@@ -408,6 +514,14 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
         bgp_neighbor.route_map_out(format!("{rm_name}-out"));
         bgp_neighbor.next_hop_self();
         bgp_neighbor.soft_reconfiguration_inbound();
+        if self.bfd.is_some() {
+            if net.get_device(n).is_internal() {
+                bgp_neighbor.bfd_multihop();
+            } else {
+                bgp_neighbor.bfd();
+            }
+            bgp_neighbor.bfd_profile(BFD_PROFILE);
+        }
         match ty {
             BgpSessionType::IBgpPeer => {}
             BgpSessionType::IBgpClient => {
@@ -678,6 +792,68 @@ impl<P: Prefix> CiscoFrrCfgGen<P> {
     }
 }
 
+/// Issue found by [`CiscoFrrCfgGen::lint`]. Line numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintWarning {
+    /// The interface is used before it is defined.
+    InterfaceNotDefined {
+        /// Line on which the interface is used.
+        line: usize,
+        /// Name of the interface
+        iface: String,
+    },
+    /// The BGP neighbor is configured before its `remote-as` is set.
+    NeighborWithoutRemoteAs {
+        /// Line on which the neighbor is first configured.
+        line: usize,
+        /// Address of the neighbor
+        neighbor: String,
+    },
+    /// The route-map is applied to a BGP neighbor, but it is never defined.
+    RouteMapNotDefined {
+        /// Line on which the route-map is applied.
+        line: usize,
+        /// Name of the route-map
+        route_map: String,
+    },
+}
+
+impl LintWarning {
+    /// Get the line on which the issue occurs.
+    pub fn line(&self) -> usize {
+        match self {
+            Self::InterfaceNotDefined { line, .. }
+            | Self::NeighborWithoutRemoteAs { line, .. }
+            | Self::RouteMapNotDefined { line, .. } => *line,
+        }
+    }
+}
+
+impl std::fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InterfaceNotDefined { line, iface } => {
+                write!(
+                    f,
+                    "line {line}: interface {iface} is used before it is defined"
+                )
+            }
+            Self::NeighborWithoutRemoteAs { line, neighbor } => {
+                write!(
+                    f,
+                    "line {line}: neighbor {neighbor} is configured without remote-as"
+                )
+            }
+            Self::RouteMapNotDefined { line, route_map } => {
+                write!(
+                    f,
+                    "line {line}: route-map {route_map} is applied but never defined"
+                )
+            }
+        }
+    }
+}
+
 /// Get the full route-map name, including `in` and `out`
 fn full_rm_name<P: Prefix, Q>(net: &Network<P, Q>, router: RouterId, direction: RmDir) -> String {
     let dir = match direction {
@@ -714,8 +890,12 @@ impl<P: Prefix, A: Addressor<P>, Q> InternalCfgGen<P, Q, A> for CiscoFrrCfgGen<P
         config.push_str("!\n");
         config.push_str(enable_bgp(self.target));
         config.push_str(enable_ospf(self.target));
+        if self.bfd.is_some() {
+            config.push_str(enable_bfd(self.target));
+        }
 
         config.push_str(&self.pec_config(addressor));
+        config.push_str(&self.bfd_config());
         config.push_str(&self.iface_config(net, addressor)?);
         config.push_str(&self.static_route_config(net, router, addressor)?);
         config.push_str(&self.ospf_config(router, addressor)?);
@@ -789,17 +969,25 @@ impl<P: Prefix, A: Addressor<P>, Q> InternalCfgGen<P, Q, A> for CiscoFrrCfgGen<P
                     map,
                     ..
                 } => {
+                    let rm_name = full_rm_name(net, neighbor, direction);
                     let next_ord = self.next_ord(neighbor, direction, map.order(), map.state());
+                    let item = self.route_map_item(&rm_name, &map, next_ord, net, addressor)?;
+                    // If the route-map item already exists, replace it in place, such that the new
+                    // item does not extend the old one.
+                    let old_map = net
+                        .get_device(self.router)
+                        .internal()
+                        .and_then(|r| r.get_bgp_route_map(neighbor, direction, map.order()));
+                    let cmd = match old_map {
+                        Some(old_map) => item.replace(
+                            &self.route_map_item(&rm_name, old_map, next_ord, net, addressor)?,
+                            self.target,
+                        ),
+                        None => item.build(self.target),
+                    };
                     Ok(format!(
                         "{}{}",
-                        self.route_map_item(
-                            &full_rm_name(net, neighbor, direction),
-                            &map,
-                            next_ord,
-                            net,
-                            addressor,
-                        )?
-                        .build(self.target),
+                        cmd,
                         // update the continues of the last route-map.
                         self.fix_prev_rm_continue(net, neighbor, direction, map.order())
                             .unwrap_or_default()
@@ -914,13 +1102,13 @@ impl<P: Prefix, A: Addressor<P>, Q> InternalCfgGen<P, Q, A> for CiscoFrrCfgGen<P
                     if let ConfigExpr::BgpRouteMap { map: old_map, .. } = from {
                         let rm_name = full_rm_name(net, neighbor, direction);
                         let next_ord = self.next_ord(neighbor, direction, map.order(), map.state());
-                        Ok(format!(
-                            "{}{}",
-                            self.route_map_item(&rm_name, &old_map, next_ord, net, addressor)?
-                                .no(self.target),
-                            self.route_map_item(&rm_name, &map, next_ord, net, addressor)?
-                                .build(self.target)
-                        ))
+                        Ok(self
+                            .route_map_item(&rm_name, &map, next_ord, net, addressor)?
+                            .replace(
+                                &self
+                                    .route_map_item(&rm_name, &old_map, next_ord, net, addressor)?,
+                                self.target,
+                            ))
                     } else {
                         unreachable!("Config Modifier must update the same kind of expression")
                     }
@@ -967,6 +1155,10 @@ impl<P: Prefix, A: Addressor<P>, Q> ExternalCfgGen<P, Q, A> for CiscoFrrCfgGen<P
         // if we are on cisco, enable the ospf and bgp feature
         config.push_str("!\n");
         config.push_str(enable_bgp(self.target));
+        if self.bfd.is_some() {
+            config.push_str(enable_bfd(self.target));
+        }
+        config.push_str(&self.bfd_config());
 
         // create the interfaces to the neighbors
         config.push_str(&self.iface_config(net, addressor)?);
@@ -975,14 +1167,18 @@ impl<P: Prefix, A: Addressor<P>, Q> ExternalCfgGen<P, Q, A> for CiscoFrrCfgGen<P
         let mut router_bgp = RouterBgp::new(self.as_id);
         router_bgp.router_id(addressor.router_address(self.router)?);
         for neighbor in router.neighbors.iter() {
-            router_bgp.neighbor(
-                RouterBgpNeighbor::new(self.router_id_to_ip(*neighbor, net, addressor)?)
-                    .update_source(self.iface(self.router, *neighbor, addressor)?)
-                    .remote_as(INTERNAL_AS)
-                    .next_hop_self()
-                    .route_map_in(EXTERNAL_RM_IN)
-                    .route_map_out(EXTERNAL_RM_OUT),
-            );
+            let mut bgp_neighbor =
+                RouterBgpNeighbor::new(self.router_id_to_ip(*neighbor, net, addressor)?);
+            bgp_neighbor
+                .update_source(self.iface(self.router, *neighbor, addressor)?)
+                .remote_as(INTERNAL_AS)
+                .next_hop_self()
+                .route_map_in(EXTERNAL_RM_IN)
+                .route_map_out(EXTERNAL_RM_OUT);
+            if self.bfd.is_some() {
+                bgp_neighbor.bfd().bfd_profile(BFD_PROFILE);
+            }
+            router_bgp.neighbor(bgp_neighbor);
         }
         // announce the internal prefix (for now).
         router_bgp.network(addressor.router_network(self.router)?);
@@ -1120,15 +1316,19 @@ impl<P: Prefix, A: Addressor<P>, Q> ExternalCfgGen<P, Q, A> for CiscoFrrCfgGen<P
         addressor: &mut A,
         neighbor: RouterId,
     ) -> Result<String, ExportError> {
+        let mut bgp_neighbor =
+            RouterBgpNeighbor::new(self.router_id_to_ip(neighbor, net, addressor)?);
+        bgp_neighbor
+            .update_source(self.iface(self.router, neighbor, addressor)?)
+            .remote_as(INTERNAL_AS)
+            .next_hop_self()
+            .route_map_in(EXTERNAL_RM_IN)
+            .route_map_out(EXTERNAL_RM_OUT);
+        if self.bfd.is_some() {
+            bgp_neighbor.bfd().bfd_profile(BFD_PROFILE);
+        }
         Ok(RouterBgp::new(self.as_id)
-            .neighbor(
-                RouterBgpNeighbor::new(self.router_id_to_ip(neighbor, net, addressor)?)
-                    .update_source(self.iface(self.router, neighbor, addressor)?)
-                    .remote_as(INTERNAL_AS)
-                    .next_hop_self()
-                    .route_map_in(EXTERNAL_RM_IN)
-                    .route_map_out(EXTERNAL_RM_OUT),
-            )
+            .neighbor(bgp_neighbor)
             .build(self.target))
     }
 
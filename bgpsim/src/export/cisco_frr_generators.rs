@@ -36,6 +36,8 @@ pub enum Target {
     CiscoNexus7000,
     /// Frr
     Frr,
+    /// Arista EOS
+    AristaEos,
 }
 
 /// Interface configuration builder for Cisco and FRR.
@@ -51,6 +53,8 @@ pub struct Interface {
     no_dead_interval: bool,
     hello_interval: Option<u16>,
     no_hello_interval: bool,
+    bfd: Option<(u16, u16, u8)>,
+    no_bfd: bool,
     mac_address: Option<[u8; 6]>,
     no_mac_address: bool,
     shutdown: Option<bool>,
@@ -71,6 +75,8 @@ impl Interface {
             no_dead_interval: false,
             hello_interval: None,
             no_hello_interval: false,
+            bfd: None,
+            no_bfd: false,
             mac_address: None,
             no_mac_address: false,
         }
@@ -349,6 +355,55 @@ impl Interface {
         self
     }
 
+    /// Set the BFD timers of single-hop sessions on the interface. `min_tx` and `min_rx` are the
+    /// transmit and receive intervals (in milliseconds), and `multiplier` is the number of missed
+    /// packets after which the session is declared down. On Cisco, this also disables ICMP
+    /// redirects, which is required for BFD. This option is not available on `Target::FRR`, where
+    /// the timers are configured in a BFD profile (see [`Bfd`]).
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{Interface, Target};
+    /// assert_eq!(
+    ///     Interface::new("Ethernet4/1").bfd(50, 50, 3).build(Target::CiscoNexus7000),
+    ///     "\
+    /// interface Ethernet4/1
+    ///   bfd interval 50 min_rx 50 multiplier 3
+    ///   no ip redirects
+    /// exit
+    /// "
+    /// );
+    /// assert_eq!(
+    ///     Interface::new("eth1").bfd(50, 50, 3).build(Target::Frr),
+    ///     "\
+    /// interface eth1
+    /// exit
+    /// "
+    /// );
+    /// ```
+    pub fn bfd(&mut self, min_tx: u16, min_rx: u16, multiplier: u8) -> &mut Self {
+        self.bfd = Some((min_tx, min_rx, multiplier));
+        self
+    }
+
+    /// Remove the BFD interval configuration from the interface. This option is not available on
+    /// `Target::FRR`.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{Interface, Target};
+    /// assert_eq!(
+    ///     Interface::new("Ethernet4/1").no_bfd().build(Target::CiscoNexus7000),
+    ///     "\
+    /// interface Ethernet4/1
+    ///   no bfd interval
+    /// exit
+    /// "
+    /// );
+    /// ```
+    pub fn no_bfd(&mut self) -> &mut Self {
+        self.no_bfd = true;
+        self
+    }
+
     /// Set the physical MAC address of the interface. This option is not available on
     /// `Target::FRR`.
     ///
@@ -453,16 +508,22 @@ impl Interface {
     pub fn build(&self, target: Target) -> String {
         let ospf_area_cmd = match target {
             Target::CiscoNexus7000 => format!("ip router ospf {ROUTER_OSPF_INSTANCE} area"),
-            Target::Frr => String::from("ip ospf area"),
+            Target::Frr | Target::AristaEos => String::from("ip ospf area"),
         };
 
         format!(
             "\
         interface {iface}\
-{addr}{cost}{area}{dead}{hello}{mac}{shutdown}
+{switchport}{addr}{cost}{area}{dead}{hello}{bfd}{mac}{shutdown}
 exit
 ",
             iface = self.iface_name,
+            switchport = match target {
+                Target::AristaEos if !self.iface_name.starts_with("Loopback") => {
+                    "\n  no switchport"
+                }
+                _ => "",
+            },
             addr = self
                 .ip_address
                 .iter()
@@ -492,6 +553,17 @@ exit
                 (_, true) => String::from("\n  no ip ospf hello-interval"),
                 (None, false) => String::new(),
             },
+            bfd = match (self.bfd, self.no_bfd, target) {
+                (_, _, Target::Frr) => String::new(),
+                (Some((tx, rx, mult)), false, Target::CiscoNexus7000) => format!(
+                    "\n  bfd interval {tx} min_rx {rx} multiplier {mult}\n  no ip redirects"
+                ),
+                (Some((tx, rx, mult)), false, Target::AristaEos) => {
+                    format!("\n  bfd interval {tx} min-rx {rx} multiplier {mult}")
+                }
+                (_, true, _) => String::from("\n  no bfd interval"),
+                (None, false, _) => String::new(),
+            },
             mac = match (self.mac_address, self.no_mac_address) {
                 (Some(mac), false) if target != Target::Frr => format!(
                     "\n  mac-address {:02x}{:02x}.{:02x}{:02x}.{:02x}{:02x}",
@@ -540,7 +612,9 @@ impl RouterOspf {
     /// ```
     pub fn no(&self, target: Target) -> String {
         match target {
-            Target::CiscoNexus7000 => format!("no router ospf {ROUTER_OSPF_INSTANCE}\n"),
+            Target::CiscoNexus7000 | Target::AristaEos => {
+                format!("no router ospf {ROUTER_OSPF_INSTANCE}\n")
+            }
             Target::Frr => String::from("no router ospf\n"),
         }
     }
@@ -638,7 +712,7 @@ impl RouterOspf {
     /// ```
     pub fn build(&self, target: Target) -> String {
         let instance_str = match target {
-            Target::CiscoNexus7000 => format!(" {ROUTER_OSPF_INSTANCE}"),
+            Target::CiscoNexus7000 | Target::AristaEos => format!(" {ROUTER_OSPF_INSTANCE}"),
             Target::Frr => String::new(),
         };
 
@@ -663,6 +737,98 @@ exit
     }
 }
 
+/// Global BFD configuration for cisco-like routers. On FRR, the timers are configured in a BFD
+/// profile that BGP neighbors refer to (see [`RouterBgpNeighbor::bfd_profile`]). Cisco Nexus and
+/// Arista EOS have no BFD profiles. There, the timers of single-hop sessions are configured on each
+/// interface (see [`Interface::bfd`]), and this builder only configures the multi-hop timers.
+#[derive(Debug)]
+pub struct Bfd {
+    profile: String,
+    interval: Option<(u16, u16, u8)>,
+}
+
+impl Bfd {
+    /// Create a new BFD configuration builder. The `profile` name is only used on FRR.
+    pub fn new(profile: impl Into<String>) -> Self {
+        Self {
+            profile: profile.into(),
+            interval: None,
+        }
+    }
+
+    /// Set the BFD timers. `min_tx` and `min_rx` are the transmit and receive intervals (in
+    /// milliseconds), and `multiplier` is the number of missed packets after which the session is
+    /// declared down.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{Bfd, Target};
+    /// assert_eq!(
+    ///     Bfd::new("fast").interval(50, 50, 3).build(Target::CiscoNexus7000),
+    ///     "bfd multihop interval 50 min_rx 50 multiplier 3\n"
+    /// );
+    /// assert_eq!(
+    ///     Bfd::new("fast").interval(50, 50, 3).build(Target::Frr),
+    ///     "\
+    /// bfd
+    ///   profile fast
+    ///     transmit-interval 50
+    ///     receive-interval 50
+    ///     detect-multiplier 3
+    ///   exit
+    /// exit
+    /// "
+    /// );
+    /// ```
+    pub fn interval(&mut self, min_tx: u16, min_rx: u16, multiplier: u8) -> &mut Self {
+        self.interval = Some((min_tx, min_rx, multiplier));
+        self
+    }
+
+    /// Generate the configuration lines as described by the builder. This will return an empty
+    /// string if no timers are configured.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{Bfd, Target};
+    /// assert_eq!(
+    ///     Bfd::new("fast").interval(50, 50, 3).build(Target::AristaEos),
+    ///     "\
+    /// router bfd
+    ///   multihop interval 50 min-rx 50 multiplier 3
+    /// exit
+    /// "
+    /// );
+    /// assert_eq!(Bfd::new("fast").build(Target::Frr), "");
+    /// ```
+    pub fn build(&self, target: Target) -> String {
+        let (tx, rx, mult) = match self.interval {
+            Some(interval) => interval,
+            None => return String::new(),
+        };
+        match target {
+            Target::CiscoNexus7000 => {
+                format!("bfd multihop interval {tx} min_rx {rx} multiplier {mult}\n")
+            }
+            Target::AristaEos => {
+                format!(
+                    "router bfd\n  multihop interval {tx} min-rx {rx} multiplier {mult}\nexit\n"
+                )
+            }
+            Target::Frr => format!(
+                "\
+bfd
+  profile {}
+    transmit-interval {tx}
+    receive-interval {rx}
+    detect-multiplier {mult}
+  exit
+exit
+",
+                self.profile
+            ),
+        }
+    }
+}
+
 /// BGP Router configuration for Cisco-like routers.
 ///
 ///
@@ -918,7 +1084,7 @@ impl RouterBgp {
     pub fn build(&self, target: Target) -> String {
         // router-id
         let router_id_pfx = match target {
-            Target::CiscoNexus7000 => "",
+            Target::CiscoNexus7000 | Target::AristaEos => "",
             Target::Frr => "bgp ",
         };
         let router_id = match (self.router_id, self.no_router_id) {
@@ -936,7 +1102,7 @@ impl RouterBgp {
 
         // remove all address-family code from the neighbors and collect them (in the same order)
         let af_neighbor_code = match target {
-            Target::CiscoNexus7000 => String::new(),
+            Target::CiscoNexus7000 | Target::AristaEos => String::new(),
             Target::Frr => {
                 let lines = neighbor_code.lines();
                 let mut new_neighbor_code = String::new();
@@ -976,12 +1142,13 @@ impl RouterBgp {
         let af = if network_code.is_empty() && af_neighbor_code.is_empty() {
             String::new()
         } else {
-            let exit_af = match target {
-                Target::CiscoNexus7000 => "",
-                Target::Frr => "-address-family",
+            let (safi, exit_af) = match target {
+                Target::CiscoNexus7000 => (" unicast", ""),
+                Target::Frr => (" unicast", "-address-family"),
+                Target::AristaEos => ("", ""),
             };
             format!(
-                "  address-family ipv4 unicast\n{network_code}{af_neighbor_code}  exit{exit_af}\n"
+                "  address-family ipv4{safi}\n{network_code}{af_neighbor_code}  exit{exit_af}\n"
             )
         };
 
@@ -1016,6 +1183,9 @@ pub struct RouterBgpNeighbor {
     no_route_map_out: bool,
     send_community: Option<bool>,
     soft_reconfiguration: Option<bool>,
+    bfd: Option<bool>,
+    bfd_multihop: bool,
+    bfd_profile: Option<String>,
 }
 
 impl RouterBgpNeighbor {
@@ -1036,6 +1206,9 @@ impl RouterBgpNeighbor {
             no_route_map_out: Default::default(),
             send_community: Default::default(),
             soft_reconfiguration: Default::default(),
+            bfd: Default::default(),
+            bfd_multihop: Default::default(),
+            bfd_profile: Default::default(),
         }
     }
 
@@ -1653,6 +1826,145 @@ impl RouterBgpNeighbor {
         self
     }
 
+    /// Use BFD to detect failures of the session to the neighbor.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{RouterBgpNeighbor, Target};
+    /// # use std::net::Ipv4Addr;
+    /// # use bgpsim::types::AsId;
+    /// let neighbor_addr: Ipv4Addr = "20.0.0.1".parse().unwrap();
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .bfd()
+    ///         .build(Target::CiscoNexus7000),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1
+    ///     bfd
+    ///   exit
+    /// "
+    /// );
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .bfd()
+    ///         .build(Target::Frr),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1 bfd
+    /// "
+    /// );
+    /// ```
+    pub fn bfd(&mut self) -> &mut Self {
+        self.bfd = Some(true);
+        self
+    }
+
+    /// Use multi-hop BFD to detect failures of the session to the neighbor. This is required for
+    /// sessions that are not directly connected, e.g., iBGP sessions between loopback addresses.
+    /// FRR and Arista EOS select multi-hop BFD automatically for such sessions.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{RouterBgpNeighbor, Target};
+    /// # use std::net::Ipv4Addr;
+    /// # use bgpsim::types::AsId;
+    /// let neighbor_addr: Ipv4Addr = "20.0.0.1".parse().unwrap();
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .bfd_multihop()
+    ///         .build(Target::CiscoNexus7000),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1
+    ///     bfd multihop
+    ///   exit
+    /// "
+    /// );
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .bfd_multihop()
+    ///         .build(Target::Frr),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1 bfd
+    /// "
+    /// );
+    /// ```
+    pub fn bfd_multihop(&mut self) -> &mut Self {
+        self.bfd = Some(true);
+        self.bfd_multihop = true;
+        self
+    }
+
+    /// Use the BFD profile `name` for the session to the neighbor (see [`Bfd`]). This option is
+    /// only available on `Target::FRR`, and it only has an effect if BFD is enabled for the
+    /// neighbor.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{RouterBgpNeighbor, Target};
+    /// # use std::net::Ipv4Addr;
+    /// # use bgpsim::types::AsId;
+    /// let neighbor_addr: Ipv4Addr = "20.0.0.1".parse().unwrap();
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .bfd()
+    ///         .bfd_profile("fast")
+    ///         .build(Target::Frr),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1 bfd profile fast
+    /// "
+    /// );
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .bfd()
+    ///         .bfd_profile("fast")
+    ///         .build(Target::CiscoNexus7000),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1
+    ///     bfd
+    ///   exit
+    /// "
+    /// );
+    /// ```
+    pub fn bfd_profile(&mut self, name: impl Into<String>) -> &mut Self {
+        self.bfd_profile = Some(name.into());
+        self
+    }
+
+    /// Do not use BFD for the session to the neighbor.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{RouterBgpNeighbor, Target};
+    /// # use std::net::Ipv4Addr;
+    /// # use bgpsim::types::AsId;
+    /// let neighbor_addr: Ipv4Addr = "20.0.0.1".parse().unwrap();
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .no_bfd()
+    ///         .build(Target::CiscoNexus7000),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   neighbor 20.0.0.1
+    ///     no bfd
+    ///   exit
+    /// "
+    /// );
+    /// assert_eq!(
+    ///     RouterBgpNeighbor::new(neighbor_addr)
+    ///         .no_bfd()
+    ///         .build(Target::Frr),
+    /// #   "  ".to_owned() +
+    ///     "\
+    ///   no neighbor 20.0.0.1 bfd
+    /// "
+    /// );
+    /// ```
+    pub fn no_bfd(&mut self) -> &mut Self {
+        self.bfd = Some(false);
+        self
+    }
+
     /// Generate the configuration lines
     pub fn build(&self, target: Target) -> String {
         let (mut cfg, pre, tab, finish) = match target {
@@ -1665,7 +1977,7 @@ impl RouterBgpNeighbor {
                 "  ",
                 "\n  exit\n",
             ),
-            Target::Frr => (
+            Target::Frr | Target::AristaEos => (
                 match self.remote_as {
                     Some(id) => format!("  neighbor {} remote-as {}", self.neighbor_id, id.0),
                     None => String::new(),
@@ -1721,6 +2033,23 @@ impl RouterBgpNeighbor {
             (None, false) => {}
         }
 
+        // bfd
+        match self.bfd.as_ref() {
+            Some(true) => {
+                let multihop = match target {
+                    Target::CiscoNexus7000 if self.bfd_multihop => " multihop",
+                    _ => "",
+                };
+                let profile = match (target, self.bfd_profile.as_ref()) {
+                    (Target::Frr, Some(name)) => format!(" profile {name}"),
+                    _ => String::new(),
+                };
+                cfg.push_str(&format!("\n  {tab}{pre}bfd{multihop}{profile}"))
+            }
+            Some(false) => cfg.push_str(&format!("\n  {tab}no {pre}bfd")),
+            None => {}
+        }
+
         // send-community
         match self.send_community.as_ref() {
             Some(true) => af.push_str(&format!("\n    {tab}{pre}send-community")),
@@ -1728,15 +2057,22 @@ impl RouterBgpNeighbor {
             _ => {}
         }
 
-        // soft-reconfiguration inbound
-        match self.soft_reconfiguration.as_ref() {
+        // soft-reconfiguration inbound (Arista EOS always keeps the Adj-RIB-In)
+        match self
+            .soft_reconfiguration
+            .as_ref()
+            .filter(|_| target != Target::AristaEos)
+        {
             Some(true) => af.push_str(&format!("\n    {tab}{pre}soft-reconfiguration inbound")),
             Some(false) => af.push_str(&format!("\n    {tab}no {pre}soft-reconfiguration inbound")),
             _ => {}
         }
 
-        // address family
-        if !af.is_empty() {
+        // address family. Arista EOS configures all neighbor properties outside of the
+        // address-family.
+        if target == Target::AristaEos {
+            cfg.push_str(&af.replace("\n    ", "\n  "));
+        } else if !af.is_empty() {
             cfg.push_str(&format!("\n  {tab}address-family ipv4 unicast"));
             cfg.push_str(&af);
             cfg.push_str(&format!("\n  {tab}exit"));
@@ -1872,7 +2208,7 @@ impl StaticRoute {
     pub fn build(&self, target: Target) -> String {
         let null = match target {
             Target::CiscoNexus7000 => "null 0",
-            Target::Frr => "Null0",
+            Target::Frr | Target::AristaEos => "Null0",
         };
         let pref = self.pref.map(|p| format!(" {p}")).unwrap_or_default();
         format!(
@@ -1912,7 +2248,7 @@ impl StaticRoute {
 /// "
 /// );
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RouteMapItem {
     name: String,
     order: u16,
@@ -2422,13 +2758,30 @@ impl RouteMapItem {
     ///     RouteMapItem::new("test", 10, true).set_community(10, 10).build(Target::Frr),
     ///     "\
     /// route-map test permit 10
-    ///   set community 10:10
+    ///   set community 10:10 additive
     /// exit
     /// "
     /// );
     /// ```
     ///
-    /// For Cisco devices, this will also add the `additive` tag:
+    /// Setting multiple communities will combine them into a single command:
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{RouteMapItem, Target};
+    /// assert_eq!(
+    ///     RouteMapItem::new("test", 10, true)
+    ///         .set_community(10, 10)
+    ///         .set_community(10, 20)
+    ///         .build(Target::Frr),
+    ///     "\
+    /// route-map test permit 10
+    ///   set community 10:10 10:20 additive
+    /// exit
+    /// "
+    /// );
+    /// ```
+    ///
+    /// For Cisco devices, the `additive` tag is placed before the communities:
     ///
     /// ```
     /// # use bgpsim::export::cisco_frr_generators::{RouteMapItem, Target};
@@ -2455,13 +2808,13 @@ impl RouteMapItem {
     ///     RouteMapItem::new("test", 10, true).no_set_community(10, 10).build(Target::Frr),
     ///     "\
     /// route-map test permit 10
-    ///   no set community 10:10
+    ///   no set community 10:10 additive
     /// exit
     /// "
     /// );
     /// ```
     ///
-    /// For Cisco devices, this will remove the community using the `additive` tag.
+    /// For Cisco devices, the `additive` tag is placed before the community:
     ///
     /// ```
     /// # use bgpsim::export::cisco_frr_generators::{RouteMapItem, Target};
@@ -2651,6 +3004,131 @@ impl RouteMapItem {
         cfg
     }
 
+    /// Replace the route-map item `old` with `self` in place, without removing the route-map item
+    /// itself. Prefix-lists, community-lists, and as-path access-lists that are used by both items
+    /// are re-created, and all clauses of `old` that are not present in `self` are removed. If both
+    /// items differ in their name, order, or mode, `old` is removed before `self` is created.
+    ///
+    /// ```
+    /// # use bgpsim::export::cisco_frr_generators::{RouteMapItem, CommunityList, Target};
+    /// assert_eq!(
+    ///     RouteMapItem::new("test", 10, true)
+    ///         .match_community_list(CommunityList::new("test-cl").community(10, 20))
+    ///         .set_local_pref(200)
+    ///         .replace(
+    ///             RouteMapItem::new("test", 10, true)
+    ///                 .match_community_list(CommunityList::new("test-cl").community(10, 10))
+    ///                 .set_weight(200),
+    ///             Target::Frr
+    ///         ),
+    ///     "\
+    /// no bgp community-list standard test-cl
+    /// bgp community-list standard test-cl permit 10:20
+    /// route-map test permit 10
+    ///   match community test-cl
+    ///   no set weight
+    ///   set local-preference 200
+    /// exit
+    /// "
+    /// );
+    /// ```
+    pub fn replace(&self, old: &RouteMapItem, target: Target) -> String {
+        if (&self.name, self.order, self.mode) != (&old.name, old.order, old.mode) {
+            return format!("{}{}", old.no(target), self.build(target));
+        }
+
+        let mut cfg = String::new();
+        let mut item = self.clone();
+
+        // lists cannot be modified. Hence, remove the lists that are used in both items, such that
+        // they are re-created, and remove the match on all lists that are no longer used.
+        for (pl, _) in old.match_prefix_list.iter() {
+            if self
+                .match_prefix_list
+                .iter()
+                .any(|(x, _)| x.name == pl.name)
+            {
+                cfg.push_str(&pl.no());
+            } else {
+                item.no_match_prefix_list(pl.clone());
+            }
+        }
+        for (pl, _) in old.match_global_prefix_list.iter() {
+            if !self.match_global_prefix_list.iter().any(|(x, _)| x == pl) {
+                item.no_match_global_prefix_list(pl);
+            }
+        }
+        for (cl, _) in old.match_community_list.iter() {
+            if self
+                .match_community_list
+                .iter()
+                .any(|(x, _)| x.name == cl.name)
+            {
+                cfg.push_str(&cl.no(target));
+            } else {
+                item.no_match_community_list(cl.clone());
+            }
+        }
+        for (asl, _) in old.match_as_path_list.iter() {
+            if self
+                .match_as_path_list
+                .iter()
+                .any(|(x, _)| x.name == asl.name)
+            {
+                cfg.push_str(&asl.no(target));
+            } else {
+                item.no_match_as_path_list(asl.clone());
+            }
+        }
+        for (pl, _) in old.match_next_hop_pl.iter() {
+            if self
+                .match_next_hop_pl
+                .iter()
+                .any(|(x, _)| x.name == pl.name)
+            {
+                cfg.push_str(&pl.no());
+            } else {
+                item.no_match_next_hop(pl.clone());
+            }
+        }
+        for (cl, _) in old.delete_community.iter() {
+            if self.delete_community.iter().any(|(x, _)| x.name == cl.name) {
+                cfg.push_str(&cl.no(target));
+            } else {
+                item.no_remove_community_list(cl.clone());
+            }
+        }
+
+        // remove all set clauses that are no longer used.
+        if self.set_next_hop.is_none() && old.set_next_hop.is_some() {
+            item.no_set_next_hop();
+        }
+        if self.set_weight.is_none() && old.set_weight.is_some() {
+            item.no_set_weight();
+        }
+        if self.set_local_pref.is_none() && old.set_local_pref.is_some() {
+            item.no_set_local_pref();
+        }
+        if self.set_med.is_none() && old.set_med.is_some() {
+            item.no_set_med();
+        }
+        // a new `set community` command replaces the old one. Hence, only remove it if the new item
+        // does not set any community.
+        if self.set_community.is_empty() && !old.set_community.is_empty() {
+            item.set_community
+                .push((old.set_community.iter().map(|(c, _)| c).join(" "), false));
+        }
+        if self.prepend_as_path.is_none() && old.prepend_as_path.is_some() {
+            item.no_prepend_as_path();
+        }
+        if self.cont.is_none() && old.cont.is_some() {
+            item.no_continues();
+        }
+
+        cfg.push_str(&item.build(target));
+        cfg
+    }
+
     /// Build the command to create or update the given route-map item.
     /// that belong to that route-map.
     ///
@@ -2755,20 +3233,38 @@ impl RouteMapItem {
             Some((_, false)) => cfg.push_str("  no set metric\n"),
             None => {}
         }
-        // add the word `additive` only to cisco devices.
-        let additive = match target {
-            Target::CiscoNexus7000 => "additive ",
-            Target::Frr => "",
+        // Communities are always added to the existing ones. Cisco expects the `additive` keyword
+        // before the communities, while FRR and Arista expect it afterwards.
+        let (additive_pre, additive_post) = match target {
+            Target::CiscoNexus7000 => ("additive ", ""),
+            Target::Frr | Target::AristaEos => ("", " additive"),
         };
-        // set_community: Vec<(String, bool)>,
-        for (c, mode) in self.set_community.iter() {
-            cfg.push_str(if *mode { "  " } else { "  no " });
-            cfg.push_str(&format!("set community {additive}{c}\n"));
+        // set_community: Vec<(String, bool)>. All communities must be set in a single command, as
+        // every `set community` command replaces the previous one.
+        let set_communities = self
+            .set_community
+            .iter()
+            .filter(|(_, mode)| *mode)
+            .map(|(c, _)| c)
+            .join(" ");
+        if !set_communities.is_empty() {
+            cfg.push_str(&format!(
+                "  set community {additive_pre}{set_communities}{additive_post}\n"
+            ));
+        }
+        for (c, _) in self.set_community.iter().filter(|(_, mode)| !*mode) {
+            cfg.push_str(&format!(
+                "  no set community {additive_pre}{c}{additive_post}\n"
+            ));
         }
         // remove_community: Vec<(CommunityList, bool)>,
+        let delete_cmd = match target {
+            Target::CiscoNexus7000 | Target::Frr => "comm-list",
+            Target::AristaEos => "community community-list",
+        };
         for (c, mode) in self.delete_community.iter() {
             cfg.push_str(if *mode { "  " } else { "  no " });
-            cfg.push_str(&format!("set comm-list {} delete\n", c.name));
+            cfg.push_str(&format!("set {delete_cmd} {} delete\n", c.name));
         }
         // prepend_as_path: Option<(Vec<AsId>, bool)>,
         match self.prepend_as_path.as_ref() {
@@ -2957,11 +3453,7 @@ impl CommunityList {
     /// );
     /// ```
     pub fn no(&self, target: Target) -> String {
-        let root = match target {
-            Target::CiscoNexus7000 => "ip",
-            Target::Frr => "bgp",
-        };
-        format!("no {} community-list standard {}\n", root, self.name)
+        format!("no {} {}\n", community_list_cmd(target), self.name)
     }
 
     /// Permit the given community. Calling `community` multiple times, the resulting community
@@ -3003,25 +3495,32 @@ impl CommunityList {
 
     /// Build the community list.
     pub fn build(&self, target: Target) -> String {
-        let root = match target {
-            Target::CiscoNexus7000 => "ip",
-            Target::Frr => "bgp",
-        };
+        let cmd = community_list_cmd(target);
         let permit = format!(
-            "{} community-list standard {} permit {}\n",
-            root,
+            "{} {} permit {}\n",
+            cmd,
             self.name,
             self.communities.iter().join(" ")
         );
         let deny = self
             .deny_communities
             .iter()
-            .map(|c| format!("{root} community-list standard {} deny {c}\n", self.name))
+            .map(|c| format!("{cmd} {} deny {c}\n", self.name))
             .join("");
         format!("{deny}{permit}")
     }
 }
 
+/// Get the command to create a standard community list. Arista EOS does not use the `standard`
+/// keyword.
+fn community_list_cmd(target: Target) -> &'static str {
+    match target {
+        Target::CiscoNexus7000 => "ip community-list standard",
+        Target::Frr => "bgp community-list standard",
+        Target::AristaEos => "ip community-list",
+    }
+}
+
 impl From<&mut CommunityList> for CommunityList {
     fn from(val: &mut CommunityList) -> Self {
         val.clone()
@@ -3055,7 +3554,7 @@ impl AsPathList {
     /// ```
     pub fn no(&self, target: Target) -> String {
         let root = match target {
-            Target::CiscoNexus7000 => "ip",
+            Target::CiscoNexus7000 | Target::AristaEos => "ip",
             Target::Frr => "bgp",
         };
         format!("no {} as-path access-list {}\n", root, self.name)
@@ -3077,7 +3576,7 @@ impl AsPathList {
     /// Build the as-path access-list.
     pub fn build(&self, target: Target) -> String {
         let root = match target {
-            Target::CiscoNexus7000 => "ip",
+            Target::CiscoNexus7000 | Target::AristaEos => "ip",
             Target::Frr => "bgp",
         };
         format!(
@@ -3093,11 +3592,13 @@ impl From<&mut AsPathList> for AsPathList {
     }
 }
 
-/// Enable the BGP feature using commands. This does nothing on FRR.
+/// Enable the BGP feature using commands. This does nothing on FRR. On Arista EOS, this enables IP
+/// routing.
 pub fn enable_bgp(target: Target) -> &'static str {
     match target {
         Target::CiscoNexus7000 => "feature bgp\n",
         Target::Frr => "",
+        Target::AristaEos => "ip routing\n",
     }
 }
 
@@ -3105,14 +3606,22 @@ pub fn enable_bgp(target: Target) -> &'static str {
 pub fn enable_ospf(target: Target) -> &'static str {
     match target {
         Target::CiscoNexus7000 => "feature ospf\n",
-        Target::Frr => "",
+        Target::Frr | Target::AristaEos => "",
+    }
+}
+
+/// Enable the BFD feature using commands. This does nothing on FRR and Arista EOS.
+pub fn enable_bfd(target: Target) -> &'static str {
+    match target {
+        Target::CiscoNexus7000 => "feature bfd\n",
+        Target::Frr | Target::AristaEos => "",
     }
 }
 
 /// Get the interface name for the given loopback.
 pub fn loopback_iface(target: Target, idx: u8) -> String {
     match target {
-        Target::CiscoNexus7000 => format!("Loopback{idx}"),
+        Target::CiscoNexus7000 | Target::AristaEos => format!("Loopback{idx}"),
         Target::Frr => String::from("lo"),
     }
 }
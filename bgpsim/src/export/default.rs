@@ -17,14 +17,13 @@
 
 //! This module describes the default config generator and IP addressor.
 
-use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
-    net::Ipv4Addr,
-};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
-use ipnet::{Ipv4Net, Ipv4Subnets};
+use ipnet::Ipv4Net;
+use serde::{Deserialize, Serialize};
+use serde_with::{As, Same};
 
-use super::{ip_err, Addressor, ExportError, LinkId, MaybePec};
+use super::{ip_err, Addressor, ExportError, IpNetwork, LinkId, MaybePec};
 use crate::{
     network::Network,
     types::{AsId, NonOverlappingPrefix, Prefix, PrefixMap, RouterId},
@@ -66,6 +65,9 @@ pub struct DefaultAddressorBuilder {
     /// The prefix length for external networks (the loopback network of external routers). The
     /// default value is 24.
     pub external_prefix_len: u8,
+    /// Assignment of a previous addressor (see [`DefaultAddressor::export_assignment`]) that
+    /// should be restored. By default, no assignment is restored.
+    pub assignment: Option<AddressorState>,
 }
 
 impl Default for DefaultAddressorBuilder {
@@ -76,6 +78,7 @@ impl Default for DefaultAddressorBuilder {
             local_prefix_len: 24,
             link_prefix_len: 30,
             external_prefix_len: 24,
+            assignment: None,
         }
     }
 }
@@ -132,6 +135,15 @@ impl DefaultAddressorBuilder {
         self.external_prefix_len = x;
         self
     }
+
+    /// Restore the assignment of a previous addressor (see
+    /// [`DefaultAddressor::export_assignment`]). The state must be generated by an addressor with
+    /// the same parameters. Addresses that were not yet assigned are assigned as usual, without
+    /// overlapping with the restored ones.
+    pub fn with_assignment(&mut self, state: AddressorState) -> &mut Self {
+        self.assignment = Some(state);
+        self
+    }
 }
 
 impl DefaultAddressorBuilder {
@@ -144,72 +156,216 @@ impl DefaultAddressorBuilder {
     }
 }
 
-/// The default IP addressor uses.
+/// The complete assignment of a [`DefaultAddressor`], including all router networks, link
+/// networks, interfaces, prefix equivalence classes, and interface names. Use
+/// [`DefaultAddressor::export_assignment`] to get the state, and
+/// [`DefaultAddressorBuilder::with_assignment`] to restore it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressorState<N: IpNetwork = Ipv4Net> {
+    /// Assigned networks and addresses of routers
+    pub router_addrs: HashMap<RouterId, (N, N::Addr)>,
+    /// Assigned networks of links
+    #[serde(with = "As::<Vec<(Same, Same)>>")]
+    pub link_addrs: HashMap<LinkId, N>,
+    /// Assigned interfaces of routers
+    pub interfaces: HashMap<RouterId, HashMap<RouterId, (usize, N::Addr)>>,
+    /// Prefix equivalence classes
+    pub pecs: Vec<(Ipv4Net, Vec<N>)>,
+    /// Interface names of each router
+    pub iface_names: HashMap<RouterId, Vec<String>>,
+}
+
+impl<N: IpNetwork> Default for AddressorState<N> {
+    fn default() -> Self {
+        Self {
+            router_addrs: Default::default(),
+            link_addrs: Default::default(),
+            interfaces: Default::default(),
+            pecs: Default::default(),
+            iface_names: Default::default(),
+        }
+    }
+}
+
+/// The default IP addressor. It is generic over the address family `N` (see [`IpNetwork`]), and
+/// uses IPv4 by default.
 #[derive(Debug, Clone)]
-pub struct DefaultAddressor<'a, P: Prefix, Q> {
+pub struct DefaultAddressor<'a, P: Prefix, Q, N: IpNetwork = Ipv4Net> {
     net: &'a Network<P, Q>,
     /// The internal netowrk
-    internal_ip_range: Ipv4Net,
+    internal_ip_range: N,
     /// the ip range for external networks
-    external_ip_range: Ipv4Net,
+    external_ip_range: N,
     /// Iterator over all networks of internal routers
-    internal_router_addr_iter: Ipv4Subnets,
+    internal_router_addr_iter: N::Subnets,
     /// Iterator over all internal link networks
-    internal_link_addr_iter: Ipv4Subnets,
+    internal_link_addr_iter: N::Subnets,
     /// Iterator over all external link networks
-    external_link_addr_iter: Ipv4Subnets,
+    external_link_addr_iter: N::Subnets,
     /// Iterator over all networks of external AS Ids
-    external_as_addr_iter: Ipv4Subnets,
+    external_as_addr_iter: N::Subnets,
     /// Iterator over all external router networks for each external AS.
-    external_router_addr_iters: HashMap<AsId, Ipv4Subnets>,
+    external_router_addr_iters: HashMap<AsId, N::Subnets>,
     /// prefix length of external routers
     external_router_prefix_len: u8,
     /// Already assigned prefixes to routers
-    router_addrs: HashMap<RouterId, (Ipv4Net, Ipv4Addr)>,
+    router_addrs: HashMap<RouterId, (N, N::Addr)>,
     /// Already assigned prefix addresses for links
-    link_addrs: HashMap<LinkId, Ipv4Net>,
+    link_addrs: HashMap<LinkId, N>,
     /// Assigned interfaces of routers
-    interfaces: HashMap<RouterId, HashMap<RouterId, (usize, Ipv4Addr)>>,
+    interfaces: HashMap<RouterId, HashMap<RouterId, (usize, N::Addr)>>,
     /// Prefix equivalence classes
-    pecs: P::Map<Vec<Ipv4Net>>,
+    pecs: P::Map<Vec<N>>,
+    /// Interface names of each router
+    iface_names: HashMap<RouterId, Vec<String>>,
 }
 
 impl<'a, P: Prefix, Q> DefaultAddressor<'a, P, Q> {
     /// Create a new Default IP Addressor. Use the [`DefaultAddressorBuilder`] to generate the parameters.
-    #[allow(clippy::too_many_arguments)]
     pub fn new(
         net: &'a Network<P, Q>,
         args: &DefaultAddressorBuilder,
     ) -> Result<Self, ExportError> {
-        let mut internal_halves = args
-            .internal_ip_range
-            .subnets(args.internal_ip_range.prefix_len() + 1)?;
+        let mut this = Self::from_ranges(
+            net,
+            args.internal_ip_range,
+            args.external_ip_range,
+            args.local_prefix_len,
+            args.link_prefix_len,
+            args.external_prefix_len,
+        )?;
+        if let Some(state) = args.assignment.as_ref() {
+            this.restore_assignment(state.clone(), args.external_prefix_len)?;
+        }
+        Ok(this)
+    }
+}
+
+impl<'a, P: Prefix, Q, N: IpNetwork> DefaultAddressor<'a, P, Q, N> {
+    /// Create a new addressor that splits the `internal_ip_range` as described in
+    /// [`DefaultAddressorBuilder::internal_ip_range`].
+    pub(super) fn from_ranges(
+        net: &'a Network<P, Q>,
+        internal_ip_range: N,
+        external_ip_range: N,
+        local_prefix_len: u8,
+        link_prefix_len: u8,
+        external_prefix_len: u8,
+    ) -> Result<Self, ExportError> {
+        let mut internal_halves = internal_ip_range.subnets(internal_ip_range.prefix_len() + 1)?;
         let internal_router_addr_range = ip_err(internal_halves.next())?;
         let mut third_and_forth_quarter =
-            ip_err(internal_halves.next())?.subnets(args.internal_ip_range.prefix_len() + 2)?;
+            ip_err(internal_halves.next())?.subnets(internal_ip_range.prefix_len() + 2)?;
         let internal_link_addr_range = ip_err(third_and_forth_quarter.next())?;
         let external_link_addr_range = ip_err(third_and_forth_quarter.next())?;
         Ok(Self {
             net,
-            internal_ip_range: args.internal_ip_range,
-            external_ip_range: args.external_ip_range,
-            internal_router_addr_iter: internal_router_addr_range.subnets(args.local_prefix_len)?,
-            internal_link_addr_iter: internal_link_addr_range.subnets(args.link_prefix_len)?,
-            external_link_addr_iter: external_link_addr_range.subnets(args.link_prefix_len)?,
-            external_as_addr_iter: args.external_ip_range.subnets(args.external_prefix_len)?,
+            internal_ip_range,
+            external_ip_range,
+            internal_router_addr_iter: internal_router_addr_range.subnets(local_prefix_len)?,
+            internal_link_addr_iter: internal_link_addr_range.subnets(link_prefix_len)?,
+            external_link_addr_iter: external_link_addr_range.subnets(link_prefix_len)?,
+            external_as_addr_iter: external_ip_range.subnets(external_prefix_len)?,
             external_router_addr_iters: HashMap::new(),
-            external_router_prefix_len: args.local_prefix_len,
+            external_router_prefix_len: local_prefix_len,
             router_addrs: HashMap::new(),
             link_addrs: HashMap::new(),
             interfaces: HashMap::new(),
             pecs: Default::default(),
+            iface_names: HashMap::new(),
         })
     }
+
+    /// Restore the assignment from `state`, and advance all iterators past the networks that are
+    /// already assigned. Networks are assigned in order, so it suffices to count the assigned
+    /// networks within each range.
+    fn restore_assignment(
+        &mut self,
+        state: AddressorState<N>,
+        external_prefix_len: u8,
+    ) -> Result<(), ExportError> {
+        fn skip<I: Iterator>(iter: &mut I, n: usize) -> Result<(), ExportError> {
+            if n > 0 {
+                ip_err(iter.nth(n - 1))?;
+            }
+            Ok(())
+        }
+
+        let internal_routers = self.subnet_for_internal_routers();
+        let internal_links = self.subnet_for_internal_links();
+        let external_links = self.subnet_for_external_links();
+
+        // count the external routers of each AS
+        let mut as_nets: HashMap<AsId, (N, usize)> = HashMap::new();
+        for (router, (net, _)) in state.router_addrs.iter() {
+            if let Some(r) = self.net.get_device(*router).external() {
+                let as_net = N::new(net.addr(), external_prefix_len)?.trunc();
+                as_nets.entry(r.as_id()).or_insert((as_net, 0)).1 += 1;
+            }
+        }
+
+        let num_internal_routers = state
+            .router_addrs
+            .values()
+            .filter(|(net, _)| internal_routers.contains_net(net))
+            .count();
+        skip(&mut self.internal_router_addr_iter, num_internal_routers)?;
+        skip(
+            &mut self.internal_link_addr_iter,
+            state
+                .link_addrs
+                .values()
+                .filter(|net| internal_links.contains_net(net))
+                .count(),
+        )?;
+        skip(
+            &mut self.external_link_addr_iter,
+            state
+                .link_addrs
+                .values()
+                .filter(|net| external_links.contains_net(net))
+                .count(),
+        )?;
+        skip(&mut self.external_as_addr_iter, as_nets.len())?;
+        for (as_id, (as_net, n)) in as_nets {
+            let mut iter = as_net.subnets(self.external_router_prefix_len)?;
+            skip(&mut iter, n)?;
+            self.external_router_addr_iters.insert(as_id, iter);
+        }
+
+        self.router_addrs = state.router_addrs;
+        self.link_addrs = state.link_addrs;
+        self.interfaces = state.interfaces;
+        self.pecs = state
+            .pecs
+            .into_iter()
+            .map(|(p, nets)| (P::from(p), nets))
+            .collect();
+        self.iface_names = state.iface_names;
+
+        Ok(())
+    }
+
+    /// Export the complete assignment of the addressor, such that it can be stored and restored
+    /// later using [`DefaultAddressorBuilder::with_assignment`].
+    pub fn export_assignment(&self) -> AddressorState<N> {
+        AddressorState {
+            router_addrs: self.router_addrs.clone(),
+            link_addrs: self.link_addrs.clone(),
+            interfaces: self.interfaces.clone(),
+            pecs: self
+                .pecs
+                .iter()
+                .map(|(p, nets)| ((*p).into(), nets.clone()))
+                .collect(),
+            iface_names: self.iface_names.clone(),
+        }
+    }
 }
 
-impl<'a, P: Prefix, Q> DefaultAddressor<'a, P, Q> {
+impl<'a, P: Prefix, Q, N: IpNetwork> DefaultAddressor<'a, P, Q, N> {
     /// Get the subnet reserved for internal routers.
-    pub fn subnet_for_internal_routers(&self) -> Ipv4Net {
+    pub fn subnet_for_internal_routers(&self) -> N {
         // unwrapping here is allowed, we have already done this operation successfully.
         self.internal_ip_range
             .subnets(self.internal_ip_range.prefix_len() + 1)
@@ -219,12 +375,12 @@ impl<'a, P: Prefix, Q> DefaultAddressor<'a, P, Q> {
     }
 
     /// Get the subnet reserved for internal routers.
-    pub fn subnet_for_external_routers(&self) -> Ipv4Net {
+    pub fn subnet_for_external_routers(&self) -> N {
         self.external_ip_range
     }
 
     /// Get the subnet reserved for internal links
-    pub fn subnet_for_internal_links(&self) -> Ipv4Net {
+    pub fn subnet_for_internal_links(&self) -> N {
         // unwrapping here is allowed, we have already done this operation successfully.
         self.internal_ip_range
             .subnets(self.internal_ip_range.prefix_len() + 2)
@@ -234,7 +390,7 @@ impl<'a, P: Prefix, Q> DefaultAddressor<'a, P, Q> {
     }
 
     /// Get the subnet reserved for external links
-    pub fn subnet_for_external_links(&self) -> Ipv4Net {
+    pub fn subnet_for_external_links(&self) -> N {
         self.internal_ip_range
             .subnets(self.internal_ip_range.prefix_len() + 2)
             .unwrap()
@@ -243,16 +399,16 @@ impl<'a, P: Prefix, Q> DefaultAddressor<'a, P, Q> {
     }
 }
 
-impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
-    fn internal_network(&mut self) -> Ipv4Net {
+impl<'a, P: Prefix, Q, N: IpNetwork> Addressor<P, N> for DefaultAddressor<'a, P, Q, N> {
+    fn internal_network(&mut self) -> N {
         self.internal_ip_range
     }
 
-    fn try_get_router(&self, router: RouterId) -> Option<(Ipv4Net, Ipv4Addr)> {
+    fn try_get_router(&self, router: RouterId) -> Option<(N, N::Addr)> {
         self.router_addrs.get(&router).copied()
     }
 
-    fn router(&mut self, router: RouterId) -> Result<(Ipv4Net, Ipv4Addr), ExportError> {
+    fn router(&mut self, router: RouterId) -> Result<(N, N::Addr), ExportError> {
         Ok(match self.router_addrs.entry(router) {
             Entry::Occupied(e) => *e.get(),
             Entry::Vacant(e) => {
@@ -269,27 +425,29 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
                 } else {
                     self.internal_router_addr_iter.next()
                 })?;
-                let addr = ip_err(net.hosts().next())?;
+                let addr = ip_err(net.router_addr())?;
                 *e.insert((net, addr))
             }
         })
     }
 
-    fn register_pec(&mut self, pec: P, prefixes: Vec<Ipv4Net>)
+    fn register_pec(&mut self, pec: P, prefixes: Vec<N>)
     where
         P: NonOverlappingPrefix,
     {
         self.pecs.insert(pec, prefixes);
     }
 
-    fn get_pecs(&self) -> &P::Map<Vec<Ipv4Net>> {
+    fn get_pecs(&self) -> &P::Map<Vec<N>> {
         &self.pecs
     }
 
-    fn prefix(&mut self, prefix: P) -> Result<MaybePec<Ipv4Net>, ExportError> {
-        let get_net = |net: Ipv4Net| {
-            if self.internal_ip_range.contains(&net) || self.external_ip_range.contains(&net) {
-                Err(ExportError::PrefixWithinReservedIpRange(net))
+    fn prefix(&mut self, prefix: P) -> Result<MaybePec<N>, ExportError> {
+        let get_net = |net: N| {
+            if self.internal_ip_range.contains_net(&net)
+                || self.external_ip_range.contains_net(&net)
+            {
+                Err(ExportError::PrefixWithinReservedIpRange(net.into()))
             } else {
                 Ok(net)
             }
@@ -304,7 +462,8 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
                     .collect::<Result<_, ExportError>>()?,
             ))
         } else {
-            Ok(MaybePec::Single(get_net(prefix.into())?))
+            let net = N::from_ipv4_prefix(prefix.into())?;
+            Ok(MaybePec::Single(get_net(net)?))
         }
     }
 
@@ -312,7 +471,7 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
         &self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Option<Result<(Ipv4Addr, Ipv4Net, usize), ExportError>> {
+    ) -> Option<Result<(N::Addr, N, usize), ExportError>> {
         let err = || ExportError::RouterNotConnectedTo(router, neighbor);
         let link = LinkId::from((router, neighbor));
         self.link_addrs.get(&link).map(|net| {
@@ -332,7 +491,7 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
         &mut self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Result<(Ipv4Addr, Ipv4Net, usize), ExportError> {
+    ) -> Result<(N::Addr, N, usize), ExportError> {
         // first, check if the network is present
         let err = || ExportError::RouterNotConnectedTo(router, neighbor);
         let link = LinkId::from((router, neighbor));
@@ -370,7 +529,7 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
         })
     }
 
-    fn list_ifaces(&self, router: RouterId) -> Vec<(RouterId, Ipv4Addr, Ipv4Net, usize)> {
+    fn list_ifaces(&self, router: RouterId) -> Vec<(RouterId, N::Addr, N, usize)> {
         self.interfaces
             .get(&router)
             .into_iter()
@@ -406,15 +565,15 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
         links
     }
 
-    fn find_address(&self, address: impl Into<Ipv4Net>) -> Result<RouterId, ExportError> {
-        let address: Ipv4Net = address.into();
+    fn find_address(&self, address: impl Into<N>) -> Result<RouterId, ExportError> {
+        let address: N = address.into();
         let ip = address.addr();
-        let net = Ipv4Net::new(address.network(), address.prefix_len())?;
+        let net = N::new(address.network(), address.prefix_len())?;
         // first, check if any router uses that address
         if let Some((r, _)) = self
             .router_addrs
             .iter()
-            .find(|(_, (x, _))| x.contains(&net))
+            .find(|(_, (x, _))| x.contains_net(&net))
         {
             return Ok(*r);
         }
@@ -427,7 +586,7 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
             return Ok(*r);
         }
         // Finally, check if the address belongs to an interface
-        if let Some((link, _)) = self.link_addrs.iter().find(|(_, x)| x.contains(&net)) {
+        if let Some((link, _)) = self.link_addrs.iter().find(|(_, x)| x.contains_net(&net)) {
             let (a, b) = (link.0, link.1);
             let a_int = self.net.get_device(a).is_internal();
             let b_int = self.net.get_device(b).is_internal();
@@ -446,20 +605,20 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
             };
         }
 
-        Err(ExportError::AddressNotFound(address))
+        Err(ExportError::AddressNotFound(address.into()))
     }
 
     fn find_next_hop(
         &self,
         router: RouterId,
-        address: impl Into<Ipv4Net>,
+        address: impl Into<N>,
     ) -> Result<RouterId, ExportError> {
-        let address: Ipv4Net = address.into();
+        let address: N = address.into();
 
         if let Some((neighbor, _)) = self
             .router_addrs
             .iter()
-            .find(|(_, (net, _))| net.contains(&address))
+            .find(|(_, (net, _))| net.contains_net(&address))
         {
             // check if the neighbor is adjacent to router
             if self
@@ -483,10 +642,10 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
             .find(|n| {
                 self.link_addrs
                     .get(&(router, *n).into())
-                    .map(|net| net.contains(&address))
+                    .map(|net| net.contains_net(&address))
                     .unwrap_or(false)
             })
-            .ok_or(ExportError::AddressNotFound(address))
+            .ok_or(ExportError::AddressNotFound(address.into()))
     }
 
     fn find_neighbor(&self, router: RouterId, iface_idx: usize) -> Result<RouterId, ExportError> {
@@ -498,6 +657,19 @@ impl<'a, P: Prefix, Q> Addressor<P> for DefaultAddressor<'a, P, Q> {
             .map(|(x, _)| *x)
             .ok_or_else(|| ExportError::InterfaceNotFound(router, format!("at {iface_idx}")))
     }
+
+    fn register_iface_names(&mut self, router: RouterId, ifaces: Vec<String>) {
+        self.iface_names.insert(router, ifaces);
+    }
+
+    fn find_iface_by_name(&self, router: RouterId, iface: &str) -> Result<RouterId, ExportError> {
+        let iface_idx = self
+            .iface_names
+            .get(&router)
+            .and_then(|names| names.iter().position(|x| x == iface))
+            .ok_or_else(|| ExportError::InterfaceNotFound(router, iface.to_string()))?;
+        self.find_neighbor(router, iface_idx)
+    }
 }
 
 #[cfg(test)]
@@ -507,7 +679,7 @@ mod test {
     use crate::{
         builder::NetworkBuilder,
         event::BasicEventQueue,
-        export::{Addressor, DefaultAddressorBuilder},
+        export::{Addressor, AddressorState, DefaultAddressorBuilder},
         network::Network,
         types::SinglePrefix as P,
     };
@@ -613,6 +785,50 @@ mod test {
         }
     }
 
+    #[test]
+    fn restore_assignment() {
+        let mut net: Network<P, _> =
+            NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+        net.build_external_routers(|_, _| vec![0.into(), 1.into()], ())
+            .unwrap();
+
+        let mut ip = DefaultAddressorBuilder::default().build(&net).unwrap();
+        ip.router(0.into()).unwrap();
+        ip.router(2.into()).unwrap();
+        ip.router(4.into()).unwrap();
+        ip.iface(0.into(), 1.into()).unwrap();
+        ip.iface(0.into(), 4.into()).unwrap();
+        ip.iface(3.into(), 1.into()).unwrap();
+        ip.register_iface_names(0.into(), vec!["eth0".to_string(), "eth1".to_string()]);
+
+        let state = ip.export_assignment();
+        let state: AddressorState =
+            serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        let mut restored = DefaultAddressorBuilder::default()
+            .with_assignment(state)
+            .build(&net)
+            .unwrap();
+        assert_eq!(restored.export_assignment(), ip.export_assignment());
+
+        // both already assigned and new addresses must be identical.
+        for r in 0..6 {
+            assert_eq!(
+                restored.router(r.into()).unwrap(),
+                ip.router(r.into()).unwrap()
+            );
+        }
+        for (a, b) in [(0, 1), (1, 0), (0, 4), (3, 1), (1, 2), (1, 5), (5, 1)] {
+            assert_eq!(
+                restored.iface(a.into(), b.into()).unwrap(),
+                ip.iface(a.into(), b.into()).unwrap()
+            );
+        }
+        assert_eq!(
+            restored.find_iface_by_name(0.into(), "eth1").unwrap(),
+            ip.find_iface_by_name(0.into(), "eth1").unwrap()
+        );
+    }
+
     #[test]
     fn reverse_ip_addressor() {
         let mut net: Network<P, _> =
@@ -711,4 +927,44 @@ mod test {
         finds_neighbor!(ip, 3, 0, 0);
         finds_neighbor!(ip, 3, 1, 1);
     }
+
+    #[test]
+    fn find_iface_by_name() {
+        let mut net: Network<P, _> =
+            NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+        net.build_external_routers(|_, _| vec![0.into(), 1.into()], ())
+            .unwrap();
+
+        let mut ip = DefaultAddressorBuilder::default().build(&net).unwrap();
+
+        ip.iface_address(0.into(), 1.into()).unwrap();
+        ip.iface_address(0.into(), 2.into()).unwrap();
+        ip.iface_address(0.into(), 4.into()).unwrap();
+        ip.iface_address(1.into(), 2.into()).unwrap();
+
+        // interface names are not yet registered
+        assert!(ip.find_iface_by_name(0.into(), "Ethernet3/1").is_err());
+
+        let ifaces = (1..=4).map(|i| format!("Ethernet3/{i}")).collect();
+        ip.register_iface_names(0.into(), ifaces);
+
+        assert_eq!(
+            ip.find_iface_by_name(0.into(), "Ethernet3/1").unwrap(),
+            1.into()
+        );
+        assert_eq!(
+            ip.find_iface_by_name(0.into(), "Ethernet3/2").unwrap(),
+            2.into()
+        );
+        assert_eq!(
+            ip.find_iface_by_name(0.into(), "Ethernet3/3").unwrap(),
+            4.into()
+        );
+        // interface exists, but is not connected
+        assert!(ip.find_iface_by_name(0.into(), "Ethernet3/4").is_err());
+        // interface does not exist
+        assert!(ip.find_iface_by_name(0.into(), "Ethernet4/1").is_err());
+        // names of router 1 are not registered
+        assert!(ip.find_iface_by_name(1.into(), "Ethernet3/1").is_err());
+    }
 }
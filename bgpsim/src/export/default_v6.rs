@@ -0,0 +1,232 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! This module describes the default IPv6 addressor.
+
+use ipnet::Ipv6Net;
+
+use super::{DefaultAddressor, ExportError};
+use crate::{network::Network, types::Prefix};
+
+/// Builder for the default IPv6 addressor. The following are the default arguments:
+///
+/// - `internal_ip_range`: "fd00:1::/32"
+/// - `external_ip_range`: "fd00:2::/32"
+/// - `local_prefix_len`: 64,
+/// - `link_prefix_len`: 127,
+/// - `external_prefix_len`: 48,
+#[derive(Debug, Clone)]
+pub struct DefaultAddressorV6Builder {
+    /// The IP Address range for the internal network. The default value is `fd00:1::/32`. This
+    /// space is split in the same way as for the [`super::DefaultAddressorBuilder`]: The first half
+    /// is used for internal routers, the third quarter for internal links, and the fourth quarter
+    /// for external links.
+    pub internal_ip_range: Ipv6Net,
+    /// The IP Address range for the external routers (used as loopback address). The default value
+    /// is `fd00:2::/32`.
+    pub external_ip_range: Ipv6Net,
+    /// Prefix length of internal networks (used as loopback networks). The default value is `64`.
+    pub local_prefix_len: u8,
+    /// The prefix length of all link networks. The default value is `127`.
+    pub link_prefix_len: u8,
+    /// The prefix length for external networks (the loopback network of external routers). The
+    /// default value is 48.
+    pub external_prefix_len: u8,
+}
+
+impl Default for DefaultAddressorV6Builder {
+    fn default() -> Self {
+        Self {
+            internal_ip_range: "fd00:1::/32".parse().unwrap(),
+            external_ip_range: "fd00:2::/32".parse().unwrap(),
+            local_prefix_len: 64,
+            link_prefix_len: 127,
+            external_prefix_len: 48,
+        }
+    }
+}
+
+impl DefaultAddressorV6Builder {
+    /// Create a new addressor builder
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The IP Address range for the internal network. The default value is `fd00:1::/32`.
+    pub fn internal_ip_range(&mut self, x: Ipv6Net) -> &mut Self {
+        self.internal_ip_range = x;
+        self
+    }
+
+    /// The IP Address range for the external routers (used as loopback address). The default value
+    /// is `fd00:2::/32`.
+    pub fn external_ip_range(&mut self, x: Ipv6Net) -> &mut Self {
+        self.external_ip_range = x;
+        self
+    }
+
+    /// Prefix length of internal networks (used as loopback networks). The default value is `64`.
+    pub fn local_prefix_len(&mut self, x: u8) -> &mut Self {
+        self.local_prefix_len = x;
+        self
+    }
+
+    /// The prefix length of all link networks. The default value is `127`.
+    pub fn link_prefix_len(&mut self, x: u8) -> &mut Self {
+        self.link_prefix_len = x;
+        self
+    }
+
+    /// The prefix length for external networks (the loopback network of external routers). The
+    /// default value is 48.
+    pub fn external_prefix_len(&mut self, x: u8) -> &mut Self {
+        self.external_prefix_len = x;
+        self
+    }
+
+    /// Generate the default IPv6 addressor from the given parameters.
+    pub fn build<'a, P: Prefix, Q>(
+        &self,
+        net: &'a Network<P, Q>,
+    ) -> Result<DefaultAddressorV6<'a, P, Q>, ExportError> {
+        DefaultAddressor::from_ranges(
+            net,
+            self.internal_ip_range,
+            self.external_ip_range,
+            self.local_prefix_len,
+            self.link_prefix_len,
+            self.external_prefix_len,
+        )
+    }
+}
+
+/// The default IPv6 addressor. It assigns addresses in the same way as the
+/// [`super::DefaultAddressor`], but for IPv6. Prefixes that are not registered as a prefix
+/// equivalence class are mapped to their IPv4-mapped IPv6 network (`::ffff:0:0/96`).
+pub type DefaultAddressorV6<'a, P, Q> = DefaultAddressor<'a, P, Q, Ipv6Net>;
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv6Addr;
+
+    use crate::{
+        builder::NetworkBuilder,
+        event::BasicEventQueue,
+        export::{Addressor, DefaultAddressorV6Builder, MaybePec},
+        network::Network,
+        types::{RouterId, SimplePrefix as P},
+    };
+
+    use ipnet::Ipv6Net;
+    use itertools::Itertools;
+
+    macro_rules! cmp_addr {
+        ($acq:expr, $exp:expr) => {
+            pretty_assertions::assert_eq!($acq.unwrap(), $exp.parse::<Ipv6Addr>().unwrap())
+        };
+    }
+
+    macro_rules! cmp_net {
+        ($acq:expr, $exp:expr) => {
+            pretty_assertions::assert_eq!($acq.unwrap(), $exp.parse::<Ipv6Net>().unwrap())
+        };
+    }
+
+    #[test]
+    fn ip_addressor_v6() {
+        let mut net: Network<P, _> =
+            NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+        net.build_external_routers(|_, _| vec![0.into(), 1.into()], ())
+            .unwrap();
+
+        let mut ip = DefaultAddressorV6Builder::default().build(&net).unwrap();
+
+        for _ in 0..=1 {
+            cmp_addr!(ip.router_address(0.into()), "fd00:1::1");
+            cmp_addr!(ip.router_address(1.into()), "fd00:1:0:1::1");
+            cmp_addr!(ip.router_address(4.into()), "fd00:2::1");
+            cmp_addr!(ip.router_address(5.into()), "fd00:2:1::1");
+            cmp_net!(ip.router_network(0.into()), "fd00:1::/64");
+            cmp_net!(ip.router_network(1.into()), "fd00:1:0:1::/64");
+            cmp_net!(ip.router_network(4.into()), "fd00:2::/64");
+            cmp_net!(ip.router_network(5.into()), "fd00:2:1::/64");
+        }
+
+        for _ in 0..=1 {
+            cmp_addr!(ip.iface_address(0.into(), 1.into()), "fd00:1:8000::");
+            cmp_addr!(ip.iface_address(1.into(), 0.into()), "fd00:1:8000::1");
+            cmp_addr!(ip.iface_address(0.into(), 4.into()), "fd00:1:c000::");
+            cmp_addr!(ip.iface_address(4.into(), 0.into()), "fd00:1:c000::1");
+            cmp_net!(ip.iface_network(0.into(), 1.into()), "fd00:1:8000::/127");
+            cmp_net!(ip.iface_network(1.into(), 0.into()), "fd00:1:8000::/127");
+            cmp_net!(ip.iface_network(0.into(), 4.into()), "fd00:1:c000::/127");
+        }
+
+        assert_eq!(ip.find_neighbor(0.into(), 0).unwrap(), 1.into());
+        assert_eq!(ip.find_neighbor(0.into(), 1).unwrap(), 4.into());
+    }
+
+    #[test]
+    fn ip_addressor_v6_no_overlap() {
+        let mut net: Network<P, _> =
+            NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 5);
+        net.build_external_routers(|_, _| vec![0.into(), 1.into(), 2.into()], ())
+            .unwrap();
+
+        let mut ip = DefaultAddressorV6Builder::default().build(&net).unwrap();
+
+        let routers: Vec<RouterId> = net.get_topology().node_indices().collect();
+        let mut networks = Vec::new();
+        for r in routers.iter() {
+            networks.push(ip.router_network(*r).unwrap());
+        }
+        for (a, b) in routers.iter().tuple_combinations() {
+            if net.get_topology().contains_edge(*a, *b) {
+                networks.push(ip.iface_network(*a, *b).unwrap());
+                assert_ne!(
+                    ip.iface_address(*a, *b).unwrap(),
+                    ip.iface_address(*b, *a).unwrap()
+                );
+            }
+        }
+
+        for (x, y) in networks.iter().tuple_combinations() {
+            assert!(!x.contains(y) && !y.contains(x), "{x} and {y} overlap!");
+        }
+    }
+
+    #[test]
+    fn ip_addressor_v6_pec() {
+        let net: Network<P, BasicEventQueue<P>> =
+            NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 2);
+        let mut ip = DefaultAddressorV6Builder::default().build(&net).unwrap();
+
+        let pec: Vec<Ipv6Net> = vec![
+            "2001:db8:0:1::/64".parse().unwrap(),
+            "2001:db8:0:2::/64".parse().unwrap(),
+        ];
+        ip.register_pec(P::from(0), pec.clone());
+        assert_eq!(
+            ip.prefix(P::from(0)).unwrap(),
+            MaybePec::Pec(P::from(0).into(), pec)
+        );
+        assert_eq!(
+            ip.prefix(P::from(1)).unwrap(),
+            MaybePec::Single("::ffff:100.0.1.0/120".parse().unwrap())
+        );
+    }
+}
@@ -188,6 +188,16 @@ pub struct ExaBgpCfgGen<P: Prefix> {
     routes: BTreeMap<P, BTreeMap<Duration, Option<BgpRoute<P>>>>,
     neighbors: BTreeSet<RouterId>,
     current_time: Duration,
+    /// Time steps at which all announcements with equal attributes are grouped into a single
+    /// command.
+    batched: BTreeSet<Duration>,
+    /// Whether to announce routes with `next-hop self`.
+    next_hop_self: bool,
+    /// Session flaps, stored as `(neighbor, down_at, up_at)`. No updates are sent to `neighbor`
+    /// between `down_at` and `up_at`.
+    flaps: Vec<(RouterId, Duration, Duration)>,
+    /// Whether to advertise the graceful-restart capability.
+    graceful_restart: bool,
 }
 
 use ipnet::Ipv4Net;
@@ -211,9 +221,41 @@ impl<P: Prefix> ExaBgpCfgGen<P> {
                 .collect(),
             neighbors: r.neighbors.iter().copied().collect(),
             current_time: Duration::ZERO,
+            batched: BTreeSet::new(),
+            next_hop_self: true,
+            flaps: Vec::new(),
+            graceful_restart: false,
         })
     }
 
+    /// Configure whether routes are announced with `next-hop self` (which is the default). If
+    /// set to `false`, the announcement to each neighbor will explicitly use the address of the
+    /// interface of the external router towards that neighbor as next-hop. In that case, each
+    /// update is sent to each neighbor individually.
+    pub fn set_next_hop_self(&mut self, next_hop_self: bool) {
+        self.next_hop_self = next_hop_self;
+    }
+
+    /// Configure whether the sessions advertise the graceful-restart capability (which is disabled
+    /// by default). If set to `true`, the `capability` block of each neighbor will contain
+    /// `graceful-restart;`.
+    pub fn set_graceful_restart(&mut self, graceful_restart: bool) {
+        self.graceful_restart = graceful_restart;
+    }
+
+    /// Get the text describing the next-hop of an announcement sent to `neighbor`.
+    fn next_hop<A: Addressor<P>>(
+        &self,
+        addressor: &mut A,
+        neighbor: RouterId,
+    ) -> Result<String, ExportError> {
+        if self.next_hop_self {
+            Ok(String::from("self"))
+        } else {
+            Ok(addressor.iface_address(self.router, neighbor)?.to_string())
+        }
+    }
+
     /// Increase the `current_time` by the given amount.
     ///
     /// After creating a new instance of `ExaBgpCfgGen`, the `current_time` will be set to 0.
@@ -241,14 +283,30 @@ impl<P: Prefix> ExaBgpCfgGen<P> {
         &self,
         addressor: &mut A,
     ) -> Result<Vec<(Vec<String>, Duration)>, ExportError> {
-        let neighbors = self
+        let neighbor_addrs = self
             .neighbors
             .iter()
-            .map(|x| addressor.iface_address(*x, self.router))
-            .collect::<Result<Vec<Ipv4Addr>, ExportError>>()?
-            .into_iter()
-            .map(|x| format!("neighbor {x}"))
-            .join(", ");
+            .map(|x| Ok((*x, addressor.iface_address(*x, self.router)?)))
+            .collect::<Result<Vec<(RouterId, Ipv4Addr)>, ExportError>>()?;
+        // Group the neighbors to which updates are sent together, along with the neighbor used to
+        // determine the next-hop of the group. Only send updates to neighbors whose session is
+        // not down at the given time. With an explicit next-hop, each neighbor needs a different
+        // next-hop, and thus, a separate group.
+        let neighbors_at = |time: Duration| -> Vec<(String, RouterId)> {
+            let up = neighbor_addrs
+                .iter()
+                .filter(|(n, _)| !self.is_down(*n, time))
+                .map(|(n, x)| (format!("neighbor {x}"), *n));
+            if self.next_hop_self {
+                let up = up.collect::<Vec<_>>();
+                match up.first() {
+                    Some((_, n)) => vec![(up.iter().map(|(x, _)| x).join(", "), *n)],
+                    None => Vec::new(),
+                }
+            } else {
+                up.collect()
+            }
+        };
 
         let mut result = Vec::new();
 
@@ -263,28 +321,146 @@ impl<P: Prefix> ExaBgpCfgGen<P> {
         }
 
         for (time, routes) in times_routes {
+            let groups = neighbors_at(time);
+            if groups.is_empty() {
+                continue;
+            }
             let mut ads: Vec<String> = Vec::new();
-            for (p, r) in routes {
-                for net in addressor.prefix(p)? {
-                    if let Some(r) = r {
+            for (neighbors, neighbor) in groups {
+                let next_hop = self.next_hop(addressor, neighbor)?;
+                if self.batched.contains(&time) {
+                    // group all announcements with the same attributes
+                    let mut groups: Vec<(String, Vec<Ipv4Net>)> = Vec::new();
+                    for (p, r) in routes.iter() {
+                        for net in addressor.prefix(*p)? {
+                            if let Some(r) = r {
+                                let attrs = route_attributes(r, &next_hop);
+                                match groups.iter_mut().find(|(a, _)| *a == attrs) {
+                                    Some((_, nets)) => nets.push(net),
+                                    None => groups.push((attrs, vec![net])),
+                                }
+                            } else {
+                                ads.push(format!(
+                                    "sys.stdout.write(\"{neighbors} withdraw route {net}\\n\")",
+                                ))
+                            }
+                        }
+                    }
+                    for (attrs, nets) in groups {
                         ads.push(format!(
-                            "sys.stdout.write(\"{} {}\\n\")",
+                            "sys.stdout.write(\"{} announce attributes {} nlri {}\\n\")",
                             neighbors,
-                            route_text(r, net)?
-                        ))
-                    } else {
-                        ads.push(format!(
-                            "sys.stdout.write(\"{neighbors} withdraw route {net}\\n\")",
+                            attrs,
+                            nets.iter().join(" ")
                         ))
                     }
+                    continue;
+                }
+                for (p, r) in routes.iter() {
+                    for net in addressor.prefix(*p)? {
+                        if let Some(r) = r {
+                            ads.push(format!(
+                                "sys.stdout.write(\"{} {}\\n\")",
+                                neighbors,
+                                route_text(r, net, &next_hop)?
+                            ))
+                        } else {
+                            ads.push(format!(
+                                "sys.stdout.write(\"{neighbors} withdraw route {net}\\n\")",
+                            ))
+                        }
+                    }
                 }
             }
             result.push((ads, time));
         }
 
+        if !self.flaps.is_empty() {
+            let mut timeline: BTreeMap<Duration, Vec<String>> =
+                result.into_iter().map(|(ads, time)| (time, ads)).collect();
+            for (neighbor, down_at, up_at) in self.flaps.iter() {
+                if !self.is_down(*neighbor, *down_at) {
+                    let ads = self.flap_withdraw_lines(addressor, *neighbor, *down_at)?;
+                    if !ads.is_empty() {
+                        timeline.entry(*down_at).or_default().extend(ads);
+                    }
+                }
+                if !self.is_down(*neighbor, *up_at) {
+                    let ads = self.readvertise_lines(addressor, *neighbor, *up_at)?;
+                    if !ads.is_empty() {
+                        timeline.entry(*up_at).or_default().extend(ads);
+                    }
+                }
+            }
+            result = timeline
+                .into_iter()
+                .map(|(time, ads)| (ads, time))
+                .collect();
+        }
+
         Ok(result)
     }
 
+    /// Check whether the session to `neighbor` is down at `time`, i.e., whether `time` lies
+    /// strictly between the start and the end of a flap of that session. Updates at the exact time
+    /// of the flap are still sent to `neighbor`.
+    fn is_down(&self, neighbor: RouterId, time: Duration) -> bool {
+        self.flaps
+            .iter()
+            .any(|(n, down_at, up_at)| *n == neighbor && *down_at < time && time < *up_at)
+    }
+
+    /// Generate the lines that withdraw all routes that are active at `time` from a single
+    /// `neighbor`.
+    fn flap_withdraw_lines<A: Addressor<P>>(
+        &self,
+        addressor: &mut A,
+        neighbor: RouterId,
+        time: Duration,
+    ) -> Result<Vec<String>, ExportError> {
+        let addr = addressor.iface_address(neighbor, self.router)?;
+        let mut ads = Vec::new();
+        for (p, routes) in self.routes.iter() {
+            if let Some((_, Some(_))) = routes.range(..=time).next_back() {
+                for net in addressor.prefix(*p)? {
+                    ads.push(format!(
+                        "sys.stdout.write(\"neighbor {addr} withdraw route {net}\\n\")"
+                    ));
+                }
+            }
+        }
+        Ok(ads)
+    }
+
+    /// Generate the lines that announce all routes that are active at `time` to a single
+    /// `neighbor`. Routes that change exactly at `time` are skipped, as they are already announced
+    /// to all neighbors.
+    fn readvertise_lines<A: Addressor<P>>(
+        &self,
+        addressor: &mut A,
+        neighbor: RouterId,
+        time: Duration,
+    ) -> Result<Vec<String>, ExportError> {
+        let addr = addressor.iface_address(neighbor, self.router)?;
+        let next_hop = self.next_hop(addressor, neighbor)?;
+        let mut ads = Vec::new();
+        for (p, routes) in self.routes.iter() {
+            if routes.contains_key(&time) {
+                continue;
+            }
+            if let Some((_, Some(r))) = routes.range(..=time).next_back() {
+                for net in addressor.prefix(*p)? {
+                    ads.push(format!(
+                        "sys.stdout.write(\"neighbor {} {}\\n\")",
+                        addr,
+                        route_text(r, net, &next_hop)?
+                    ));
+                }
+            }
+        }
+        Ok(ads)
+    }
+
     /// Generate the python script that does not loop, but trigger the events once. The header of
     /// the script is not generated!
     fn generate_script_no_loop<A: Addressor<P>>(
@@ -330,16 +506,64 @@ neighbor {} {{
     local-as {};
     peer-as {};
     family {{ ipv4 unicast; }}
-    capability {{ route-refresh; }}
+    capability {{ route-refresh;{} }}
 }}",
             addressor.iface_address(neighbor, self.router)?,
             addressor.router_address(self.router)?,
             addressor.iface_address(self.router, neighbor)?,
             self.as_id.0,
             INTERNAL_AS.0,
+            if self.graceful_restart {
+                " graceful-restart;"
+            } else {
+                ""
+            },
         ))
     }
 
+    /// Generate the commands for advertising multiple routes at once. All routes are advertised at
+    /// the `current_time`, and all announcements of that time step that share the same attributes
+    /// are grouped into a single `announce attributes ... nlri ...` command. This reduces the size
+    /// of the script and the processing time of ExaBGP for large prefix equivalence classes. Like
+    /// [`ExternalCfgGen::advertise_route`], this function returns the entire python script.
+    pub fn advertise_routes_batch<Q, A: Addressor<P>>(
+        &mut self,
+        _net: &Network<P, Q>,
+        addressor: &mut A,
+        routes: &[BgpRoute<P>],
+    ) -> Result<String, ExportError> {
+        for route in routes {
+            self.routes
+                .entry(route.prefix)
+                .or_default()
+                .insert(self.current_time, Some(route.clone()));
+        }
+        self.batched.insert(self.current_time);
+        self.generate_script(addressor)
+    }
+
+    /// Flap the eBGP session to `neighbor` for `down_for`. At the `current_time`, all routes that
+    /// are active at that time are withdrawn from `neighbor`. Until `down_for` has passed, no
+    /// announcements or withdrawals are sent to `neighbor`. Afterwards, all routes that are active
+    /// at that time are announced again to `neighbor`. The BGP session itself stays established,
+    /// since ExaBGP would immediately reconnect after a `teardown`. This function does not change
+    /// the `current_time`. Like [`ExternalCfgGen::advertise_route`], this function returns the
+    /// entire python script.
+    pub fn flap_session<Q, A: Addressor<P>>(
+        &mut self,
+        _net: &Network<P, Q>,
+        addressor: &mut A,
+        neighbor: RouterId,
+        down_for: Duration,
+    ) -> Result<String, ExportError> {
+        if !self.neighbors.contains(&neighbor) {
+            return Err(ExportError::RouterNotConnectedTo(self.router, neighbor));
+        }
+        self.flaps
+            .push((neighbor, self.current_time, self.current_time + down_for));
+        self.generate_script(addressor)
+    }
+
     /// Function to get all neighbors of that external router.
     pub fn neighbors(&self) -> &BTreeSet<RouterId> {
         &self.neighbors
@@ -347,10 +571,23 @@ neighbor {} {{
 }
 
 /// Get the text to announce a route.
-fn route_text<P: Prefix>(route: &BgpRoute<P>, address: Ipv4Net) -> Result<String, ExportError> {
+fn route_text<P: Prefix>(
+    route: &BgpRoute<P>,
+    address: Ipv4Net,
+    next_hop: &str,
+) -> Result<String, ExportError> {
     Ok(format!(
-        "announce route {} next-hop self as-path [{}]{}{}",
+        "announce route {} {}",
         address,
+        route_attributes(route, next_hop)
+    ))
+}
+
+/// Get the text describing all attributes of a route.
+fn route_attributes<P: Prefix>(route: &BgpRoute<P>, next_hop: &str) -> String {
+    format!(
+        "next-hop {} as-path [{}]{}{}",
+        next_hop,
         route.as_path.iter().map(|x| x.0).join(", "),
         if let Some(med) = route.med {
             format!(" metric {med}")
@@ -369,7 +606,7 @@ fn route_text<P: Prefix>(route: &BgpRoute<P>, address: Ipv4Net) -> Result<String
                     .join(", ")
             )
         },
-    ))
+    )
 }
 
 impl<P: Prefix, A: Addressor<P>, Q> ExternalCfgGen<P, Q, A> for ExaBgpCfgGen<P> {
@@ -0,0 +1,293 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Export an external router as a Juniper device, using JunOS `set`-style commands.
+
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools;
+
+use crate::{
+    bgp::BgpRoute,
+    network::Network,
+    types::{AsId, Prefix, RouterId},
+};
+
+use super::{Addressor, ExportError, ExternalCfgGen, INTERNAL_AS};
+
+/// Name of the BGP group that contains all sessions towards the internal network.
+const BGP_GROUP: &str = "internal-network";
+
+/// Config generator for an external router running JunOS. All commands are generated in the
+/// `set`-style, such that they can be pasted into the configuration mode (followed by `commit`).
+///
+/// Each advertised prefix is originated using a static `discard` route, and exported using a
+/// dedicated policy-statement `prefix-<id>` that sets the AS path, the MED, and the communities.
+/// Each community `<c>` is defined once as `comm-<c>`, and shared by all routes that carry it. It
+/// is only deleted once no advertised route uses it anymore. An advertisement for the prefix
+/// `100.0.0.0/24` with AS path `[100, 200, 300]`, MED 10, and community 20 will look as follows:
+///
+/// ```text
+/// set routing-options static route 100.0.0.0/24 discard
+/// set policy-options community comm-20 members 65535:20
+/// set policy-options policy-statement prefix-0 term advertise from route-filter 100.0.0.0/24 exact
+/// set policy-options policy-statement prefix-0 term advertise then as-path-prepend "200 300"
+/// set policy-options policy-statement prefix-0 term advertise then metric 10
+/// set policy-options policy-statement prefix-0 term advertise then community add comm-20
+/// set policy-options policy-statement prefix-0 term advertise then accept
+/// set protocols bgp group internal-network export prefix-0
+/// ```
+#[derive(Debug)]
+pub struct JunosExternalCfgGen<P: Prefix> {
+    router: RouterId,
+    as_id: AsId,
+    ifaces: Vec<String>,
+    /// list of routes (external) that are advertised, together with their communities
+    advertised_routes: HashMap<P, BTreeSet<u32>>,
+}
+
+impl<P: Prefix> JunosExternalCfgGen<P> {
+    /// Create a new config generator for the specified external router.
+    pub fn new<Q>(
+        net: &Network<P, Q>,
+        router: RouterId,
+        ifaces: Vec<String>,
+    ) -> Result<Self, ExportError> {
+        let r = net
+            .get_device(router)
+            .external_or(ExportError::NotAnExternalRouter(router))?;
+        Ok(Self {
+            router,
+            as_id: r.as_id(),
+            ifaces,
+            advertised_routes: HashMap::new(),
+        })
+    }
+
+    /// Get the interface name at the given index
+    pub fn iface_name(&self, idx: usize) -> Result<&str, ExportError> {
+        if let Some(iface) = self.ifaces.get(idx) {
+            Ok(iface.as_str())
+        } else {
+            Err(ExportError::NotEnoughInterfaces(self.router))
+        }
+    }
+
+    /// Generate the commands to configure a single BGP neighbor.
+    fn neighbor_config<A: Addressor<P>>(
+        &self,
+        addressor: &mut A,
+        neighbor: RouterId,
+    ) -> Result<String, ExportError> {
+        let neighbor_addr = addressor.iface_address(neighbor, self.router)?;
+        let local_addr = addressor.iface_address(self.router, neighbor)?;
+        Ok(format!(
+            "set protocols bgp group {BGP_GROUP} neighbor {neighbor_addr} local-address {local_addr}\n"
+        ))
+    }
+
+    /// Check if any advertised route carries the community `c`.
+    fn community_in_use(&self, c: u32) -> bool {
+        self.advertised_routes
+            .values()
+            .any(|comms| comms.contains(&c))
+    }
+}
+
+/// Get the name of the policy-statement that exports the given prefix.
+fn policy_name<P: Prefix>(prefix: P) -> String {
+    format!("prefix-{}", prefix.as_num())
+}
+
+/// Get the name of the community definition for the community `c`.
+fn community_name(c: u32) -> String {
+    format!("comm-{c}")
+}
+
+impl<P: Prefix, A: Addressor<P>, Q> ExternalCfgGen<P, Q, A> for JunosExternalCfgGen<P> {
+    fn generate_config(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+    ) -> Result<String, ExportError> {
+        let mut config = String::new();
+        let router = net
+            .get_device(self.router)
+            .external_or(ExportError::NotAnExternalRouter(self.router))?;
+
+        // interfaces
+        for neighbor in router.neighbors.iter().sorted() {
+            let (_, _, idx) = addressor.iface(self.router, *neighbor)?;
+            config.push_str(&format!(
+                "set interfaces {} unit 0 family inet address {}\n",
+                self.iface_name(idx)?,
+                addressor.iface_address_full(self.router, *neighbor)?
+            ));
+        }
+        config.push_str(&format!(
+            "set interfaces lo0 unit 0 family inet address {}/32\n",
+            addressor.router_address(self.router)?
+        ));
+
+        // routing options
+        config.push_str(&format!(
+            "set routing-options router-id {}\n",
+            addressor.router_address(self.router)?
+        ));
+        config.push_str(&format!(
+            "set routing-options autonomous-system {}\n",
+            self.as_id.0
+        ));
+
+        // BGP group and sessions
+        config.push_str(&format!(
+            "set protocols bgp group {BGP_GROUP} type external\n"
+        ));
+        config.push_str(&format!(
+            "set protocols bgp group {BGP_GROUP} peer-as {}\n",
+            INTERNAL_AS.0
+        ));
+        for neighbor in router.neighbors.iter().sorted() {
+            config.push_str(&self.neighbor_config(addressor, *neighbor)?);
+        }
+
+        // advertisements
+        for (_, route) in router.active_routes.iter().sorted_by_key(|(p, _)| *p) {
+            config.push_str(&self.advertise_route(net, addressor, route)?);
+        }
+
+        Ok(config)
+    }
+
+    fn advertise_route(
+        &mut self,
+        net: &Network<P, Q>,
+        addressor: &mut A,
+        route: &BgpRoute<P>,
+    ) -> Result<String, ExportError> {
+        let mut config = String::new();
+
+        // check if the prefix is already present. If so, first withdraw the route
+        if self.advertised_routes.contains_key(&route.prefix) {
+            config.push_str(&self.withdraw_route(net, addressor, route.prefix)?);
+        }
+
+        let policy = policy_name(route.prefix);
+        let term = format!("set policy-options policy-statement {policy} term advertise");
+        let networks = addressor.prefix(route.prefix)?.to_vec();
+
+        for net in networks.iter() {
+            config.push_str(&format!("set routing-options static route {net} discard\n"));
+        }
+        // only define communities that are not yet used by any other route
+        for c in route.community.iter() {
+            if !self.community_in_use(*c) {
+                config.push_str(&format!(
+                    "set policy-options community {} members {}:{c}\n",
+                    community_name(*c),
+                    INTERNAL_AS.0
+                ));
+            }
+        }
+        self.advertised_routes
+            .insert(route.prefix, route.community.clone());
+
+        for net in networks.iter() {
+            config.push_str(&format!("{term} from route-filter {net} exact\n"));
+        }
+        // the first AS in the path is added by the router itself.
+        if route.as_path.len() > 1 {
+            config.push_str(&format!(
+                "{term} then as-path-prepend \"{}\"\n",
+                route.as_path.iter().skip(1).map(|x| x.0).join(" ")
+            ));
+        }
+        if let Some(med) = route.med {
+            config.push_str(&format!("{term} then metric {med}\n"));
+        }
+        for c in route.community.iter() {
+            config.push_str(&format!(
+                "{term} then community add {}\n",
+                community_name(*c)
+            ));
+        }
+        config.push_str(&format!("{term} then accept\n"));
+        config.push_str(&format!(
+            "set protocols bgp group {BGP_GROUP} export {policy}\n"
+        ));
+
+        Ok(config)
+    }
+
+    fn withdraw_route(
+        &mut self,
+        _net: &Network<P, Q>,
+        addressor: &mut A,
+        prefix: P,
+    ) -> Result<String, ExportError> {
+        let communities = self
+            .advertised_routes
+            .remove(&prefix)
+            .ok_or(ExportError::WithdrawUnadvertisedRoute)?;
+
+        let policy = policy_name(prefix);
+        let mut config = format!("delete protocols bgp group {BGP_GROUP} export {policy}\n");
+        config.push_str(&format!(
+            "delete policy-options policy-statement {policy}\n"
+        ));
+        // only delete communities that are no longer used by any other route
+        for c in communities {
+            if !self.community_in_use(c) {
+                config.push_str(&format!(
+                    "delete policy-options community {}\n",
+                    community_name(c)
+                ));
+            }
+        }
+        for net in addressor.prefix(prefix)? {
+            config.push_str(&format!("delete routing-options static route {net}\n"));
+        }
+
+        Ok(config)
+    }
+
+    fn establish_ebgp_session(
+        &mut self,
+        _net: &Network<P, Q>,
+        addressor: &mut A,
+        neighbor: RouterId,
+    ) -> Result<String, ExportError> {
+        Ok(format!(
+            "set protocols bgp group {BGP_GROUP} type external\n\
+             set protocols bgp group {BGP_GROUP} peer-as {}\n{}",
+            INTERNAL_AS.0,
+            self.neighbor_config(addressor, neighbor)?
+        ))
+    }
+
+    fn teardown_ebgp_session(
+        &mut self,
+        _net: &Network<P, Q>,
+        addressor: &mut A,
+        neighbor: RouterId,
+    ) -> Result<String, ExportError> {
+        Ok(format!(
+            "delete protocols bgp group {BGP_GROUP} neighbor {}\n",
+            addressor.iface_address(neighbor, self.router)?
+        ))
+    }
+}
@@ -20,9 +20,16 @@
 //! orchestrate the export. Further, the trait `InternalCfgGen` and `ExternalCfgGen` are used to
 //! create the actual configuration, and can be implemented for any arbitrary target.
 
-use std::{fmt::Display, net::Ipv4Addr};
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    net::Ipv4Addr,
+};
 
-use ipnet::Ipv4Net;
+use ipnet::{IpNet, Ipv4AddrRange, Ipv4Net, Ipv4Subnets, PrefixLenError};
+#[cfg(feature = "ipv6")]
+use ipnet::{Ipv6AddrRange, Ipv6Net, Ipv6Subnets};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
@@ -32,20 +39,29 @@ use crate::{
     types::{AsId, NonOverlappingPrefix, Prefix, RouterId},
 };
 
+mod arista_eos;
 mod cisco_frr;
 pub mod cisco_frr_generators;
 mod default;
+#[cfg(feature = "ipv6")]
+mod default_v6;
 mod exabgp;
-
-pub use cisco_frr::CiscoFrrCfgGen;
-pub use default::{DefaultAddressor, DefaultAddressorBuilder};
+mod junos;
+
+pub use arista_eos::AristaEosCfgGen;
+pub use cisco_frr::{CiscoFrrCfgGen, LintWarning};
+pub use default::{AddressorState, DefaultAddressor, DefaultAddressorBuilder};
+#[cfg(feature = "ipv6")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ipv6")))]
+pub use default_v6::{DefaultAddressorV6, DefaultAddressorV6Builder};
 pub use exabgp::ExaBgpCfgGen;
+pub use junos::JunosExternalCfgGen;
 
 /// The internal AS Number
 pub const INTERNAL_AS: AsId = AsId(65535);
 
 /// Link index used in the IP addressor.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LinkId(RouterId, RouterId);
 
 impl LinkId {
@@ -65,6 +81,154 @@ impl From<(RouterId, RouterId)> for LinkId {
     }
 }
 
+/// The network type of an address family that can be used by an [`Addressor`]. This trait is
+/// implemented for [`Ipv4Net`], and for [`ipnet::Ipv6Net`] if the feature `ipv6` is enabled.
+pub trait IpNetwork:
+    Copy
+    + Eq
+    + Ord
+    + Hash
+    + Debug
+    + Display
+    + From<Self::Addr>
+    + Into<IpNet>
+    + Serialize
+    + DeserializeOwned
+{
+    /// The address type of the address family.
+    type Addr: Copy + Eq + Ord + Hash + Debug + Display + Serialize + DeserializeOwned;
+    /// Iterator over all subnets of a network.
+    type Subnets: Iterator<Item = Self> + Clone + Debug;
+    /// Iterator over all host addresses of a network.
+    type Hosts: Iterator<Item = Self::Addr>;
+
+    /// Create a new network from an address and a prefix length.
+    fn new(addr: Self::Addr, prefix_len: u8) -> Result<Self, PrefixLenError>;
+
+    /// Get the address of the network (not truncated).
+    fn addr(&self) -> Self::Addr;
+
+    /// Get the network address (truncated to the prefix length).
+    fn network(&self) -> Self::Addr;
+
+    /// Get the prefix length.
+    fn prefix_len(&self) -> u8;
+
+    /// Truncate the address to the prefix length.
+    fn trunc(&self) -> Self;
+
+    /// Check if `other` is contained within `self`.
+    fn contains_net(&self, other: &Self) -> bool;
+
+    /// Iterate over all subnets with the given prefix length.
+    fn subnets(&self, new_prefix_len: u8) -> Result<Self::Subnets, PrefixLenError>;
+
+    /// Iterate over all host addresses of the network.
+    fn hosts(&self) -> Self::Hosts;
+
+    /// Get the address of a router within its own network.
+    fn router_addr(&self) -> Option<Self::Addr>;
+
+    /// Get the network of the address family that represents the given IPv4 prefix.
+    fn from_ipv4_prefix(prefix: Ipv4Net) -> Result<Self, PrefixLenError>;
+}
+
+impl IpNetwork for Ipv4Net {
+    type Addr = Ipv4Addr;
+    type Subnets = Ipv4Subnets;
+    type Hosts = Ipv4AddrRange;
+
+    fn new(addr: Ipv4Addr, prefix_len: u8) -> Result<Self, PrefixLenError> {
+        Ipv4Net::new(addr, prefix_len)
+    }
+
+    fn addr(&self) -> Ipv4Addr {
+        Ipv4Net::addr(self)
+    }
+
+    fn network(&self) -> Ipv4Addr {
+        Ipv4Net::network(self)
+    }
+
+    fn prefix_len(&self) -> u8 {
+        Ipv4Net::prefix_len(self)
+    }
+
+    fn trunc(&self) -> Self {
+        Ipv4Net::trunc(self)
+    }
+
+    fn contains_net(&self, other: &Self) -> bool {
+        self.contains(other)
+    }
+
+    fn subnets(&self, new_prefix_len: u8) -> Result<Ipv4Subnets, PrefixLenError> {
+        Ipv4Net::subnets(self, new_prefix_len)
+    }
+
+    fn hosts(&self) -> Ipv4AddrRange {
+        Ipv4Net::hosts(self)
+    }
+
+    fn router_addr(&self) -> Option<Ipv4Addr> {
+        Ipv4Net::hosts(self).next()
+    }
+
+    fn from_ipv4_prefix(prefix: Ipv4Net) -> Result<Self, PrefixLenError> {
+        Ok(prefix)
+    }
+}
+
+/// Prefixes are mapped into the IPv4-mapped IPv6 address space (`::ffff:0:0/96`), and routers skip
+/// the subnet-router anycast address of their network.
+#[cfg(feature = "ipv6")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ipv6")))]
+impl IpNetwork for Ipv6Net {
+    type Addr = std::net::Ipv6Addr;
+    type Subnets = Ipv6Subnets;
+    type Hosts = Ipv6AddrRange;
+
+    fn new(addr: std::net::Ipv6Addr, prefix_len: u8) -> Result<Self, PrefixLenError> {
+        Ipv6Net::new(addr, prefix_len)
+    }
+
+    fn addr(&self) -> std::net::Ipv6Addr {
+        Ipv6Net::addr(self)
+    }
+
+    fn network(&self) -> std::net::Ipv6Addr {
+        Ipv6Net::network(self)
+    }
+
+    fn prefix_len(&self) -> u8 {
+        Ipv6Net::prefix_len(self)
+    }
+
+    fn trunc(&self) -> Self {
+        Ipv6Net::trunc(self)
+    }
+
+    fn contains_net(&self, other: &Self) -> bool {
+        self.contains(other)
+    }
+
+    fn subnets(&self, new_prefix_len: u8) -> Result<Ipv6Subnets, PrefixLenError> {
+        Ipv6Net::subnets(self, new_prefix_len)
+    }
+
+    fn hosts(&self) -> Ipv6AddrRange {
+        Ipv6Net::hosts(self)
+    }
+
+    fn router_addr(&self) -> Option<std::net::Ipv6Addr> {
+        Ipv6Net::hosts(self).nth(1)
+    }
+
+    fn from_ipv4_prefix(prefix: Ipv4Net) -> Result<Self, PrefixLenError> {
+        Ipv6Net::new(prefix.addr().to_ipv6_mapped(), prefix.prefix_len() + 96)
+    }
+}
+
 /// A trait for generating configurations for an internal router
 pub trait InternalCfgGen<P: Prefix, Q, A> {
     /// Generate all configuration files for the device.
@@ -126,77 +290,75 @@ pub trait ExternalCfgGen<P: Prefix, Q, A> {
 }
 
 /// A trait for generating IP address ranges and AS numbers. For this addressor, a single [`Prefix`]
-/// represents an equivalence class, and is thus associated with multiple addresses.
-pub trait Addressor<P: Prefix> {
+/// represents an equivalence class, and is thus associated with multiple addresses. The addressor
+/// is generic over the address family `N` (see [`IpNetwork`]), which defaults to IPv4.
+pub trait Addressor<P: Prefix, N: IpNetwork = Ipv4Net> {
     /// Get the internal network
-    fn internal_network(&mut self) -> Ipv4Net;
+    fn internal_network(&mut self) -> N;
 
     /// Try to get router address (router ID) for the given router or return `None` if the router
     /// has not been allocated.
-    fn try_get_router_address(&self, router: RouterId) -> Option<Ipv4Addr> {
+    fn try_get_router_address(&self, router: RouterId) -> Option<N::Addr> {
         self.try_get_router(router).map(|r| r.1)
     }
 
     /// Get router address (router ID) for the given router.
-    fn router_address(&mut self, router: RouterId) -> Result<Ipv4Addr, ExportError> {
+    fn router_address(&mut self, router: RouterId) -> Result<N::Addr, ExportError> {
         Ok(self.router(router)?.1)
     }
 
     /// Try to get router address (router ID) for the given router, including the prefix length.
     /// Returns `None` if the router has not been allocated.
-    fn try_get_router_address_full(
-        &self,
-        router: RouterId,
-    ) -> Option<Result<Ipv4Net, ExportError>> {
+    fn try_get_router_address_full(&self, router: RouterId) -> Option<Result<N, ExportError>> {
         self.try_get_router(router)
-            .map(|(net, ip)| Ok(Ipv4Net::new(ip, net.prefix_len())?))
+            .map(|(net, ip)| Ok(N::new(ip, net.prefix_len())?))
     }
 
     /// Get router address (router ID) for the given router, including the prefix length.
-    fn router_address_full(&mut self, router: RouterId) -> Result<Ipv4Net, ExportError> {
+    fn router_address_full(&mut self, router: RouterId) -> Result<N, ExportError> {
         let (net, ip) = self.router(router)?;
-        Ok(Ipv4Net::new(ip, net.prefix_len())?)
+        Ok(N::new(ip, net.prefix_len())?)
     }
 
     /// Try to get the network of the router itself. This address will be announced via BGP.
     /// Returns `None` if the router has not been allocated.
-    fn try_get_router_network(&self, router: RouterId) -> Option<Ipv4Net> {
+    fn try_get_router_network(&self, router: RouterId) -> Option<N> {
         self.try_get_router(router).map(|r| r.0)
     }
 
     /// Get the network of the router itself. This address will be announced via BGP.
-    fn router_network(&mut self, router: RouterId) -> Result<Ipv4Net, ExportError> {
+    fn router_network(&mut self, router: RouterId) -> Result<N, ExportError> {
         Ok(self.router(router)?.0)
     }
 
     /// Try to get both the network and the IP address of a router or return `None` if the router
     /// has not been allocated.
-    fn try_get_router(&self, router: RouterId) -> Option<(Ipv4Net, Ipv4Addr)>;
+    fn try_get_router(&self, router: RouterId) -> Option<(N, N::Addr)>;
 
     /// Get both the network and the IP address of a router.
-    fn router(&mut self, router: RouterId) -> Result<(Ipv4Net, Ipv4Addr), ExportError>;
+    fn router(&mut self, router: RouterId) -> Result<(N, N::Addr), ExportError>;
 
     /// Register a prefix equivalence class. That is, an assignment of a prefix to a prefix list.
     ///
     /// **Warning**: This function must be called before you generate any configuration! It will not
     /// affect the configuration that was generated before registering new prefix equivalence
     /// classes.
-    fn register_pec(&mut self, pec: P, prefixes: Vec<Ipv4Net>)
+    fn register_pec(&mut self, pec: P, prefixes: Vec<N>)
     where
         P: NonOverlappingPrefix;
 
     /// Get all prefix equivalence classes
-    fn get_pecs(&self) -> &P::Map<Vec<Ipv4Net>>;
+    fn get_pecs(&self) -> &P::Map<Vec<N>>;
 
     /// Get the network that are associated with that prefix. This function will ignore any prefix
     /// equivalence classes.
-    fn prefix(&mut self, prefix: P) -> Result<MaybePec<Ipv4Net>, ExportError>;
+    fn prefix(&mut self, prefix: P) -> Result<MaybePec<N>, ExportError>;
 
     /// For each network associated with that prefix, get the first host IP in the prefix range,
     /// including the prefix length. This function will ignore any prefix equivalence classes.
-    fn prefix_address(&mut self, prefix: P) -> Result<MaybePec<Ipv4Net>, ExportError> {
-        fn get_net(net: Ipv4Net) -> Result<Ipv4Net, ExportError> {
-            Ok(Ipv4Net::new(
+    fn prefix_address(&mut self, prefix: P) -> Result<MaybePec<N>, ExportError> {
+        fn get_net<N: IpNetwork>(net: N) -> Result<N, ExportError> {
+            Ok(N::new(
                 net.hosts().next().ok_or(ExportError::NotEnoughAddresses)?,
                 net.prefix_len(),
             )
@@ -220,7 +382,7 @@ pub trait Addressor<P: Prefix> {
         &self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Option<Result<Ipv4Addr, ExportError>> {
+    ) -> Option<Result<N::Addr, ExportError>> {
         self.try_get_iface(router, neighbor)
             .map(|r| r.map(|iface| iface.0))
     }
@@ -230,7 +392,7 @@ pub trait Addressor<P: Prefix> {
         &mut self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Result<Ipv4Addr, ExportError> {
+    ) -> Result<N::Addr, ExportError> {
         Ok(self.iface(router, neighbor)?.0)
     }
 
@@ -240,9 +402,9 @@ pub trait Addressor<P: Prefix> {
         &self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Option<Result<Ipv4Net, ExportError>> {
+    ) -> Option<Result<N, ExportError>> {
         self.try_get_iface(router, neighbor)
-            .map(|r| r.and_then(|(ip, net, _)| Ok(Ipv4Net::new(ip, net.prefix_len())?)))
+            .map(|r| r.and_then(|(ip, net, _)| Ok(N::new(ip, net.prefix_len())?)))
     }
 
     /// Get the full interface address, including the network mask
@@ -250,9 +412,9 @@ pub trait Addressor<P: Prefix> {
         &mut self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Result<Ipv4Net, ExportError> {
+    ) -> Result<N, ExportError> {
         let (ip, net, _) = self.iface(router, neighbor)?;
-        Ok(Ipv4Net::new(ip, net.prefix_len())?)
+        Ok(N::new(ip, net.prefix_len())?)
     }
 
     /// Try to get the interface index of the specified link and router in the network. Returns
@@ -272,16 +434,12 @@ pub trait Addressor<P: Prefix> {
     }
 
     /// Try to get the link network. Returns `None` if the router has not been allocated.
-    fn try_get_iface_network(
-        &self,
-        a: RouterId,
-        b: RouterId,
-    ) -> Option<Result<Ipv4Net, ExportError>> {
+    fn try_get_iface_network(&self, a: RouterId, b: RouterId) -> Option<Result<N, ExportError>> {
         self.try_get_iface(a, b).map(|r| r.map(|iface| iface.1))
     }
 
     /// Get the link network.
-    fn iface_network(&mut self, a: RouterId, b: RouterId) -> Result<Ipv4Net, ExportError> {
+    fn iface_network(&mut self, a: RouterId, b: RouterId) -> Result<N, ExportError> {
         Ok(self.iface(a, b)?.1)
     }
 
@@ -291,31 +449,31 @@ pub trait Addressor<P: Prefix> {
         &self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Option<Result<(Ipv4Addr, Ipv4Net, usize), ExportError>>;
+    ) -> Option<Result<(N::Addr, N, usize), ExportError>>;
 
     /// Get the IP address, the network and the interface index of a router connected to another.
     fn iface(
         &mut self,
         router: RouterId,
         neighbor: RouterId,
-    ) -> Result<(Ipv4Addr, Ipv4Net, usize), ExportError>;
+    ) -> Result<(N::Addr, N, usize), ExportError>;
 
     /// Get a list of all interfaces of a single router. Each interface is a four-tuple, containing
     /// the connected router-id, the IP address of the interface, the network of the link, and the
     /// interface index. The returned list **may not** be ordered.
-    fn list_ifaces(&self, router: RouterId) -> Vec<(RouterId, Ipv4Addr, Ipv4Net, usize)>;
+    fn list_ifaces(&self, router: RouterId) -> Vec<(RouterId, N::Addr, N, usize)>;
 
     /// List all links in the network. Each link is a tuple of the two endpoints. Each endpoint is
     /// represented by its router-id and the interface index.
     fn list_links(&self) -> Vec<((RouterId, usize), (RouterId, usize))>;
 
     /// Lookup an IP address in the addressor, and return the RouterId to which the address belongs
-    /// to. You can provide either an Ipv4Net or an Ipv4Addr. In case the provided address is a link
+    /// to. You can provide either a network or an address. In case the provided address is a link
     /// network, and the IP does not match one of the connected routers, this function will return
     /// the router along the following list of preference:
     /// - internal routers over external ones.
     /// - Router with the lower IP address specified on the link.
-    fn find_address(&self, address: impl Into<Ipv4Net>) -> Result<RouterId, ExportError>;
+    fn find_address(&self, address: impl Into<N>) -> Result<RouterId, ExportError>;
 
     /// Compute the next-hop router-id of the next-hop. The next-hop IP is searched as follows: If
     /// the IP belongs to a router, and this router is adjacent to `router`, then return that
@@ -324,11 +482,26 @@ pub trait Addressor<P: Prefix> {
     fn find_next_hop(
         &self,
         router: RouterId,
-        address: impl Into<Ipv4Net>,
+        address: impl Into<N>,
     ) -> Result<RouterId, ExportError>;
 
     /// Find the neighbor RouterId that is connected to the `router` with the given `iface_idx`.
     fn find_neighbor(&self, router: RouterId, iface_idx: usize) -> Result<RouterId, ExportError>;
+
+    /// Register the interface names of a router. The interface at position `i` in `ifaces` has
+    /// interface index `i`. Use the same list that was used to create the config generator (see
+    /// [`CiscoFrrCfgGen::ifaces`]). The default implementation ignores the interface names.
+    fn register_iface_names(&mut self, router: RouterId, ifaces: Vec<String>) {
+        let _ = (router, ifaces);
+    }
+
+    /// Find the neighbor RouterId that is connected to the `router` with the interface named
+    /// `iface` (for instance, `Ethernet3/1`). The interface names must be registered first using
+    /// [`Addressor::register_iface_names`]. The default implementation does not know any interface
+    /// names, and always returns `Err(ExportError::InterfaceNotFound)`.
+    fn find_iface_by_name(&self, router: RouterId, iface: &str) -> Result<RouterId, ExportError> {
+        Err(ExportError::InterfaceNotFound(router, iface.to_string()))
+    }
 }
 
 /// Error thrown by the exporter
@@ -369,7 +542,7 @@ pub enum ExportError {
     ModifierDoesNotAffectRouter,
     /// The given IP Address could not be found.
     #[error("IP Address {0} could not be associated with any router!")]
-    AddressNotFound(Ipv4Net),
+    AddressNotFound(IpNet),
     /// The interface was not found.
     #[error("Interface {1} of router {0:?} does not exist!")]
     InterfaceNotFound(RouterId, String),
@@ -378,7 +551,7 @@ pub enum ExportError {
     RoutersNotConnected(RouterId, RouterId),
     /// A prefix IP network is within a reserved IP range.
     #[error("The network {0} or the prefix lies within a reserved IP range.")]
-    PrefixWithinReservedIpRange(Ipv4Net),
+    PrefixWithinReservedIpRange(IpNet),
     /// Did not expect a prefix equivalence class at this point.
     #[error("Did not expect a prefix equivalence class of {0}!")]
     UnexpectedPec(Ipv4Net),
@@ -504,6 +677,19 @@ impl<T> MaybePec<T> {
         }
     }
 
+    /// Get random samples from the prefix equivalence class, using a random number generator that
+    /// is initialized with the given `seed`. Calling this function with the same seed will always
+    /// yield the same samples, also across platforms and versions of `rand`. See [`MaybePec::sample_random_n`] for how the samples are chosen.
+    #[cfg(feature = "rand")]
+    pub fn sample_seeded_n(&self, seed: u64, n: usize) -> Vec<&T>
+    where
+        T: Ord,
+    {
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+        self.sample_random_n(&mut rng, n)
+    }
+
     /// Get `n` samples from the prefix equivalence class that are equally spaced. This function may
     /// panic if `n < 2`. If `n == 2`, then return the smallest and largest element.
     pub fn sample_uniform_n(&self, n: usize) -> Vec<&T>
@@ -588,3 +774,27 @@ impl<'a, T> IntoIterator for &'a MaybePec<T> {
         MaybePecIter { x: self, idx: 0 }
     }
 }
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use super::MaybePec;
+
+    #[test]
+    fn sample_seeded_n() {
+        let pec = MaybePec::Pec("100.0.0.0/8".parse().unwrap(), (0..1000).collect());
+
+        let a = pec.sample_seeded_n(42, 10);
+        let b = pec.sample_seeded_n(42, 10);
+        let c = pec.sample_seeded_n(43, 10);
+
+        assert_eq!(a.len(), 10);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        // the first and the last element are always part of the sample
+        assert_eq!(a.first(), Some(&&0));
+        assert_eq!(a.last(), Some(&&999));
+
+        let single = MaybePec::Single(5);
+        assert_eq!(single.sample_seeded_n(42, 10), vec![&5]);
+    }
+}
@@ -764,6 +764,9 @@ impl<'a, 'n, P: Prefix, Q> NetworkFormatter<'a, 'n, P, Q> for NetworkError {
                 dst.fmt(net)
             ),
             NetworkError::NoConvergence => String::from("Network could not converge!"),
+            NetworkError::QueueNotEmpty(n) => {
+                format!("The event queue still contains {n} events for the prefix!")
+            }
             NetworkError::InvalidBgpTable(r) => {
                 format!("Router {} has an invalid BGP table!", r.fmt(net))
             }
@@ -152,6 +152,64 @@ impl<P: Prefix> ForwardingState<P> {
         self.get_paths(source, prefix)
     }
 
+    /// Returns the set of forwarding paths from the source router to a specific prefix, together
+    /// with the fraction of traffic sent along each path. Each router splits its traffic evenly
+    /// among all of its ECMP next-hops, so the fraction of a path is the product of `1 / n` for
+    /// every hop, where `n` is the number of next-hops at that hop. The fractions of all returned
+    /// paths sum up to 1.
+    pub fn get_weighted_paths(
+        &mut self,
+        source: RouterId,
+        prefix: P,
+    ) -> Result<Vec<(Vec<RouterId>, f64)>, NetworkError> {
+        let paths = self.get_paths(source, prefix)?;
+        Ok(paths
+            .into_iter()
+            .map(|path| {
+                let fraction = path[..path.len().saturating_sub(1)]
+                    .iter()
+                    .map(|r| {
+                        self.state
+                            .get(r)
+                            .and_then(|fib| fib.get_lpm(&prefix))
+                            .map(|(_, nhs)| nhs.len().max(1))
+                            .unwrap_or(1)
+                    })
+                    .fold(1.0, |frac, n| frac / n as f64);
+                (path, fraction)
+            })
+            .collect())
+    }
+
+    /// Follow a single packet from `source` towards `prefix`, and return its fate together with the
+    /// (partial) path it traversed. If a router has multiple next-hops, the packet follows the
+    /// first one.
+    pub fn trace(&self, source: RouterId, prefix: P) -> PacketTrace {
+        let mut visited = HashSet::new();
+        let mut path = vec![source];
+        let mut cur = source;
+        loop {
+            visited.insert(cur);
+            let nh = self
+                .state
+                .get(&cur)
+                .and_then(|fib| fib.get_lpm(&prefix))
+                .and_then(|(_, nhs)| nhs.first())
+                .copied();
+            match nh {
+                None => return PacketTrace::BlackHole(path),
+                Some(nh) if nh == *TO_DST => return PacketTrace::Delivered(path),
+                Some(nh) => {
+                    path.push(nh);
+                    if visited.contains(&nh) {
+                        return PacketTrace::Loop(path);
+                    }
+                    cur = nh;
+                }
+            }
+        }
+    }
+
     /// Recursive function to build the paths recursively.
     fn get_paths_recursive(
         &mut self,
@@ -280,6 +338,31 @@ impl<P: Prefix> ForwardingState<P> {
             .unwrap_or(&EMPTY_SET)
     }
 
+    /// Group all routers by the egress through which their traffic towards `prefix` leaves the
+    /// network. The egress of a path is its last router, i.e., the terminal (see
+    /// [`ForwardingState::get_terminals`]). Routers that use ECMP across multiple egresses appear in
+    /// each of the corresponding groups. Terminals themselves, as well as routers that black-hole
+    /// the prefix or are part of a forwarding loop, are not part of any group. Each group is sorted
+    /// by router ID.
+    pub fn egress_groups(&mut self, prefix: P) -> HashMap<RouterId, Vec<RouterId>> {
+        let mut routers: Vec<RouterId> = self.state.keys().copied().collect();
+        routers.sort();
+
+        let mut groups: HashMap<RouterId, Vec<RouterId>> = HashMap::new();
+        for router in routers {
+            if self.is_terminal(router, prefix) {
+                continue;
+            }
+            if let Ok(paths) = self.get_paths(router, prefix) {
+                for egress in paths.iter().filter_map(|path| path.last()).unique() {
+                    groups.entry(*egress).or_default().push(router);
+                }
+            }
+        }
+
+        groups
+    }
+
     /// Returns `true` if `router` is a terminal for `prefix`.
     pub fn is_terminal(&self, router: RouterId, prefix: P) -> bool {
         self.get_terminals(prefix).contains(&router)
@@ -391,6 +474,54 @@ impl<P: Prefix> ForwardingState<P> {
         }
     }
 
+    /// Update a single edge on the forwarding state (see [`ForwardingState::update`]), and return
+    /// the set of routers whose forwarding paths towards `prefix` changed due to this update. Only
+    /// `source` and the routers upstream of it (those that forward traffic via `source`) can be
+    /// affected. A router is considered to be affected if its set of paths changes, or if it
+    /// changes between reaching the destination, black-holing traffic, and being part of a
+    /// forwarding loop. The returned routers are sorted by their ID.
+    pub fn update_and_affected(
+        &mut self,
+        source: RouterId,
+        prefix: P,
+        next_hops: Vec<RouterId>,
+    ) -> Vec<RouterId> {
+        // collect all routers upstream of source. They do not change when updating source.
+        let mut upstream = HashSet::new();
+        let mut to_visit = vec![source];
+        while let Some(cur) = to_visit.pop() {
+            if upstream.insert(cur) {
+                to_visit.extend(self.get_prev_hops(cur, prefix).iter().copied());
+            }
+        }
+        let mut upstream = Vec::from_iter(upstream);
+        upstream.sort();
+
+        let old_paths: Vec<CacheResult> = upstream
+            .iter()
+            .map(|r| self.get_paths_summary(*r, prefix))
+            .collect();
+
+        self.update(source, prefix, next_hops);
+
+        upstream
+            .into_iter()
+            .zip(old_paths)
+            .filter(|(r, old)| &self.get_paths_summary(*r, prefix) != old)
+            .map(|(r, _)| r)
+            .collect()
+    }
+
+    /// Get the paths from `router` towards `prefix`, but ignore where exactly a black hole or a
+    /// forwarding loop is found.
+    fn get_paths_summary(&mut self, router: RouterId, prefix: P) -> CacheResult {
+        match self.get_paths(router, prefix) {
+            Ok(paths) => CacheResult::Path(paths),
+            Err(NetworkError::ForwardingLoop(_)) => CacheResult::Loop(Vec::new()),
+            Err(_) => CacheResult::Hole(Vec::new()),
+        }
+    }
+
     /// Recursive invalidate the cache starting at `source` for `prefix`.
     fn recursive_invalidate_cache(&mut self, source: RouterId, prefix: P) {
         if self
@@ -533,6 +664,20 @@ impl ForwardingState<SimplePrefix> {
     }
 }
 
+/// The fate of a single packet, as returned by [`ForwardingState::trace`]. Each variant contains
+/// the path traversed by the packet, starting at the source.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PacketTrace {
+    /// The packet reached a router that knows the destination. The last router on the path is that
+    /// terminal.
+    Delivered(Vec<RouterId>),
+    /// The packet was dropped by the last router on the path.
+    BlackHole(Vec<RouterId>),
+    /// The packet entered a forwarding loop. The last router on the path is the first one that
+    /// was visited twice.
+    Loop(Vec<RouterId>),
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum CacheResult {
     Path(Vec<Vec<RouterId>>),
@@ -854,6 +999,66 @@ mod test {
             check_route!(fw, 5, p => fwloop (5, 4, 2, 3, 4));
         }
 
+        #[test]
+        fn update_and_affected<P: Prefix>() {
+            let p = P::from(0);
+            let mut fw = fw_state! {
+                1 => {p => 100},
+                2 => {p => 1},
+                3 => {p => 2},
+                4 => {p => 2},
+                5 => {p => 101},
+                6 => {p => 5},
+            };
+
+            check_route!(fw, 3, p => ((3, 2, 1, 100)));
+            check_route!(fw, 4, p => ((4, 2, 1, 100)));
+
+            assert_eq!(
+                fw.update_and_affected(2.into(), p, vec![5.into()]),
+                _path!(2, 3, 4)
+            );
+
+            check_route!(fw, 1, p => ((1, 100)));
+            check_route!(fw, 2, p => ((2, 5, 101)));
+            check_route!(fw, 3, p => ((3, 2, 5, 101)));
+            check_route!(fw, 4, p => ((4, 2, 5, 101)));
+            check_route!(fw, 6, p => ((6, 5, 101)));
+
+            // updating to the same next-hop does not affect any router
+            assert!(fw
+                .update_and_affected(2.into(), p, vec![5.into()])
+                .is_empty());
+
+            // creating a black hole affects the same routers
+            assert_eq!(fw.update_and_affected(2.into(), p, vec![]), _path!(2, 3, 4));
+            check_route!(fw, 3, p => blackhole (3, 2));
+        }
+
+        #[test]
+        fn trace<P: Prefix>() {
+            let p = P::from(0);
+            let fw = fw_state! {
+                1 => {p => 100},
+                2 => {p => (1, 3)},
+                3 => {p => ()},
+                4 => {p => 5},
+                5 => {p => 6},
+                6 => {p => 4},
+                7 => {p => (3, 1)},
+            };
+
+            assert_eq!(
+                fw.trace(2.into(), p),
+                PacketTrace::Delivered(_path!(2, 1, 100))
+            );
+            assert_eq!(fw.trace(100.into(), p), PacketTrace::Delivered(_path!(100)));
+            assert_eq!(fw.trace(7.into(), p), PacketTrace::BlackHole(_path!(7, 3)));
+            assert_eq!(fw.trace(3.into(), p), PacketTrace::BlackHole(_path!(3)));
+            assert_eq!(fw.trace(8.into(), p), PacketTrace::BlackHole(_path!(8)));
+            assert_eq!(fw.trace(4.into(), p), PacketTrace::Loop(_path!(4, 5, 6, 4)));
+        }
+
         #[instantiate_tests(<SinglePrefix>)]
         mod single {}
 
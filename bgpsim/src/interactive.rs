@@ -76,6 +76,21 @@ where
         &mut self,
     ) -> Result<Option<(StepUpdate<P>, Event<P, Q::Priority>)>, NetworkError>;
 
+    /// Simulate events on the queue until an event matching `predicate` was processed. All events
+    /// before that one are processed as well, in the order of the queue. This function returns the
+    /// change in forwarding behavior caused by the matching event, as well as the event itself. If
+    /// this function returns `Ok(None)`, then the queue became empty before any matching event was
+    /// processed. Like [`Network::simulate`], this function returns
+    /// [`NetworkError::NoConvergence`] once the maximum allowed events have been processed (which
+    /// can be set by `self.set_msg_limit`).
+    #[allow(clippy::type_complexity)]
+    fn process_next_matching<F>(
+        &mut self,
+        predicate: F,
+    ) -> Result<Option<(StepUpdate<P>, Event<P, Q::Priority>)>, NetworkError>
+    where
+        F: FnMut(&Event<P, Q::Priority>) -> bool;
+
     /// Undo the last event in the network.
     ///
     /// **Note**: This funtion is only available with the `undo` feature.
@@ -126,10 +141,14 @@ impl<P: Prefix, Q: EventQueue<P>> InteractiveNetwork<P, Q> for Network<P, Q> {
         if let Some(event) = self.queue.pop() {
             // log the job
             log::trace!("{}", event.fmt(self));
-            // execute the event
-            let (step_update, events) = self
+            // execute the event. The router-IDs are moved out of the network for the time of the
+            // event, such that the device can borrow them while being borrowed mutably.
+            let router_ids = std::mem::take(&mut self.bgp_router_ids);
+            let result = self
                 .get_device_mut(event.router())
-                .handle_event(event.clone())?;
+                .handle_event(event.clone(), &router_ids);
+            self.bgp_router_ids = router_ids;
+            let (step_update, events) = result?;
 
             if self.verbose {
                 println!(
@@ -155,6 +174,32 @@ impl<P: Prefix, Q: EventQueue<P>> InteractiveNetwork<P, Q> for Network<P, Q> {
         }
     }
 
+    fn process_next_matching<F>(
+        &mut self,
+        mut predicate: F,
+    ) -> Result<Option<(StepUpdate<P>, Event<P, Q::Priority>)>, NetworkError>
+    where
+        F: FnMut(&Event<P, Q::Priority>) -> bool,
+    {
+        let mut remaining_iter = self.stop_after;
+        while !self.queue.is_empty() {
+            if let Some(rem) = remaining_iter {
+                if rem == 0 {
+                    debug!("Network could not converge!");
+                    return Err(NetworkError::NoConvergence);
+                }
+                remaining_iter = Some(rem - 1);
+            }
+            if let Some((step_update, event)) = self.simulate_step()? {
+                if predicate(&event) {
+                    return Ok(Some((step_update, event)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     #[cfg(feature = "undo")]
     #[cfg_attr(docsrs, doc(cfg(feature = "undo")))]
     fn undo_step(&mut self) -> Result<(), NetworkError> {
@@ -197,6 +242,12 @@ impl<P: Prefix, Q: EventQueue<P>> InteractiveNetwork<P, Q> for Network<P, Q> {
                     UndoAction::UndoDevice(id) => {
                         self.get_device_mut(id).undo_event()?;
                     }
+                    UndoAction::SetBgpRouterId(router, Some(id)) => {
+                        self.bgp_router_ids.insert(router, id);
+                    }
+                    UndoAction::SetBgpRouterId(router, None) => {
+                        self.bgp_router_ids.remove(&router);
+                    }
                 }
             }
         } else {
@@ -399,6 +450,7 @@ impl<'a, P: Prefix, Q> PartialClone<'a, P, Q> {
         // clone new.net if the configuration is different
         if !self.reuse_config {
             new.ospf = source.ospf.clone();
+            new.bgp_router_ids = source.bgp_router_ids.clone();
         }
 
         if !self.reuse_advertisements {
@@ -432,6 +484,7 @@ impl<'a, P: Prefix, Q> PartialClone<'a, P, Q> {
 
             if !self.reuse_config {
                 r.do_load_balancing = r_source.do_load_balancing;
+                r.cluster_id = r_source.cluster_id;
                 r.neighbors = r_source.neighbors.clone();
                 r.static_routes = r_source.static_routes.clone();
                 r.bgp_sessions = r_source.bgp_sessions.clone();
@@ -58,8 +58,9 @@
 //!   come at a significant performance cost, as every event needs to be recorded.
 //! - `rand`: This feature enables helper functions in the [`builder`] for generating random
 //!   configurations.
-//! - `rand_queue`: This feature enables the [`event::SimpleTimingModel`], and adds
-//!   [rand](https://docs.rs/rand/latest/rand/index.html) as a dependency (requiring `std`).
+//! - `rand_queue`: This feature enables the [`event::SimpleTimingModel`] and the
+//!   [`event::MeasuredTimingModel`], and adds [rand](https://docs.rs/rand/latest/rand/index.html)
+//!   as a dependency (requiring `std`).
 //! - `serde`: This feature adds serialize and deserialize functionality to (almost) every type in
 //!   this crate. Enabling this significantly impact build times.
 //! - `topology_zoo`: This adds the module `topology_zoo` including a `*.graphml` parser, and a
@@ -21,7 +21,7 @@
 //! network.
 
 use crate::{
-    bgp::{BgpSessionType, BgpState, BgpStateRef},
+    bgp::{BgpRoute, BgpRouterIds, BgpSessionType, BgpState, BgpStateRef},
     config::{NetworkConfig, RouteMapEdit},
     event::{BasicEventQueue, Event, EventQueue},
     external_router::ExternalRouter,
@@ -32,7 +32,7 @@ use crate::{
     router::{Router, StaticRoute},
     types::{
         AsId, IgpNetwork, LinkWeight, NetworkDevice, NetworkDeviceMut, NetworkError, Prefix,
-        PrefixSet, RouterId, SimplePrefix,
+        PrefixSet, RouterId, SimplePrefix, StepUpdate,
     },
 };
 
@@ -42,7 +42,10 @@ use petgraph::{
     visit::{EdgeRef, IntoEdgeReferences},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    net::Ipv4Addr,
+};
 
 static DEFAULT_STOP_AFTER: usize = 1_000_000;
 
@@ -104,6 +107,8 @@ pub struct Network<P: Prefix = SimplePrefix, Q = BasicEventQueue<SimplePrefix>>
     pub(crate) queue: Q,
     pub(crate) skip_queue: bool,
     pub(crate) verbose: bool,
+    #[serde(default)]
+    pub(crate) bgp_router_ids: BgpRouterIds,
     #[cfg(feature = "undo")]
     pub(crate) undo_stack: Vec<Vec<Vec<UndoAction>>>,
 }
@@ -123,6 +128,7 @@ impl<P: Prefix, Q: Clone> Clone for Network<P, Q> {
             queue: self.queue.clone(),
             skip_queue: self.skip_queue,
             verbose: self.verbose,
+            bgp_router_ids: self.bgp_router_ids.clone(),
             #[cfg(feature = "undo")]
             undo_stack: self.undo_stack.clone(),
         }
@@ -148,6 +154,7 @@ impl<P: Prefix, Q> Network<P, Q> {
             queue,
             skip_queue: false,
             verbose: false,
+            bgp_router_ids: HashMap::new(),
             #[cfg(feature = "undo")]
             undo_stack: Vec::new(),
         }
@@ -429,6 +436,7 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
             queue,
             skip_queue: self.skip_queue,
             verbose: self.verbose,
+            bgp_router_ids: self.bgp_router_ids,
             #[cfg(feature = "undo")]
             undo_stack: self.undo_stack,
         })
@@ -511,7 +519,7 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
                 .routers
                 .get_mut(&source)
                 .ok_or(NetworkError::DeviceNotFound(source))?
-                .set_bgp_session(target, source_type)?;
+                .set_bgp_session(target, source_type, &self.bgp_router_ids)?;
             self.enqueue_events(events);
         }
         // configure target
@@ -531,7 +539,7 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
                 .routers
                 .get_mut(&target)
                 .ok_or(NetworkError::DeviceNotFound(target))?
-                .set_bgp_session(source, target_type)?;
+                .set_bgp_session(source, target_type, &self.bgp_router_ids)?;
             self.enqueue_events(events);
         }
 
@@ -641,7 +649,7 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
             .routers
             .get_mut(&router)
             .ok_or(NetworkError::DeviceNotFound(router))?
-            .set_bgp_route_map(neighbor, direction, route_map)?;
+            .set_bgp_route_map(neighbor, direction, route_map, &self.bgp_router_ids)?;
 
         // add the undo action
         #[cfg(feature = "undo")]
@@ -676,7 +684,7 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
             .routers
             .get_mut(&router)
             .ok_or(NetworkError::DeviceNotFound(router))?
-            .remove_bgp_route_map(neighbor, direction, order)?;
+            .remove_bgp_route_map(neighbor, direction, order, &self.bgp_router_ids)?;
 
         // add the undo action
         #[cfg(feature = "undo")]
@@ -708,7 +716,7 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
             .routers
             .get_mut(&router)
             .ok_or(NetworkError::DeviceNotFound(router))?
-            .batch_update_route_maps(updates)?;
+            .batch_update_route_maps(updates, &self.bgp_router_ids)?;
 
         // add the undo action
         #[cfg(feature = "undo")]
@@ -767,6 +775,95 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
         Ok(old_val)
     }
 
+    /// Set the cluster ID of a route reflector, and return the old value. If `cluster_id` is
+    /// `None`, the router ID is used as cluster ID. The cluster ID is added to the CLUSTER_LIST
+    /// of routes that are reflected afterwards.
+    ///
+    /// *Undo Functionality*: this function will push a new undo event to the queue.
+    pub fn set_bgp_cluster_id(
+        &mut self,
+        router: RouterId,
+        cluster_id: Option<RouterId>,
+    ) -> Result<Option<RouterId>, NetworkError> {
+        // update the device
+        let old_val = self
+            .routers
+            .get_mut(&router)
+            .ok_or(NetworkError::DeviceNotFound(router))?
+            .set_cluster_id(cluster_id);
+
+        // push undo stack
+        #[cfg(feature = "undo")]
+        self.undo_stack
+            .push(vec![vec![UndoAction::UndoDevice(router)]]);
+
+        Ok(old_val)
+    }
+
+    /// Get the effective BGP router-ID of `router`, if it was configured using
+    /// [`Network::set_bgp_router_id`]. If `None` is returned, the router uses its [`RouterId`].
+    pub fn get_bgp_router_id(&self, router: RouterId) -> Option<Ipv4Addr> {
+        self.bgp_router_ids.get(&router).copied()
+    }
+
+    /// Get all effective BGP router-IDs configured using [`Network::set_bgp_router_id`]. Pass
+    /// them to [`BgpRoute::explain_preference`] or [`Router::would_prefer`] to compare routes in
+    /// the same way as the routers in the network.
+    pub fn get_bgp_router_ids(&self) -> &BgpRouterIds {
+        &self.bgp_router_ids
+    }
+
+    /// Set the effective BGP router-ID of `router` (either an internal or an external router), and
+    /// return the old value. The final tie-breaks of the BGP decision process (next-hop,
+    /// router-id, and neighbor ID) compare the effective router-IDs instead of the [`RouterId`]s,
+    /// where lower values are preferred. If `id` is `None`, the [`RouterId`] is used again. Use
+    /// this function to match the router-IDs of a real deployment, e.g., the loopback addresses
+    /// assigned by the addressor when exporting the configuration.
+    ///
+    /// This function will re-run the BGP decision process on all routers *and* run the
+    /// simulation.
+    ///
+    /// *Undo Functionality*: this function will push a new undo event to the queue.
+    pub fn set_bgp_router_id(
+        &mut self,
+        router: RouterId,
+        id: Option<Ipv4Addr>,
+    ) -> Result<Option<Ipv4Addr>, NetworkError> {
+        if !self.routers.contains_key(&router) && !self.external_routers.contains_key(&router) {
+            return Err(NetworkError::DeviceNotFound(router));
+        }
+
+        let old_id = match id {
+            Some(id) => self.bgp_router_ids.insert(router, id),
+            None => self.bgp_router_ids.remove(&router),
+        };
+
+        // prepare undo stack
+        #[cfg(feature = "undo")]
+        self.undo_stack
+            .push(vec![vec![UndoAction::SetBgpRouterId(router, old_id)]]);
+
+        // update all routers, as all of them compare routes using the effective router-ID.
+        let mut events = Vec::new();
+        for r in self.routers.values_mut() {
+            events.append(&mut r.update_bgp_router_ids(&self.bgp_router_ids)?);
+
+            // add the undo action
+            #[cfg(feature = "undo")]
+            self.undo_stack
+                .last_mut()
+                .unwrap()
+                .last_mut()
+                .unwrap()
+                .push(UndoAction::UndoDevice(r.router_id()));
+        }
+
+        self.enqueue_events(events);
+        self.do_queue_maybe_skip()?;
+
+        Ok(old_id)
+    }
+
     /// Advertise an external route and let the network converge, The source must be a `RouterId`
     /// of an `ExternalRouter`. If not, an error is returned. When advertising a route, all
     /// eBGP neighbors will receive an update with the new route. If a neighbor is added later
@@ -851,6 +948,98 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
         self.do_queue_maybe_skip()
     }
 
+    /// Change the advertisement of an external router for a single prefix, and process only the BGP
+    /// messages triggered by that change. If `route` is `None`, the prefix is retracted. Otherwise,
+    /// the AS path, MED, and communities of `route` are advertised. The function returns, for each
+    /// router that changed its next-hop for `prefix`, the next-hop before and after the update.
+    /// Routers that changed their next-hop only transiently are not reported. The messages are
+    /// processed even if the network is in manual simulation mode.
+    ///
+    /// Events for other prefixes that are still enqueued are not processed. They are set aside
+    /// while processing the update, and enqueued again (in their original order) afterwards. Notice
+    /// that timing-based queues compute a new delivery time for those events. If the queue contains
+    /// events for `prefix`, `NetworkError::QueueNotEmpty` is returned without changing the network,
+    /// as these events would be mixed with the ones triggered by this change.
+    ///
+    /// *Undo Functionality*: this function will push a new undo event to the queue.
+    pub fn update_advertisement_incremental(
+        &mut self,
+        source: RouterId,
+        prefix: impl Into<P>,
+        route: Option<BgpRoute<P>>,
+    ) -> Result<HashMap<RouterId, StepUpdate<P>>, NetworkError> {
+        let prefix: P = prefix.into();
+
+        // set aside all pending events, such that we only process the events triggered by this
+        // change.
+        let mut deferred = Vec::with_capacity(self.queue.len());
+        while let Some(event) = self.queue.pop() {
+            deferred.push(event);
+        }
+        let num_pending = deferred
+            .iter()
+            .filter(|e| e.prefix() == Some(prefix))
+            .count();
+        let result = if num_pending > 0 {
+            Err(NetworkError::QueueNotEmpty(num_pending))
+        } else {
+            self.process_advertisement_update(source, prefix, route)
+        };
+
+        // enqueue the pending events again
+        self.enqueue_events(deferred);
+        result
+    }
+
+    /// Change the advertisement of an external router and process all events in the queue,
+    /// recording the forwarding changes for `prefix`. See
+    /// [`Network::update_advertisement_incremental`].
+    fn process_advertisement_update(
+        &mut self,
+        source: RouterId,
+        prefix: P,
+        route: Option<BgpRoute<P>>,
+    ) -> Result<HashMap<RouterId, StepUpdate<P>>, NetworkError> {
+        // only enqueue the events triggered by the external router
+        let skip_queue = std::mem::replace(&mut self.skip_queue, true);
+        let result = match route {
+            Some(route) => self.advertise_external_route(
+                source,
+                prefix,
+                route.as_path,
+                route.med,
+                route.community,
+            ),
+            None => self.retract_external_route(source, prefix),
+        };
+        self.skip_queue = skip_queue;
+        result?;
+
+        // process the queue, recording all forwarding changes for the prefix.
+        let mut updates: HashMap<RouterId, StepUpdate<P>> = HashMap::new();
+        let mut remaining_iter = self.stop_after;
+        while !self.queue.is_empty() {
+            if let Some(rem) = remaining_iter {
+                if rem == 0 {
+                    debug!("Network could not converge!");
+                    return Err(NetworkError::NoConvergence);
+                }
+                remaining_iter = Some(rem - 1);
+            }
+            if let Some((update, event)) = self.simulate_step()? {
+                if update.prefix == Some(prefix) {
+                    updates
+                        .entry(event.router())
+                        .or_insert_with(|| StepUpdate::new(prefix, update.old.clone(), Vec::new()))
+                        .new = update.new;
+                }
+            }
+        }
+
+        updates.retain(|_, update| update.changed());
+        Ok(updates)
+    }
+
     /// Simulate a link failure in the network. This is done by removing the actual link from the
     /// network topology. Afterwards, it will update the IGP forwarding table, and perform the BGP
     /// decision process, which will cause a convergence process. This function will also
@@ -1035,7 +1224,11 @@ impl<P: Prefix, Q: EventQueue<P>> Network<P, Q> {
         // update igp table
         let mut events = vec![];
         for r in self.routers.values_mut() {
-            events.append(&mut r.write_igp_forwarding_table(&self.net, &ospf_state)?);
+            events.append(&mut r.write_igp_forwarding_table(
+                &self.net,
+                &ospf_state,
+                &self.bgp_router_ids,
+            )?);
 
             // add the undo action
             #[cfg(feature = "undo")]
@@ -1142,6 +1335,10 @@ where
             return false;
         }
 
+        if self.bgp_router_ids != other.bgp_router_ids {
+            return false;
+        }
+
         if self.get_config() != other.get_config() {
             return false;
         }
@@ -1200,4 +1397,6 @@ pub(crate) enum UndoAction {
     // AddExternalRouter(RouterId, Box<ExternalRouter>),
     /// Perform the undo action on a device
     UndoDevice(RouterId),
+    /// Reset the effective BGP router-ID of a router.
+    SetBgpRouterId(RouterId, Option<Ipv4Addr>),
 }
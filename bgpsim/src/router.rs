@@ -18,7 +18,7 @@
 //! Module defining an internal router with BGP functionality.
 
 use crate::{
-    bgp::{BgpEvent, BgpRibEntry, BgpRoute, BgpSessionType},
+    bgp::{BgpEvent, BgpRibEntry, BgpRoute, BgpRouterIds, BgpSessionType},
     config::RouteMapEdit,
     event::{Event, EventOutcome},
     formatter::NetworkFormatter,
@@ -40,6 +40,7 @@ use ordered_float::NotNan;
 use petgraph::visit::EdgeRef;
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
     fmt::Write,
     mem::swap,
@@ -83,6 +84,9 @@ pub struct Router<P: Prefix> {
     /// cost. load balancing will only work within OSPF. BGP Additional Paths is not yet
     /// implemented.
     pub(crate) do_load_balancing: bool,
+    /// Cluster ID of the router, used when reflecting routes. If `None`, the router ID is used
+    /// as cluster ID.
+    pub(crate) cluster_id: Option<RouterId>,
     /// Stack to undo action from every event. Each processed event will push a new vector onto the
     /// stack, containing all actions to perform in order to undo the event.
     #[cfg(feature = "undo")]
@@ -106,6 +110,7 @@ impl<P: Prefix> Clone for Router<P> {
             bgp_route_maps_in: self.bgp_route_maps_in.clone(),
             bgp_route_maps_out: self.bgp_route_maps_out.clone(),
             do_load_balancing: self.do_load_balancing,
+            cluster_id: self.cluster_id,
             #[cfg(feature = "undo")]
             undo_stack: self.undo_stack.clone(),
         }
@@ -129,6 +134,7 @@ impl<P: Prefix> Router<P> {
             bgp_route_maps_in: HashMap::new(),
             bgp_route_maps_out: HashMap::new(),
             do_load_balancing: false,
+            cluster_id: None,
             #[cfg(feature = "undo")]
             undo_stack: Vec::new(),
         }
@@ -202,12 +208,14 @@ impl<P: Prefix> Router<P> {
     }
 
     /// handle an `Event`. This function returns all events triggered by this function, and a
-    /// boolean to check if there was an update or not.
+    /// boolean to check if there was an update or not. The BGP decision process uses the effective
+    /// `router_ids` of the network.
     ///
     /// *Undo Functionality*: this function will push a new undo event to the queue.
     pub(crate) fn handle_event<T: Default>(
         &mut self,
         event: Event<P, T>,
+        router_ids: &BgpRouterIds,
     ) -> Result<EventOutcome<P, T>, DeviceError> {
         // first, push a new entry onto the stack
         #[cfg(feature = "undo")]
@@ -227,7 +235,7 @@ impl<P: Prefix> Router<P> {
                         (p, true) => (p, true),
                         (p, false) => {
                             // there is nothing to do here. we simply ignore this event!
-                            trace!("Ignore BGP update with ORIGINATOR_ID of self.");
+                            trace!("Ignore BGP update with ORIGINATOR_ID or CLUSTER_ID of self.");
                             let old = self.get_next_hop(p);
                             return Ok((StepUpdate::new(p, old.clone(), old), vec![]));
                         }
@@ -247,9 +255,9 @@ impl<P: Prefix> Router<P> {
                 // phase 2
                 let old = self.get_next_hop(prefix);
                 let changed = if new {
-                    self.run_bgp_decision_process_for_new_route(prefix, from)
+                    self.run_bgp_decision_process_for_new_route(prefix, from, router_ids)
                 } else {
-                    self.run_bgp_decision_process_for_prefix(prefix)
+                    self.run_bgp_decision_process_for_prefix(prefix, router_ids)
                 }?;
                 if changed {
                     let new = self.get_next_hop(prefix);
@@ -366,6 +374,7 @@ impl<P: Prefix> Router<P> {
                         self.static_routes.remove(&prefix);
                     }
                     UndoAction::SetLoadBalancing(value) => self.do_load_balancing = value,
+                    UndoAction::SetClusterId(value) => self.cluster_id = value,
                 }
             }
         }
@@ -470,6 +479,45 @@ impl<P: Prefix> Router<P> {
         do_load_balancing
     }
 
+    /// Get the cluster ID of the router, which is added to the CLUSTER_LIST when reflecting
+    /// routes. Unless configured otherwise, this is the router ID.
+    pub fn get_cluster_id(&self) -> RouterId {
+        self.cluster_id.unwrap_or(self.router_id)
+    }
+
+    /// Update the configured cluster ID, and return the old value. If set to `None`, the router
+    /// ID is used as cluster ID. The new cluster ID only affects routes that are reflected
+    /// afterwards.
+    ///
+    /// *Undo Functionality*: this function will push a new undo event to the queue.
+    pub(crate) fn set_cluster_id(&mut self, mut cluster_id: Option<RouterId>) -> Option<RouterId> {
+        // set the cluster id
+        std::mem::swap(&mut self.cluster_id, &mut cluster_id);
+
+        // prepare the undo stack
+        #[cfg(feature = "undo")]
+        self.undo_stack
+            .push(vec![UndoAction::SetClusterId(cluster_id)]);
+
+        cluster_id
+    }
+
+    /// Re-run the BGP decision process for all prefixes after the effective `router_ids` of the
+    /// network have changed. This function returns all events triggered by this action.
+    ///
+    /// *Undo Functionality*: this function will push a new undo event to the queue.
+    pub(crate) fn update_bgp_router_ids<T: Default>(
+        &mut self,
+        router_ids: &BgpRouterIds,
+    ) -> Result<Vec<Event<P, T>>, DeviceError> {
+        // prepare the undo stack
+        #[cfg(feature = "undo")]
+        self.undo_stack.push(Vec::new());
+
+        // the tie-breaks may change, so re-run the decision process.
+        self.update_bgp_tables(false, router_ids)
+    }
+
     /// Change or remove a static route from the router. This function returns the old static route
     /// (if it exists).
     ///
@@ -505,6 +553,7 @@ impl<P: Prefix> Router<P> {
         &mut self,
         target: RouterId,
         session_type: Option<BgpSessionType>,
+        router_ids: &BgpRouterIds,
     ) -> UpdateOutcome<BgpSessionType, P, T> {
         // prepare the undo stack
         #[cfg(feature = "undo")]
@@ -552,7 +601,7 @@ impl<P: Prefix> Router<P> {
             .push(UndoAction::BgpSession(target, old_type));
 
         // udpate the tables
-        self.update_bgp_tables(true)
+        self.update_bgp_tables(true, router_ids)
             .map(|events| (old_type, events))
     }
 
@@ -579,6 +628,7 @@ impl<P: Prefix> Router<P> {
         neighbor: RouterId,
         direction: RouteMapDirection,
         mut route_map: RouteMap<P>,
+        router_ids: &BgpRouterIds,
     ) -> UpdateOutcome<RouteMap<P>, P, T> {
         // prepare the undo action
         #[cfg(feature = "undo")]
@@ -628,7 +678,8 @@ impl<P: Prefix> Router<P> {
                 old_map.clone(),
             ));
 
-        self.update_bgp_tables(true).map(|events| (old_map, events))
+        self.update_bgp_tables(true, router_ids)
+            .map(|events| (old_map, events))
     }
 
     /// Update or remove multiple route-map items. Any existing route-map entry for the same
@@ -639,6 +690,7 @@ impl<P: Prefix> Router<P> {
     pub(crate) fn batch_update_route_maps<T: Default>(
         &mut self,
         updates: &[RouteMapEdit<P>],
+        router_ids: &BgpRouterIds,
     ) -> Result<Vec<Event<P, T>>, DeviceError> {
         // prepare the undo action
         #[cfg(feature = "undo")]
@@ -688,7 +740,7 @@ impl<P: Prefix> Router<P> {
                 ));
         }
 
-        self.update_bgp_tables(true)
+        self.update_bgp_tables(true, router_ids)
     }
 
     /// Remove any route map that has the specified order and direction. If the route-map does not
@@ -703,6 +755,7 @@ impl<P: Prefix> Router<P> {
         neighbor: RouterId,
         direction: RouteMapDirection,
         order: i16,
+        router_ids: &BgpRouterIds,
     ) -> UpdateOutcome<RouteMap<P>, P, T> {
         // prepare the undo action
         #[cfg(feature = "undo")]
@@ -751,7 +804,7 @@ impl<P: Prefix> Router<P> {
                 Some(old_map.clone()),
             ));
 
-        self.update_bgp_tables(true)
+        self.update_bgp_tables(true, router_ids)
             .map(|events| (Some(old_map), events))
     }
 
@@ -839,6 +892,53 @@ impl<P: Prefix> Router<P> {
             .collect()
     }
 
+    /// Check if the router would select `candidate` for `prefix` if the neighbor `from` advertised
+    /// it, without modifying the router. The candidate is received using the BGP session type
+    /// towards `from` (or an iBGP peer session if there is none). It is processed by the incoming
+    /// route-maps of that neighbor, and it replaces any route currently learned from `from`. This
+    /// function returns `true` if the processed candidate is strictly preferred over all other
+    /// routes in `RIB_IN`, using the effective `router_ids` of the network (see
+    /// [`Network::get_bgp_router_ids`]). Candidates for a prefix other than `prefix` are never
+    /// preferred.
+    pub fn would_prefer(
+        &self,
+        prefix: P,
+        from: RouterId,
+        candidate: &BgpRoute<P>,
+        router_ids: &BgpRouterIds,
+    ) -> bool {
+        if candidate.prefix != prefix {
+            return false;
+        }
+        let entry = BgpRibEntry {
+            route: candidate.clone(),
+            from_type: self
+                .bgp_sessions
+                .get(&from)
+                .copied()
+                .unwrap_or(BgpSessionType::IBgpPeer),
+            from_id: from,
+            to_id: None,
+            igp_cost: None,
+            weight: 100,
+        };
+        let candidate = match self.process_bgp_rib_in_route(entry).ok().flatten() {
+            Some(e) => e,
+            None => return false,
+        };
+
+        let best = self.bgp_rib_in.get(&prefix).and_then(|rib| {
+            rib.values()
+                .filter(|e| e.from_id != from)
+                .filter_map(|e| self.process_bgp_rib_in_route(e.clone()).ok().flatten())
+                .max_by(|a, b| a.compare_with_router_ids(b, router_ids).0)
+        });
+
+        best.map_or(true, |best| {
+            candidate.compare_with_router_ids(&best, router_ids).0 == Ordering::Greater
+        })
+    }
+
     /// write forawrding table based on graph and return the set of events triggered by this action.
     /// This function requres that all RouterIds are set to the GraphId, and update the BGP tables.
     ///
@@ -847,6 +947,7 @@ impl<P: Prefix> Router<P> {
         &mut self,
         graph: &IgpNetwork,
         ospf: &OspfState,
+        router_ids: &BgpRouterIds,
     ) -> Result<Vec<Event<P, T>>, DeviceError> {
         // prepare the undo action
         #[cfg(feature = "undo")]
@@ -890,7 +991,7 @@ impl<P: Prefix> Router<P> {
             }
         }
 
-        self.update_bgp_tables(false)
+        self.update_bgp_tables(false, router_ids)
     }
 
     /// Update the bgp tables only. If `force_dissemination` is set to true, then this function will
@@ -900,11 +1001,12 @@ impl<P: Prefix> Router<P> {
     fn update_bgp_tables<T: Default>(
         &mut self,
         force_dissemination: bool,
+        router_ids: &BgpRouterIds,
     ) -> Result<Vec<Event<P, T>>, DeviceError> {
         let mut events = Vec::new();
         // run the decision process
         for prefix in self.bgp_known_prefixes.iter().copied().collect::<Vec<_>>() {
-            let changed = self.run_bgp_decision_process_for_prefix(prefix)?;
+            let changed = self.run_bgp_decision_process_for_prefix(prefix, router_ids)?;
             // if the decision process selected a new route, also run the dissemination process.
             if changed || force_dissemination {
                 events.append(&mut self.run_bgp_route_dissemination_for_prefix(prefix)?);
@@ -958,6 +1060,7 @@ impl<P: Prefix> Router<P> {
         &mut self,
         prefix: P,
         neighbor: RouterId,
+        router_ids: &BgpRouterIds,
     ) -> Result<bool, DeviceError> {
         // search the best route and compare
         let old_entry = self.bgp_rib.get(&prefix);
@@ -972,7 +1075,11 @@ impl<P: Prefix> Router<P> {
             (None, None) => Ok(false),
             // otherwise, if the new route is better than the old one, we can replace it in any
             // case, even if the origin of both routes would be the same.
-            (old, Some(new)) if new > old => {
+            (old, Some(new))
+                if old.map_or(true, |old| {
+                    new.compare_with_router_ids(old, router_ids).0 == Ordering::Greater
+                }) =>
+            {
                 // replace the old with the better, new route
                 let _old_entry = self.bgp_rib.insert(prefix, new);
                 // add the undo action
@@ -989,7 +1096,7 @@ impl<P: Prefix> Router<P> {
             // need to re-run the entire decision process.
             (Some(old), _) if old.from_id == neighbor => {
                 // the old is replaced by the new. Now, we need to re-run the decision process.
-                self.run_bgp_decision_process_for_prefix(prefix)
+                self.run_bgp_decision_process_for_prefix(prefix, router_ids)
             }
             // If the old selected route is not from that neighbor that is updated right now, and
             // the new route is worse than the old route (due to the second case in this match
@@ -1005,16 +1112,19 @@ impl<P: Prefix> Router<P> {
     /// (and the dissemination process should be executed).
     ///
     /// *Undo Functionality*: this function will push some actions to the last undo event.
-    fn run_bgp_decision_process_for_prefix(&mut self, prefix: P) -> Result<bool, DeviceError> {
+    fn run_bgp_decision_process_for_prefix(
+        &mut self,
+        prefix: P,
+        router_ids: &BgpRouterIds,
+    ) -> Result<bool, DeviceError> {
         // search the best route and compare
         let old_entry = self.bgp_rib.get(&prefix);
 
         // find the new best route
         let new_entry = self.bgp_rib_in.get(&prefix).and_then(|rib| {
-            Iterator::max(
-                rib.values()
-                    .filter_map(|e| self.process_bgp_rib_in_route(e.clone()).ok().flatten()),
-            )
+            rib.values()
+                .filter_map(|e| self.process_bgp_rib_in_route(e.clone()).ok().flatten())
+                .max_by(|a, b| a.compare_with_router_ids(b, router_ids).0)
         });
 
         // check if the entry will get changed
@@ -1143,7 +1253,8 @@ impl<P: Prefix> Router<P> {
     /// route-maps does not requrie a new update from the neighbor.
     ///
     /// This function returns the prefix, along with a boolean. If that boolean is `false`, then
-    /// no route was inserted into the table because ORIGINATOR_ID equals the current router id.
+    /// no route was inserted into the table because ORIGINATOR_ID equals the current router id, or
+    /// because the CLUSTER_LIST contains the cluster id of the router.
     ///
     /// *Undo Functionality*: this function will push some actions to the last undo event.
     fn insert_bgp_route(
@@ -1156,9 +1267,11 @@ impl<P: Prefix> Router<P> {
             .get(&from)
             .ok_or(DeviceError::NoBgpSession(from))?;
 
-        // if the ORIGINATOR_ID field equals the id of the router, then ignore this route and return
-        // nothing.
-        if route.originator_id == Some(self.router_id) {
+        // if the ORIGINATOR_ID field equals the id of the router, or if the CLUSTER_LIST contains
+        // the cluster id of the router, then ignore this route and return nothing.
+        if route.originator_id == Some(self.router_id)
+            || route.cluster_list.contains(&self.get_cluster_id())
+        {
             return Ok((route.prefix, false));
         }
 
@@ -1283,8 +1396,8 @@ impl<P: Prefix> Router<P> {
         if entry.from_type.is_ibgp() && target_session_type.is_ibgp() {
             // route is to be reflected. Modify the ORIGINATOR_ID and the CLUSTER_LIST.
             entry.route.originator_id.get_or_insert(entry.from_id);
-            // append self to the cluster_list
-            entry.route.cluster_list.push(self.router_id);
+            // append the cluster id to the cluster_list
+            entry.route.cluster_list.push(self.get_cluster_id());
         }
 
         // set the to_id to the target peer
@@ -1345,6 +1458,7 @@ impl<P: Prefix> PartialEq for Router<P> {
     fn eq(&self, other: &Self) -> bool {
         if !(self.name == other.name
             && self.do_load_balancing == other.do_load_balancing
+            && self.cluster_id == other.cluster_id
             && self.router_id == other.router_id
             && self.as_id == other.as_id
             && self.igp_table == other.igp_table
@@ -1389,6 +1503,7 @@ pub(crate) enum UndoAction<P: Prefix> {
     DelKnownPrefix(P),
     StaticRoute(P, Option<StaticRoute>),
     SetLoadBalancing(bool),
+    SetClusterId(Option<RouterId>),
 }
 
 /// Static route description that can either point to the direct link to the target, or to use the
@@ -1435,6 +1550,7 @@ impl<P: Prefix> Serialize for Router<P> {
             bgp_route_maps_in: Vec<(RouterId, Vec<RouteMap<P>>)>,
             bgp_route_maps_out: Vec<(RouterId, Vec<RouteMap<P>>)>,
             do_load_balancing: bool,
+            cluster_id: Option<RouterId>,
             #[cfg(feature = "undo")]
             undo_stack: Vec<Vec<UndoAction<P>>>,
         }
@@ -1463,6 +1579,7 @@ impl<P: Prefix> Serialize for Router<P> {
             bgp_route_maps_in: self.bgp_route_maps_in.clone().into_iter().collect(),
             bgp_route_maps_out: self.bgp_route_maps_out.clone().into_iter().collect(),
             do_load_balancing: self.do_load_balancing,
+            cluster_id: self.cluster_id,
             #[cfg(feature = "undo")]
             undo_stack: self.undo_stack.clone(),
         }
@@ -1492,6 +1609,8 @@ impl<'de, P: Prefix> Deserialize<'de> for Router<P> {
             bgp_route_maps_in: Vec<(RouterId, Vec<RouteMap<P>>)>,
             bgp_route_maps_out: Vec<(RouterId, Vec<RouteMap<P>>)>,
             do_load_balancing: bool,
+            #[serde(default)]
+            cluster_id: Option<RouterId>,
             #[cfg(feature = "undo")]
             undo_stack: Vec<Vec<UndoAction<P>>>,
         }
@@ -1519,6 +1638,7 @@ impl<'de, P: Prefix> Deserialize<'de> for Router<P> {
             bgp_route_maps_in: router.bgp_route_maps_in.into_iter().collect(),
             bgp_route_maps_out: router.bgp_route_maps_out.into_iter().collect(),
             do_load_balancing: router.do_load_balancing,
+            cluster_id: router.cluster_id,
             #[cfg(feature = "undo")]
             undo_stack: router.undo_stack.into_iter().collect(),
         })
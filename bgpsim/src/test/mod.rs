@@ -96,6 +96,7 @@ macro_rules! test_bad_route {
     };
 }
 
+mod test_bgp;
 mod test_builder;
 mod test_config;
 #[cfg(feature = "export")]
@@ -0,0 +1,216 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::{cmp::Ordering, net::Ipv4Addr};
+
+use ordered_float::NotNan;
+
+use crate::{
+    bgp::{BgpRibEntry, BgpRoute, BgpRouterIds, BgpSessionType::*, PreferenceReason},
+    types::{AsId, SimplePrefix},
+};
+
+fn entry() -> BgpRibEntry<SimplePrefix> {
+    BgpRibEntry {
+        route: BgpRoute {
+            prefix: SimplePrefix::from(0),
+            as_path: vec![AsId(100), AsId(200)],
+            next_hop: 10.into(),
+            local_pref: Some(100),
+            med: Some(10),
+            community: Default::default(),
+            originator_id: None,
+            cluster_list: Vec::new(),
+        },
+        from_type: IBgpPeer,
+        from_id: 10.into(),
+        to_id: None,
+        igp_cost: Some(NotNan::new(10.0).unwrap()),
+        weight: 100,
+    }
+}
+
+/// Check that `better` is preferred over `worse` due to `reason`, in both directions.
+fn check(
+    better: &BgpRibEntry<SimplePrefix>,
+    worse: &BgpRibEntry<SimplePrefix>,
+    reason: PreferenceReason,
+) {
+    assert_eq!(better.cmp(worse), Ordering::Greater);
+    assert_eq!(worse.cmp(better), Ordering::Less);
+    let ids = BgpRouterIds::new();
+    assert_eq!(better.explain_preference(worse, &ids), reason);
+    assert_eq!(worse.explain_preference(better, &ids), reason);
+}
+
+#[test]
+fn explain_equal() {
+    let a = entry();
+    assert_eq!(a.cmp(&entry()), Ordering::Equal);
+    assert_eq!(
+        a.explain_preference(&entry(), &BgpRouterIds::new()),
+        PreferenceReason::Equal
+    );
+}
+
+#[test]
+fn explain_weight() {
+    let mut a = entry();
+    let mut b = entry();
+    a.weight = 200;
+    // weight takes precedence over the local preference.
+    b.route.local_pref = Some(200);
+    check(&a, &b, PreferenceReason::Weight);
+}
+
+#[test]
+fn explain_local_pref() {
+    let mut a = entry();
+    let mut b = entry();
+    a.route.local_pref = Some(200);
+    // local preference takes precedence over the AS path length.
+    a.route.as_path.push(AsId(300));
+    b.route.local_pref = None;
+    check(&a, &b, PreferenceReason::LocalPref);
+}
+
+#[test]
+fn explain_as_path_length() {
+    let a = entry();
+    let mut b = entry();
+    b.route.as_path.push(AsId(300));
+    // AS path length takes precedence over the MED.
+    b.route.med = Some(0);
+    check(&a, &b, PreferenceReason::AsPathLength);
+}
+
+#[test]
+fn explain_med() {
+    let a = entry();
+    let mut b = entry();
+    b.route.med = Some(20);
+    // MED takes precedence over the session type.
+    b.from_type = EBgp;
+    check(&a, &b, PreferenceReason::Med);
+
+    // MED is ignored for routes from different neighboring ASes.
+    b.route.as_path[0] = AsId(101);
+    b.route.next_hop = 20.into();
+    b.from_type = IBgpPeer;
+    assert_eq!(
+        a.explain_preference(&b, &BgpRouterIds::new()),
+        PreferenceReason::NextHop
+    );
+}
+
+#[test]
+fn explain_session_type() {
+    let mut a = entry();
+    let b = entry();
+    a.from_type = EBgp;
+    // session type takes precedence over the IGP cost.
+    a.igp_cost = Some(NotNan::new(20.0).unwrap());
+    check(&a, &b, PreferenceReason::SessionType);
+
+    // iBGP peers and route-reflector clients are treated equally.
+    a.from_type = IBgpClient;
+    assert_eq!(
+        a.explain_preference(&b, &BgpRouterIds::new()),
+        PreferenceReason::IgpCost
+    );
+}
+
+#[test]
+fn explain_igp_cost() {
+    let a = entry();
+    let mut b = entry();
+    b.igp_cost = Some(NotNan::new(20.0).unwrap());
+    // IGP cost takes precedence over the next-hop.
+    b.route.next_hop = 5.into();
+    check(&a, &b, PreferenceReason::IgpCost);
+}
+
+#[test]
+fn explain_next_hop() {
+    let a = entry();
+    let mut b = entry();
+    b.route.next_hop = 20.into();
+    // next-hop takes precedence over the router-id.
+    b.from_id = 5.into();
+    check(&a, &b, PreferenceReason::NextHop);
+}
+
+#[test]
+fn explain_next_hop_router_ids() {
+    let a = entry();
+    let mut b = entry();
+    b.route.next_hop = 20.into();
+    // with configured router-IDs, the effective ID of the next-hop decides.
+    let ids: BgpRouterIds = [
+        (10.into(), Ipv4Addr::new(10, 0, 0, 2)),
+        (20.into(), Ipv4Addr::new(10, 0, 0, 1)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        b.compare_with_router_ids(&a, &ids),
+        (Ordering::Greater, PreferenceReason::NextHop)
+    );
+    assert_eq!(a.explain_preference(&b, &ids), PreferenceReason::NextHop);
+
+    // routers with a configured router-ID are preferred over those without.
+    let ids: BgpRouterIds = [(20.into(), Ipv4Addr::new(10, 0, 0, 1))]
+        .into_iter()
+        .collect();
+    assert_eq!(b.compare_with_router_ids(&a, &ids).0, Ordering::Greater);
+    assert_eq!(
+        a.compare_with_router_ids(&b, &BgpRouterIds::new()),
+        (Ordering::Less, PreferenceReason::NextHop)
+    );
+}
+
+#[test]
+fn explain_router_id() {
+    let mut a = entry();
+    let mut b = entry();
+    b.route.originator_id = Some(20.into());
+    // the router-id takes precedence over the cluster list length.
+    a.route.cluster_list.push(1.into());
+    check(&a, &b, PreferenceReason::RouterId);
+}
+
+#[test]
+fn explain_cluster_list_length() {
+    let mut a = entry();
+    let mut b = entry();
+    a.route.originator_id = Some(20.into());
+    b.route.originator_id = Some(20.into());
+    b.route.cluster_list.push(1.into());
+    // the cluster list length takes precedence over the neighbor ID.
+    a.from_id = 30.into();
+    check(&a, &b, PreferenceReason::ClusterListLength);
+}
+
+#[test]
+fn explain_neighbor_id() {
+    let mut a = entry();
+    let mut b = entry();
+    a.route.originator_id = Some(20.into());
+    b.route.originator_id = Some(20.into());
+    b.from_id = 30.into();
+    check(&a, &b, PreferenceReason::NeighborId);
+}
@@ -0,0 +1,53 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use pretty_assertions::assert_str_eq;
+
+use crate::{
+    builder::{constant_link_weight, NetworkBuilder},
+    event::BasicEventQueue,
+    export::{cisco_frr_generators::Target::AristaEos as Target, AristaEosCfgGen, InternalCfgGen},
+    network::Network,
+    types::SimplePrefix,
+};
+
+#[test]
+fn generate_internal_config_full_mesh() {
+    assert_str_eq!(
+        super::generate_internal_config_full_mesh(Target),
+        include_str!("internal_config_full_mesh")
+    );
+}
+
+#[test]
+fn generate_internal_config_arista_cfg_gen() {
+    let mut net: Network<SimplePrefix, _> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+    net.build_external_routers(|_, _| vec![0.into(), 1.into()], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 100.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+
+    let mut ip = super::addressor(&net);
+
+    let mut cfg_gen = AristaEosCfgGen::new(&net, 0.into(), super::iface_names(Target)).unwrap();
+    assert_str_eq!(
+        InternalCfgGen::generate_config(&mut cfg_gen, &net, &mut ip).unwrap(),
+        include_str!("internal_config_full_mesh")
+    );
+}
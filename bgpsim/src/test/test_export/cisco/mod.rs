@@ -21,6 +21,7 @@ use crate::{
     config::{ConfigExpr, ConfigModifier::*},
     export::{
         cisco_frr_generators::Target::CiscoNexus7000 as Target, CiscoFrrCfgGen, InternalCfgGen,
+        LintWarning,
     },
     route_map::{RouteMapBuilder, RouteMapDirection::Incoming},
     types::{NonOverlappingPrefix, Prefix, SimplePrefix, SinglePrefix},
@@ -69,6 +70,17 @@ fn generate_internal_config_full_mesh() {
     );
 }
 
+#[test]
+fn generate_internal_config_bfd() {
+    assert_str_eq!(
+        super::generate_internal_config_bfd(Target, true),
+        include_str!("internal_config_bfd")
+    );
+    let cfg = super::generate_internal_config_bfd(Target, false);
+    assert!(!cfg.contains("bfd"));
+    assert_str_eq!(cfg, include_str!("internal_config_full_mesh"));
+}
+
 #[test]
 fn generate_internal_config_route_reflector() {
     assert_str_eq!(
@@ -246,6 +258,151 @@ no route-map neighbor-R0_ext_4-in permit 32781
     );
 }
 
+#[test]
+fn generate_route_map_set_community() {
+    let net = super::net_for_route_maps::<SimplePrefix>();
+    let mut ip = super::addressor(&net);
+    let mut cfg_gen =
+        CiscoFrrCfgGen::new(&net, 0.into(), Target, super::iface_names(Target)).unwrap();
+
+    let cmd = ConfigExpr::BgpRouteMap {
+        router: 0.into(),
+        neighbor: 4.into(),
+        direction: Incoming,
+        map: RouteMapBuilder::new()
+            .allow()
+            .order(5)
+            .set_community(10)
+            .set_community(20)
+            .set_local_pref(200)
+            .build(),
+    };
+
+    let rm = "\
+route-map neighbor-R0_ext_4-in permit 32773
+  set local-preference 200
+  set community additive 65535:10 65535:20
+  continue 32778
+exit
+";
+
+    assert_str_eq!(
+        cfg_gen
+            .generate_command(&net, &mut ip, Insert(cmd.clone()))
+            .unwrap(),
+        rm
+    );
+
+    // inserting the same route-map again yields the same commands.
+    assert_str_eq!(
+        cfg_gen
+            .generate_command(&net, &mut ip, Insert(cmd))
+            .unwrap(),
+        rm
+    );
+}
+
+#[test]
+fn generate_route_map_replace() {
+    let net = super::net_for_route_maps::<SimplePrefix>();
+    let mut ip = super::addressor(&net);
+    let mut cfg_gen =
+        CiscoFrrCfgGen::new(&net, 0.into(), Target, super::iface_names(Target)).unwrap();
+
+    // replace the existing route-map item with order 10.
+    let cmd = ConfigExpr::BgpRouteMap {
+        router: 0.into(),
+        neighbor: 4.into(),
+        direction: Incoming,
+        map: RouteMapBuilder::new()
+            .allow()
+            .order(10)
+            .match_prefix(0.into())
+            .set_community(10)
+            .set_local_pref(200)
+            .continue_at(30)
+            .build(),
+    };
+
+    assert_str_eq!(
+        cfg_gen
+            .generate_command(&net, &mut ip, Insert(cmd))
+            .unwrap(),
+        "\
+no ip prefix-list neighbor-R0_ext_4-in-32778-pl
+ip prefix-list neighbor-R0_ext_4-in-32778-pl seq 1 permit 100.0.0.0/24
+no ip community-list standard neighbor-R0_ext_4-in-32778-cl
+route-map neighbor-R0_ext_4-in permit 32778
+  match ip address prefix-list neighbor-R0_ext_4-in-32778-pl
+  no match community neighbor-R0_ext_4-in-32778-cl
+  no set weight
+  set local-preference 200
+  set community additive 65535:10
+  continue 32798
+exit
+"
+    );
+}
+
+#[test]
+fn lint_clean_config() {
+    assert_eq!(
+        CiscoFrrCfgGen::<SimplePrefix>::lint(include_str!("internal_config_full_mesh")),
+        Vec::new()
+    );
+    assert_eq!(
+        CiscoFrrCfgGen::<SimplePrefix>::lint(include_str!("internal_config_route_maps")),
+        Vec::new()
+    );
+}
+
+#[test]
+fn lint_malformed_config() {
+    let config = "\
+interface Ethernet8/1
+  ip address 10.128.0.1/30
+  no shutdown
+exit
+router bgp 65535
+  neighbor 10.0.1.1
+    update-source Loopback0
+    address-family ipv4 unicast
+      route-map neighbor-R1-in in
+    exit
+  exit
+  neighbor 10.0.2.1 remote-as 65535
+    update-source Ethernet8/1
+    address-family ipv4 unicast
+      route-map neighbor-R2-in in
+    exit
+  exit
+exit
+route-map neighbor-R2-in permit 65535
+exit
+interface Loopback0
+  ip address 10.0.0.1/32
+exit
+";
+
+    assert_eq!(
+        CiscoFrrCfgGen::<SimplePrefix>::lint(config),
+        vec![
+            LintWarning::NeighborWithoutRemoteAs {
+                line: 6,
+                neighbor: "10.0.1.1".to_string()
+            },
+            LintWarning::InterfaceNotDefined {
+                line: 7,
+                iface: "Loopback0".to_string()
+            },
+            LintWarning::RouteMapNotDefined {
+                line: 9,
+                route_map: "neighbor-R1-in".to_string()
+            },
+        ]
+    );
+}
+
 #[test]
 fn generate_external_config_withdraw() {
     let (cfg, cmd) = super::generate_external_config_withdraw(Target);
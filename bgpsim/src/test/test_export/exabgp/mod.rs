@@ -16,6 +16,7 @@
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
 use crate::{
+    bgp::BgpRoute,
     builder::NetworkBuilder,
     event::BasicEventQueue,
     export::{Addressor, ExaBgpCfgGen, ExternalCfgGen},
@@ -198,3 +199,182 @@ fn script_2n_2p_withdraw_pec() {
     let script = gen.withdraw_route(&net, &mut ip, 0.into()).unwrap();
     assert_eq!(script, include_str!("config_2n_2p_withdraw_pec.py"));
 }
+
+#[test]
+fn script_1n_batch_pec() {
+    let num_neighbors = 1;
+    let net = get_test_net::<SimplePrefix>(num_neighbors);
+    let ext: RouterId = (num_neighbors as u32).into();
+    let mut ip = addressor(&net);
+    let pec = vec![
+        prefix!("200.0.1.0/24"),
+        prefix!("200.0.2.0/24"),
+        prefix!("200.0.3.0/24"),
+    ];
+    ip.register_pec(0.into(), pec.clone());
+    let mut gen = ExaBgpCfgGen::new(&net, ext).unwrap();
+    let _ = gen.generate_config(&net, &mut ip).unwrap();
+    gen.step_time(Duration::from_secs(10));
+    let routes = vec![
+        BgpRoute::new(ext, 0, [100, 60], None, []),
+        BgpRoute::new(ext, 1, [100, 60], None, []),
+    ];
+    let script = gen.advertise_routes_batch(&net, &mut ip, &routes).unwrap();
+
+    // all prefixes are announced in a single group
+    let groups = script
+        .lines()
+        .filter(|l| l.contains("announce attributes"))
+        .collect::<Vec<_>>();
+    assert_eq!(groups.len(), 1);
+    assert!(!script.contains("announce route"));
+    for p in pec
+        .iter()
+        .chain(ip.prefix(1.into()).unwrap().to_vec().iter())
+    {
+        assert!(groups[0].contains(&p.to_string()));
+    }
+}
+
+#[test]
+fn script_1n_next_hop_self() {
+    let num_neighbors = 1;
+    let net = get_test_net::<SimplePrefix>(num_neighbors);
+    let ext: RouterId = (num_neighbors as u32).into();
+    let mut ip = addressor(&net);
+    let route = BgpRoute::new(ext, 0, [100, 60], None, []);
+    let net_0 = ip.prefix(0.into()).unwrap().to_vec()[0];
+    let ext_addr = ip.iface_address(ext, 0.into()).unwrap();
+
+    // default: next-hop self
+    let mut gen = ExaBgpCfgGen::new(&net, ext).unwrap();
+    let script = gen.advertise_route(&net, &mut ip, &route).unwrap();
+    assert!(script.contains(&format!(
+        "announce route {net_0} next-hop self as-path [100, 60]"
+    )));
+
+    // explicit next-hop
+    let mut gen = ExaBgpCfgGen::new(&net, ext).unwrap();
+    gen.set_next_hop_self(false);
+    let script = gen.advertise_route(&net, &mut ip, &route).unwrap();
+    assert!(script.contains(&format!(
+        "announce route {net_0} next-hop {ext_addr} as-path [100, 60]"
+    )));
+    assert!(!script.contains("next-hop self"));
+}
+
+#[test]
+fn script_2n_explicit_next_hop() {
+    let num_neighbors = 2;
+    let net = get_test_net::<SimplePrefix>(num_neighbors);
+    let ext: RouterId = (num_neighbors as u32).into();
+    let mut ip = addressor(&net);
+    let route = BgpRoute::new(ext, 0, [100, 60], None, []);
+    let net_0 = ip.prefix(0.into()).unwrap().to_vec()[0];
+
+    let mut gen = ExaBgpCfgGen::new(&net, ext).unwrap();
+    gen.set_next_hop_self(false);
+    let _ = gen.advertise_route(&net, &mut ip, &route).unwrap();
+    let lines = gen.generate_lines(&mut ip).unwrap();
+
+    // each neighbor receives the route with the address of the interface towards it.
+    let expected = [0, 1]
+        .into_iter()
+        .map(|n| {
+            let addr = ip.iface_address(n.into(), ext).unwrap();
+            let ext_addr = ip.iface_address(ext, n.into()).unwrap();
+            format!(
+                "sys.stdout.write(\"neighbor {addr} announce route {net_0} next-hop {ext_addr} \
+                 as-path [100, 60]\\n\")"
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(lines.last().unwrap().0, expected);
+}
+
+#[test]
+fn script_2n_flap_session() {
+    let num_neighbors = 2;
+    let mut net = get_test_net::<SimplePrefix>(num_neighbors);
+    let ext: RouterId = (num_neighbors as u32).into();
+    net.advertise_external_route(ext, 0, [100, 60], None, None)
+        .unwrap();
+    let mut ip = addressor(&net);
+    let neighbor = RouterId::from(0);
+    let addr = ip.iface_address(neighbor, ext).unwrap();
+    let net_0 = ip.prefix(0.into()).unwrap().to_vec()[0];
+    let attrs = "next-hop self as-path [100, 60]";
+
+    let mut gen = ExaBgpCfgGen::new(&net, ext).unwrap();
+    let _ = gen.generate_config(&net, &mut ip).unwrap();
+    gen.step_time(Duration::from_secs(10));
+    gen.flap_session(&net, &mut ip, neighbor, Duration::from_secs(5))
+        .unwrap();
+
+    // withdraw at 10 seconds, and re-announce the route only to that neighbor at 15 seconds.
+    let lines = gen.generate_lines(&mut ip).unwrap();
+    assert_eq!(
+        lines.iter().map(|(_, t)| t.as_secs()).collect::<Vec<_>>(),
+        vec![0, 10, 15]
+    );
+    assert_eq!(
+        lines[1].0,
+        vec![format!(
+            "sys.stdout.write(\"neighbor {addr} withdraw route {net_0}\\n\")"
+        )]
+    );
+    assert_eq!(
+        lines[2].0,
+        vec![format!(
+            "sys.stdout.write(\"neighbor {addr} announce route {net_0} {attrs}\\n\")"
+        )]
+    );
+
+    // updates within the down window are only sent to the other neighbor, and the new route is
+    // announced to the flapped neighbor once the session is up again.
+    let other_addr = ip.iface_address(1.into(), ext).unwrap();
+    gen.step_time(Duration::from_secs(2));
+    let new_route = BgpRoute::new(ext, 0, [100, 40, 60], None, []);
+    let _ = gen.advertise_route(&net, &mut ip, &new_route).unwrap();
+    let new_attrs = "next-hop self as-path [100, 40, 60]";
+    let lines = gen.generate_lines(&mut ip).unwrap();
+    assert_eq!(
+        lines.iter().map(|(_, t)| t.as_secs()).collect::<Vec<_>>(),
+        vec![0, 10, 12, 15]
+    );
+    assert_eq!(
+        lines[2].0,
+        vec![format!(
+            "sys.stdout.write(\"neighbor {other_addr} announce route {net_0} {new_attrs}\\n\")"
+        )]
+    );
+    assert_eq!(
+        lines[3].0,
+        vec![format!(
+            "sys.stdout.write(\"neighbor {addr} announce route {net_0} {new_attrs}\\n\")"
+        )]
+    );
+
+    // the external router cannot flap a session to itself.
+    assert!(gen
+        .flap_session(&net, &mut ip, ext, Duration::from_secs(5))
+        .is_err());
+}
+
+#[test]
+fn config_1n_graceful_restart() {
+    let num_neighbors = 1;
+    let net = get_test_net::<SimplePrefix>(num_neighbors);
+    let ext: RouterId = (num_neighbors as u32).into();
+    let mut ip = addressor(&net);
+
+    let mut gen = ExaBgpCfgGen::new(&net, ext).unwrap();
+    gen.set_graceful_restart(true);
+    let cfg = gen.generate_config(&net, &mut ip).unwrap();
+    assert!(cfg.contains("    capability { route-refresh; graceful-restart; }\n"));
+
+    // without graceful restart, the config is unchanged.
+    gen.set_graceful_restart(false);
+    let cfg = gen.generate_config(&net, &mut ip).unwrap();
+    assert_eq!(cfg, include_str!("config_1n.ini"));
+}
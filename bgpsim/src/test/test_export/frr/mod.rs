@@ -63,6 +63,17 @@ fn generate_internal_config_full_mesh() {
     );
 }
 
+#[test]
+fn generate_internal_config_bfd() {
+    assert_str_eq!(
+        super::generate_internal_config_bfd(Target, true),
+        include_str!("internal_config_bfd")
+    );
+    let cfg = super::generate_internal_config_bfd(Target, false);
+    assert!(!cfg.contains("bfd"));
+    assert_str_eq!(cfg, include_str!("internal_config_full_mesh"));
+}
+
 #[test]
 fn generate_internal_config_route_maps_with_pec() {
     assert_str_eq!(
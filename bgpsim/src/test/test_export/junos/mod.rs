@@ -0,0 +1,118 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use crate::{
+    bgp::BgpRoute,
+    event::BasicEventQueue,
+    export::{ExternalCfgGen, JunosExternalCfgGen},
+    network::Network,
+    types::{AsId, RouterId, SimplePrefix as P},
+};
+use pretty_assertions::assert_str_eq;
+
+use super::addressor;
+
+fn get_test_net() -> Network<P, BasicEventQueue<P>> {
+    let mut net = Network::build_complete_graph(BasicEventQueue::new(), 1);
+    let ext = net.add_external_router("external_router", AsId(100));
+    net.get_routers()
+        .into_iter()
+        .for_each(|r| net.add_link(r, ext));
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+    net.build_link_weights(|_, _, _, _| 1.0, ()).unwrap();
+
+    net
+}
+
+fn iface_names() -> Vec<String> {
+    (0..8).map(|i| format!("ge-0/0/{i}")).collect()
+}
+
+#[test]
+fn establish_session() {
+    let net = get_test_net();
+    let ext: RouterId = 1.into();
+    let mut ip = addressor(&net);
+
+    let mut gen = JunosExternalCfgGen::new(&net, ext, iface_names()).unwrap();
+    assert_str_eq!(
+        gen.establish_ebgp_session(&net, &mut ip, 0.into()).unwrap(),
+        include_str!("establish_session")
+    );
+    assert_str_eq!(
+        gen.teardown_ebgp_session(&net, &mut ip, 0.into()).unwrap(),
+        "delete protocols bgp group internal-network neighbor 10.192.0.1\n"
+    );
+}
+
+#[test]
+fn advertise_route() {
+    let net = get_test_net();
+    let ext: RouterId = 1.into();
+    let mut ip = addressor(&net);
+
+    let mut gen = JunosExternalCfgGen::new(&net, ext, iface_names()).unwrap();
+    let route = BgpRoute::new(ext, P::from(0), [100, 200, 300], Some(10), [20, 30]);
+    assert_str_eq!(
+        gen.advertise_route(&net, &mut ip, &route).unwrap(),
+        include_str!("advertise_route")
+    );
+}
+
+#[test]
+fn withdraw_route() {
+    let net = get_test_net();
+    let ext: RouterId = 1.into();
+    let mut ip = addressor(&net);
+
+    let mut gen = JunosExternalCfgGen::new(&net, ext, iface_names()).unwrap();
+    assert!(gen.withdraw_route(&net, &mut ip, P::from(0)).is_err());
+
+    let route = BgpRoute::new(ext, P::from(0), [100], None, None);
+    gen.advertise_route(&net, &mut ip, &route).unwrap();
+    assert_str_eq!(
+        gen.withdraw_route(&net, &mut ip, P::from(0)).unwrap(),
+        include_str!("withdraw_route")
+    );
+}
+
+#[test]
+fn withdraw_route_shared_community() {
+    let net = get_test_net();
+    let ext: RouterId = 1.into();
+    let mut ip = addressor(&net);
+
+    let mut gen = JunosExternalCfgGen::new(&net, ext, iface_names()).unwrap();
+    let route_a = BgpRoute::new(ext, P::from(0), [100], None, [20, 30]);
+    let route_b = BgpRoute::new(ext, P::from(1), [100], None, [20]);
+    gen.advertise_route(&net, &mut ip, &route_a).unwrap();
+
+    // the community 20 is already defined by the first route.
+    let cfg = gen.advertise_route(&net, &mut ip, &route_b).unwrap();
+    assert!(!cfg.contains("set policy-options community comm-20 members"));
+    assert!(cfg.contains("prefix-1 term advertise then community add comm-20\n"));
+
+    // the community 20 is still used by the second route.
+    let cfg = gen.withdraw_route(&net, &mut ip, P::from(0)).unwrap();
+    assert!(!cfg.contains("delete policy-options community comm-20\n"));
+    assert!(cfg.contains("delete policy-options community comm-30\n"));
+
+    // the last route using community 20 is withdrawn.
+    let cfg = gen.withdraw_route(&net, &mut ip, P::from(1)).unwrap();
+    assert!(cfg.contains("delete policy-options community comm-20\n"));
+}
@@ -29,14 +29,17 @@ use crate::{
     types::{NonOverlappingPrefix, Prefix, SimplePrefix},
 };
 
+mod arista;
 mod cisco;
 mod exabgp;
 mod frr;
+mod junos;
 
 pub(self) fn iface_names(target: Target) -> Vec<String> {
     match target {
         Target::CiscoNexus7000 => (1..=48).map(|i| format!("Ethernet8/{i}")).collect(),
         Target::Frr => (1..=8).map(|i| format!("eth{i}")).collect(),
+        Target::AristaEos => (1..=48).map(|i| format!("Ethernet{i}")).collect(),
     }
 }
 
@@ -65,6 +68,22 @@ pub(self) fn generate_internal_config_full_mesh(target: Target) -> String {
     InternalCfgGen::generate_config(&mut cfg_gen, &net, &mut ip).unwrap()
 }
 
+pub(self) fn generate_internal_config_bfd(target: Target, bfd: bool) -> String {
+    let mut net: Network<SimplePrefix, _> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+    net.build_external_routers(|_, _| vec![0.into(), 1.into()], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 100.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+
+    let mut ip = addressor(&net);
+
+    let mut cfg_gen = CiscoFrrCfgGen::new(&net, 0.into(), target, iface_names(target)).unwrap();
+    cfg_gen.set_bfd(bfd, 50, 50, 3);
+    InternalCfgGen::generate_config(&mut cfg_gen, &net, &mut ip).unwrap()
+}
+
 pub(self) fn generate_internal_config_route_reflector(target: Target) -> String {
     let mut net: Network<SimplePrefix, _> =
         NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
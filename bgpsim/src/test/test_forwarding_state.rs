@@ -293,6 +293,97 @@ mod t {
         }
     }
 
+    #[test]
+    fn weighted_paths_diamond<P: Prefix>() {
+        let mut net: Network<P, _> = Network::default();
+
+        let r0 = net.add_router("r0");
+        let r1 = net.add_router("r1");
+        let r2 = net.add_router("r2");
+        let r3 = net.add_router("r3");
+        let e = net.add_external_router("e", AsId(65100));
+
+        for (a, b) in [(r0, r1), (r0, r2), (r1, r3), (r2, r3), (r3, e)] {
+            net.add_link(a, b);
+            net.set_link_weight(a, b, 1.0).unwrap();
+            net.set_link_weight(b, a, 1.0).unwrap();
+        }
+
+        for a in [r0, r1, r2, r3] {
+            for b in [r0, r1, r2, r3] {
+                if a < b {
+                    net.set_bgp_session(a, b, Some(IBgpPeer)).unwrap();
+                }
+            }
+        }
+        net.set_bgp_session(r3, e, Some(EBgp)).unwrap();
+
+        // use both paths from r0 to r3
+        for r in [r0, r1, r2, r3] {
+            net.set_load_balancing(r, true).unwrap();
+        }
+
+        let p = P::from(0);
+        net.advertise_external_route(e, p, [65100], None, None)
+            .unwrap();
+
+        let mut fw_state = net.get_forwarding_state();
+        let mut paths = fw_state.get_weighted_paths(r0, p).unwrap();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            paths,
+            vec![(vec![r0, r1, r3, e], 0.5), (vec![r0, r2, r3, e], 0.5)]
+        );
+        assert_eq!(paths.iter().map(|(_, f)| f).sum::<f64>(), 1.0);
+
+        assert_eq!(
+            fw_state.get_weighted_paths(r1, p).unwrap(),
+            vec![(vec![r1, r3, e], 1.0)]
+        );
+    }
+
+    #[test]
+    fn egress_groups_two_egresses<P: Prefix>() {
+        let mut net: Network<P, _> = Network::default();
+
+        let r0 = net.add_router("r0");
+        let r1 = net.add_router("r1");
+        let r2 = net.add_router("r2");
+        let r3 = net.add_router("r3");
+        let e0 = net.add_external_router("e0", AsId(65100));
+        let e3 = net.add_external_router("e3", AsId(65103));
+
+        for (a, b) in [(r0, r1), (r1, r2), (r2, r3), (r0, e0), (r3, e3)] {
+            net.add_link(a, b);
+            net.set_link_weight(a, b, 1.0).unwrap();
+            net.set_link_weight(b, a, 1.0).unwrap();
+        }
+
+        for a in [r0, r1, r2, r3] {
+            for b in [r0, r1, r2, r3] {
+                if a < b {
+                    net.set_bgp_session(a, b, Some(IBgpPeer)).unwrap();
+                }
+            }
+        }
+        net.set_bgp_session(r0, e0, Some(EBgp)).unwrap();
+        net.set_bgp_session(r3, e3, Some(EBgp)).unwrap();
+
+        let p = P::from(0);
+        net.advertise_external_route(e0, p, [65100, 65200], None, None)
+            .unwrap();
+        net.advertise_external_route(e3, p, [65103, 65200], None, None)
+            .unwrap();
+
+        let mut fw_state = net.get_forwarding_state();
+        let groups = fw_state.egress_groups(p);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&e0], vec![r0, r1]);
+        assert_eq!(groups[&e3], vec![r2, r3]);
+    }
+
     #[instantiate_tests(<SimplePrefix>)]
     mod simple {}
 
@@ -23,14 +23,15 @@ mod t {
     use std::collections::{BTreeMap, BTreeSet};
 
     use crate::{
-        bgp::{BgpRoute, BgpSessionType::*},
+        bgp::{BgpRoute, BgpSessionType::*, PreferenceReason},
         builder::{constant_link_weight, equal_preferences, NetworkBuilder},
         config::{ConfigExpr::IgpLinkWeight, NetworkConfig},
-        event::BasicEventQueue,
+        event::{BasicEventQueue, EventQueue},
         network::Network,
         prelude::BgpSessionType,
         route_map::{
-            RouteMap, RouteMapDirection::*, RouteMapFlow::*, RouteMapSet as Set, RouteMapState::*,
+            RouteMap, RouteMapBuilder, RouteMapDirection::*, RouteMapFlow::*, RouteMapSet as Set,
+            RouteMapState::*,
         },
         router::StaticRoute::*,
         types::{
@@ -678,6 +679,169 @@ mod t {
         test_route!(net, *R4, p, [*R4, *E4]);
     }
 
+    #[test]
+    fn test_update_advertisement_incremental<P: Prefix>() {
+        let mut net = get_test_net_bgp::<P>();
+
+        let p = P::from(0);
+
+        net.advertise_external_route(*E1, p, vec![AsId(65101), AsId(65201)], None, None)
+            .unwrap();
+        net.advertise_external_route(*E4, p, vec![AsId(65104), AsId(65201)], None, None)
+            .unwrap();
+
+        let next_hops = |net: &Network<P, BasicEventQueue<P>>| -> Vec<Vec<RouterId>> {
+            [*R1, *R2, *R3, *R4]
+                .into_iter()
+                .map(|r| net.get_device(r).unwrap_internal().get_next_hop(p))
+                .collect()
+        };
+
+        // change the AS path incrementally, and compare to a full reconvergence
+        let mut net_full = net.clone();
+        net_full
+            .advertise_external_route(
+                *E4,
+                p,
+                vec![AsId(65104), AsId(65500), AsId(65201)],
+                None,
+                None,
+            )
+            .unwrap();
+        let route = BgpRoute::new(
+            *E4,
+            p,
+            vec![AsId(65104), AsId(65500), AsId(65201)],
+            None,
+            None,
+        );
+        let updates = net
+            .update_advertisement_incremental(*E4, p, Some(route))
+            .unwrap();
+        assert_eq!(next_hops(&net), next_hops(&net_full));
+        assert_eq!(
+            updates.keys().copied().collect::<BTreeSet<_>>(),
+            btreeset! {*R2, *R4}
+        );
+        assert_eq!(updates[&*R2].old, vec![*R4]);
+        assert_eq!(updates[&*R2].new, vec![*R3]);
+        assert_eq!(updates[&*R4].old, vec![*E4]);
+        assert_eq!(updates[&*R4].new, vec![*R2]);
+        test_route!(net, *R4, p, [*R4, *R2, *R3, *R1, *E1]);
+
+        // withdraw the route from E1 incrementally, and compare to a full reconvergence
+        let mut net_full = net.clone();
+        net_full.retract_external_route(*E1, p).unwrap();
+        let updates = net.update_advertisement_incremental(*E1, p, None).unwrap();
+        assert_eq!(next_hops(&net), next_hops(&net_full));
+        assert_eq!(
+            updates.keys().copied().collect::<BTreeSet<_>>(),
+            btreeset! {*R1, *R2, *R3, *R4}
+        );
+        test_route!(net, *R1, p, [*R1, *R3, *R2, *R4, *E4]);
+
+        // pending events for the same prefix must be processed before the incremental update.
+        use crate::interactive::InteractiveNetwork;
+        net.manual_simulation();
+        net.advertise_external_route(*E1, p, vec![AsId(65101), AsId(65201)], None, None)
+            .unwrap();
+        let num_events = net.queue().len();
+        assert!(num_events > 0);
+        assert!(matches!(
+            net.update_advertisement_incremental(*E4, p, None),
+            Err(NetworkError::QueueNotEmpty(n)) if n == num_events
+        ));
+        assert_eq!(net.queue().len(), num_events);
+    }
+
+    #[test]
+    fn test_update_advertisement_incremental_interleaved<P: Prefix>() {
+        use crate::interactive::InteractiveNetwork;
+
+        let p = P::from(0);
+        let q = P::from(1);
+        // `SinglePrefix` cannot distinguish the two prefixes.
+        if p == q {
+            return;
+        }
+
+        let mut net = get_test_net_bgp::<P>();
+        net.advertise_external_route(*E1, p, vec![AsId(65101), AsId(65201)], None, None)
+            .unwrap();
+        net.advertise_external_route(*E4, p, vec![AsId(65104), AsId(65201)], None, None)
+            .unwrap();
+
+        let next_hops = |net: &Network<P, BasicEventQueue<P>>, prefix: P| -> Vec<Vec<RouterId>> {
+            [*R1, *R2, *R3, *R4]
+                .into_iter()
+                .map(|r| net.get_device(r).unwrap_internal().get_next_hop(prefix))
+                .collect()
+        };
+
+        // enqueue the events for a different prefix without processing them.
+        net.manual_simulation();
+        net.advertise_external_route(*E1, q, vec![AsId(65101), AsId(65202)], None, None)
+            .unwrap();
+        let num_events = net.queue().len();
+        assert!(num_events > 0);
+
+        // reference: process all events for `q`, and then change the advertisement for `p`.
+        let path = vec![AsId(65104), AsId(65500), AsId(65201)];
+        let mut net_full = net.clone();
+        net_full.simulate().unwrap();
+        net_full
+            .advertise_external_route(*E4, p, path.clone(), None, None)
+            .unwrap();
+        net_full.simulate().unwrap();
+
+        // the incremental update only processes the events for `p`.
+        let route = BgpRoute::new(*E4, p, path, None, None);
+        let updates = net
+            .update_advertisement_incremental(*E4, p, Some(route))
+            .unwrap();
+        assert_eq!(next_hops(&net, p), next_hops(&net_full, p));
+        assert_eq!(
+            updates.keys().copied().collect::<BTreeSet<_>>(),
+            btreeset! {*R2, *R4}
+        );
+        assert_eq!(net.queue().len(), num_events);
+        assert_eq!(net.queue().peek().and_then(|e| e.prefix()), Some(q));
+        assert_eq!(next_hops(&net, q), vec![Vec::<RouterId>::new(); 4]);
+
+        // afterwards, the events for `q` are processed as if they were never interrupted.
+        net.simulate().unwrap();
+        assert_eq!(next_hops(&net, p), next_hops(&net_full, p));
+        assert_eq!(next_hops(&net, q), next_hops(&net_full, q));
+    }
+
+    #[test]
+    fn test_process_next_matching<P: Prefix>() {
+        use crate::interactive::InteractiveNetwork;
+
+        let mut net = get_test_net_bgp::<P>();
+        let p = P::from(0);
+
+        net.manual_simulation();
+        net.advertise_external_route(*E1, p, vec![AsId(65101), AsId(65201)], None, None)
+            .unwrap();
+
+        // only return events processed by R3, while all others are processed in between.
+        let mut num_events = 0;
+        while let Some((_, event)) = net.process_next_matching(|e| e.router() == *R3).unwrap() {
+            assert_eq!(event.router(), *R3);
+            num_events += 1;
+        }
+        assert!(num_events > 0);
+        assert!(net.queue().is_empty());
+        assert_eq!(
+            net.get_device(*R3).unwrap_internal().get_next_hop(p),
+            vec![*R1]
+        );
+
+        // once the queue is empty, no event matches anymore.
+        assert_eq!(net.process_next_matching(|_| true), Ok(None));
+    }
+
     #[cfg(feature = "undo")]
     #[test]
     fn test_bgp_decision_undo<P: Prefix>() {
@@ -1723,6 +1887,165 @@ mod t {
         );
     }
 
+    #[test]
+    fn would_prefer<P: Prefix>() {
+        let mut net = get_test_net_bgp::<P>();
+        let p = P::from(0);
+        net.advertise_external_route(*E1, p, [65101, 65200], None, None)
+            .unwrap();
+
+        let r3 = net.get_device(*R3).unwrap_internal();
+        assert_eq!(r3.get_selected_bgp_route(p).unwrap().from_id, *R1);
+
+        let ids = net.get_bgp_router_ids();
+        let mut candidate = BgpRoute::new(*R4, p, [65104, 65200], None, []);
+        candidate.local_pref = Some(200);
+        assert!(r3.would_prefer(p, *R4, &candidate, ids));
+
+        // candidates for a different prefix are never preferred
+        let q = P::from(1);
+        if q != p {
+            assert!(!r3.would_prefer(q, *R4, &candidate, ids));
+        }
+
+        candidate.local_pref = Some(50);
+        assert!(!r3.would_prefer(p, *R4, &candidate, ids));
+
+        // the candidate replaces the route from the neighbor that sends it, even if the next-hop
+        // is a different router.
+        let mut candidate = BgpRoute::new(*E1, p, [65101, 65200], None, []);
+        candidate.local_pref = Some(50);
+        assert!(r3.would_prefer(p, *R1, &candidate, ids));
+
+        // the router state is not modified
+        assert_eq!(r3.get_selected_bgp_route(p).unwrap().from_id, *R1);
+        assert!(!r3.get_bgp_rib_in().get(&p).unwrap().contains_key(&*R4));
+    }
+
+    #[test]
+    fn bgp_router_id_tiebreak<P: Prefix>() {
+        let mut net: Network<P, BasicEventQueue<P>> = Network::default();
+        let r0 = net.add_router("r0");
+        let e1 = net.add_external_router("e1", AsId(65101));
+        let e2 = net.add_external_router("e2", AsId(65102));
+        net.add_link(r0, e1);
+        net.add_link(r0, e2);
+        for e in [e1, e2] {
+            net.set_link_weight(r0, e, 1.0).unwrap();
+            net.set_link_weight(e, r0, 1.0).unwrap();
+        }
+        net.set_bgp_session(r0, e1, Some(EBgp)).unwrap();
+        net.set_bgp_session(r0, e2, Some(EBgp)).unwrap();
+
+        let p = P::from(0);
+        net.advertise_external_route(e1, p, [65101, 65200], None, None)
+            .unwrap();
+        net.advertise_external_route(e2, p, [65102, 65200], None, None)
+            .unwrap();
+
+        let selected = |net: &Network<P, BasicEventQueue<P>>| {
+            net.get_device(r0)
+                .unwrap_internal()
+                .get_selected_bgp_route(p)
+                .unwrap()
+                .from_id
+        };
+
+        // without explicit router-ids, the lower router ID is preferred
+        assert_eq!(selected(&net), e1);
+
+        // with explicit router-ids, the lower effective router-id is preferred
+        assert_eq!(
+            net.set_bgp_router_id(e1, Some("10.0.0.2".parse().unwrap())),
+            Ok(None)
+        );
+        net.set_bgp_router_id(e2, Some("10.0.0.1".parse().unwrap()))
+            .unwrap();
+        assert_eq!(selected(&net), e2);
+        assert_eq!(net.get_bgp_router_id(e2), Some("10.0.0.1".parse().unwrap()));
+
+        // explaining the preference uses the same comparison as the router
+        let r = net.get_device(r0).unwrap_internal();
+        let rib_in = r.get_bgp_rib_in().get(&p).unwrap();
+        let (route_1, route_2) = (rib_in.get(&e1).unwrap(), rib_in.get(&e2).unwrap());
+        assert_eq!(
+            route_2.compare_with_router_ids(route_1, net.get_bgp_router_ids()),
+            (std::cmp::Ordering::Greater, PreferenceReason::NextHop)
+        );
+        assert_eq!(
+            route_1.explain_preference(route_2, net.get_bgp_router_ids()),
+            PreferenceReason::NextHop
+        );
+        let mut candidate = route_1.route.clone();
+        candidate.local_pref = Some(100);
+        assert!(!r.would_prefer(p, e1, &candidate, net.get_bgp_router_ids()));
+
+        // resetting the router-ids restores the original decision
+        net.set_bgp_router_id(e1, None).unwrap();
+        net.set_bgp_router_id(e2, None).unwrap();
+        assert_eq!(selected(&net), e1);
+
+        assert_eq!(
+            net.set_bgp_router_id(100.into(), None),
+            Err(NetworkError::DeviceNotFound(100.into()))
+        );
+    }
+
+    #[test]
+    fn route_map_match_community<P: Prefix>() {
+        let mut net = get_test_net_bgp::<P>();
+        let p = P::from(0);
+        net.advertise_external_route(*E1, p, [65101, 65201], None, None)
+            .unwrap();
+        net.advertise_external_route(*E4, p, [65104, 65201], None, [10])
+            .unwrap();
+
+        test_route!(net, *R3, p, [*R3, *R1, *E1]);
+
+        // prefer routes tagged with community 10 at R3
+        net.set_bgp_route_map(
+            *R3,
+            *R4,
+            Incoming,
+            RouteMapBuilder::new()
+                .allow()
+                .order(10)
+                .match_community(10)
+                .set_local_pref(200)
+                .build(),
+        )
+        .unwrap();
+
+        test_route!(net, *R1, p, [*R1, *E1]);
+        test_route!(net, *R2, p, [*R2, *R4, *E4]);
+        test_route!(net, *R3, p, [*R3, *R2, *R4, *E4]);
+        test_route!(net, *R4, p, [*R4, *E4]);
+        assert_eq!(
+            net.get_device(*R3)
+                .unwrap_internal()
+                .get_selected_bgp_route(p)
+                .unwrap()
+                .route
+                .local_pref,
+            Some(200)
+        );
+
+        // routes without the community are not affected
+        net.set_bgp_route_map(
+            *R3,
+            *R1,
+            Incoming,
+            RouteMapBuilder::new()
+                .allow()
+                .order(10)
+                .match_community(20)
+                .set_local_pref(300)
+                .build(),
+        )
+        .unwrap();
+        test_route!(net, *R3, p, [*R3, *R2, *R4, *E4]);
+    }
+
     #[instantiate_tests(<SinglePrefix>)]
     mod single {}
 
@@ -23,7 +23,7 @@ use crate::{
 };
 
 #[cfg(feature = "rand_queue")]
-use crate::event::{GeoTimingModel, ModelParams, SimpleTimingModel};
+use crate::event::{GeoTimingModel, MeasuredTimingModel, ModelParams, SimpleTimingModel};
 
 #[generic_tests::define]
 mod t {
@@ -160,6 +160,57 @@ mod t {
         test_route!(net, b1, prefix, [b1, e1]);
     }
 
+    #[test]
+    #[cfg(feature = "rand_queue")]
+    fn test_measured_model<P: Prefix>() {
+        use std::time::Duration;
+
+        use maplit::hashmap;
+
+        use crate::{event::Event, interactive::InteractiveNetwork};
+
+        let mut net: Network<P, _> = Network::new(MeasuredTimingModel::new(
+            hashmap! {},
+            Duration::from_millis(10),
+        ));
+
+        let prefix = P::from(0);
+
+        let (e0, b0, r0, r1, b1, e1) = setup_simple(&mut net);
+
+        // the link from e0 to b0 is much slower than the one from e1 to b1.
+        let queue = net.queue_mut();
+        queue.set_delay(e0, b0, Duration::from_secs(10));
+        queue.set_delay(e1, b1, Duration::from_secs(1));
+
+        // advertise the same prefix on both routers, first on the slow link.
+        net.manual_simulation();
+        net.advertise_external_route(e0, prefix, vec![AsId(1), AsId(2), AsId(3)], None, None)
+            .unwrap();
+        net.advertise_external_route(e1, prefix, vec![AsId(1), AsId(2), AsId(3)], None, None)
+            .unwrap();
+
+        // the message over the fast link is processed first, even though it was sent later.
+        let t0 = net.queue().get_time().unwrap();
+        let mut t_fast = None;
+        let mut t_slow = None;
+        while let Some((_, Event::Bgp(t, src, dst, _))) = net.simulate_step().unwrap() {
+            if (src, dst) == (e1, b1) {
+                t_fast = Some(t.into_inner());
+            } else if (src, dst) == (e0, b0) {
+                t_slow = Some(t.into_inner());
+            }
+        }
+        assert!((t_fast.unwrap() - t0 - 1.0).abs() < 1e-9);
+        assert!((t_slow.unwrap() - t0 - 10.0).abs() < 1e-9);
+
+        // check that all routes are correct
+        test_route!(net, b0, prefix, [b0, e0]);
+        test_route!(net, r0, prefix, [r0, b0, e0]);
+        test_route!(net, r1, prefix, [r1, b1, e1]);
+        test_route!(net, b1, prefix, [b1, e1]);
+    }
+
     /// Setup the second network, and return `(e1, r1, r2, r3, r4, e4)`
     /// - All IGP weights are set to 1, except r3 -- r4: 2
     /// - BGP sessions before:
@@ -20,7 +20,7 @@ use std::collections::HashSet;
 #[allow(unused_imports)]
 use crate::bgp::BgpSessionType::{EBgp, IBgpClient, IBgpPeer};
 use crate::{
-    bgp::{BgpEvent, BgpRoute},
+    bgp::{BgpEvent, BgpRoute, BgpRouterIds},
     event::Event,
     external_router::*,
     ospf::Ospf,
@@ -37,15 +37,23 @@ mod t2 {
     #[test]
     fn test_bgp_single<P: Prefix>() {
         use crate::bgp::BgpSessionType::{EBgp, IBgpClient, IBgpPeer};
+        let ids = BgpRouterIds::new();
 
         let mut r = Router::<P>::new("test".to_string(), 0.into(), AsId(65001));
-        r.set_bgp_session::<()>(100.into(), Some(EBgp)).unwrap();
-        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(3.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(4.into(), Some(IBgpClient)).unwrap();
-        r.set_bgp_session::<()>(5.into(), Some(IBgpClient)).unwrap();
-        r.set_bgp_session::<()>(6.into(), Some(IBgpClient)).unwrap();
+        r.set_bgp_session::<()>(100.into(), Some(EBgp), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(3.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(4.into(), Some(IBgpClient), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(5.into(), Some(IBgpClient), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(6.into(), Some(IBgpClient), &ids)
+            .unwrap();
         r.igp_table = hashmap! {
             100.into() => (vec![100.into()], 0.0),
             1.into()   => (vec![1.into()], 1.0),
@@ -63,21 +71,24 @@ mod t2 {
         /////////////////////
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                100.into(),
-                0.into(),
-                BgpEvent::Update(BgpRoute {
-                    prefix: P::from(200),
-                    as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
-                    next_hop: 100.into(),
-                    local_pref: None,
-                    med: None,
-                    community: Default::default(),
-                    originator_id: None,
-                    cluster_list: Vec::new(),
-                }),
-            ))
+            .handle_event(
+                Event::Bgp(
+                    (),
+                    100.into(),
+                    0.into(),
+                    BgpEvent::Update(BgpRoute {
+                        prefix: P::from(200),
+                        as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
+                        next_hop: 100.into(),
+                        local_pref: None,
+                        med: None,
+                        community: Default::default(),
+                        originator_id: None,
+                        cluster_list: Vec::new(),
+                    }),
+                ),
+                &ids,
+            )
             .unwrap();
 
         // check that the router now has a route selected for 100 with the correct data
@@ -105,21 +116,24 @@ mod t2 {
         // update from route reflector
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                1.into(),
-                0.into(),
-                BgpEvent::Update(BgpRoute {
-                    prefix: P::from(201),
-                    as_path: vec![AsId(1), AsId(2), AsId(3)],
-                    next_hop: 11.into(),
-                    local_pref: Some(50),
-                    med: None,
-                    community: Default::default(),
-                    originator_id: None,
-                    cluster_list: Vec::new(),
-                }),
-            ))
+            .handle_event(
+                Event::Bgp(
+                    (),
+                    1.into(),
+                    0.into(),
+                    BgpEvent::Update(BgpRoute {
+                        prefix: P::from(201),
+                        as_path: vec![AsId(1), AsId(2), AsId(3)],
+                        next_hop: 11.into(),
+                        local_pref: Some(50),
+                        med: None,
+                        community: Default::default(),
+                        originator_id: None,
+                        cluster_list: Vec::new(),
+                    }),
+                ),
+                &ids,
+            )
             .unwrap();
 
         // check that the router now has a route selected for 100 with the correct data
@@ -150,21 +164,24 @@ mod t2 {
         // update from route reflector
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                2.into(),
-                0.into(),
-                BgpEvent::Update(BgpRoute {
-                    prefix: P::from(200),
-                    as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
-                    next_hop: 10.into(),
-                    local_pref: None,
-                    med: None,
-                    community: Default::default(),
-                    originator_id: None,
-                    cluster_list: Vec::new(),
-                }),
-            ))
+            .handle_event(
+                Event::Bgp(
+                    (),
+                    2.into(),
+                    0.into(),
+                    BgpEvent::Update(BgpRoute {
+                        prefix: P::from(200),
+                        as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
+                        next_hop: 10.into(),
+                        local_pref: None,
+                        med: None,
+                        community: Default::default(),
+                        originator_id: None,
+                        cluster_list: Vec::new(),
+                    }),
+                ),
+                &ids,
+            )
             .unwrap();
 
         // check that
@@ -180,32 +197,35 @@ mod t2 {
         // update from route reflector
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                5.into(),
-                0.into(),
-                BgpEvent::Update(BgpRoute {
-                    prefix: P::from(200),
-                    as_path: vec![
-                        AsId(1),
-                        AsId(2),
-                        AsId(3),
-                        AsId(4),
-                        AsId(5),
-                        AsId(6),
-                        AsId(7),
-                        AsId(8),
-                        AsId(9),
-                        AsId(10),
-                    ],
-                    next_hop: 5.into(),
-                    local_pref: Some(150),
-                    med: None,
-                    community: Default::default(),
-                    originator_id: None,
-                    cluster_list: Vec::new(),
-                }),
-            ))
+            .handle_event(
+                Event::Bgp(
+                    (),
+                    5.into(),
+                    0.into(),
+                    BgpEvent::Update(BgpRoute {
+                        prefix: P::from(200),
+                        as_path: vec![
+                            AsId(1),
+                            AsId(2),
+                            AsId(3),
+                            AsId(4),
+                            AsId(5),
+                            AsId(6),
+                            AsId(7),
+                            AsId(8),
+                            AsId(9),
+                            AsId(10),
+                        ],
+                        next_hop: 5.into(),
+                        local_pref: Some(150),
+                        med: None,
+                        community: Default::default(),
+                        originator_id: None,
+                        cluster_list: Vec::new(),
+                    }),
+                ),
+                &ids,
+            )
             .unwrap();
 
         // check that the router now has a route selected for 100 with the correct data
@@ -240,12 +260,10 @@ mod t2 {
         ///////////////////////
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                2.into(),
-                0.into(),
-                BgpEvent::Withdraw(P::from(200)),
-            ))
+            .handle_event(
+                Event::Bgp((), 2.into(), 0.into(), BgpEvent::Withdraw(P::from(200))),
+                &ids,
+            )
             .unwrap();
 
         // check that the router now has a route selected for 100 with the correct data
@@ -258,12 +276,10 @@ mod t2 {
         ////////////////////////
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                5.into(),
-                0.into(),
-                BgpEvent::Withdraw(P::from(200)),
-            ))
+            .handle_event(
+                Event::Bgp((), 5.into(), 0.into(), BgpEvent::Withdraw(P::from(200))),
+                &ids,
+            )
             .unwrap();
 
         // check that the router now has a route selected for 100 with the correct data
@@ -292,12 +308,10 @@ mod t2 {
         ////////////////////////
 
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                100.into(),
-                0.into(),
-                BgpEvent::Withdraw(P::from(200)),
-            ))
+            .handle_event(
+                Event::Bgp((), 100.into(), 0.into(), BgpEvent::Withdraw(P::from(200))),
+                &ids,
+            )
             .unwrap();
 
         // check that the router now has a route selected for 100 with the correct data
@@ -318,14 +332,22 @@ mod t2 {
     #[cfg(feature = "undo")]
     #[test]
     fn test_bgp_single_undo<P: Prefix>() {
+        let ids = BgpRouterIds::new();
         let mut r = Router::<P>::new("test".to_string(), 0.into(), AsId(65001));
-        r.set_bgp_session::<()>(100.into(), Some(EBgp)).unwrap();
-        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(3.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(4.into(), Some(IBgpClient)).unwrap();
-        r.set_bgp_session::<()>(5.into(), Some(IBgpClient)).unwrap();
-        r.set_bgp_session::<()>(6.into(), Some(IBgpClient)).unwrap();
+        r.set_bgp_session::<()>(100.into(), Some(EBgp), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(3.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(4.into(), Some(IBgpClient), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(5.into(), Some(IBgpClient), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(6.into(), Some(IBgpClient), &ids)
+            .unwrap();
         r.igp_table = hashmap! {
             100.into() => (vec![100.into()], 0.0),
             1.into()   => (vec![1.into()], 1.0),
@@ -344,21 +366,24 @@ mod t2 {
 
         let store_r_1 = r.clone();
 
-        r.handle_event(Event::Bgp(
-            (),
-            100.into(),
-            0.into(),
-            BgpEvent::Update(BgpRoute {
-                prefix: P::from(200),
-                as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
-                next_hop: 100.into(),
-                local_pref: None,
-                med: None,
-                community: Default::default(),
-                originator_id: None,
-                cluster_list: Vec::new(),
-            }),
-        ))
+        r.handle_event(
+            Event::Bgp(
+                (),
+                100.into(),
+                0.into(),
+                BgpEvent::Update(BgpRoute {
+                    prefix: P::from(200),
+                    as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
+                    next_hop: 100.into(),
+                    local_pref: None,
+                    med: None,
+                    community: Default::default(),
+                    originator_id: None,
+                    cluster_list: Vec::new(),
+                }),
+            ),
+            &ids,
+        )
         .unwrap();
 
         /////////////////////
@@ -369,21 +394,24 @@ mod t2 {
 
         // update from route reflector
 
-        r.handle_event(Event::Bgp(
-            (),
-            1.into(),
-            0.into(),
-            BgpEvent::Update(BgpRoute {
-                prefix: P::from(201),
-                as_path: vec![AsId(1), AsId(2), AsId(3)],
-                next_hop: 11.into(),
-                local_pref: Some(50),
-                med: None,
-                community: Default::default(),
-                originator_id: None,
-                cluster_list: Vec::new(),
-            }),
-        ))
+        r.handle_event(
+            Event::Bgp(
+                (),
+                1.into(),
+                0.into(),
+                BgpEvent::Update(BgpRoute {
+                    prefix: P::from(201),
+                    as_path: vec![AsId(1), AsId(2), AsId(3)],
+                    next_hop: 11.into(),
+                    local_pref: Some(50),
+                    med: None,
+                    community: Default::default(),
+                    originator_id: None,
+                    cluster_list: Vec::new(),
+                }),
+            ),
+            &ids,
+        )
         .unwrap();
 
         //////////////////
@@ -394,21 +422,24 @@ mod t2 {
 
         // update from route reflector
 
-        r.handle_event(Event::Bgp(
-            (),
-            2.into(),
-            0.into(),
-            BgpEvent::Update(BgpRoute {
-                prefix: P::from(200),
-                as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
-                next_hop: 10.into(),
-                local_pref: None,
-                med: None,
-                community: Default::default(),
-                originator_id: None,
-                cluster_list: Vec::new(),
-            }),
-        ))
+        r.handle_event(
+            Event::Bgp(
+                (),
+                2.into(),
+                0.into(),
+                BgpEvent::Update(BgpRoute {
+                    prefix: P::from(200),
+                    as_path: vec![AsId(1), AsId(2), AsId(3), AsId(4), AsId(5)],
+                    next_hop: 10.into(),
+                    local_pref: None,
+                    med: None,
+                    community: Default::default(),
+                    originator_id: None,
+                    cluster_list: Vec::new(),
+                }),
+            ),
+            &ids,
+        )
         .unwrap();
 
         ///////////////////
@@ -419,32 +450,35 @@ mod t2 {
 
         // update from route reflector
 
-        r.handle_event(Event::Bgp(
-            (),
-            5.into(),
-            0.into(),
-            BgpEvent::Update(BgpRoute {
-                prefix: P::from(200),
-                as_path: vec![
-                    AsId(1),
-                    AsId(2),
-                    AsId(3),
-                    AsId(4),
-                    AsId(5),
-                    AsId(6),
-                    AsId(7),
-                    AsId(8),
-                    AsId(9),
-                    AsId(10),
-                ],
-                next_hop: 5.into(),
-                local_pref: Some(150),
-                med: None,
-                community: Default::default(),
-                originator_id: None,
-                cluster_list: Vec::new(),
-            }),
-        ))
+        r.handle_event(
+            Event::Bgp(
+                (),
+                5.into(),
+                0.into(),
+                BgpEvent::Update(BgpRoute {
+                    prefix: P::from(200),
+                    as_path: vec![
+                        AsId(1),
+                        AsId(2),
+                        AsId(3),
+                        AsId(4),
+                        AsId(5),
+                        AsId(6),
+                        AsId(7),
+                        AsId(8),
+                        AsId(9),
+                        AsId(10),
+                    ],
+                    next_hop: 5.into(),
+                    local_pref: Some(150),
+                    med: None,
+                    community: Default::default(),
+                    originator_id: None,
+                    cluster_list: Vec::new(),
+                }),
+            ),
+            &ids,
+        )
         .unwrap();
 
         ///////////////////////
@@ -453,12 +487,10 @@ mod t2 {
 
         let store_r_5 = r.clone();
 
-        r.handle_event(Event::Bgp(
-            (),
-            2.into(),
-            0.into(),
-            BgpEvent::Withdraw(P::from(200)),
-        ))
+        r.handle_event(
+            Event::Bgp((), 2.into(), 0.into(), BgpEvent::Withdraw(P::from(200))),
+            &ids,
+        )
         .unwrap();
 
         ////////////////////////
@@ -467,12 +499,10 @@ mod t2 {
 
         let store_r_6 = r.clone();
 
-        r.handle_event(Event::Bgp(
-            (),
-            5.into(),
-            0.into(),
-            BgpEvent::Withdraw(P::from(200)),
-        ))
+        r.handle_event(
+            Event::Bgp((), 5.into(), 0.into(), BgpEvent::Withdraw(P::from(200))),
+            &ids,
+        )
         .unwrap();
 
         ////////////////////////
@@ -480,12 +510,10 @@ mod t2 {
         ////////////////////////
         let store_r_7 = r.clone();
 
-        r.handle_event(Event::Bgp(
-            (),
-            100.into(),
-            0.into(),
-            BgpEvent::Withdraw(P::from(200)),
-        ))
+        r.handle_event(
+            Event::Bgp((), 100.into(), 0.into(), BgpEvent::Withdraw(P::from(200))),
+            &ids,
+        )
         .unwrap();
 
         ////////////////////////////
@@ -528,6 +556,7 @@ mod t1 {
 
     #[test]
     fn test_fw_table_simple<P: Prefix>() {
+        let ids = BgpRouterIds::new();
         let mut net: IgpNetwork = IgpNetwork::new();
         let mut r_a = Router::<P>::new("A".to_string(), net.add_node(()), AsId(65001));
         let mut r_b = Router::<P>::new("B".to_string(), net.add_node(()), AsId(65001));
@@ -557,7 +586,8 @@ mod t1 {
 
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_a.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_a.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         let expected_forwarding_table = hashmap! {
             r_a.router_id() => (vec![], 0.0),
@@ -576,7 +606,8 @@ mod t1 {
 
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_b.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_b.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         let expected_forwarding_table = hashmap! {
             r_a.router_id() => (vec![r_a.router_id()], 1.0),
@@ -595,7 +626,8 @@ mod t1 {
 
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_c.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_c.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         let expected_forwarding_table = hashmap! {
             r_a.router_id() => (vec![r_b.router_id()], 2.0),
@@ -615,6 +647,7 @@ mod t1 {
 
     #[test]
     fn test_igp_fw_table_complex<P: Prefix>() {
+        let ids = BgpRouterIds::new();
         let mut net: IgpNetwork = IgpNetwork::new();
         let mut r_a = Router::<P>::new("A".to_string(), net.add_node(()), AsId(65001));
         let r_b = Router::<P>::new("B".to_string(), net.add_node(()), AsId(65001));
@@ -660,7 +693,8 @@ mod t1 {
 
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_a.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_a.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         let expected_forwarding_table = hashmap! {
             r_a.router_id() => (vec![], 0.0),
@@ -682,7 +716,8 @@ mod t1 {
 
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_c.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_c.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         let expected_forwarding_table = hashmap! {
             r_a.router_id() => (vec![r_f.router_id()], 3.0),
@@ -840,6 +875,7 @@ mod t1 {
     #[cfg(feature = "undo")]
     #[test]
     fn test_undo_fw_table<P: Prefix>() {
+        let ids = BgpRouterIds::new();
         let mut net: IgpNetwork = IgpNetwork::new();
         let mut r_a = Router::<P>::new("A".to_string(), net.add_node(()), AsId(65001));
         let mut r_b = Router::<P>::new("B".to_string(), net.add_node(()), AsId(65001));
@@ -871,9 +907,12 @@ mod t1 {
 
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_a.write_igp_forwarding_table::<()>(&net, &state).unwrap();
-        r_b.write_igp_forwarding_table::<()>(&net, &state).unwrap();
-        r_c.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_a.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
+        r_b.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
+        r_c.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         let r_a_clone = r_a.clone();
         let r_b_clone = r_b.clone();
@@ -886,9 +925,12 @@ mod t1 {
         // update the IGP state
         let ospf = Ospf::new();
         let state = ospf.compute(&net, &HashSet::new());
-        r_a.write_igp_forwarding_table::<()>(&net, &state).unwrap();
-        r_b.write_igp_forwarding_table::<()>(&net, &state).unwrap();
-        r_c.write_igp_forwarding_table::<()>(&net, &state).unwrap();
+        r_a.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
+        r_b.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
+        r_c.write_igp_forwarding_table::<()>(&net, &state, &ids)
+            .unwrap();
 
         // undo the state change and compare the nodes.
         r_a.undo_event();
@@ -917,11 +959,16 @@ mod ipv4 {
 
     #[test]
     fn test_hierarchical_bgp() {
+        let ids = BgpRouterIds::new();
         let mut r = Router::<Ipv4Prefix>::new("test".to_string(), 0.into(), AsId(65001));
-        r.set_bgp_session::<()>(100.into(), Some(EBgp)).unwrap();
-        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(3.into(), Some(IBgpClient)).unwrap();
+        r.set_bgp_session::<()>(100.into(), Some(EBgp), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(3.into(), Some(IBgpClient), &ids)
+            .unwrap();
         r.igp_table = hashmap! {
             100.into() => (vec![100.into()], 0.0),
             1.into()   => (vec![1.into()], 1.0),
@@ -934,12 +981,15 @@ mod ipv4 {
 
         // external update
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                100.into(),
-                0.into(),
-                BgpEvent::Update(BgpRoute::new(100.into(), p0, 1..=5, None, None)),
-            ))
+            .handle_event(
+                Event::Bgp(
+                    (),
+                    100.into(),
+                    0.into(),
+                    BgpEvent::Update(BgpRoute::new(100.into(), p0, 1..=5, None, None)),
+                ),
+                &ids,
+            )
             .unwrap();
         assert_eq!(events.len(), 3);
         for event in events {
@@ -954,12 +1004,15 @@ mod ipv4 {
 
         // Internal update
         let (_, events) = r
-            .handle_event(Event::Bgp(
-                (),
-                100.into(),
-                0.into(),
-                BgpEvent::Update(BgpRoute::new(100.into(), p1, 1..=7, None, None)),
-            ))
+            .handle_event(
+                Event::Bgp(
+                    (),
+                    100.into(),
+                    0.into(),
+                    BgpEvent::Update(BgpRoute::new(100.into(), p1, 1..=7, None, None)),
+                ),
+                &ids,
+            )
             .unwrap();
         assert_eq!(events.len(), 3);
         for event in events {
@@ -988,11 +1041,16 @@ mod ipv4 {
 
     #[test]
     fn next_hop_static_route() {
+        let ids = BgpRouterIds::new();
         let mut r = Router::<Ipv4Prefix>::new("test".to_string(), 0.into(), AsId(65001));
-        r.set_bgp_session::<()>(100.into(), Some(EBgp)).unwrap();
-        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer)).unwrap();
-        r.set_bgp_session::<()>(3.into(), Some(IBgpClient)).unwrap();
+        r.set_bgp_session::<()>(100.into(), Some(EBgp), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(1.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(2.into(), Some(IBgpPeer), &ids)
+            .unwrap();
+        r.set_bgp_session::<()>(3.into(), Some(IBgpClient), &ids)
+            .unwrap();
         r.igp_table = hashmap! {
             100.into() => (vec![100.into()], 0.0),
             1.into()   => (vec![1.into()], 1.0),
@@ -1009,12 +1067,15 @@ mod ipv4 {
         let p1: Ipv4Prefix = "10.0.0.0/24".parse::<Ipv4Net>().unwrap().into();
 
         // send a BGP route
-        r.handle_event(Event::Bgp(
-            (),
-            100.into(),
-            0.into(),
-            BgpEvent::Update(BgpRoute::new(100.into(), p0, 1..=5, None, None)),
-        ))
+        r.handle_event(
+            Event::Bgp(
+                (),
+                100.into(),
+                0.into(),
+                BgpEvent::Update(BgpRoute::new(100.into(), p0, 1..=5, None, None)),
+            ),
+            &ids,
+        )
         .unwrap();
 
         // set a static route
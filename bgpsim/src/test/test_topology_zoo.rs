@@ -18,7 +18,7 @@
 use crate::{
     event::BasicEventQueue,
     network::Network,
-    topology_zoo::TopologyZoo,
+    topology_zoo::{TopologyZoo, TopologyZooParser},
     types::{SimplePrefix, SinglePrefix},
 };
 
@@ -44,6 +44,16 @@ fn test_all_simple() {
     }
 }
 
+#[test]
+fn test_topologies_in_range() {
+    let topos = TopologyZoo::topologies_in_range(10, 20);
+    assert!(!topos.is_empty());
+    for topo in topos.iter() {
+        assert!((10..=20).contains(&topo.num_internals()));
+    }
+    assert!(TopologyZoo::topologies_in_range(20, 10).is_empty());
+}
+
 #[test]
 fn test_extract() {
     let n: Network<SimplePrefix, _> = TopologyZoo::Epoch.build(BasicEventQueue::new());
@@ -74,3 +84,62 @@ fn test_extract() {
     assert!(n.get_topology().find_edge(4.into(), 3.into()).is_some());
     assert!(n.get_topology().find_edge(5.into(), 4.into()).is_some());
 }
+
+#[test]
+fn test_link_weights() {
+    let graphml = r#"<?xml version="1.0" encoding="utf-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key attr.name="LinkSpeedRaw" attr.type="double" for="edge" id="d41" />
+  <key attr.name="LinkSpeedUnits" attr.type="string" for="edge" id="d40" />
+  <key attr.name="LinkSpeed" attr.type="string" for="edge" id="d39" />
+  <key attr.name="LinkLabel" attr.type="string" for="edge" id="d36" />
+  <key attr.name="label" attr.type="string" for="node" id="d34" />
+  <key attr.name="Internal" attr.type="int" for="node" id="d29" />
+  <graph edgedefault="undirected">
+    <node id="0">
+      <data key="d29">1</data>
+      <data key="d34">A</data>
+    </node>
+    <node id="1">
+      <data key="d29">1</data>
+      <data key="d34">B</data>
+    </node>
+    <node id="2">
+      <data key="d29">1</data>
+      <data key="d34">C</data>
+    </node>
+    <node id="3">
+      <data key="d29">1</data>
+      <data key="d34">D</data>
+    </node>
+    <edge source="0" target="1">
+      <data key="d41">10000000000.0</data>
+    </edge>
+    <edge source="1" target="2">
+      <data key="d39">1</data>
+      <data key="d40">G</data>
+    </edge>
+    <edge source="2" target="3">
+      <data key="d36">100 Mbps</data>
+    </edge>
+    <edge source="3" target="0">
+      <data key="d36">DWDM</data>
+    </edge>
+  </graph>
+</graphml>"#;
+
+    let parser = TopologyZooParser::new(graphml).unwrap();
+    let weights = parser.get_link_weights();
+
+    // the link without a known speed has no weight
+    assert_eq!(weights.len(), 6);
+    assert!(!weights.contains_key(&(3.into(), 0.into())));
+
+    // weights are symmetric and inversely proportional to the link speed
+    assert_eq!(weights[&(0.into(), 1.into())], 1.0);
+    assert_eq!(weights[&(1.into(), 0.into())], 1.0);
+    assert_eq!(weights[&(1.into(), 2.into())], 10.0);
+    assert_eq!(weights[&(2.into(), 1.into())], 10.0);
+    assert_eq!(weights[&(2.into(), 3.into())], 100.0);
+    assert_eq!(weights[&(3.into(), 2.into())], 100.0);
+}
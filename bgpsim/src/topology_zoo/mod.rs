@@ -21,9 +21,9 @@
 //!
 //! In addition, we provide every a structure of every single topology zoo file
 //!
-//! Right now, only node names and types, as well as edges are exported. In the future, we may also
-//! include reading speed of the links to deduce link weights. Use the [`super::builder`] module to
-//! quickly create a configuration for that network.
+//! Right now, only node names and types, as well as edges and their link speeds are exported. Use
+//! [`TopologyZooParser::get_link_weights`] to deduce link weights from the link speeds, and the
+//! [`super::builder`] module to quickly create a configuration for that network.
 //!
 //! If you use the TopologyZoo dataset, please add the following citation:
 //!
@@ -145,7 +145,7 @@ impl TopologyZooParser {
             })
             .collect::<Result<HashMap<&str, RouterId>, TopologyZooError>>()?;
 
-        for TopologyZooEdge { source, target } in self.edges.iter() {
+        for TopologyZooEdge { source, target, .. } in self.edges.iter() {
             let src = *nodes_lut
                 .get(source.as_str())
                 .ok_or_else(|| TopologyZooError::NodeNotFound(source.clone()))?;
@@ -177,6 +177,40 @@ impl TopologyZooParser {
             .collect()
     }
 
+    /// Compute the link weights based on the link speeds, such that faster links get lower
+    /// weights. The weight of a link is inversely proportional to its bandwidth, where the fastest
+    /// link in the topology gets a weight of `1.0`. The weights are symmetric, i.e., the map
+    /// contains both directions of each link. Links without any known speed are not part of the
+    /// returned map. If two nodes are connected by multiple links, the fastest one is used.
+    pub fn get_link_weights(&self) -> HashMap<(RouterId, RouterId), f64> {
+        let nodes_lut: HashMap<&str, RouterId> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), (i as IndexType).into()))
+            .collect();
+
+        let mut speeds: HashMap<(RouterId, RouterId), f64> = HashMap::new();
+        for edge in self.edges.iter() {
+            if let (Some(src), Some(dst), Some(speed)) = (
+                nodes_lut.get(edge.source.as_str()),
+                nodes_lut.get(edge.target.as_str()),
+                edge.speed,
+            ) {
+                for key in [(*src, *dst), (*dst, *src)] {
+                    let entry = speeds.entry(key).or_insert(0.0);
+                    *entry = entry.max(speed.into_inner());
+                }
+            }
+        }
+
+        let max_speed = speeds.values().copied().fold(0.0, f64::max);
+        speeds
+            .into_iter()
+            .map(|(link, speed)| (link, max_speed / speed))
+            .collect()
+    }
+
     /// Parse the topology zoo
     fn setup_keys(&mut self) -> Result<(), TopologyZooError> {
         self.keys = self
@@ -324,7 +358,46 @@ impl TopologyZooParser {
             ))?
             .to_string();
 
-        Ok(TopologyZooEdge { source, target })
+        let data = get_data(e)?;
+
+        let mut speed_raw: Option<NotNan<f64>> = None;
+        let mut speed: Option<f64> = None;
+        let mut speed_units: Option<String> = None;
+        let mut label: Option<String> = None;
+
+        for (key, value) in data.into_iter() {
+            let idx = *self
+                .key_id_lut
+                .get(&key)
+                .ok_or(TopologyZooError::UnknownKey(key))?;
+            let key = &self.keys[idx];
+            if key.name == "LinkSpeedRaw" {
+                if AttrType::Float != key.ty {
+                    return Err(TopologyZooError::AttrInvalidType(AttrType::Float, key.ty));
+                }
+                speed_raw = value.parse().ok();
+            } else if key.name == "LinkSpeed" {
+                speed = value.parse().ok();
+            } else if key.name == "LinkSpeedUnits" {
+                speed_units = Some(value);
+            } else if key.name == "LinkLabel" {
+                label = Some(value);
+            }
+        }
+
+        // prefer the raw link speed, then the link speed with its units, and finally the label.
+        let speed = speed_raw
+            .map(|x| x.into_inner())
+            .or_else(|| Some(speed? * unit_factor(speed_units.as_deref().unwrap_or_default())?))
+            .or_else(|| parse_link_label(label.as_deref()?))
+            .filter(|x| *x > 0.0)
+            .and_then(|x| NotNan::new(x).ok());
+
+        Ok(TopologyZooEdge {
+            source,
+            target,
+            speed,
+        })
     }
 }
 
@@ -355,6 +428,29 @@ fn get_data(e: &Element) -> Result<Vec<(String, String)>, TopologyZooError> {
         .collect()
 }
 
+/// Get the factor to transform a link speed with the given units into bits per second.
+fn unit_factor(units: &str) -> Option<f64> {
+    match units.trim().to_uppercase().as_str() {
+        "" => Some(1.0),
+        "K" => Some(1e3),
+        "M" => Some(1e6),
+        "G" => Some(1e9),
+        "T" => Some(1e12),
+        _ => None,
+    }
+}
+
+/// Parse the link speed in bits per second from a link label like `1G Ethernet` or `155 Mbps`.
+fn parse_link_label(label: &str) -> Option<f64> {
+    let label = label.trim();
+    let num_len = label
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(label.len());
+    let value: f64 = label[..num_len].parse().ok()?;
+    let units = label[num_len..].trim_start().chars().next()?;
+    Some(value * unit_factor(&units.to_string())?)
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 struct TopologyZooKey {
     name: String,
@@ -375,6 +471,8 @@ struct TopologyZooNode {
 struct TopologyZooEdge {
     source: String,
     target: String,
+    /// Link speed in bits per second
+    speed: Option<NotNan<f64>>,
 }
 
 /// Attribute Type
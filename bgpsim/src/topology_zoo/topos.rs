@@ -4599,6 +4599,16 @@ impl TopologyZoo {
             Self::Kdl,
         ]
     }
+
+    /// Get all topologies whose number of internal nodes lies within `min` and `max` (both
+    /// inclusive), ordered by increasing number of internal nodes.
+    pub fn topologies_in_range(min: usize, max: usize) -> Vec<Self> {
+        Self::topologies_increasing_nodes()
+            .iter()
+            .copied()
+            .filter(|t| (min..=max).contains(&t.num_internals()))
+            .collect()
+    }
 }
 
 impl std::fmt::Display for TopologyZoo {
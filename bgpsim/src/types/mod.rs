@@ -20,7 +20,10 @@
 use crate::event::EventOutcome;
 use crate::formatter::NetworkFormatter;
 use crate::{
-    bgp::BgpSessionType, event::Event, external_router::ExternalRouter, network::Network,
+    bgp::{BgpRouterIds, BgpSessionType},
+    event::Event,
+    external_router::ExternalRouter,
+    network::Network,
     router::Router,
 };
 use itertools::Itertools;
@@ -451,13 +454,14 @@ impl<'a, P: Prefix> NetworkDeviceMut<'a, P> {
 
     /// handle an `Event`. This function returns all events triggered by this function, and a
     /// boolean to check if there was an update or not. If the device does not exist, then raise an
-    /// error.
+    /// error. Internal routers use `router_ids` to compare BGP routes.
     pub(crate) fn handle_event<T: Default>(
         &mut self,
         event: Event<P, T>,
+        router_ids: &BgpRouterIds,
     ) -> Result<EventOutcome<P, T>, NetworkError> {
         match self {
-            NetworkDeviceMut::InternalRouter(r) => Ok(r.handle_event(event)?),
+            NetworkDeviceMut::InternalRouter(r) => Ok(r.handle_event(event, router_ids)?),
             NetworkDeviceMut::ExternalRouter(r) => Ok(r.handle_event(event)?),
             NetworkDeviceMut::None(id) => Err(NetworkError::DeviceNotFound(*id)),
         }
@@ -534,6 +538,9 @@ pub enum NetworkError {
     /// Convergence Problem
     #[error("Network cannot converge in the given time!")]
     NoConvergence,
+    /// The event queue still contains events for the prefix that were not yet processed.
+    #[error("The event queue is not empty: {0} events for the prefix are still enqueued!")]
+    QueueNotEmpty(usize),
     /// The BGP table is invalid
     #[error("Invalid BGP table for router {0:?}")]
     InvalidBgpTable(RouterId),
@@ -572,6 +579,7 @@ impl PartialEq for NetworkError {
             (Self::InconsistentBgpSession(l0, l1), Self::InconsistentBgpSession(r0, r1)) => {
                 l0 == r0 && l1 == r1
             }
+            (Self::QueueNotEmpty(l0), Self::QueueNotEmpty(r0)) => l0 == r0,
             (Self::InvalidBgpTable(l0), Self::InvalidBgpTable(r0)) => l0 == r0,
             (Self::UndoError(l0), Self::UndoError(r0)) => l0 == r0,
             (Self::JsonError(l), Self::JsonError(r)) => l.to_string() == r.to_string(),
@@ -17,7 +17,7 @@
 
 //! This module contains the code for reading the configuration.
 
-use std::{cmp::Reverse, net::Ipv4Addr};
+use std::{cmp::Reverse, net::Ipv4Addr, time::Duration};
 
 use ipnet::Ipv4Net;
 use itertools::Itertools;
@@ -63,36 +63,8 @@ lazy_static! {
             *CONFIG_DIR
         )
     };
-    pub static ref VDCS: Vec<RouterProperties> = {
-        #[derive(Debug, Deserialize)]
-        struct RoutersConfigFile {
-            vdcs: Vec<String>,
-        }
-        let routers_str = expect!(
-            std::fs::read_to_string(format!("{}/routers.toml", *CONFIG_DIR)),
-            "Cannot read '{}/routers.toml'",
-            *CONFIG_DIR
-        );
-        let routers: RoutersConfigFile = expect!(
-            toml::from_str(&routers_str),
-            "Cannot parse '{}/routers.toml'",
-            *CONFIG_DIR
-        );
-        routers
-            .vdcs
-            .into_iter()
-            .map(|nane| {
-                let file = format!("{}/{}.toml", *CONFIG_DIR, nane);
-                let router_str = expect!(std::fs::read_to_string(&file), "Cannot read '{}'", file);
-                expect!(
-                    toml::from_str::<RouterProperties>(&router_str),
-                    "Cannot parse '{}'",
-                    file
-                )
-            })
-            .sorted_by_key(|x| Reverse(x.ifaces.len()))
-            .collect()
-    };
+    /// All VDCs in the lab, as configured in `{CONFIG_DIR}/routers.toml`. See [`read_vdcs`].
+    pub static ref VDCS: Vec<RouterProperties> = read_vdcs(&*CONFIG_DIR);
     pub static ref ROUTERS: Vec<String> = {
         #[derive(Debug, Deserialize)]
         struct RoutersConfigFile {
@@ -112,6 +84,45 @@ lazy_static! {
     };
 }
 
+/// Read the VDC inventory from `{config_dir}/routers.toml`. That file must contain the list
+/// `vdcs` with the names of all VDCs, and each VDC `name` must be described in the file
+/// `{config_dir}/{name}.toml` (see [`RouterProperties`]). Adding a VDC to the lab thus only requires
+/// changing the configuration. The returned VDCs are sorted by their number of interfaces (in
+/// decreasing order).
+///
+/// This function panics if any of the files cannot be read or parsed.
+pub fn read_vdcs(config_dir: impl AsRef<str>) -> Vec<RouterProperties> {
+    #[derive(Debug, Deserialize)]
+    struct RoutersConfigFile {
+        vdcs: Vec<String>,
+    }
+    let config_dir = config_dir.as_ref();
+    let routers_str = expect!(
+        std::fs::read_to_string(format!("{config_dir}/routers.toml")),
+        "Cannot read '{}/routers.toml'",
+        config_dir
+    );
+    let routers: RoutersConfigFile = expect!(
+        toml::from_str(&routers_str),
+        "Cannot parse '{}/routers.toml'",
+        config_dir
+    );
+    routers
+        .vdcs
+        .into_iter()
+        .map(|name| {
+            let file = format!("{config_dir}/{name}.toml");
+            let router_str = expect!(std::fs::read_to_string(&file), "Cannot read '{}'", file);
+            expect!(
+                toml::from_str::<RouterProperties>(&router_str),
+                "Cannot parse '{}'",
+                file
+            )
+        })
+        .sorted_by_key(|x| Reverse(x.ifaces.len()))
+        .collect()
+}
+
 /// Properties of routers.
 #[derive(Debug, Clone, Deserialize)]
 pub struct RouterProperties {
@@ -142,6 +153,26 @@ pub struct Config {
     pub server: ServerConfig,
     pub tofino: TofinoConfig,
     pub addresses: AddressConfig,
+    #[serde(default)]
+    pub ssh: SshConfig,
+}
+
+/// Configuration of the SSH sessions to the routers. This section is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SshConfig {
+    /// Interval (in seconds) at which keepalive messages are sent to the routers while waiting for
+    /// convergence. Keepalive messages are disabled if this value is missing or zero.
+    #[serde(default)]
+    pub keepalive_interval: Option<u64>,
+}
+
+impl SshConfig {
+    /// Get the keepalive interval, or `None` if keepalive messages are disabled.
+    pub fn keepalive_interval(&self) -> Option<Duration> {
+        self.keepalive_interval
+            .filter(|x| *x > 0)
+            .map(Duration::from_secs)
+    }
 }
 
 /// Configuration for the assigned IP addresses.
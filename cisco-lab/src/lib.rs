@@ -90,7 +90,6 @@
 //!                                   │ - traffig generator     │
 //!                                   │ - traffic collector     │
 //!                                   │ - delay mechanism       │
-//!                                   │   (not yet implemented) │
 //!                                   └─────────────────────────┘
 //! ```
 //!
@@ -124,10 +123,16 @@
 //! ## Delay Mechanism
 //!
 //! In order to emulate delays on the system, the tofino modifies the packets to include the delay
-//! time and sends them to the server. The server will then cache the packets for the given duration
-//! and send them back to the tofino.
+//! time and sends them out on a delayer port. The delayer connected to that port (which is not part
+//! of this crate) holds the packets for the given duration and sends them back to the tofino.
 //!
-//! **This functionaly is not yet implemented.**
+//! Use [`CiscoLab::set_link_delay`] or [`CiscoLab::set_link_delays_from_geolocation`] to configure
+//! the delay of individual links. When connecting to the lab, the tofino is configured to steer all
+//! packets of these links through the delayer ports (`server.delayer_tofino_ports` in
+//! `config/config.toml`), distributing the links among these ports in a round-robin fashion.
+//! Connecting fails with [`CiscoLabError::NoDelayerPorts`] if links have a delay but no delayer
+//! port is configured. Delay values are corrected by `server.delayer_loop_offset` to account for
+//! the time it takes to pass through the delayer loop.
 
 #![doc(html_logo_url = "https://iospf.tibors.ch/images/bgpsim/dark_only.svg")]
 
@@ -136,7 +141,7 @@ use bgpsim::{
     types::{NetworkError, NonOverlappingPrefix},
 };
 use ipnet::Ipv4Net;
-use router::{CiscoSession, CiscoShellError};
+use router::{CiscoSession, CiscoShellError, GreedyMapping, MappingStrategy};
 use server::{CmdError, ExaBgpHandle, ServerSession, TrafficCaptureError};
 use ssh::SshError;
 use thiserror::Error;
@@ -148,7 +153,9 @@ pub mod server;
 pub mod ssh;
 mod tofino;
 
-pub use server::export_capture_to_csv;
+#[cfg(feature = "parquet")]
+pub use server::export_capture_to_parquet;
+pub use server::{export_capture_to_csv, export_capture_to_csv_long};
 
 #[cfg(test)]
 mod test;
@@ -205,16 +212,29 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Inactive> {
     /// Generate a new instance to manage the network. This will only allocate strucutres, but not
     /// change anything on the network itself. This function will not yet connect to any router.
     pub fn new(net: &'n Network<P, Q>) -> Result<Self, CiscoLabError> {
-        let routers = Self::prepare_internal_routers(net)?;
+        Self::new_with_strategy(net, GreedyMapping)
+    }
+
+    /// Generate a new instance to manage the network, using the given `strategy` to assign routers
+    /// to VDCs (see [`MappingStrategy`]). [`CiscoLab::new`] uses the [`GreedyMapping`].
+    pub fn new_with_strategy<M: MappingStrategy>(
+        net: &'n Network<P, Q>,
+        strategy: M,
+    ) -> Result<Self, CiscoLabError> {
+        let routers = Self::prepare_internal_routers(net, &strategy)?;
         let external_routers = Self::prepare_external_routers(net)?;
-        let addressor = DefaultAddressorBuilder {
+        let mut addressor = DefaultAddressorBuilder {
             internal_ip_range: CONFIG.addresses.internal_ip_range,
             external_ip_range: CONFIG.addresses.external_ip_range,
             local_prefix_len: CONFIG.addresses.local_prefix_len,
             link_prefix_len: CONFIG.addresses.link_prefix_len,
             external_prefix_len: CONFIG.addresses.external_prefix_len,
+            assignment: None,
         }
         .build(net)?;
+        for (r, (_, gen)) in routers.iter() {
+            addressor.register_iface_names(*r, gen.ifaces().to_vec());
+        }
 
         Ok(Self {
             net,
@@ -344,6 +364,23 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
     pub fn addressor(&self) -> &DefaultAddressor<'n, P, Q> {
         &self.addressor
     }
+
+    /// Get the MAC address of the interface on `router` that connects to `neighbor`. This combines
+    /// the interface index assigned by the addressor with the interfaces of the VDC associated
+    /// with `router`. Returns `None` if `router` is not an internal router, or if the addressor
+    /// has not yet assigned an interface towards `neighbor`.
+    pub fn mac_for_router_iface(&self, router: RouterId, neighbor: RouterId) -> Option<[u8; 6]> {
+        let (_, _, iface_idx) = self.addressor.try_get_iface(router, neighbor)?.ok()?;
+        let (vdc, _) = self.routers.get(&router)?;
+        vdc.ifaces.get(iface_idx).map(|iface| iface.mac)
+    }
+
+    /// Get the neighbor of `router` that is connected to the interface named `iface` (for instance,
+    /// `Ethernet4/1`). Returns `None` if `router` is not an internal router, if the interface does
+    /// not exist, or if the addressor has not yet assigned that interface.
+    pub fn neighbor_for_router_iface(&self, router: RouterId, iface: &str) -> Option<RouterId> {
+        self.addressor.find_iface_by_name(router, iface).ok()
+    }
 }
 
 /// Error type thrown while managing the lab network.
@@ -370,9 +407,10 @@ pub enum CiscoLabError {
     /// Network contains too many routers to simulate it in the lab network.
     #[error("Cannot fit {0} routers in the lab network")]
     TooManyRouters(usize),
-    /// Network contains too many nodes with too high degree.
+    /// A router has more links (the first field) than the VDC it is assigned to (the second field)
+    /// has interfaces.
     #[error("Cannot fit {0} links in the lab network on router {1}.")]
-    TooManyLinks(usize, &'static str),
+    TooManyLinks(usize, String),
     /// Cannot obtain the lock.
     #[error("Cannot obtain the lock! {0} owns the lock tho the lab.")]
     CannotObtainLock(String),
@@ -397,4 +435,10 @@ pub enum CiscoLabError {
     /// The supervisor status on a router is suboptimal. Reboot the router
     #[error("Supervisor on {0} is in a bad state! Maybe `reload` the router?")]
     WrongSupervisorStatus(&'static str),
+    /// The number of destinations for a traffic capture must be at least 2.
+    #[error("Cannot probe {0} destinations! At least 2 are required.")]
+    InvalidNumberOfDestinations(usize),
+    /// Links have a delay configured, but there is no delayer port in `config/config.toml`.
+    #[error("Cannot delay links, as `server.delayer_tofino_ports` is empty!")]
+    NoDelayerPorts,
 }
@@ -0,0 +1,92 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Line-oriented diff between two router configurations.
+
+/// A single line of the diff between two configurations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// The line is present in both configurations.
+    Same(String),
+    /// The line is only present in the new configuration.
+    Added(String),
+    /// The line is only present in the old configuration.
+    Removed(String),
+}
+
+impl std::fmt::Display for DiffLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffLine::Same(l) => write!(f, "  {l}"),
+            DiffLine::Added(l) => write!(f, "+ {l}"),
+            DiffLine::Removed(l) => write!(f, "- {l}"),
+        }
+    }
+}
+
+/// Compute the line-oriented diff between two configurations. Only meaningful lines are compared;
+/// blank lines and comments (starting with `!`) are ignored, as well as trailing whitespace. The
+/// result contains all lines of both configurations in order, such that removed lines appear
+/// before the added lines that replace them.
+///
+/// This is useful to compare the output of `generate_config` before and after applying a command
+/// with `generate_command`.
+pub fn config_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = meaningful_lines(old);
+    let new = meaningful_lines(new);
+    let (n, m) = (old.len(), new.len());
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    diff.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    diff
+}
+
+/// Get all lines that are neither empty nor a comment, with trailing whitespace removed.
+fn meaningful_lines(config: &str) -> Vec<&str> {
+    config
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim_start().is_empty() && !l.trim_start().starts_with('!'))
+        .collect()
+}
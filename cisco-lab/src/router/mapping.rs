@@ -0,0 +1,81 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Strategies to assign the internal routers of a network to the VDCs in the lab.
+
+use std::cmp::Reverse;
+
+use bgpsim::prelude::*;
+
+use crate::{config::RouterProperties, CiscoLabError};
+
+/// Strategy to assign each internal router of a network to a VDC in the lab. A VDC can only host a
+/// router if it has at least as many interfaces as the router has neighbors.
+pub trait MappingStrategy {
+    /// Assign each internal router of `net` to a distinct VDC. The result contains, for each
+    /// internal router, the index of the VDC in `vdcs`.
+    fn assign<P: Prefix, Q>(
+        &self,
+        net: &Network<P, Q>,
+        vdcs: &[RouterProperties],
+    ) -> Result<Vec<(RouterId, usize)>, CiscoLabError>;
+}
+
+/// Get the number of interfaces required by a router.
+fn degree<P: Prefix, Q>(net: &Network<P, Q>, r: RouterId) -> usize {
+    net.get_topology().neighbors(r).count()
+}
+
+/// Get all internal routers, sorted by their degree (in decreasing order).
+fn sorted_routers<P: Prefix, Q>(net: &Network<P, Q>) -> Vec<RouterId> {
+    let mut routers = net.get_routers();
+    routers.sort_by_key(|r| (Reverse(degree(net, *r)), r.index()));
+    routers
+}
+
+/// Greedy strategy (the default). Routers are sorted by their degree, and the router with the
+/// `i`-th highest degree is assigned to the `i`-th VDC. This strategy assumes that VDCs are sorted
+/// by their number of interfaces (in decreasing order), as returned by
+/// [`crate::config::read_vdcs`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyMapping;
+
+impl MappingStrategy for GreedyMapping {
+    fn assign<P: Prefix, Q>(
+        &self,
+        net: &Network<P, Q>,
+        vdcs: &[RouterProperties],
+    ) -> Result<Vec<(RouterId, usize)>, CiscoLabError> {
+        let routers = sorted_routers(net);
+        if routers.len() > vdcs.len() {
+            return Err(CiscoLabError::TooManyRouters(routers.len()));
+        }
+        routers
+            .into_iter()
+            .zip(vdcs)
+            .enumerate()
+            .map(|(i, (r, vdc))| {
+                let deg = degree(net, r);
+                if deg > vdc.ifaces.len() {
+                    Err(CiscoLabError::TooManyLinks(deg, vdc.ssh_name.clone()))
+                } else {
+                    Ok((r, i))
+                }
+            })
+            .collect()
+    }
+}
@@ -21,7 +21,6 @@
 //! device.
 
 use std::{
-    cmp::Reverse,
     collections::{BTreeMap, HashMap, HashSet},
     net::Ipv4Addr,
     time::{Duration, Instant},
@@ -45,15 +44,22 @@ use tokio::{
 };
 
 use crate::{
-    config::{RouterProperties, ROUTERS, VDCS},
+    config::{RouterProperties, CONFIG, ROUTERS, VDCS},
     ssh::SshSession,
     Active, CiscoLab, CiscoLabError, Inactive,
 };
 
+mod diff;
+mod mapping;
 mod session;
+pub use diff::{config_diff, DiffLine};
+pub use mapping::{GreedyMapping, MappingStrategy};
+#[cfg(test)]
+pub(crate) use session::exec_with_timeout;
 pub use session::{
-    invert_config, BgpNeighbor, BgpPathType, BgpRoute, BgpRoutesDetailError, CiscoSession,
-    CiscoShell, CiscoShellError, OspfNeighbor, OspfRoute, ParseError, TableParseError,
+    invert_config, BgpAttrs, BgpNeighbor, BgpPathType, BgpRoute, BgpRoutesDetailError,
+    CiscoSession, CiscoShell, CiscoShellError, ExpectedAttrs, OspfLsa, OspfLsaType, OspfNeighbor,
+    OspfRoute, ParseError, TableParseError,
 };
 
 const OSPF_CONVERGENCE_THRESHOLD_SECS: u64 = 10;
@@ -61,43 +67,14 @@ const BGP_CONVERGENCE_THRESHOLD_SECS: u64 = 10;
 const BGP_PEC_CHECK: usize = 10;
 
 impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Inactive> {
-    /// Prepare all internal routers (used in the constructor of `CiscoLab`).
-    pub(super) fn prepare_internal_routers(
+    /// Prepare all internal routers, assigning them to VDCs with the given `strategy` (used in the
+    /// constructor of `CiscoLab`).
+    pub(super) fn prepare_internal_routers<M: MappingStrategy>(
         net: &'n Network<P, Q>,
+        strategy: &M,
     ) -> Result<BTreeMap<RouterId, (&'static RouterProperties, CiscoFrrCfgGen<P>)>, CiscoLabError>
     {
-        // get all internal routers
-        let mut internal_routers = net.get_routers();
-        // sort by their degree
-        internal_routers
-            .sort_by_key(|r| (Reverse(net.get_topology().neighbors(*r).count()), r.index()));
-        let n = internal_routers.len();
-
-        // assign routers
-        internal_routers
-            .into_iter()
-            .enumerate()
-            .map(|(i, r)| {
-                let mut gen = CiscoFrrCfgGen::new(
-                    net,
-                    r,
-                    CiscoNexus7000,
-                    VDCS[i].ifaces.iter().map(|x| x.iface.clone()).collect(),
-                )?;
-                gen.set_ospf_parameters(None, None);
-                for iface in VDCS[i].ifaces.iter() {
-                    gen.set_mac_address(&iface.iface, iface.mac);
-                }
-                Ok((
-                    r,
-                    (
-                        VDCS.get(i)
-                            .ok_or_else(|| CiscoLabError::TooManyRouters(n))?,
-                        gen,
-                    ),
-                ))
-            })
-            .collect()
+        map_internal_routers(net, &VDCS, strategy)
     }
 
     /// Connect to all routers in parallel, and return a HashMap with all sessions. If any
@@ -127,6 +104,33 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Inactive> {
     }
 }
 
+/// Assign each internal router of `net` to one of the `vdcs` using the given `strategy`, and
+/// prepare the configuration generator of each router.
+pub(crate) fn map_internal_routers<'a, P: Prefix, Q, M: MappingStrategy>(
+    net: &Network<P, Q>,
+    vdcs: &'a [RouterProperties],
+    strategy: &M,
+) -> Result<BTreeMap<RouterId, (&'a RouterProperties, CiscoFrrCfgGen<P>)>, CiscoLabError> {
+    strategy
+        .assign(net, vdcs)?
+        .into_iter()
+        .map(|(r, i)| {
+            let vdc = &vdcs[i];
+            let mut gen = CiscoFrrCfgGen::new(
+                net,
+                r,
+                CiscoNexus7000,
+                vdc.ifaces.iter().map(|x| x.iface.clone()).collect(),
+            )?;
+            gen.set_ospf_parameters(None, None);
+            for iface in vdc.ifaces.iter() {
+                gen.set_mac_address(&iface.iface, iface.mac);
+            }
+            Ok((r, (vdc, gen)))
+        })
+        .collect()
+}
+
 impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
     /// Get the router SSH host name that is associated with the given router ID. If the router ID
     /// is not an internal router, this function will return a `NetworkError`.
@@ -241,16 +245,30 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
     }
 
     /// Get the configuration of all internal routers, including their associated SSH host name.
+    /// In debug builds, all configurations are checked with [`CiscoFrrCfgGen::lint`], and every
+    /// warning is logged.
     pub fn generate_router_config_all(
         &mut self,
     ) -> Result<BTreeMap<RouterId, (&'static str, String)>, CiscoLabError> {
-        self.routers
+        let configs = self
+            .routers
             .iter()
             .map(|(r, (vdc, _))| (*r, vdc.ssh_name.as_str()))
             .collect::<Vec<_>>()
             .into_iter()
             .map(|(r, vdc)| Ok((r, (vdc, self.generate_router_config(r)?))))
-            .collect()
+            .collect::<Result<BTreeMap<_, _>, CiscoLabError>>()?;
+
+        // In debug builds, check the generated configuration for obvious mistakes.
+        if cfg!(debug_assertions) {
+            for (vdc, config) in configs.values() {
+                for warning in CiscoFrrCfgGen::<P>::lint(config) {
+                    log::warn!("[{vdc}] Generated configuration: {warning}");
+                }
+            }
+        }
+
+        Ok(configs)
     }
 
     /// Get a mutable reference to the configuration generator of a specific router. If the router
@@ -432,6 +450,17 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
     /// from the router threads to the controller thread. The second one is a Broadcast channel used
     /// by the controller thread to trigger the next state of the workers.
     pub async fn wait_for_convergence(&mut self) -> Result<(), CiscoLabError> {
+        self.wait_for_convergence_with_progress(None).await
+    }
+
+    /// Wait for OSPF and BGP to converge (see [`CiscoLab::wait_for_convergence`]), and call
+    /// `progress` with the current phase, the number of routers that have completed that phase, and
+    /// the total number of routers, every time a router reports in and whenever a phase starts or
+    /// completes.
+    pub async fn wait_for_convergence_with_progress(
+        &mut self,
+        progress: Option<ConvergenceProgress>,
+    ) -> Result<(), CiscoLabError> {
         if cfg!(feature = "ignore-routers") {
             log::warn!("Skip convergence! (Feature `ignore-routers` is enabled)");
             return Ok(());
@@ -445,6 +474,19 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
         log::info!("[convergence] Wait for convergence");
         let num_workers = self.routers.len();
 
+        // keep the sessions alive while waiting (the handles stop the tasks once dropped).
+        let _keepalive = CONFIG
+            .ssh
+            .keepalive_interval()
+            .map(|interval| {
+                self.state
+                    .routers
+                    .values()
+                    .map(|s| s.keepalive(interval))
+                    .collect_vec()
+            })
+            .unwrap_or_default();
+
         let mut workers = Vec::new();
         for (worker_id, (router, (cfg, _))) in self.routers.iter().enumerate() {
             // compute the expected OSPF state
@@ -496,7 +538,9 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
         std::mem::drop(state_rx);
 
         // call the controller
-        let result = self.wait_convergence_controller(message_rx, state_tx).await;
+        let result = self
+            .wait_convergence_controller(message_rx, state_tx, progress.as_ref())
+            .await;
 
         // join all workers
         for worker in workers {
@@ -571,7 +615,7 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
 
         log::info!("[convergence] Wait for BGP to stop sending messages.");
 
-        self.wait_convergence_no_message(
+        wait_convergence_no_message(
             &mut message_rx,
             ConvergenceState::BgpState,
             deadline,
@@ -594,88 +638,19 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
     /// Main controller for waiting for convergence
     async fn wait_convergence_controller(
         &self,
-        mut message_rx: mpsc::Receiver<ConvergenceMessage>,
+        message_rx: mpsc::Receiver<ConvergenceMessage>,
         state_tx: broadcast::Sender<ConvergenceState>,
+        progress: Option<&ConvergenceProgress>,
     ) -> Result<(), CiscoLabError> {
-        let deadline = Duration::from_secs(300);
-        let start_time = Instant::now();
-
-        log::info!("[convergence] Wait for OSPF to establish neighbors");
-
-        // first, wati for done messages
-        self.wait_convergence_done_messages(
-            &mut message_rx,
-            ConvergenceState::OspfNeighbors,
-            deadline,
-            start_time,
-        )
-        .await?;
-        state_tx
-            .send(ConvergenceState::OspfState)
-            .map_err(|_| CiscoLabError::ConvergenceError)?;
-
-        log::info!("[convergence] Wait for OSPF to converge");
-
-        // then, wait for no update message in ospf state
-        self.wait_convergence_no_message(
-            &mut message_rx,
-            ConvergenceState::OspfState,
-            deadline,
-            start_time,
+        convergence_controller(
+            self.routers.len(),
+            message_rx,
+            state_tx,
+            progress,
             Duration::from_secs(OSPF_CONVERGENCE_THRESHOLD_SECS),
-        )
-        .await?;
-        state_tx
-            .send(ConvergenceState::BgpNeighbors)
-            .map_err(|_| CiscoLabError::ConvergenceError)?;
-
-        log::info!("[convergence] Wait for BGP to establish neighbors");
-
-        // Then, wait for all BGP sessions to connect
-        self.wait_convergence_done_messages(
-            &mut message_rx,
-            ConvergenceState::BgpNeighbors,
-            deadline,
-            start_time,
-        )
-        .await?;
-        state_tx
-            .send(ConvergenceState::BgpNextHop)
-            .map_err(|_| CiscoLabError::ConvergenceError)?;
-
-        log::info!("[convergence] Wait for BGP to reach the desired state");
-
-        // Then, wait for all BGP sessions to connect
-        self.wait_convergence_done_messages(
-            &mut message_rx,
-            ConvergenceState::BgpNextHop,
-            deadline,
-            start_time,
-        )
-        .await?;
-        state_tx
-            .send(ConvergenceState::BgpState)
-            .map_err(|_| CiscoLabError::ConvergenceError)?;
-
-        log::info!("[convergence] Wait for BGP to converge");
-
-        // Finally, wait for BGP to converge
-        self.wait_convergence_no_message(
-            &mut message_rx,
-            ConvergenceState::BgpState,
-            deadline,
-            start_time,
             Duration::from_secs(BGP_CONVERGENCE_THRESHOLD_SECS),
         )
         .await?;
-        state_tx
-            .send(ConvergenceState::Done)
-            .map_err(|_| CiscoLabError::ConvergenceError)?;
-
-        log::info!(
-            "[convergence] Network has converged after {} seconds",
-            start_time.elapsed().as_secs()
-        );
 
         for (rid, cisco_session) in self.state.routers.iter() {
             log::trace!(
@@ -688,99 +663,6 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
         Ok(())
     }
 
-    async fn wait_convergence_done_messages(
-        &self,
-        message_rx: &mut mpsc::Receiver<ConvergenceMessage>,
-        state: ConvergenceState,
-        deadline: Duration,
-        start_time: Instant,
-    ) -> Result<(), CiscoLabError> {
-        let mut seen_messages = HashSet::new();
-
-        while seen_messages.len() < self.routers.len() {
-            let until_deadline = deadline.saturating_sub(start_time.elapsed());
-            match timeout(until_deadline, message_rx.recv()).await {
-                // timeout occurred
-                Err(_) => {
-                    log::warn!(
-                        "[convergence] Timeout occurred while waiting for convergence in state {:?}",
-                        state
-                    );
-                    return Err(CiscoLabError::ConvergenceTimeout);
-                }
-                // Channels closed
-                Ok(None) => {
-                    log::warn!(
-                        "[convergence] MPSC channel for receiving messages has no senders left!",
-                    );
-                    return Err(CiscoLabError::ConvergenceError);
-                }
-                // received message from correct state
-                Ok(Some(ConvergenceMessage(s, i))) if s == state => {
-                    log::debug!("[convergence] Received message from {}", i);
-                    seen_messages.insert(i);
-                }
-                // received message from wrong state
-                Ok(Some(ConvergenceMessage(s, i))) => {
-                    log::debug!(
-                        "[convergence] Received message from {} in old state {:?}. Ignore the message",
-                        i,
-                        s
-                    );
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    async fn wait_convergence_no_message(
-        &self,
-        message_rx: &mut mpsc::Receiver<ConvergenceMessage>,
-        state: ConvergenceState,
-        deadline: Duration,
-        start_time: Instant,
-        threshold: Duration,
-    ) -> Result<(), CiscoLabError> {
-        let mut last_update = Instant::now();
-        while start_time.elapsed() < deadline {
-            let until_threshold = threshold.saturating_sub(last_update.elapsed());
-            match timeout(until_threshold, message_rx.recv()).await {
-                // If the timeout was reached, we can proceed
-                Err(_) => {
-                    log::debug!("[convergence] No update from workers received! Transition to the next state");
-                    return Ok(());
-                }
-                // channels broke down.
-                Ok(None) => {
-                    log::warn!(
-                        "[convergence] MPSC channel for receiving messages has no senders left!",
-                    );
-                    return Err(CiscoLabError::ConvergenceError);
-                }
-                // received message from correct state
-                Ok(Some(ConvergenceMessage(s, i))) if s == state => {
-                    log::debug!("[convergence] Received message from {}", i);
-                    last_update = Instant::now();
-                }
-                // received message from wrong state
-                Ok(Some(ConvergenceMessage(s, i))) => {
-                    log::debug!(
-                        "[convergence] Received message from {} in old state {:?}. Ignore the message",
-                        i,
-                        s
-                    );
-                }
-            }
-        }
-
-        log::warn!(
-            "[convergence] Timeout occurred while waiting for convergence in state {:?}",
-            state
-        );
-        Err(CiscoLabError::ConvergenceTimeout)
-    }
-
     /// Compute the expected BGP state, which is a list of routes and their expected BGP next-hop
     /// for each router in the network.
     ///
@@ -903,8 +785,241 @@ pub(crate) async fn _check_router_ha_status(router: &'static str) -> Result<(),
     Err(CiscoLabError::WrongSupervisorStatus(router))
 }
 
+/// Main controller for waiting for convergence. The controller waits until all `num_workers`
+/// workers have reported in each phase, or until no worker has sent a message for the given
+/// threshold. After each phase, it triggers the next state of the workers.
+pub(crate) async fn convergence_controller(
+    num_workers: usize,
+    mut message_rx: mpsc::Receiver<ConvergenceMessage>,
+    state_tx: broadcast::Sender<ConvergenceState>,
+    progress: Option<&ConvergenceProgress>,
+    ospf_threshold: Duration,
+    bgp_threshold: Duration,
+) -> Result<(), CiscoLabError> {
+    let deadline = Duration::from_secs(300);
+    let start_time = Instant::now();
+    let report = |phase: ConvergencePhase, done: usize| {
+        if let Some(progress) = progress {
+            progress(phase, done, num_workers)
+        }
+    };
+
+    log::info!("[convergence] Wait for OSPF to establish neighbors");
+    report(ConvergencePhase::OspfNeighbors, 0);
+
+    // first, wati for done messages
+    wait_convergence_done_messages(
+        num_workers,
+        &mut message_rx,
+        ConvergenceState::OspfNeighbors,
+        deadline,
+        start_time,
+        progress,
+    )
+    .await?;
+    state_tx
+        .send(ConvergenceState::OspfState)
+        .map_err(|_| CiscoLabError::ConvergenceError)?;
+
+    log::info!("[convergence] Wait for OSPF to converge");
+    report(ConvergencePhase::OspfState, 0);
+
+    // then, wait for no update message in ospf state
+    wait_convergence_no_message(
+        &mut message_rx,
+        ConvergenceState::OspfState,
+        deadline,
+        start_time,
+        ospf_threshold,
+    )
+    .await?;
+    report(ConvergencePhase::OspfState, num_workers);
+    state_tx
+        .send(ConvergenceState::BgpNeighbors)
+        .map_err(|_| CiscoLabError::ConvergenceError)?;
+
+    log::info!("[convergence] Wait for BGP to establish neighbors");
+    report(ConvergencePhase::BgpNeighbors, 0);
+
+    // Then, wait for all BGP sessions to connect
+    wait_convergence_done_messages(
+        num_workers,
+        &mut message_rx,
+        ConvergenceState::BgpNeighbors,
+        deadline,
+        start_time,
+        progress,
+    )
+    .await?;
+    state_tx
+        .send(ConvergenceState::BgpNextHop)
+        .map_err(|_| CiscoLabError::ConvergenceError)?;
+
+    log::info!("[convergence] Wait for BGP to reach the desired state");
+    report(ConvergencePhase::BgpNextHop, 0);
+
+    // Then, wait for all BGP sessions to connect
+    wait_convergence_done_messages(
+        num_workers,
+        &mut message_rx,
+        ConvergenceState::BgpNextHop,
+        deadline,
+        start_time,
+        progress,
+    )
+    .await?;
+    state_tx
+        .send(ConvergenceState::BgpState)
+        .map_err(|_| CiscoLabError::ConvergenceError)?;
+
+    log::info!("[convergence] Wait for BGP to converge");
+    report(ConvergencePhase::BgpState, 0);
+
+    // Finally, wait for BGP to converge
+    wait_convergence_no_message(
+        &mut message_rx,
+        ConvergenceState::BgpState,
+        deadline,
+        start_time,
+        bgp_threshold,
+    )
+    .await?;
+    report(ConvergencePhase::BgpState, num_workers);
+    state_tx
+        .send(ConvergenceState::Done)
+        .map_err(|_| CiscoLabError::ConvergenceError)?;
+
+    log::info!(
+        "[convergence] Network has converged after {} seconds",
+        start_time.elapsed().as_secs()
+    );
+    report(ConvergencePhase::Done, num_workers);
+
+    Ok(())
+}
+
+async fn wait_convergence_done_messages(
+    num_workers: usize,
+    message_rx: &mut mpsc::Receiver<ConvergenceMessage>,
+    state: ConvergenceState,
+    deadline: Duration,
+    start_time: Instant,
+    progress: Option<&ConvergenceProgress>,
+) -> Result<(), CiscoLabError> {
+    let mut seen_messages = HashSet::new();
+
+    while seen_messages.len() < num_workers {
+        let until_deadline = deadline.saturating_sub(start_time.elapsed());
+        match timeout(until_deadline, message_rx.recv()).await {
+            // timeout occurred
+            Err(_) => {
+                log::warn!(
+                    "[convergence] Timeout occurred while waiting for convergence in state {:?}",
+                    state
+                );
+                return Err(CiscoLabError::ConvergenceTimeout);
+            }
+            // Channels closed
+            Ok(None) => {
+                log::warn!(
+                    "[convergence] MPSC channel for receiving messages has no senders left!",
+                );
+                return Err(CiscoLabError::ConvergenceError);
+            }
+            // received message from correct state
+            Ok(Some(ConvergenceMessage(s, i))) if s == state => {
+                log::debug!("[convergence] Received message from {}", i);
+                if seen_messages.insert(i) {
+                    if let (Some(progress), Some(phase)) = (progress, state.phase()) {
+                        progress(phase, seen_messages.len(), num_workers);
+                    }
+                }
+            }
+            // received message from wrong state
+            Ok(Some(ConvergenceMessage(s, i))) => {
+                log::debug!(
+                    "[convergence] Received message from {} in old state {:?}. Ignore the message",
+                    i,
+                    s
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn wait_convergence_no_message(
+    message_rx: &mut mpsc::Receiver<ConvergenceMessage>,
+    state: ConvergenceState,
+    deadline: Duration,
+    start_time: Instant,
+    threshold: Duration,
+) -> Result<(), CiscoLabError> {
+    let mut last_update = Instant::now();
+    while start_time.elapsed() < deadline {
+        let until_threshold = threshold.saturating_sub(last_update.elapsed());
+        match timeout(until_threshold, message_rx.recv()).await {
+            // If the timeout was reached, we can proceed
+            Err(_) => {
+                log::debug!(
+                    "[convergence] No update from workers received! Transition to the next state"
+                );
+                return Ok(());
+            }
+            // channels broke down.
+            Ok(None) => {
+                log::warn!(
+                    "[convergence] MPSC channel for receiving messages has no senders left!",
+                );
+                return Err(CiscoLabError::ConvergenceError);
+            }
+            // received message from correct state
+            Ok(Some(ConvergenceMessage(s, i))) if s == state => {
+                log::debug!("[convergence] Received message from {}", i);
+                last_update = Instant::now();
+            }
+            // received message from wrong state
+            Ok(Some(ConvergenceMessage(s, i))) => {
+                log::debug!(
+                    "[convergence] Received message from {} in old state {:?}. Ignore the message",
+                    i,
+                    s
+                );
+            }
+        }
+    }
+
+    log::warn!(
+        "[convergence] Timeout occurred while waiting for convergence in state {:?}",
+        state
+    );
+    Err(CiscoLabError::ConvergenceTimeout)
+}
+
+/// Callback to report the progress while waiting for convergence. It is called with the current
+/// phase, the number of routers that have completed that phase, and the total number of routers.
+pub type ConvergenceProgress = Box<dyn Fn(ConvergencePhase, usize, usize) + Send + Sync>;
+
+/// Phase while waiting for convergence, see [`CiscoLab::wait_for_convergence`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ConvergencePhase {
+    /// Waiting for all OSPF neighbors to be established.
+    OspfNeighbors,
+    /// Waiting for the OSPF table to not change anymore.
+    OspfState,
+    /// Waiting for all BGP sessions to be established.
+    BgpNeighbors,
+    /// Waiting for BGP to select the expected next-hops.
+    BgpNextHop,
+    /// Waiting for the BGP table to not change anymore.
+    BgpState,
+    /// The network has converged.
+    Done,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub(self) enum ConvergenceState {
+pub(crate) enum ConvergenceState {
     OspfNeighbors,
     OspfNeighborsDone,
     OspfState,
@@ -917,4 +1032,21 @@ pub(self) enum ConvergenceState {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub(self) struct ConvergenceMessage(ConvergenceState, usize);
+pub(crate) struct ConvergenceMessage(pub ConvergenceState, pub usize);
+
+impl ConvergenceState {
+    /// Get the phase reported to the progress callback, or `None` for internal states.
+    fn phase(&self) -> Option<ConvergencePhase> {
+        match self {
+            ConvergenceState::OspfNeighbors => Some(ConvergencePhase::OspfNeighbors),
+            ConvergenceState::OspfState => Some(ConvergencePhase::OspfState),
+            ConvergenceState::BgpNeighbors => Some(ConvergencePhase::BgpNeighbors),
+            ConvergenceState::BgpNextHop => Some(ConvergencePhase::BgpNextHop),
+            ConvergenceState::BgpState => Some(ConvergencePhase::BgpState),
+            ConvergenceState::Done => Some(ConvergencePhase::Done),
+            ConvergenceState::OspfNeighborsDone
+            | ConvergenceState::BgpNeighborsDone
+            | ConvergenceState::BgpNextHopDone => None,
+        }
+    }
+}
@@ -28,9 +28,10 @@ use bgpsim::types::AsId;
 use ipnet::Ipv4Net;
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_json::Value;
 use thiserror::Error;
 
-use super::{table_parser::parse_table_with_alignment, ParseError};
+use super::{json_rows, json_str, table_parser::parse_table_with_alignment, ParseError};
 
 /// Structure containing a BGP Route in detail. It is parsed from showing the detailed route list on
 /// cisco routers.
@@ -222,6 +223,107 @@ impl BgpRoute {
     }
 }
 
+/// Attributes of the selected BGP route for a destination, parsed from the JSON output of `show ip
+/// bgp {net} | json`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BgpAttrs {
+    /// The next-hop attribute
+    pub next_hop: Ipv4Addr,
+    /// The local-pref attribute
+    pub local_pref: Option<u32>,
+    /// The set of communities of that route.
+    pub communities: BTreeSet<(AsId, u32)>,
+}
+
+impl BgpAttrs {
+    /// Parse the JSON output of `show ip bgp {net} | json` into a mapping from each destination
+    /// prefix to the attributes of the selected (best) path. Prefixes without a selected path are
+    /// omitted.
+    ///
+    /// Tables of the JSON output contain a single object if there is only one row, and an array
+    /// otherwise. Both variants are accepted.
+    pub fn selected_from_json(input: &str) -> Result<HashMap<Ipv4Net, BgpAttrs>, ParseError> {
+        // Allow a completely empty output (the router does not know any route)
+        if input.trim().is_empty() {
+            return Ok(HashMap::new());
+        }
+        let doc: Value = serde_json::from_str(input)?;
+
+        let mut result = HashMap::new();
+        for vrf in json_rows(&doc, "TABLE_vrf", "ROW_vrf") {
+            for afi in json_rows(vrf, "TABLE_afi", "ROW_afi") {
+                for safi in json_rows(afi, "TABLE_safi", "ROW_safi") {
+                    for rd in json_rows(safi, "TABLE_rd", "ROW_rd") {
+                        for prefix in json_rows(rd, "TABLE_prefix", "ROW_prefix") {
+                            let net: Ipv4Net = json_str(prefix, "ipprefix")?.parse()?;
+                            for path in json_rows(prefix, "TABLE_path", "ROW_path") {
+                                if json_str(path, "pathbest")? == "true" {
+                                    result.insert(net, Self::from_json_path(path)?);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Parse the attributes of a single path (`ROW_path`).
+    fn from_json_path(path: &Value) -> Result<Self, ParseError> {
+        let communities = json_rows(path, "TABLE_community", "ROW_community")
+            .into_iter()
+            .map(|c| json_str(c, "community"))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flat_map(|c| c.split(' '))
+            .filter_map(|x| x.split_once(':'))
+            .map(|(id, x)| Ok::<_, ParseError>((AsId(id.parse()?), x.parse()?)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            next_hop: json_str(path, "ipnexthop")?.parse()?,
+            local_pref: match path.get("localpref").and_then(Value::as_str) {
+                Some(lp) => Some(lp.parse()?),
+                None => None,
+            },
+            communities,
+        })
+    }
+}
+
+/// Expected attributes of the selected BGP route for a destination, used to check the state of the
+/// routers beyond the next-hop.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ExpectedAttrs {
+    /// The expected next-hop, or `None` if no route should be selected.
+    pub next_hop: Option<Ipv4Addr>,
+    /// The expected local-pref. It is not checked if set to `None`.
+    pub local_pref: Option<u32>,
+    /// The expected set of communities. They are not checked if set to `None`.
+    pub communities: Option<BTreeSet<(AsId, u32)>>,
+}
+
+impl ExpectedAttrs {
+    /// Check if the `acquired` attributes of the selected route match the expected ones.
+    pub fn matches(&self, acquired: Option<&BgpAttrs>) -> bool {
+        match (self.next_hop, acquired) {
+            (None, None) => true,
+            (Some(next_hop), Some(acquired)) => {
+                next_hop == acquired.next_hop
+                    && self
+                        .local_pref
+                        .map_or(true, |lp| acquired.local_pref == Some(lp))
+                    && self
+                        .communities
+                        .as_ref()
+                        .map_or(true, |c| c == &acquired.communities)
+            }
+            _ => false,
+        }
+    }
+}
+
 /// From where was the route learned?
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BgpPathType {
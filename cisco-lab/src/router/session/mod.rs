@@ -17,12 +17,15 @@
 
 //! This module implements an SSH session for Cisco IOS devices.
 
-use std::{net::Ipv4Addr, process::Stdio};
+use std::{collections::HashMap, future::Future, net::Ipv4Addr, process::Stdio, time::Duration};
 
+use ipnet::Ipv4Net;
+use serde_json::Value;
 use thiserror::Error;
+use tokio::time::timeout;
 
 use crate::{
-    ssh::{SshError, SshSession, EMPTY},
+    ssh::{spawn_keepalive, KeepaliveHandle, SshError, SshSession, EMPTY},
     CiscoLabError,
 };
 
@@ -31,8 +34,8 @@ mod ospf;
 mod reset_config;
 mod shell;
 pub(self) mod table_parser;
-pub use bgp::{BgpNeighbor, BgpPathType, BgpRoute, BgpRoutesDetailError};
-pub use ospf::{OspfNeighbor, OspfRoute};
+pub use bgp::{BgpAttrs, BgpNeighbor, BgpPathType, BgpRoute, BgpRoutesDetailError, ExpectedAttrs};
+pub use ospf::{OspfLsa, OspfLsaType, OspfNeighbor, OspfRoute};
 pub use reset_config::invert_config;
 pub use shell::{CiscoShell, CiscoShellError};
 pub use table_parser::TableParseError;
@@ -125,6 +128,71 @@ impl CiscoSession {
         self.0.execute_cmd_stdout(&["show", cmd.as_ref()]).await
     }
 
+    /// Execute an arbitrary command on the router and return its output (STDOUT). If the command
+    /// does not finish within `duration`, this function returns [`CiscoShellError::Timeout`].
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use cisco_lab::router::CiscoSession;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// let s = CiscoSession::new("router1.domain.com").await?;
+    /// let clock = s.exec_timeout("show clock", Duration::from_secs(5)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn exec_timeout(
+        &self,
+        cmd: &str,
+        duration: Duration,
+    ) -> Result<String, CiscoShellError> {
+        exec_with_timeout(cmd, duration, self.0.execute_cmd_stdout(&[cmd])).await
+    }
+
+    /// Get the OSPF link-state database using `show ip ospf database | json`.
+    ///
+    /// ```rust,no_run
+    /// use cisco_lab::router::CiscoSession;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// let s = CiscoSession::new("router1.domain.com").await?;
+    /// let lsdb = s.get_ospf_lsdb().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_ospf_lsdb(&self) -> Result<Vec<OspfLsa>, CiscoShellError> {
+        Ok(OspfLsa::from_json_output(
+            &self.show("ip ospf database | json").await?,
+        )?)
+    }
+
+    /// Check the attributes of the selected BGP route for each destination in `expected`, using
+    /// `show ip bgp {net} | json`. In addition to the next-hop, this function compares the
+    /// local-pref and the communities (if given in [`ExpectedAttrs`]). The function returns the
+    /// (sorted) list of destinations whose selected route does not match the expected attributes.
+    pub async fn check_bgp_attributes(
+        &self,
+        expected: &HashMap<Ipv4Net, ExpectedAttrs>,
+    ) -> Result<Vec<Ipv4Net>, CiscoShellError> {
+        let mut mismatches = Vec::new();
+        for (net, exp) in expected.iter() {
+            let acquired =
+                BgpAttrs::selected_from_json(&self.show(format!("ip bgp {net} | json")).await?)?;
+            if !exp.matches(acquired.get(net)) {
+                log::debug!(
+                    "[{}] Wrong BGP attributes for {net}! Expected {exp:?}, but got {:?}",
+                    self.name(),
+                    acquired.get(net)
+                );
+                mismatches.push(*net);
+            }
+        }
+        mismatches.sort();
+        Ok(mismatches)
+    }
+
     /// Send the command `clear ip arp force-delete` to clear all entries in the ARP cache. This
     /// helps to avoid connectivity problems when reloading different topology configurations on
     /// the testbed that use the same IP prefixes.
@@ -174,12 +242,55 @@ impl CiscoSession {
         CiscoShell::new(shell_process, self.name().to_string()).await
     }
 
+    /// Spawn a task that keeps the session alive while it is idle, by executing `show clock` every
+    /// `interval`. The task stops once the returned handle is dropped.
+    pub fn keepalive(&self, interval: Duration) -> KeepaliveHandle {
+        let session = self.clone();
+        spawn_keepalive(self.name(), interval, move || {
+            let session = session.clone();
+            async move { session.show("clock").await.map(|_| ()) }
+        })
+    }
+
     /// Get the SSH hostname of the target.
     pub fn name(&self) -> &str {
         self.0.name()
     }
 }
 
+/// Get all rows of the table `table` in a JSON object. The row is either a single object, or an
+/// array of objects. If the table does not exist, then the result is empty.
+pub(self) fn json_rows<'a>(value: &'a Value, table: &str, row: &str) -> Vec<&'a Value> {
+    match value.get(table).and_then(|t| t.get(row)) {
+        Some(Value::Array(rows)) => rows.iter().collect(),
+        Some(row) => vec![row],
+        None => Vec::new(),
+    }
+}
+
+/// Get the string value of the field `key` in a JSON object.
+pub(self) fn json_str<'a>(value: &'a Value, key: &'static str) -> Result<&'a str, ParseError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or(ParseError::MissingJsonField(key))
+}
+
+/// Wait for the output of the command `cmd` (computed by `exec`), but for at most `duration`.
+pub(crate) async fn exec_with_timeout(
+    cmd: &str,
+    duration: Duration,
+    exec: impl Future<Output = Result<String, SshError>>,
+) -> Result<String, CiscoShellError> {
+    match timeout(duration, exec).await {
+        Ok(result) => Ok(result?),
+        Err(_) => {
+            log::warn!("Timeout while executing `{cmd}`");
+            Err(CiscoShellError::Timeout(cmd.to_string()))
+        }
+    }
+}
+
 /// Error while parsing output from a cisco router.
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -192,6 +303,12 @@ pub enum ParseError {
     /// Unexpected XML Structure
     #[error("Missing a specific XML tag")]
     MissingXmlTag(&'static str),
+    /// JSON Error
+    #[error("JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Unexpected JSON Structure
+    #[error("Missing the JSON field {0}")]
+    MissingJsonField(&'static str),
     /// Tag is not a text node
     #[error("Tag is not a text node!")]
     NoText,
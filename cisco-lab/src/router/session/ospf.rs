@@ -21,8 +21,9 @@ use std::{collections::HashMap, net::Ipv4Addr};
 
 use ipnet::Ipv4Net;
 use roxmltree::Node;
+use serde_json::Value;
 
-use super::{table_parser::parse_table, ParseError};
+use super::{json_rows, json_str, table_parser::parse_table, ParseError};
 
 /// Structure that captrues a specific OSPF Route. This structure contains infromation from
 /// executing the command `show ip ospf route` on a cisco router.
@@ -220,6 +221,88 @@ impl OspfNeighbor {
     }
 }
 
+/// Structure that captures a single LSA of the OSPF link-state database. This is the parsed output
+/// of the command `show ip ospf database | json`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OspfLsa {
+    /// The area in which the LSA is flooded
+    pub area: Ipv4Addr,
+    /// The type of the LSA
+    pub lsa_type: OspfLsaType,
+    /// Link-state ID of the LSA
+    pub link_state_id: Ipv4Addr,
+    /// Router ID of the router that originated the LSA
+    pub adv_router: Ipv4Addr,
+}
+
+/// Type of an OSPF LSA
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OspfLsaType {
+    /// Router LSA (type 1)
+    Router,
+    /// Network LSA (type 2)
+    Network,
+    /// Network summary LSA (type 3)
+    Summary,
+    /// ASBR summary LSA (type 4)
+    AsbrSummary,
+    /// AS external LSA (type 5)
+    External,
+    /// NSSA external LSA (type 7)
+    Nssa,
+    /// Any other LSA type, as printed by the router
+    Other(String),
+}
+
+impl From<&str> for OspfLsaType {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "router" => Self::Router,
+            "network" => Self::Network,
+            "summary" | "summary-network" => Self::Summary,
+            "asbr-summary" | "summary-asbr" => Self::AsbrSummary,
+            "external" | "ase" | "as-external" => Self::External,
+            "nssa" | "nssa-external" => Self::Nssa,
+            _ => Self::Other(s.to_string()),
+        }
+    }
+}
+
+impl OspfLsa {
+    /// Parse the JSON output from executing the command `show ip ospf database | json`, and
+    /// return all LSAs of all areas.
+    ///
+    /// Tables of the JSON output contain a single object if there is only one row, and an array
+    /// otherwise. Both variants are accepted.
+    pub fn from_json_output(input: &str) -> Result<Vec<OspfLsa>, ParseError> {
+        // Allow a completely empty output
+        if input.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let doc: Value = serde_json::from_str(input)?;
+
+        let mut result = Vec::new();
+        for ctx in json_rows(&doc, "TABLE_ctx", "ROW_ctx") {
+            for area in json_rows(ctx, "TABLE_area", "ROW_area") {
+                let area_id: Ipv4Addr = json_str(area, "aname")?.parse()?;
+                for lsc in json_rows(area, "TABLE_lsctype", "ROW_lsctype") {
+                    let lsa_type = OspfLsaType::from(json_str(lsc, "lsa_type")?);
+                    for lsa in json_rows(lsc, "TABLE_lsa", "ROW_lsa") {
+                        result.push(OspfLsa {
+                            area: area_id,
+                            lsa_type: lsa_type.clone(),
+                            link_state_id: json_str(lsa, "lsid")?.parse()?,
+                            adv_router: json_str(lsa, "rid")?.parse()?,
+                        })
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
 /// Decend an xml tree to find a specific node, following a path of node names.
 pub(self) fn decend_xml_tree<'a, 'b, const N: usize>(
     root: roxmltree::Node<'a, 'b>,
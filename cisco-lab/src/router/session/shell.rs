@@ -36,7 +36,7 @@ use tokio::{
 
 use crate::{
     router::{session::invert_config, ConvergenceMessage, ConvergenceState},
-    ssh::wait_prompt,
+    ssh::{wait_prompt, SshError},
     CiscoLabError,
 };
 
@@ -552,4 +552,10 @@ pub enum CiscoShellError {
     /// Synchronization error occurred
     #[error("Synchronization Error")]
     Synchronization,
+    /// Error while executing a command over SSH
+    #[error("SSH error: {0}")]
+    Ssh(#[from] SshError),
+    /// The command did not finish within the given time
+    #[error("Timeout while executing `{0}`")]
+    Timeout(String),
 }
@@ -42,8 +42,10 @@ mod session;
 pub(crate) mod traffic_capture;
 pub use cmd::{CmdError, CmdHandle};
 pub use exabgp::ExaBgpHandle;
-pub use session::ServerSession;
-pub use traffic_capture::{CaptureSample, TrafficCaptureError, TrafficCaptureHandle, TrafficFlow};
+pub use session::{LockStatus, ServerSession};
+pub use traffic_capture::{
+    CaptureSample, ProberRamp, ProberStep, TrafficCaptureError, TrafficCaptureHandle, TrafficFlow,
+};
 
 use crate::{config::CONFIG, ssh::SshSession, Active, CiscoLab, CiscoLabError, Inactive};
 
@@ -59,6 +61,25 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Inactive> {
             .map(|r| Ok((r, ExaBgpCfgGen::new(net, r)?)))
             .collect()
     }
+
+    /// Check whether the lab is currently free, without trying to obtain the lock. This function
+    /// connects to the server and reads the lock file (if it exists). Use this function to wait
+    /// until the lab is free before calling [`CiscoLab::connect`].
+    pub async fn lab_status(&self) -> Result<LockStatus, CiscoLabError> {
+        let s = SshSession::new(&CONFIG.server.ssh_name).await?;
+        Ok(LockStatus::read(&s).await?)
+    }
+
+    /// Remove a stale lock left behind by a crashed experiment. The lock is only removed if it is
+    /// held by `confirm_user`, or if `force` is set. Otherwise, this function returns
+    /// [`CiscoLabError::CannotObtainLock`]. Breaking a lock is logged as a warning, including the
+    /// user who broke the lock.
+    ///
+    /// **Warning**: Only use `force` if you are sure that nobody is using the lab!
+    pub async fn force_unlock(&self, confirm_user: &str, force: bool) -> Result<(), CiscoLabError> {
+        let s = SshSession::new(&CONFIG.server.ssh_name).await?;
+        session::force_unlock(&s, confirm_user, force).await
+    }
 }
 
 impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
@@ -114,12 +135,11 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
         Ok(c)
     }
 
-    /// Generate the python script to execute in exabgp
-    pub fn generate_exabgp_runner(&mut self) -> Result<String, CiscoLabError> {
-        let mut s = String::from(
-            "#!/usr/bin/env python3\nimport sys\nimport time\nfrom os.path import expanduser as full\n\n",
-        );
-
+    /// Compute the timeline of all commands sent by exabgp. For each point in time (sorted in
+    /// ascending order), this function returns the lines that the python runner (see
+    /// [`CiscoLab::generate_exabgp_runner`]) will write to exabgp once the control file reaches
+    /// that time.
+    pub fn exabgp_timeline(&mut self) -> Result<Vec<(Duration, Vec<String>)>, CiscoLabError> {
         let mut lines: BTreeMap<Duration, Vec<String>> = BTreeMap::new();
         self.external_routers
             .values()
@@ -128,6 +148,16 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
             .into_iter()
             .flatten()
             .for_each(|(l, time)| lines.entry(time).or_default().extend(l));
+        Ok(lines.into_iter().collect())
+    }
+
+    /// Generate the python script to execute in exabgp
+    pub fn generate_exabgp_runner(&mut self) -> Result<String, CiscoLabError> {
+        let mut s = String::from(
+            "#!/usr/bin/env python3\nimport sys\nimport time\nfrom os.path import expanduser as full\n\n",
+        );
+
+        let lines = self.exabgp_timeline()?;
 
         // write the function to wait until
         let c = CONFIG.server.exabgp_runner_control_filename.as_str();
@@ -203,6 +233,82 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
             .withdraw_route(self.net, &mut self.addressor, prefix)?;
         Ok(())
     }
+
+    /// Flap the eBGP session between the external `router` and its `neighbor`. All routes of
+    /// `router` are withdrawn from `neighbor` at the current time, no updates are sent to
+    /// `neighbor` for `down_for`, and all routes are announced again afterwards (see
+    /// [`ExaBgpCfgGen::flap_session`]). This will only change the python runner for exabgp that
+    /// are generated in the future.
+    pub fn flap_external_session(
+        &mut self,
+        router: RouterId,
+        neighbor: RouterId,
+        down_for: Duration,
+    ) -> Result<(), CiscoLabError> {
+        self.external_routers
+            .get_mut(&router)
+            .ok_or_else(|| NetworkError::DeviceNotFound(router))?
+            .flap_session(self.net, &mut self.addressor, neighbor, down_for)?;
+        Ok(())
+    }
+
+    /// Compute the flows for a traffic capture, from every prober interface towards at most
+    /// `max_destinations` destinations.
+    pub(crate) fn capture_flows(
+        &mut self,
+        max_destinations: usize,
+    ) -> Result<Vec<TrafficFlow>, CiscoLabError> {
+        if max_destinations < 2 {
+            return Err(CiscoLabError::InvalidNumberOfDestinations(max_destinations));
+        }
+
+        let mut prefixes: Vec<_> = self.get_prefix_ip_lookup()?.into_keys().collect();
+        prefixes.sort();
+        let selected_prefixes = choose_k(max_destinations, prefixes);
+
+        Ok(self
+            .prober_ifaces
+            .values()
+            .flat_map(|(_, mac, addr)| {
+                selected_prefixes.iter().map(move |dst_ip| TrafficFlow {
+                    src_mac: *mac,
+                    src_ip: *addr,
+                    dst_ip: *dst_ip,
+                })
+            })
+            .collect())
+    }
+
+    /// Compute the prefix lookup. In case of a preifx equivalence class, this function will return
+    /// the first prefix, the last, and one in between.
+    fn get_prefix_ip_lookup(&mut self) -> Result<HashMap<Ipv4Addr, P>, ExportError> {
+        let mut lookup = HashMap::new();
+        for p in self.net.get_known_prefixes() {
+            match self.addressor.prefix(*p)? {
+                bgpsim::export::MaybePec::Single(net) => {
+                    lookup.insert(
+                        net.hosts().next().ok_or(ExportError::NotEnoughAddresses)?,
+                        *p,
+                    );
+                }
+                bgpsim::export::MaybePec::Pec(_, mut networks) => {
+                    networks.sort_by_cached_key(|n| n.to_string());
+                    let n = networks.len();
+                    let networks = match n {
+                        0 | 1 | 2 => networks,
+                        _ => vec![networks[0], networks[n / 2], networks[n - 1]],
+                    };
+                    for net in networks {
+                        lookup.insert(
+                            net.hosts().next().ok_or(ExportError::NotEnoughAddresses)?,
+                            *p,
+                        );
+                    }
+                }
+            }
+        }
+        Ok(lookup)
+    }
 }
 
 impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
@@ -323,43 +429,47 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
     /// router and for each prefix, one such flow is created.
     ///
     /// If there are more than 5 prefixes, the capture will probe the first, 25% median, median,
-    /// 75% median and last prefix.
+    /// 75% median and last prefix. Use [`CiscoLab::start_capture_with`] to probe a different
+    /// number of destinations.
     pub async fn start_capture(
         &mut self,
         frequency: u64,
     ) -> Result<TrafficCaptureHandle, CiscoLabError> {
-        let mut prefixes: Vec<_> = self.get_prefix_ip_lookup()?.into_keys().collect();
-        prefixes.sort();
-
         // ensure that our prober is not being overloaded by choosing at most 5 destinations
-        let selected_prefixes = Self::choose_k(5, prefixes);
-
-        let flows: Vec<TrafficFlow> = self
-            .prober_ifaces
-            .values()
-            .flat_map(|(_, mac, addr)| {
-                selected_prefixes.iter().map(move |dst_ip| TrafficFlow {
-                    src_mac: *mac,
-                    src_ip: *addr,
-                    dst_ip: *dst_ip,
-                })
-            })
-            .collect();
+        self.start_capture_with(frequency, 5).await
+    }
 
+    /// Start a capture that will test all routers and at most `max_destinations` destinations in
+    /// the network. See [`CiscoLab::start_capture`] for more information. If there are more than
+    /// `max_destinations` destinations, the capture will probe the first, last, and
+    /// `max_destinations - 2` equidistant destinations. `max_destinations` must be at least 2.
+    pub async fn start_capture_with(
+        &mut self,
+        frequency: u64,
+        max_destinations: usize,
+    ) -> Result<TrafficCaptureHandle, CiscoLabError> {
+        let flows = self.capture_flows(max_destinations)?;
         let mut handle = TrafficCaptureHandle::new(self.state.server.0.clone(), &flows).await?;
         handle.start(frequency).await?;
         Ok(handle)
     }
 
-    /// Choose up to `k` elements from a `Vec<T>`. If there are more than `k` elements, the result
-    /// will contain the first, last, and `k-2` equidistant elements from the `Vec<T>`.
-    fn choose_k<T: Copy>(k: usize, xs: Vec<T>) -> Vec<T> {
-        let l = xs.len() - 1;
-        if l >= k {
-            (0..k).map(|i| xs[(i * l) / (k - 1)]).collect()
-        } else {
-            xs
-        }
+    /// Start a capture that will test all routers and at most 5 destinations in the network (see
+    /// [`CiscoLab::start_capture`]), while the prober linearly changes its frequency from
+    /// `start_hz` to `end_hz` within `duration`. After `duration`, the prober keeps sending with
+    /// `end_hz`. See [`ProberRamp::schedule`] for how the ramp is applied.
+    pub async fn start_capture_ramp(
+        &mut self,
+        start_hz: u64,
+        end_hz: u64,
+        duration: Duration,
+    ) -> Result<TrafficCaptureHandle, CiscoLabError> {
+        let flows = self.capture_flows(5)?;
+        let mut handle = TrafficCaptureHandle::new(self.state.server.0.clone(), &flows).await?;
+        handle
+            .start_ramp(ProberRamp::new(start_hz, end_hz, duration))
+            .await?;
+        Ok(handle)
     }
 
     /// Stop a packet capture and parse the results. The returned hashmap contains, for each
@@ -459,37 +569,6 @@ impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
         Ok(())
     }
 
-    /// Compute the prefix lookup. In case of a preifx equivalence class, this function will return
-    /// the first prefix, the last, and one in between.
-    fn get_prefix_ip_lookup(&mut self) -> Result<HashMap<Ipv4Addr, P>, ExportError> {
-        let mut lookup = HashMap::new();
-        for p in self.net.get_known_prefixes() {
-            match self.addressor.prefix(*p)? {
-                bgpsim::export::MaybePec::Single(net) => {
-                    lookup.insert(
-                        net.hosts().next().ok_or(ExportError::NotEnoughAddresses)?,
-                        *p,
-                    );
-                }
-                bgpsim::export::MaybePec::Pec(_, mut networks) => {
-                    networks.sort_by_cached_key(|n| n.to_string());
-                    let n = networks.len();
-                    let networks = match n {
-                        0 | 1 | 2 => networks,
-                        _ => vec![networks[0], networks[n / 2], networks[n - 1]],
-                    };
-                    for net in networks {
-                        lookup.insert(
-                            net.hosts().next().ok_or(ExportError::NotEnoughAddresses)?,
-                            *p,
-                        );
-                    }
-                }
-            }
-        }
-        Ok(lookup)
-    }
-
     /// compute the router IP address lookup
     fn get_external_router_mac_lookup(
         &mut self,
@@ -537,22 +616,7 @@ pub fn export_capture_to_csv<P: Prefix, Q>(
     root: impl AsRef<OsStr>,
     name: impl AsRef<str>,
 ) -> Result<PathBuf, std::io::Error> {
-    let cur_time = OffsetDateTime::now_local()
-        .unwrap_or_else(|_| OffsetDateTime::now_utc())
-        .format(
-            &format_description::parse("[year]-[month]-[day]_[hour]-[minute]-[second]").unwrap(),
-        )
-        .unwrap();
-    let mut path = PathBuf::from(root.as_ref());
-    path.push(format!("{}_{cur_time}", name.as_ref()));
-
-    let mut idx = None;
-    while path.exists() {
-        let i = idx.unwrap_or(0) + 1;
-        idx = Some(i);
-        path.pop();
-        path.push(format!("{}_{cur_time}_{i}", name.as_ref()));
-    }
+    let mut path = unique_path(root, name, "");
     std::fs::create_dir_all(&path)?;
 
     // write the measurement result to file
@@ -584,3 +648,311 @@ pub fn export_capture_to_csv<P: Prefix, Q>(
     }
     Ok(path)
 }
+
+/// Export the captured traffic into a single CSV file in long format.
+///
+/// This function will create the file `{root}/{name}_{timestamp}.csv`. Each row of that file
+/// describes a single captured packet, using the columns `src,prefix,addr,dst,send_time,recv_time,seq`.
+/// Here, `src` is the name of the router from where the packet originates, `prefix` is the prefix,
+/// `addr` is the destination IP address of the packet, and `dst` is the name of the external router
+/// to which the packet was forwarded.
+///
+/// This function returns the path to the file that was created.
+pub fn export_capture_to_csv_long<P: Prefix, Q>(
+    net: &Network<P, Q>,
+    capture: &Capture<P>,
+    root: impl AsRef<OsStr>,
+    name: impl AsRef<str>,
+) -> Result<PathBuf, std::io::Error> {
+    let path = unique_path(root, name, ".csv");
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::io::BufWriter::new(
+        std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(&path)?,
+    );
+    file.write_all(b"src,prefix,addr,dst,send_time,recv_time,seq\n")?;
+    for ((src, prefix, addr), data) in capture.iter().sorted_by_key(|(k, _)| *k) {
+        for (t_send, t_recv, ext, k) in data {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{}",
+                src.fmt(net),
+                prefix,
+                addr,
+                ext.fmt(net),
+                t_send,
+                t_recv,
+                k
+            )?;
+        }
+    }
+    file.flush()?;
+
+    Ok(path)
+}
+
+/// Export the captured traffic into a single Parquet file.
+///
+/// This function will create the file `{root}/{name}_{timestamp}.parquet`, containing the same
+/// columns as [`export_capture_to_csv_long`], i.e., `src`, `prefix`, `addr`, `dst` (all strings),
+/// `send_time`, `recv_time` (both `f64`), and `seq` (`u64`).
+///
+/// This function returns the path to the file that was created.
+#[cfg(feature = "parquet")]
+pub fn export_capture_to_parquet<P: Prefix, Q>(
+    net: &Network<P, Q>,
+    capture: &Capture<P>,
+    root: impl AsRef<OsStr>,
+    name: impl AsRef<str>,
+) -> Result<PathBuf, std::io::Error> {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{ArrayRef, Float64Array, StringArray, UInt64Array},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    };
+    use parquet::arrow::ArrowWriter;
+
+    fn to_io_err(e: impl std::error::Error + Send + Sync + 'static) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::Other, e)
+    }
+
+    let path = unique_path(root, name, ".parquet");
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut src_col = Vec::new();
+    let mut prefix_col = Vec::new();
+    let mut addr_col = Vec::new();
+    let mut dst_col = Vec::new();
+    let mut send_col = Vec::new();
+    let mut recv_col = Vec::new();
+    let mut seq_col = Vec::new();
+    for ((src, prefix, addr), data) in capture.iter().sorted_by_key(|(k, _)| *k) {
+        for (t_send, t_recv, ext, k) in data {
+            src_col.push(src.fmt(net).to_string());
+            prefix_col.push(prefix.to_string());
+            addr_col.push(addr.to_string());
+            dst_col.push(ext.fmt(net).to_string());
+            send_col.push(*t_send);
+            recv_col.push(*t_recv);
+            seq_col.push(*k);
+        }
+    }
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("src", DataType::Utf8, false),
+        Field::new("prefix", DataType::Utf8, false),
+        Field::new("addr", DataType::Utf8, false),
+        Field::new("dst", DataType::Utf8, false),
+        Field::new("send_time", DataType::Float64, false),
+        Field::new("recv_time", DataType::Float64, false),
+        Field::new("seq", DataType::UInt64, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(src_col)),
+        Arc::new(StringArray::from(prefix_col)),
+        Arc::new(StringArray::from(addr_col)),
+        Arc::new(StringArray::from(dst_col)),
+        Arc::new(Float64Array::from(send_col)),
+        Arc::new(Float64Array::from(recv_col)),
+        Arc::new(UInt64Array::from(seq_col)),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(to_io_err)?;
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .open(&path)?;
+    let mut writer = ArrowWriter::try_new(file, schema, None).map_err(to_io_err)?;
+    writer.write(&batch).map_err(to_io_err)?;
+    writer.close().map_err(to_io_err)?;
+
+    Ok(path)
+}
+
+/// Statistics of a single flow computed from a [`Capture`] using [`capture_statistics`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlowStats {
+    /// Number of packets sent. This is computed as the largest sequence number received plus 1.
+    pub sent: u64,
+    /// Number of packets received.
+    pub received: u64,
+    /// Ratio of packets that were lost, i.e., `1 - received / sent`.
+    pub loss: f64,
+    /// Number of packets that were received after a packet with a higher sequence number.
+    pub out_of_order: u64,
+    /// Minimum one-way delay (`recv_time - send_time`) in seconds.
+    pub min_delay: f64,
+    /// Maximum one-way delay (`recv_time - send_time`) in seconds.
+    pub max_delay: f64,
+    /// Mean one-way delay (`recv_time - send_time`) in seconds.
+    pub mean_delay: f64,
+}
+
+/// Compute the [`FlowStats`] (loss, reordering, and one-way delay) of each flow in the capture.
+/// Flows without any received packet are ignored.
+pub fn capture_statistics<P: Prefix>(
+    capture: &Capture<P>,
+) -> HashMap<(RouterId, P, Ipv4Addr), FlowStats> {
+    capture
+        .iter()
+        .filter(|(_, data)| !data.is_empty())
+        .map(|(flow, data)| {
+            let sent = data.iter().map(|(_, _, _, k)| *k).max().unwrap_or(0) + 1;
+            let received = data.len() as u64;
+
+            let mut out_of_order = 0;
+            let mut max_seq = None;
+            for (_, _, _, k) in data.iter().sorted_by(|a, b| a.1.total_cmp(&b.1)) {
+                if max_seq.map(|m| *k < m).unwrap_or(false) {
+                    out_of_order += 1;
+                }
+                max_seq = max_seq.max(Some(*k));
+            }
+
+            let delays = data.iter().map(|(t_send, t_recv, _, _)| t_recv - t_send);
+            let min_delay = delays.clone().fold(f64::INFINITY, f64::min);
+            let max_delay = delays.clone().fold(f64::NEG_INFINITY, f64::max);
+            let mean_delay = delays.sum::<f64>() / received as f64;
+
+            (
+                *flow,
+                FlowStats {
+                    sent,
+                    received,
+                    loss: 1.0 - received as f64 / sent as f64,
+                    out_of_order,
+                    min_delay,
+                    max_delay,
+                    mean_delay,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Compute the time windows during which no packets of a flow were delivered. For each flow, this
+/// function returns the list of all intervals `(start, end)` between two consecutively received
+/// packets (according to their `recv_time`) that are longer than `gap_threshold`.
+pub fn capture_blackhole_windows<P: Prefix>(
+    capture: &Capture<P>,
+    gap_threshold: Duration,
+) -> HashMap<(RouterId, P, Ipv4Addr), Vec<(f64, f64)>> {
+    let threshold = gap_threshold.as_secs_f64();
+    capture
+        .iter()
+        .map(|(flow, data)| {
+            let windows = data
+                .iter()
+                .map(|(_, t_recv, _, _)| *t_recv)
+                .sorted_by(f64::total_cmp)
+                .tuple_windows()
+                .filter(|(a, b)| b - a > threshold)
+                .collect();
+            (*flow, windows)
+        })
+        .collect()
+}
+
+/// Compute the egress timeline of each source and prefix. For each pair `(src, prefix)`, this
+/// function returns all packets as `(recv_time, egress)` pairs, sorted by `recv_time`, where
+/// `egress` is the external router which received the packet. Packets towards different
+/// destination addresses of the same prefix are merged into the same timeline.
+pub fn capture_egress_timeline<P: Prefix>(
+    capture: &Capture<P>,
+) -> HashMap<(RouterId, P), Vec<(f64, RouterId)>> {
+    let mut result: HashMap<(RouterId, P), Vec<(f64, RouterId)>> = HashMap::new();
+    for ((src, prefix, _), data) in capture {
+        result
+            .entry((*src, *prefix))
+            .or_default()
+            .extend(data.iter().map(|(_, t_recv, ext, _)| (*t_recv, *ext)));
+    }
+    result
+        .values_mut()
+        .for_each(|timeline| timeline.sort_by(|a, b| a.0.total_cmp(&b.0)));
+    result
+}
+
+/// Find the duplicate packets of each flow. For each flow, this function returns the sorted list
+/// of all sequence numbers that were received more than once (e.g., due to retransmissions or
+/// mirrored packets). Each duplicate sequence number is reported only once.
+pub fn capture_duplicates<P: Prefix>(
+    capture: &Capture<P>,
+) -> HashMap<(RouterId, P, Ipv4Addr), Vec<u64>> {
+    capture
+        .iter()
+        .map(|(flow, data)| {
+            let duplicates = data
+                .iter()
+                .map(|(_, _, _, k)| *k)
+                .counts()
+                .into_iter()
+                .filter(|(_, n)| *n > 1)
+                .map(|(k, _)| k)
+                .sorted()
+                .collect();
+            (*flow, duplicates)
+        })
+        .collect()
+}
+
+/// Align the clock of the collector to the clock of the prober. The `send_time` is measured by the
+/// prober, while the `recv_time` is measured by the collector, which may use a different clock.
+/// For each flow, this function estimates the clock offset as the smallest observed one-way delay
+/// (`recv_time - send_time`), assuming that the fastest packet had a negligible delay. It then
+/// shifts the `recv_time` of all packets of that flow by the estimated offset, such that the
+/// smallest one-way delay becomes zero.
+pub fn capture_align_clocks<P: Prefix>(capture: &mut Capture<P>) {
+    for data in capture.values_mut() {
+        let offset = data
+            .iter()
+            .map(|(t_send, t_recv, _, _)| t_recv - t_send)
+            .fold(f64::INFINITY, f64::min);
+        if offset.is_finite() {
+            data.iter_mut()
+                .for_each(|(_, t_recv, _, _)| *t_recv -= offset);
+        }
+    }
+}
+
+/// Choose up to `k` elements from a `Vec<T>`. If there are more than `k` elements, the result
+/// will contain the first, last, and `k-2` equidistant elements from the `Vec<T>`.
+fn choose_k<T: Copy>(k: usize, xs: Vec<T>) -> Vec<T> {
+    if xs.len() > k {
+        let l = xs.len() - 1;
+        (0..k).map(|i| xs[(i * l) / (k - 1)]).collect()
+    } else {
+        xs
+    }
+}
+
+/// Create a path `{root}/{name}_{timestamp}{ext}` that does not yet exist. If the path already
+/// exists, then an index is appended to the name.
+fn unique_path(root: impl AsRef<OsStr>, name: impl AsRef<str>, ext: &str) -> PathBuf {
+    let cur_time = OffsetDateTime::now_local()
+        .unwrap_or_else(|_| OffsetDateTime::now_utc())
+        .format(
+            &format_description::parse("[year]-[month]-[day]_[hour]-[minute]-[second]").unwrap(),
+        )
+        .unwrap();
+    let mut path = PathBuf::from(root.as_ref());
+    path.push(format!("{}_{cur_time}{ext}", name.as_ref()));
+
+    let mut idx = None;
+    while path.exists() {
+        let i = idx.unwrap_or(0) + 1;
+        idx = Some(i);
+        path.pop();
+        path.push(format!("{}_{cur_time}_{i}{ext}", name.as_ref()));
+    }
+    path
+}
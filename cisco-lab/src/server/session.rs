@@ -31,6 +31,90 @@ use super::ExaBgpHandle;
 
 const LOCK_FILE_PATH: &str = "/tmp/cisco-lab.lock";
 
+/// Status of the lock of the lab, see [`crate::CiscoLab::lab_status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockStatus {
+    /// Nobody holds the lock. The lab is free to use.
+    Free,
+    /// The lock is held by another user.
+    Held {
+        /// The user who holds the lock.
+        user: String,
+    },
+}
+
+impl LockStatus {
+    /// Get the lock status from the content of the lock file, or `None` if the lock file does not
+    /// exist.
+    pub(crate) fn from_lock_file(content: Option<String>) -> Self {
+        match content {
+            Some(user) => Self::Held {
+                user: user.trim().to_string(),
+            },
+            None => Self::Free,
+        }
+    }
+
+    /// Check whether the lock may be removed by someone who claims that it is held by
+    /// `confirm_user`. This is only the case if the lock is held by `confirm_user`, or if `force`
+    /// is set. A free lab cannot be unlocked.
+    pub(crate) fn may_unlock(&self, confirm_user: &str, force: bool) -> bool {
+        match self {
+            Self::Free => false,
+            Self::Held { user } => force || user == confirm_user,
+        }
+    }
+
+    /// Read the lock status from the server, without modifying the lock.
+    pub(crate) async fn read(s: &SshSession) -> Result<Self, SshError> {
+        let content = if s
+            .execute_cmd_status(&["test", "-e", LOCK_FILE_PATH])
+            .await?
+            .success()
+        {
+            Some(s.execute_cmd_stdout(&["cat", LOCK_FILE_PATH]).await?)
+        } else {
+            None
+        };
+        Ok(Self::from_lock_file(content))
+    }
+}
+
+/// Remove the lock file on the server, if it is held by `confirm_user` (or if `force` is set).
+pub(crate) async fn force_unlock(
+    s: &SshSession,
+    confirm_user: &str,
+    force: bool,
+) -> Result<(), CiscoLabError> {
+    let status = LockStatus::read(s).await?;
+    match &status {
+        LockStatus::Free => {
+            log::info!("[{}] The lab is not locked.", s.name());
+            Ok(())
+        }
+        LockStatus::Held { user } if status.may_unlock(confirm_user, force) => {
+            let whoami = s.execute_cmd_stdout(&["whoami"]).await?;
+            log::warn!(
+                "[{}] User {} breaks the lock held by {}!",
+                s.name(),
+                whoami.trim(),
+                user
+            );
+            s.execute_cmd(&["rm", "-f", LOCK_FILE_PATH]).await?;
+            Ok(())
+        }
+        LockStatus::Held { user } => {
+            log::error!(
+                "[{}] Refusing to break the lock held by {}, expected {}!",
+                s.name(),
+                user,
+                confirm_user
+            );
+            Err(CiscoLabError::CannotObtainLock(user.clone()))
+        }
+    }
+}
+
 /// An active session to the server. This instance is used to manage the server.
 pub struct ServerSession(pub(super) SshSession);
 
@@ -43,12 +127,8 @@ impl ServerSession {
         let s = SshSession::new(&CONFIG.server.ssh_name).await?;
 
         log::trace!("[{}] Obtaining the lock", s.name());
-        if s.execute_cmd_status(&["test", "-e", LOCK_FILE_PATH])
-            .await?
-            .success()
-        {
+        if let LockStatus::Held { user } = LockStatus::read(&s).await? {
             // the file exists!
-            let user = s.execute_cmd_stdout(&["cat", LOCK_FILE_PATH]).await?;
             log::error!(
                 "[{}] Cannot obtain the lock! User {} is already running experiments!",
                 s.name(),
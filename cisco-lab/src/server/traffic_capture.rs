@@ -18,9 +18,10 @@
 //! Module for generating a pcap-file containing all ping packets, replaying that ping packet, and
 //! capturing all these ping packets on the server.
 
-use std::{collections::HashMap, net::Ipv4Addr, process::Stdio};
+use std::{collections::HashMap, net::Ipv4Addr, process::Stdio, time::Duration};
 
 use hex::FromHex;
+use itertools::Itertools;
 use serde::Serialize;
 use thiserror::Error;
 use tokio::{
@@ -67,6 +68,12 @@ use crate::{
 /// source mac address, source IP address, destination IP address, and the counter) as a
 /// comma-separated line.
 ///
+/// With [`TrafficCaptureHandle::start_ramp`], the packet rate of the prober changes over time. As
+/// the prober sends at a fixed rate, it is restarted with a new rate at every step of the ramp
+/// (see [`ProberRamp`]). Every restart resets the counter of the prober, such that multiple packets
+/// of the same flow may share the same counter. Such packets are matched using their send time
+/// (see [`TrafficCaptureHandle::get_samples`]).
+///
 /// Once the processes have been started, we also start a `tokio` task that reads from `stdout` of
 /// `collector` and parses packets concurrently (and in parallel). This task as an associated kill
 /// `oneshot` channel, that we can use to kill the thread. Once killed, this task will return the
@@ -88,7 +95,7 @@ pub struct TrafficCaptureHandle {
         JoinHandle<
             Result<
                 (
-                    HashMap<(Ipv4Addr, Ipv4Addr, u64), f64>,
+                    Vec<((Ipv4Addr, Ipv4Addr, u64), f64)>,
                     Lines<BufReader<ChildStdout>>,
                 ),
                 TrafficCaptureError,
@@ -99,8 +106,9 @@ pub struct TrafficCaptureHandle {
     prober_stdout: Option<Lines<BufReader<ChildStdout>>>,
     /// kill channel for the prober reader.
     prober_reader_kill: Option<oneshot::Sender<()>>,
-    /// All sent packets by the prober
-    prober_samples: HashMap<(Ipv4Addr, Ipv4Addr, u64), f64>,
+    /// Send times of all packets sent by the prober. The prober restarts its counter at each step
+    /// of a ramp, so there may be multiple packets with the same counter.
+    prober_samples: HashMap<(Ipv4Addr, Ipv4Addr, u64), Vec<f64>>,
     /// Child process for `collector` (if still running)
     collector_child: Option<Child>,
     /// Thread that reads the collector output concurrently
@@ -164,8 +172,24 @@ impl TrafficCaptureHandle {
     ///
     /// The `frequency` specifies the number of ping packets per flow per second that should be
     /// sent. This will set the `--pps` options in tcpreplay appropriately. For instance, if there
-    /// are 10 flows, and `frequency` is set to 10, then `--pps=100` will be set.
+    /// are 10 flows, and `frequency` is set to 10, then `--pps=100` will be set. The `frequency`
+    /// must be positive.
     pub async fn start(&mut self, frequency: u64) -> Result<(), TrafficCaptureError> {
+        self.start_schedule(&[(None, frequency)]).await
+    }
+
+    /// Start both the prober and the collector, such that the prober linearly changes its rate
+    /// from `ramp.start_hz` to `ramp.end_hz` ping packets per flow per second within
+    /// `ramp.duration`. After that, the prober keeps sending at `ramp.end_hz`. The ramp is
+    /// approximated by steps of constant rate (see [`ProberRamp::schedule`]). If the capture is
+    /// already running, this function will do nothing. Both rates must be positive.
+    pub async fn start_ramp(&mut self, ramp: ProberRamp) -> Result<(), TrafficCaptureError> {
+        self.start_schedule(&ramp.schedule()).await
+    }
+
+    /// Start both the prober and the collector, where the prober follows the given `schedule` (see
+    /// [`prober_command`]).
+    async fn start_schedule(&mut self, schedule: &[ProberStep]) -> Result<(), TrafficCaptureError> {
         if self.collector_child.is_some()
             || self.collector_reader.is_some()
             || self.collector_reader_kill.is_some()
@@ -174,6 +198,9 @@ impl TrafficCaptureHandle {
         {
             return Ok(());
         }
+        if schedule.iter().any(|(_, frequency)| *frequency == 0) {
+            return Err(TrafficCaptureError::ZeroFrequency);
+        }
 
         let cmd = format!("sudo collector -b 256 {}", &CONFIG.server.exabgp_iface);
 
@@ -189,12 +216,7 @@ impl TrafficCaptureHandle {
 
         let collector_stdout = BufReader::new(collector_child.stdout.take().unwrap()).lines();
 
-        // compute packets per second
-        let cmd = format!(
-            "sudo prober -f {} {}",
-            1_000_000 / frequency,
-            &CONFIG.server.prober_config_filename
-        );
+        let cmd = prober_command(schedule, &CONFIG.server.prober_config_filename);
 
         log::trace!("[{}] {}", self.session.name(), cmd);
         let mut prober_child = self
@@ -319,11 +341,13 @@ impl TrafficCaptureHandle {
     /// `self.samples`.
     fn update_samples(&mut self) {
         let collector_samples = std::mem::take(&mut self.collector_samples);
+        let offset = clock_offset(&collector_samples, &self.prober_samples);
         self.samples
             .extend(collector_samples.into_iter().filter_map(|s| {
+                let send_times = self.prober_samples.get(&(s.src_ip, s.dst_ip, s.counter))?;
                 Some(CaptureSample {
                     time: s.time,
-                    send_time: *self.prober_samples.get(&(s.src_ip, s.dst_ip, s.counter))?,
+                    send_time: match_send_time(send_times, s.time, offset)?,
                     mac: s.mac,
                     src_ip: s.src_ip,
                     dst_ip: s.dst_ip,
@@ -346,7 +370,7 @@ impl TrafficCaptureHandle {
             self.prober_reader_kill = Some(kill_tx);
 
             let prober_reader = tokio::task::spawn(async move {
-                let mut result = HashMap::new();
+                let mut result = Vec::new();
                 'reader_loop: loop {
                     select! {
                         biased;
@@ -355,7 +379,7 @@ impl TrafficCaptureHandle {
                             Err(e) => break 'reader_loop Err(TrafficCaptureError::Io(e)),
                             Ok(None) => break 'reader_loop Ok((result, reader)),
                             Ok(Some(l)) => if let Some(sample) = ProberSample::from_line(l.trim()) {
-                                result.insert((sample.src_ip, sample.dst_ip, sample.counter), sample.time);
+                                result.push(((sample.src_ip, sample.dst_ip, sample.counter), sample.time));
                             } else {
                                 log::trace!("Cannot parse line: {l}");
                             }
@@ -413,7 +437,9 @@ impl TrafficCaptureHandle {
                 let _ = kill_tx.send(());
                 // wait for the thread to finish
                 let (samples, prober_stdout) = reader_job.await??;
-                self.prober_samples.extend(samples);
+                for (key, send_time) in samples {
+                    self.prober_samples.entry(key).or_default().push(send_time);
+                }
                 self.prober_stdout = Some(prober_stdout);
             }
         }
@@ -444,6 +470,114 @@ impl TrafficCaptureHandle {
     }
 }
 
+/// A single step of the prober schedule: the prober sends `frequency` ping packets per flow per
+/// second (the second element) for the given duration (the first element). A step without
+/// duration lasts until the capture is stopped.
+pub type ProberStep = (Option<Duration>, u64);
+
+/// Duration of a single step of a [`ProberRamp`].
+const RAMP_STEP: Duration = Duration::from_secs(1);
+
+/// Linear ramp of the prober's packet rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProberRamp {
+    /// Initial rate, in packets per flow per second.
+    pub start_hz: u64,
+    /// Final rate, in packets per flow per second.
+    pub end_hz: u64,
+    /// Time to go from `start_hz` to `end_hz`.
+    pub duration: Duration,
+}
+
+impl ProberRamp {
+    /// Create a new ramp from `start_hz` to `end_hz` within `duration`.
+    pub fn new(start_hz: u64, end_hz: u64, duration: Duration) -> Self {
+        Self {
+            start_hz,
+            end_hz,
+            duration,
+        }
+    }
+
+    /// Compute the schedule of the prober. The ramp is split into steps of one second (the last
+    /// step may be shorter), and the rate of each step is interpolated linearly between `start_hz`
+    /// and `end_hz` at the beginning of that step. The last step sends at `end_hz` until the
+    /// capture is stopped.
+    pub fn schedule(&self) -> Vec<ProberStep> {
+        let num_steps = (self.duration.as_nanos() as f64 / RAMP_STEP.as_nanos() as f64).ceil();
+        let start = self.start_hz as f64;
+        let delta = self.end_hz as f64 - start;
+        let mut schedule: Vec<ProberStep> = (0..num_steps as u32)
+            .map(|i| {
+                let length = RAMP_STEP.min(self.duration - RAMP_STEP * i);
+                let rate =
+                    start + delta * (RAMP_STEP * i).as_secs_f64() / self.duration.as_secs_f64();
+                (Some(length), rate.round() as u64)
+            })
+            .collect();
+        schedule.push((None, self.end_hz));
+        schedule
+    }
+}
+
+/// Generate the command that runs the prober according to `schedule`, using the prober config
+/// stored at `config`. Each step runs a new prober process with the interval between two packets
+/// of each flow (in microseconds) computed from the frequency of that step. Steps with a duration
+/// are terminated using `timeout`. All frequencies must be positive.
+pub(crate) fn prober_command(schedule: &[ProberStep], config: &str) -> String {
+    schedule
+        .iter()
+        .map(|(duration, frequency)| match duration {
+            Some(d) => format!(
+                "sudo timeout {} prober -f {} {config}",
+                d.as_secs_f64(),
+                1_000_000 / frequency
+            ),
+            None => format!("sudo prober -f {} {config}", 1_000_000 / frequency),
+        })
+        .join("; ")
+}
+
+/// Estimate the clock offset between the collector and the prober as the smallest one-way delay
+/// of all packets whose counter is unique. Returns `None` if there is no such packet.
+fn clock_offset(
+    collector_samples: &[CollectorSample],
+    prober_samples: &HashMap<(Ipv4Addr, Ipv4Addr, u64), Vec<f64>>,
+) -> Option<f64> {
+    collector_samples
+        .iter()
+        .filter_map(|s| {
+            match prober_samples
+                .get(&(s.src_ip, s.dst_ip, s.counter))?
+                .as_slice()
+            {
+                [send_time] => Some(s.time - send_time),
+                _ => None,
+            }
+        })
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+/// Find the send time of a packet received at `recv_time`, given all `send_times` of packets with
+/// the same flow and counter. If there are multiple candidates, pick the one closest to the
+/// receive time, corrected by the clock `offset` (see [`clock_offset`]). Without an offset, pick
+/// the latest candidate.
+pub(crate) fn match_send_time(
+    send_times: &[f64],
+    recv_time: f64,
+    offset: Option<f64>,
+) -> Option<f64> {
+    match (send_times, offset) {
+        ([send_time], _) => Some(*send_time),
+        (_, Some(offset)) => send_times.iter().copied().min_by(|a, b| {
+            (recv_time - offset - a)
+                .abs()
+                .total_cmp(&(recv_time - offset - b).abs())
+        }),
+        (_, None) => send_times.last().copied(),
+    }
+}
+
 /// Describing a single traffic flow to monitor.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct TrafficFlow {
@@ -569,4 +703,7 @@ pub enum TrafficCaptureError {
     /// Cannot join a parallel job
     #[error("Cannot join thread: {0}")]
     Join(#[from] tokio::task::JoinError),
+    /// The prober cannot send packets with a frequency of zero.
+    #[error("The frequency of the prober must be positive!")]
+    ZeroFrequency,
 }
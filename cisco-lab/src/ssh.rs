@@ -19,6 +19,7 @@
 
 use std::{
     ffi::{OsStr, OsString},
+    future::Future,
     io::ErrorKind,
     process::{Command as StdCommand, ExitStatus, Output, Stdio},
     str::FromStr,
@@ -31,7 +32,8 @@ use thiserror::Error;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     process::{ChildStdout, Command},
-    time::timeout,
+    task::JoinHandle,
+    time::{interval_at, timeout, Instant, MissedTickBehavior},
 };
 
 pub const EMPTY: &[&str] = &[];
@@ -461,6 +463,16 @@ impl SshSession {
         Ok(())
     }
 
+    /// Spawn a task that keeps the session alive by executing `true` on the remote host every
+    /// `interval` (see [`spawn_keepalive`]). The task stops once the returned handle is dropped.
+    pub fn keepalive(&self, interval: Duration) -> KeepaliveHandle {
+        let session = self.clone();
+        spawn_keepalive(self.name(), interval, move || {
+            let session = session.clone();
+            async move { session.execute_cmd(&["true"]).await.map(|_| ()) }
+        })
+    }
+
     /// Create a raw `ssh` command with the following attributes set:
     /// - `oControlMaster=auto`
     /// - `oControlPath=/tmp/.ssh-%r@%h:%p`
@@ -493,6 +505,47 @@ impl SshSession {
         cmd
     }
 }
+
+/// Handle to a keepalive task created by [`spawn_keepalive`]. The task is aborted once the handle
+/// is dropped.
+#[derive(Debug)]
+pub struct KeepaliveHandle(JoinHandle<()>);
+
+impl Drop for KeepaliveHandle {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Spawn a task that calls `send` every `interval` to send a keepalive message (a command without
+/// any effect) to the host `name`. The first message is sent after `interval` has elapsed. Errors
+/// while sending a keepalive message are logged, but do not stop the task. The task runs until the
+/// returned handle is dropped.
+///
+/// This function panics if `interval` is zero.
+pub fn spawn_keepalive<F, Fut>(
+    name: impl Into<String>,
+    interval: Duration,
+    send: F,
+) -> KeepaliveHandle
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), SshError>> + Send,
+{
+    let name = name.into();
+    KeepaliveHandle(tokio::task::spawn(async move {
+        let mut ticker = interval_at(Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            log::trace!("[{name}] send keepalive");
+            if let Err(e) = send().await {
+                log::warn!("[{name}] Cannot send keepalive: {e}");
+            }
+        }
+    }))
+}
+
 /// Wait on the stdout until we get the next prompt. If the timeout triggers before we read the
 /// prompt, return `None`.
 pub(crate) async fn wait_prompt(
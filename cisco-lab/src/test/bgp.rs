@@ -21,7 +21,7 @@ use bgpsim::types::AsId;
 use maplit::{btreemap, btreeset};
 use pretty_assertions::assert_eq;
 
-use crate::router::{BgpNeighbor, BgpPathType, BgpRoute};
+use crate::router::{BgpAttrs, BgpNeighbor, BgpPathType, BgpRoute, ExpectedAttrs};
 
 #[test]
 fn neighbors_table() {
@@ -243,3 +243,53 @@ Flags: (0x080002) (high32 00000000) on xmit-list, is not in urib
 
     Ok(())
 }
+
+#[test]
+fn bgp_attributes() {
+    let doc = include_str!("files/show_ip_bgp_prefix.json");
+    let net = "100.0.0.0/24".parse().unwrap();
+    let parsed = BgpAttrs::selected_from_json(doc).unwrap();
+    assert_eq!(parsed.len(), 1);
+    let acquired = &parsed[&net];
+    assert_eq!(
+        acquired,
+        &BgpAttrs {
+            next_hop: "1.0.1.1".parse().unwrap(),
+            local_pref: Some(100),
+            communities: btreeset! {(AsId(65535), 10), (AsId(65535), 11)},
+        }
+    );
+
+    let expected = ExpectedAttrs {
+        next_hop: Some("1.0.1.1".parse().unwrap()),
+        local_pref: Some(100),
+        communities: Some(btreeset! {(AsId(65535), 10), (AsId(65535), 11)}),
+    };
+    assert!(expected.matches(Some(acquired)));
+
+    // attributes that are not given are not checked
+    let expected_nh_only = ExpectedAttrs {
+        next_hop: Some("1.0.1.1".parse().unwrap()),
+        ..Default::default()
+    };
+    assert!(expected_nh_only.matches(Some(acquired)));
+
+    // mismatching local-pref
+    let expected_lp = ExpectedAttrs {
+        local_pref: Some(200),
+        ..expected.clone()
+    };
+    assert!(!expected_lp.matches(Some(acquired)));
+
+    // mismatching communities
+    let expected_comm = ExpectedAttrs {
+        communities: Some(btreeset! {(AsId(65535), 10)}),
+        ..expected.clone()
+    };
+    assert!(!expected_comm.matches(Some(acquired)));
+
+    // missing routes
+    assert!(!expected.matches(None));
+    assert!(ExpectedAttrs::default().matches(None));
+    assert!(!ExpectedAttrs::default().matches(Some(acquired)));
+}
@@ -0,0 +1,231 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::{net::Ipv4Addr, time::Duration};
+
+use bgpsim::prelude::*;
+
+use crate::server::{
+    capture_align_clocks, capture_blackhole_windows, capture_duplicates, capture_egress_timeline,
+    capture_statistics, export_capture_to_csv_long, Capture,
+};
+
+fn test_net() -> (
+    Network<SimplePrefix, BasicEventQueue<SimplePrefix>>,
+    RouterId,
+    RouterId,
+) {
+    let mut net = Network::default();
+    let r = net.add_router("r0");
+    let e = net.add_external_router("e0", AsId(100));
+    net.add_link(r, e);
+    (net, r, e)
+}
+
+fn test_capture(r: RouterId, e: RouterId) -> Capture<SimplePrefix> {
+    let p = SimplePrefix::from(0);
+    let mut capture: Capture<SimplePrefix> = Default::default();
+    capture.insert(
+        (r, p, Ipv4Addr::new(100, 0, 0, 1)),
+        vec![(0.0, 0.1, e, 0), (0.1, 0.2, e, 1), (0.2, 0.3, e, 2)],
+    );
+    capture.insert(
+        (r, p, Ipv4Addr::new(100, 0, 0, 2)),
+        vec![(0.0, 0.1, e, 0), (0.1, 0.2, e, 1)],
+    );
+    capture
+}
+
+#[test]
+fn csv_long_format() {
+    let (net, r, e) = test_net();
+    let capture = test_capture(r, e);
+    let num_samples: usize = capture.values().map(|x| x.len()).sum();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = export_capture_to_csv_long(&net, &capture, dir.path(), "test").unwrap();
+    let content = std::fs::read_to_string(path).unwrap();
+    let mut lines = content.lines();
+
+    assert_eq!(
+        lines.next(),
+        Some("src,prefix,addr,dst,send_time,recv_time,seq")
+    );
+    assert_eq!(lines.count(), num_samples);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_roundtrip() {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    use crate::server::export_capture_to_parquet;
+
+    let (net, r, e) = test_net();
+    let capture = test_capture(r, e);
+    let num_samples: usize = capture.values().map(|x| x.len()).sum();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = export_capture_to_parquet(&net, &capture, dir.path(), "test").unwrap();
+    let reader = SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap();
+
+    assert_eq!(
+        reader.metadata().file_metadata().num_rows(),
+        num_samples as i64
+    );
+}
+
+#[test]
+fn statistics() {
+    let (_, r, e) = test_net();
+    let p = SimplePrefix::from(0);
+    let flow = (r, p, Ipv4Addr::new(100, 0, 0, 1));
+    let mut capture: Capture<SimplePrefix> = Default::default();
+    // packet 4 is lost, and packet 2 arrives after packet 3.
+    capture.insert(
+        flow,
+        vec![
+            (0.0, 0.1, e, 0),
+            (0.1, 0.2, e, 1),
+            (0.3, 0.35, e, 3),
+            (0.2, 0.4, e, 2),
+            (0.5, 0.6, e, 5),
+        ],
+    );
+
+    let stats = capture_statistics(&capture);
+    assert_eq!(stats.len(), 1);
+    let s = stats[&flow];
+    assert_eq!(s.sent, 6);
+    assert_eq!(s.received, 5);
+    assert!((s.loss - 1.0 / 6.0).abs() < 1e-9);
+    assert_eq!(s.out_of_order, 1);
+    assert!((s.min_delay - 0.05).abs() < 1e-9);
+    assert!((s.max_delay - 0.2).abs() < 1e-9);
+    assert!((s.mean_delay - 0.55 / 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn blackhole_windows() {
+    let (_, r, e) = test_net();
+    let p = SimplePrefix::from(0);
+    let flow = (r, p, Ipv4Addr::new(100, 0, 0, 1));
+    let mut capture: Capture<SimplePrefix> = Default::default();
+    // one packet every 10ms, except for a gap of 200ms between packet 10 and 11.
+    capture.insert(
+        flow,
+        (0..20)
+            .map(|k| {
+                let t = k as f64 * 0.01 + if k > 10 { 0.19 } else { 0.0 };
+                (t, t + 0.001, e, k)
+            })
+            .collect(),
+    );
+
+    let windows = capture_blackhole_windows(&capture, Duration::from_millis(50));
+    assert_eq!(windows.len(), 1);
+    let w = &windows[&flow];
+    assert_eq!(w.len(), 1);
+    let (start, end) = w[0];
+    assert!((start - 0.101).abs() < 1e-9);
+    assert!((end - start - 0.2).abs() < 1e-9);
+}
+
+#[test]
+fn egress_timeline() {
+    let (mut net, r, e1) = test_net();
+    let e2 = net.add_external_router("e1", AsId(200));
+    net.add_link(r, e2);
+    let p = SimplePrefix::from(0);
+    let mut capture: Capture<SimplePrefix> = Default::default();
+    // traffic moves from e1 to e2 at time 0.25
+    capture.insert(
+        (r, p, Ipv4Addr::new(100, 0, 0, 1)),
+        vec![(0.0, 0.1, e1, 0), (0.2, 0.3, e2, 2)],
+    );
+    capture.insert(
+        (r, p, Ipv4Addr::new(100, 0, 0, 2)),
+        vec![(0.1, 0.2, e1, 1), (0.3, 0.4, e2, 3)],
+    );
+
+    let timeline = capture_egress_timeline(&capture);
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(
+        timeline[&(r, p)],
+        vec![(0.1, e1), (0.2, e1), (0.3, e2), (0.4, e2)]
+    );
+}
+
+#[test]
+fn duplicates() {
+    let (_, r, e) = test_net();
+    let p = SimplePrefix::from(0);
+    let flow_a = (r, p, Ipv4Addr::new(100, 0, 0, 1));
+    let flow_b = (r, p, Ipv4Addr::new(100, 0, 0, 2));
+    let mut capture: Capture<SimplePrefix> = Default::default();
+    // packet 1 is received three times, and packet 3 twice.
+    capture.insert(
+        flow_a,
+        vec![
+            (0.0, 0.1, e, 0),
+            (0.1, 0.2, e, 1),
+            (0.3, 0.35, e, 3),
+            (0.1, 0.25, e, 1),
+            (0.2, 0.3, e, 2),
+            (0.3, 0.4, e, 3),
+            (0.1, 0.45, e, 1),
+        ],
+    );
+    capture.insert(flow_b, vec![(0.0, 0.1, e, 0), (0.1, 0.2, e, 1)]);
+
+    let duplicates = capture_duplicates(&capture);
+    assert_eq!(duplicates.len(), 2);
+    assert_eq!(duplicates[&flow_a], vec![1, 3]);
+    assert!(duplicates[&flow_b].is_empty());
+}
+
+#[test]
+fn align_clocks() {
+    let (_, r, e) = test_net();
+    let p = SimplePrefix::from(0);
+    let flow_a = (r, p, Ipv4Addr::new(100, 0, 0, 1));
+    let flow_b = (r, p, Ipv4Addr::new(100, 0, 0, 2));
+    let delays = [0.002, 0.001, 0.005, 0.001, 0.003];
+    let mut capture: Capture<SimplePrefix> = Default::default();
+    // the collector clock is 2.5s behind for flow a, and 1.5s ahead for flow b.
+    for (flow, offset) in [(flow_a, -2.5), (flow_b, 1.5)] {
+        capture.insert(
+            flow,
+            delays
+                .iter()
+                .enumerate()
+                .map(|(k, d)| {
+                    let t = k as f64 * 0.01;
+                    (t, t + d + offset, e, k as u64)
+                })
+                .collect(),
+        );
+    }
+
+    capture_align_clocks(&mut capture);
+
+    for flow in [flow_a, flow_b] {
+        for ((t_send, t_recv, _, _), d) in capture[&flow].iter().zip(delays) {
+            assert!((t_recv - t_send - (d - 0.001)).abs() < 1e-9);
+        }
+    }
+}
@@ -24,7 +24,7 @@ mod t {
     use itertools::Itertools;
     use pretty_assertions::assert_eq;
 
-    use crate::CiscoLab;
+    use crate::{CiscoLab, CiscoLabError};
 
     fn fix_addressor<P: Prefix, Q>(addressor: &mut DefaultAddressor<'_, P, Q>) {
         for a in 0..4 {
@@ -72,6 +72,48 @@ mod t {
         assert_eq!(&cfg, include_str!("files/test_net_router_2.conf"));
     }
 
+    #[test]
+    fn mac_for_router_iface<P: Prefix>() {
+        let net = test_net::<P>();
+        let mut lab = CiscoLab::new(&net).unwrap();
+        // interfaces are not yet assigned.
+        assert_eq!(lab.mac_for_router_iface(0.into(), 1.into()), None);
+        // the MAC addresses must match those in `test_net_router_0.conf`.
+        let _ = lab.generate_router_config(0.into()).unwrap();
+        assert_eq!(
+            lab.mac_for_router_iface(0.into(), 1.into()),
+            Some([0xde, 0xad, 0x00, 0x6f, 0x04, 0x01])
+        );
+        assert_eq!(
+            lab.mac_for_router_iface(0.into(), 4.into()),
+            Some([0xde, 0xad, 0x00, 0x6f, 0x04, 0x04])
+        );
+        // router 0 is not connected to router 5, and router 4 is an external router.
+        assert_eq!(lab.mac_for_router_iface(0.into(), 5.into()), None);
+        assert_eq!(lab.mac_for_router_iface(4.into(), 0.into()), None);
+    }
+
+    #[test]
+    fn neighbor_for_router_iface<P: Prefix>() {
+        let net = test_net::<P>();
+        let mut lab = CiscoLab::new(&net).unwrap();
+        // interfaces are not yet assigned.
+        assert_eq!(lab.neighbor_for_router_iface(0.into(), "Ethernet4/1"), None);
+        // the interface names must match those in `test_net_router_0.conf`.
+        let _ = lab.generate_router_config(0.into()).unwrap();
+        assert_eq!(
+            lab.neighbor_for_router_iface(0.into(), "Ethernet4/1"),
+            Some(1.into())
+        );
+        assert_eq!(
+            lab.neighbor_for_router_iface(0.into(), "Ethernet4/4"),
+            Some(4.into())
+        );
+        // the prober interface is not connected to any router.
+        assert_eq!(lab.neighbor_for_router_iface(0.into(), "Ethernet4/5"), None);
+        assert_eq!(lab.neighbor_for_router_iface(0.into(), "Ethernet9/9"), None);
+    }
+
     #[test]
     fn all_router_configs<P: Prefix>() {
         let net = test_net::<P>();
@@ -106,6 +148,29 @@ mod t {
         assert_eq!(script, include_str!("files/test_net_exabgp_withdraw.py"));
     }
 
+    #[test]
+    fn exabgp_timeline<P: Prefix>() {
+        let net = test_net::<P>();
+        let mut lab = CiscoLab::new(&net).unwrap();
+        lab.step_external_time();
+        let route = BgpRoute::new(4.into(), P::from(1), [AsId(4), AsId(100)], None, None);
+        lab.advertise_route(4.into(), &route).unwrap();
+        lab.step_external_time();
+        let route = BgpRoute::new(5.into(), P::from(2), [AsId(5), AsId(200)], None, None);
+        lab.advertise_route(5.into(), &route).unwrap();
+
+        let timeline = lab.exabgp_timeline().unwrap();
+        assert_eq!(
+            timeline.iter().map(|(t, _)| t.as_secs()).collect_vec(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(timeline[0].1.len(), 2);
+        assert_eq!(timeline[1].1.len(), 1);
+        assert!(timeline[1].1[0].contains("neighbor 1.192.0.1 announce route"));
+        assert_eq!(timeline[2].1.len(), 1);
+        assert!(timeline[2].1[0].contains("neighbor 1.192.0.5 announce route"));
+    }
+
     #[test]
     fn exabgp_netplan_config<P: Prefix>() {
         let net = test_net::<P>();
@@ -139,9 +204,164 @@ mod t {
         assert_eq!(script_mod, include_str!("files/controller.py"));
     }
 
+    #[test]
+    fn tofino_delay_rules<P: Prefix>() {
+        let net = test_net::<P>();
+        let mut lab = CiscoLab::new(&net).unwrap();
+        fix_addressor(lab.addressor_mut());
+        let _ = lab.generate_router_config_all().unwrap();
+        for a in 0..4u32 {
+            for b in 0..4u32 {
+                if a != b {
+                    lab.set_link_delay(a.into(), b.into(), 1000 * (a + 1));
+                }
+            }
+        }
+        let rules = lab.generate_tofino_delay_rules_with(&[10, 11]).unwrap();
+        assert_eq!(rules.lines().count(), 12);
+        for a in 0..4u32 {
+            let delay = format!("'delay': {},", 1000 * (a + 1));
+            assert_eq!(rules.lines().filter(|l| l.contains(&delay)).count(), 3);
+        }
+        for port in [10, 11] {
+            let port = format!("'delay_port': {port},");
+            assert_eq!(rules.lines().filter(|l| l.contains(&port)).count(), 6);
+        }
+    }
+
+    #[test]
+    fn tofino_delay_rules_no_delayer_port<P: Prefix>() {
+        let net = test_net::<P>();
+        let mut lab = CiscoLab::new(&net).unwrap();
+        fix_addressor(lab.addressor_mut());
+        let _ = lab.generate_router_config_all().unwrap();
+        assert_eq!(lab.generate_tofino_delay_rules_with(&[]).unwrap(), "");
+        lab.set_link_delay(0.into(), 1.into(), 1000);
+        assert!(matches!(
+            lab.generate_tofino_delay_rules_with(&[]),
+            Err(CiscoLabError::NoDelayerPorts)
+        ));
+    }
+
+    #[test]
+    fn capture_flows_max_destinations<P: Prefix>() {
+        let mut net = Network::build_complete_graph(BasicEventQueue::<P>::new(), 4);
+        net.build_external_routers(|_, _| vec![0.into(), 1.into()], ())
+            .unwrap();
+        net.build_link_weights(constant_link_weight, 10.0).unwrap();
+        net.build_ebgp_sessions().unwrap();
+        net.build_ibgp_full_mesh().unwrap();
+        for p in 0..8 {
+            net.build_advertisements(P::from(p), equal_preferences, 2)
+                .unwrap();
+        }
+        // `SinglePrefix` only knows a single prefix.
+        let num_prefixes = net.get_known_prefixes().count();
+
+        let mut lab = CiscoLab::new(&net).unwrap();
+        // generating the router configuration will compute the prober interfaces
+        let _ = lab.generate_router_config_all().unwrap();
+        let num_probers = lab.get_prober_ifaces().len();
+
+        for k in [2, 3, 5, 8] {
+            let flows = lab.capture_flows(k).unwrap();
+            let num_dst = k.min(num_prefixes);
+            assert_eq!(flows.iter().map(|f| f.dst_ip).unique().count(), num_dst);
+            assert_eq!(flows.len(), num_dst * num_probers);
+        }
+
+        // more destinations than available only yields the available ones.
+        let flows = lab.capture_flows(20).unwrap();
+        assert_eq!(
+            flows.iter().map(|f| f.dst_ip).unique().count(),
+            num_prefixes
+        );
+
+        assert!(matches!(
+            lab.capture_flows(1),
+            Err(CiscoLabError::InvalidNumberOfDestinations(1))
+        ));
+    }
+
     #[instantiate_tests(<SinglePrefix>)]
     mod single {}
 
     #[instantiate_tests(<SimplePrefix>)]
     mod simple {}
 }
+
+/// Write a lab configuration into `dir`, with one VDC for each entry in `num_ifaces` that has the
+/// given number of interfaces.
+fn write_vdcs(dir: &std::path::Path, num_ifaces: &[usize]) {
+    use std::fmt::Write;
+
+    let mut routers = String::from("vdcs = [\n");
+    let mut port = 0;
+    for (i, n) in num_ifaces.iter().enumerate() {
+        let name = format!("lab-vdc{i}");
+        writeln!(routers, "  \"{name}\",").unwrap();
+        let mut vdc =
+            format!("ssh_name = \"{name}\"\nmgnt_addr = \"172.16.100.{i}\"\nifaces = [\n");
+        for j in 0..*n {
+            writeln!(
+                vdc,
+                "  [\"Ethernet4/{}\", 0xdead0000{:02x}{:02x}, \"{}/{}\", {}],",
+                j + 1,
+                i,
+                j,
+                port / 4 + 1,
+                port % 4,
+                port
+            )
+            .unwrap();
+            port += 1;
+        }
+        vdc.push_str("]\n");
+        std::fs::write(dir.join(format!("{name}.toml")), vdc).unwrap();
+    }
+    routers.push_str("]\n\nrouters = []\n");
+    std::fs::write(dir.join("routers.toml"), routers).unwrap();
+}
+
+#[test]
+fn map_sixteen_vdcs() {
+    use bgpsim::prelude::*;
+
+    use crate::{
+        config::read_vdcs,
+        router::{map_internal_routers, GreedyMapping},
+    };
+
+    // write a lab configuration with 16 VDCs, each with 4 interfaces
+    let dir = tempfile::tempdir().unwrap();
+    write_vdcs(dir.path(), &[4; 16]);
+    let vdcs = read_vdcs(dir.path().to_str().unwrap());
+    assert_eq!(vdcs.len(), 16);
+
+    // build a ring of 16 routers
+    let mut net: Network<SimplePrefix, _> = Network::default();
+    let rs = (0..16)
+        .map(|i| net.add_router(format!("r{i}")))
+        .collect::<Vec<_>>();
+    for i in 0..16 {
+        net.add_link(rs[i], rs[(i + 1) % 16]);
+    }
+
+    let mapping = map_internal_routers(&net, &vdcs, &GreedyMapping).unwrap();
+    assert_eq!(mapping.len(), 16);
+    assert_eq!(
+        mapping
+            .values()
+            .map(|(vdc, _)| vdc.ssh_name.as_str())
+            .collect::<std::collections::HashSet<_>>()
+            .len(),
+        16
+    );
+
+    // one router too many
+    net.add_router("r16");
+    assert!(matches!(
+        map_internal_routers(&net, &vdcs, &GreedyMapping),
+        Err(crate::CiscoLabError::TooManyRouters(17))
+    ));
+}
@@ -0,0 +1,90 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use pretty_assertions::assert_eq;
+
+use crate::router::{config_diff, DiffLine};
+
+const OLD: &str = "\
+!
+! BGP
+!
+router bgp 65535
+  router-id 10.0.0.1
+  neighbor 10.0.1.1 remote-as 65535
+    update-source Loopback0
+  exit
+exit
+";
+
+const NEW: &str = "\
+!
+! BGP
+!
+
+router bgp 65535
+  router-id 10.0.0.1
+  neighbor 10.0.1.1 remote-as 65535
+    update-source Loopback0
+  exit
+  neighbor 10.0.2.1 remote-as 65535
+    update-source Loopback0
+  exit
+exit
+";
+
+#[test]
+fn diff_bgp_neighbor() {
+    let same = |s: &str| DiffLine::Same(s.to_string());
+    let added = |s: &str| DiffLine::Added(s.to_string());
+    let removed = |s: &str| DiffLine::Removed(s.to_string());
+
+    assert_eq!(
+        config_diff(OLD, NEW),
+        vec![
+            same("router bgp 65535"),
+            same("  router-id 10.0.0.1"),
+            same("  neighbor 10.0.1.1 remote-as 65535"),
+            same("    update-source Loopback0"),
+            same("  exit"),
+            added("  neighbor 10.0.2.1 remote-as 65535"),
+            added("    update-source Loopback0"),
+            added("  exit"),
+            same("exit"),
+        ]
+    );
+
+    assert_eq!(
+        config_diff(NEW, OLD)
+            .into_iter()
+            .filter(|l| !matches!(l, DiffLine::Same(_)))
+            .collect::<Vec<_>>(),
+        vec![
+            removed("  neighbor 10.0.2.1 remote-as 65535"),
+            removed("    update-source Loopback0"),
+            removed("  exit"),
+        ]
+    );
+}
+
+#[test]
+fn diff_ignores_comments() {
+    let stripped = OLD.replace("!\n", "").replace("! BGP\n", "");
+    assert!(config_diff(OLD, &format!("\n{stripped}\n!\n"))
+        .iter()
+        .all(|l| matches!(l, DiffLine::Same(_))));
+}
@@ -0,0 +1,104 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::{broadcast, mpsc};
+
+use crate::router::{
+    convergence_controller, ConvergenceMessage, ConvergencePhase, ConvergenceProgress,
+    ConvergenceState,
+};
+
+#[test]
+fn progress_callback() {
+    let num_workers = 3;
+    let reports: Arc<Mutex<Vec<(ConvergencePhase, usize, usize)>>> = Default::default();
+    let reports_cb = reports.clone();
+    let progress: ConvergenceProgress =
+        Box::new(move |phase, done, total| reports_cb.lock().unwrap().push((phase, done, total)));
+
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let (message_tx, message_rx) = mpsc::channel::<ConvergenceMessage>(1024);
+            let (state_tx, mut state_rx) = broadcast::channel::<ConvergenceState>(1024);
+
+            // mock of all workers, reporting in every phase in which the controller waits for all
+            // workers, and staying quiet in all others.
+            let workers = tokio::task::spawn(async move {
+                let mut state = ConvergenceState::OspfNeighbors;
+                loop {
+                    if matches!(
+                        state,
+                        ConvergenceState::OspfNeighbors
+                            | ConvergenceState::BgpNeighbors
+                            | ConvergenceState::BgpNextHop
+                    ) {
+                        for i in 0..num_workers {
+                            message_tx.send(ConvergenceMessage(state, i)).await.unwrap();
+                        }
+                    }
+                    state = state_rx.recv().await.unwrap();
+                    if state == ConvergenceState::Done {
+                        break;
+                    }
+                }
+            });
+
+            convergence_controller(
+                num_workers,
+                message_rx,
+                state_tx,
+                Some(&progress),
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+            workers.await.unwrap();
+        });
+
+    use ConvergencePhase::*;
+    assert_eq!(
+        reports.lock().unwrap().clone(),
+        vec![
+            (OspfNeighbors, 0, 3),
+            (OspfNeighbors, 1, 3),
+            (OspfNeighbors, 2, 3),
+            (OspfNeighbors, 3, 3),
+            (OspfState, 0, 3),
+            (OspfState, 3, 3),
+            (BgpNeighbors, 0, 3),
+            (BgpNeighbors, 1, 3),
+            (BgpNeighbors, 2, 3),
+            (BgpNeighbors, 3, 3),
+            (BgpNextHop, 0, 3),
+            (BgpNextHop, 1, 3),
+            (BgpNextHop, 2, 3),
+            (BgpNextHop, 3, 3),
+            (BgpState, 0, 3),
+            (BgpState, 3, 3),
+            (Done, 3, 3),
+        ]
+    );
+}
@@ -18,9 +18,16 @@
 use crate::config::{CONFIG, VDCS};
 
 mod bgp;
+mod capture;
 mod config;
+mod config_diff;
+mod convergence;
 mod ospf;
 mod reset_config;
+mod server;
+mod session;
+mod ssh;
+mod tofino;
 mod traffic_capture;
 
 #[test]
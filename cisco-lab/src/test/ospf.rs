@@ -15,7 +15,7 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-use crate::router::{OspfNeighbor, OspfRoute};
+use crate::router::{OspfLsa, OspfLsaType, OspfNeighbor, OspfRoute};
 
 #[test]
 fn document_parsing() {
@@ -70,3 +70,27 @@ fn neighbors() {
         ],
     );
 }
+
+#[test]
+fn lsdb() {
+    let doc = include_str!("files/show_ip_ospf_database.json");
+    let parsed = OspfLsa::from_json_output(doc).unwrap();
+    let lsa = |area: &str, lsa_type, id: &str, adv: &str| OspfLsa {
+        area: area.parse().unwrap(),
+        lsa_type,
+        link_state_id: id.parse().unwrap(),
+        adv_router: adv.parse().unwrap(),
+    };
+    assert_eq!(
+        parsed,
+        vec![
+            lsa("0.0.0.0", OspfLsaType::Router, "1.0.0.1", "1.0.0.1"),
+            lsa("0.0.0.0", OspfLsaType::Router, "1.0.1.1", "1.0.1.1"),
+            lsa("0.0.0.0", OspfLsaType::Network, "1.128.0.2", "1.0.1.1"),
+            lsa("0.0.0.1", OspfLsaType::Summary, "1.0.2.0", "1.0.0.1"),
+        ]
+    );
+
+    // an empty output results in an empty database
+    assert!(OspfLsa::from_json_output("").unwrap().is_empty());
+}
@@ -0,0 +1,47 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use pretty_assertions::assert_eq;
+
+use crate::server::LockStatus;
+
+#[test]
+fn lock_status_free() {
+    assert_eq!(LockStatus::from_lock_file(None), LockStatus::Free);
+}
+
+#[test]
+fn lock_status_held() {
+    assert_eq!(
+        LockStatus::from_lock_file(Some("alice\n".to_string())),
+        LockStatus::Held {
+            user: "alice".to_string()
+        }
+    );
+}
+
+#[test]
+fn lock_may_unlock() {
+    let held = LockStatus::Held {
+        user: "alice".to_string(),
+    };
+    assert!(held.may_unlock("alice", false));
+    assert!(!held.may_unlock("bob", false));
+    assert!(held.may_unlock("bob", true));
+    assert!(!LockStatus::Free.may_unlock("alice", false));
+    assert!(!LockStatus::Free.may_unlock("alice", true));
+}
@@ -0,0 +1,48 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::time::Duration;
+
+use crate::router::{exec_with_timeout, CiscoShellError};
+
+fn run<F: std::future::Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
+
+#[test]
+fn exec_timeout_output() {
+    let result = run(exec_with_timeout(
+        "show clock",
+        Duration::from_millis(100),
+        async { Ok("12:00:00.000 UTC Mon Jan 01 2024".to_string()) },
+    ));
+    assert_eq!(result.unwrap(), "12:00:00.000 UTC Mon Jan 01 2024");
+}
+
+#[test]
+fn exec_timeout_hang() {
+    let result = run(exec_with_timeout(
+        "show clock",
+        Duration::from_millis(100),
+        std::future::pending(),
+    ));
+    assert!(matches!(result, Err(CiscoShellError::Timeout(cmd)) if cmd == "show clock"));
+}
@@ -0,0 +1,86 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{config::CONFIG, ssh::spawn_keepalive};
+
+#[test]
+fn keepalive_config() {
+    assert_eq!(
+        CONFIG.ssh.keepalive_interval(),
+        Some(Duration::from_secs(60))
+    );
+}
+
+/// Advance the paused clock by `duration` and let the spawned tasks run.
+async fn advance(duration: Duration) {
+    tokio::time::advance(duration).await;
+    tokio::task::yield_now().await;
+}
+
+#[test]
+fn keepalive_interval() {
+    let interval = Duration::from_millis(50);
+    let sent: Arc<Mutex<Vec<Instant>>> = Default::default();
+
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(async {
+            tokio::time::pause();
+            let start = Instant::now();
+            let sent_mock = sent.clone();
+            let handle = spawn_keepalive("mock", interval, move || {
+                let sent = sent_mock.clone();
+                async move {
+                    sent.lock().unwrap().push(Instant::now());
+                    Ok(())
+                }
+            });
+            // let the task start its ticker at `start`.
+            tokio::task::yield_now().await;
+
+            // no keepalive is sent before the first interval has elapsed.
+            advance(interval - Duration::from_millis(1)).await;
+            assert!(sent.lock().unwrap().is_empty());
+            advance(Duration::from_millis(1)).await;
+            assert_eq!(sent.lock().unwrap().clone(), vec![start + interval]);
+
+            // afterwards, a keepalive is sent exactly every interval.
+            for _ in 0..4 {
+                advance(interval).await;
+            }
+            assert_eq!(
+                sent.lock().unwrap().clone(),
+                (1..=5).map(|i| start + interval * i).collect::<Vec<_>>()
+            );
+
+            // dropping the handle stops the task.
+            std::mem::drop(handle);
+            for _ in 0..3 {
+                advance(interval).await;
+            }
+            assert_eq!(sent.lock().unwrap().len(), 5);
+        });
+}
@@ -0,0 +1,34 @@
+// BgpSim: BGP Network Simulator written in Rust
+// Copyright (C) 2022-2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use bgpsim::{prelude::*, topology_zoo::TopologyZoo};
+
+use crate::tofino::link_delays_from_geolocation;
+
+#[test]
+fn geo_link_delays_abilene() {
+    let topo = TopologyZoo::Abilene;
+    let net: Network<SimplePrefix, _> = topo.build(BasicEventQueue::new());
+    let delays = link_delays_from_geolocation(&net, topo.geo_location());
+
+    let id = |name: &str| net.get_router_id(name).unwrap();
+    let long = delays[&(id("Seattle"), id("Denver"))];
+    let short = delays[&(id("NewYork"), id("WashingtonDC"))];
+
+    assert!(long > short);
+    assert_eq!(long, delays[&(id("Denver"), id("Seattle"))]);
+}
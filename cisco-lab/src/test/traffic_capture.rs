@@ -15,9 +15,11 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-use std::net::Ipv4Addr;
+use std::{net::Ipv4Addr, time::Duration};
 
-use crate::server::traffic_capture::{CollectorSample, ProberSample};
+use crate::server::traffic_capture::{
+    match_send_time, prober_command, CollectorSample, ProberRamp, ProberSample,
+};
 
 #[test]
 fn collector_sample_parser() {
@@ -45,3 +47,56 @@ fn prober_sample_parser() {
         }
     );
 }
+
+#[test]
+fn prober_ramp_schedule() {
+    let ms = Duration::from_millis;
+    assert_eq!(
+        ProberRamp::new(100, 1100, ms(2500)).schedule(),
+        vec![
+            (Some(ms(1000)), 100),
+            (Some(ms(1000)), 500),
+            (Some(ms(500)), 900),
+            (None, 1100),
+        ]
+    );
+    assert_eq!(
+        ProberRamp::new(1000, 100, ms(3000)).schedule(),
+        vec![
+            (Some(ms(1000)), 1000),
+            (Some(ms(1000)), 700),
+            (Some(ms(1000)), 400),
+            (None, 100),
+        ]
+    );
+    assert_eq!(
+        ProberRamp::new(100, 1000, Duration::ZERO).schedule(),
+        vec![(None, 1000)]
+    );
+}
+
+#[test]
+fn prober_ramp_command() {
+    let schedule = ProberRamp::new(100, 1000, Duration::from_millis(1500)).schedule();
+    assert_eq!(
+        prober_command(&schedule, "prober.toml"),
+        "sudo timeout 1 prober -f 10000 prober.toml; \
+         sudo timeout 0.5 prober -f 1428 prober.toml; \
+         sudo prober -f 1000 prober.toml"
+    );
+    assert_eq!(
+        prober_command(&[(None, 100)], "prober.toml"),
+        "sudo prober -f 10000 prober.toml"
+    );
+}
+
+#[test]
+fn prober_ramp_match_send_time() {
+    // the counter was reused after a restart of the prober
+    let send_times = [10.0, 11.0, 12.5];
+    assert_eq!(match_send_time(&send_times, 1.52, Some(-11.0)), Some(12.5));
+    assert_eq!(match_send_time(&send_times, 0.02, Some(-11.0)), Some(11.0));
+    assert_eq!(match_send_time(&send_times, 0.02, None), Some(12.5));
+    assert_eq!(match_send_time(&[10.0], 0.02, Some(-11.0)), Some(10.0));
+    assert_eq!(match_send_time(&[], 0.02, Some(-11.0)), None);
+}
@@ -22,6 +22,7 @@ use std::{collections::HashMap, fmt::Write, fs::read_to_string, time::Duration};
 
 use bgpsim::{
     export::{Addressor, ExportError},
+    network::Network,
     prelude::NetworkFormatter,
     types::{NetworkError, Prefix, RouterId},
 };
@@ -68,41 +69,55 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
         &mut self,
         geo: impl Into<HashMap<RouterId, Location>>,
     ) {
-        let mut geo: HashMap<RouterId, Location> = geo.into();
-        geo.retain(|_, v| v.latitude() != 0f64 || v.longitude() != 0f64);
-
-        // set all missing routers to the center of all other points.
-        let center_point = Location::center(&geo.values().collect_vec());
-        self.routers.keys().for_each(|r| {
-            geo.entry(*r).or_insert(center_point);
-        });
-
-        // compute the distance between all nodes
-        for e in self.net.get_topology().edge_indices() {
-            let (a, b) = self.net.get_topology().edge_endpoints(e).unwrap();
-            if a > b {
-                continue;
-            }
+        for ((a, b), delay_us) in link_delays_from_geolocation(self.net, geo) {
+            self.set_link_delay(a, b, delay_us);
+        }
+    }
 
-            // get the distance between a and b
-            let zero = Location::new(0, 0);
-            let a_loc = *geo.get(&a).unwrap_or(&zero);
-            let b_loc = *geo.get(&b).unwrap_or(&zero);
+    /// Generate the rules for the Tofino that steer packets of all links with a configured delay
+    /// (see [`CiscoLab::set_link_delay`]) through the delayer. Each line describes one directed
+    /// link, identified by the tofino port of the sending router, together with the delay (in
+    /// microseconds, corrected by the `delayer_loop_offset`), the port of the delayer, and the
+    /// tofino port of the receiving router. Links whose interfaces are not yet assigned are skipped.
+    /// Links are assigned to the delayer ports of `config/config.toml` in a round-robin fashion.
+    pub(crate) fn generate_tofino_delay_rules(&mut self) -> Result<String, CiscoLabError> {
+        self.generate_tofino_delay_rules_with(&CONFIG.server.delayer_tofino_ports)
+    }
 
-            // check if either a_loc or b_loc is pointing to zero.
-            if a_loc == zero || b_loc == zero {
-                continue;
+    /// Generate the rules for the Tofino that steer delayed links through the given
+    /// `delayer_ports` (see [`CiscoLab::generate_tofino_delay_rules`]). Returns
+    /// [`CiscoLabError::NoDelayerPorts`] if a link must be delayed, but `delayer_ports` is empty.
+    pub(crate) fn generate_tofino_delay_rules_with(
+        &mut self,
+        delayer_ports: &[u8],
+    ) -> Result<String, CiscoLabError> {
+        let mut delay_port = delayer_ports.iter().cycle();
+        let mut delays_rules = String::new();
+        for ((a, b), delay) in self.link_delays.iter() {
+            if let (Ok(a_idx), Ok(b_idx)) = (
+                self.addressor.iface_index(*a, *b),
+                self.addressor.iface_index(*b, *a),
+            ) {
+                if let (Some(a_port), Some(b_port)) = (
+                    self.routers
+                        .get(a)
+                        .and_then(|(c, _)| c.ifaces.get(a_idx))
+                        .map(|x| x.tofino_port),
+                    self.routers
+                        .get(b)
+                        .and_then(|(c, _)| c.ifaces.get(b_idx))
+                        .map(|x| x.tofino_port),
+                ) {
+                    writeln!(
+                        &mut delays_rules,
+                        "    {a_port: >3}: {{ {DELAY_ADDRS}, 'delay': {}, 'delay_port': {}, 'receiver_port': {b_port} }},",
+                        (*delay as i32 + CONFIG.server.delayer_loop_offset as i32).max(0),
+                        delay_port.next().ok_or(CiscoLabError::NoDelayerPorts)?,
+                    )?;
+                }
             }
-
-            let distance = a_loc
-                .distance_to(&b_loc)
-                .unwrap_or_else(|_| a_loc.haversine_distance_to(&b_loc))
-                .meters();
-            let delay = distance / SPEED_OF_LIGHT;
-            let delay_us = (delay * 1_000_000f64) as u32;
-            self.set_link_delay(a, b, delay_us);
-            self.set_link_delay(b, a, delay_us);
         }
+        Ok(delays_rules)
     }
 
     /// Generate the string for the tofino controller.
@@ -162,32 +177,7 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
             }
         }
 
-        let mut delay_port = CONFIG.server.delayer_tofino_ports.iter().cycle();
-        let mut delays_rules = String::new();
-        for ((a, b), delay) in self.link_delays.iter() {
-            if let (Ok(a_idx), Ok(b_idx)) = (
-                self.addressor.iface_index(*a, *b),
-                self.addressor.iface_index(*b, *a),
-            ) {
-                if let (Some(a_port), Some(b_port)) = (
-                    self.routers
-                        .get(a)
-                        .and_then(|(c, _)| c.ifaces.get(a_idx))
-                        .map(|x| x.tofino_port),
-                    self.routers
-                        .get(b)
-                        .and_then(|(c, _)| c.ifaces.get(b_idx))
-                        .map(|x| x.tofino_port),
-                ) {
-                    writeln!(
-                        &mut delays_rules,
-                        "    {a_port: >3}: {{ {DELAY_ADDRS}, 'delay': {}, 'delay_port': {}, 'receiver_port': {b_port} }},",
-                        (*delay as i32 + CONFIG.server.delayer_loop_offset as i32).max(0),
-                        delay_port.next().unwrap(),
-                    )?;
-                }
-            }
-        }
+        let delays_rules = self.generate_tofino_delay_rules()?;
         let delays = delays_rules.trim_matches('\n');
 
         // generate basic data-plane traffic with iperf
@@ -263,6 +253,54 @@ impl<'n, P: Prefix, Q, S> CiscoLab<'n, P, Q, S> {
     }
 }
 
+/// Compute the propagation delay (in microseconds) of every link in the network from the
+/// geolocation of each router (see [`CiscoLab::set_link_delays_from_geolocation`]). The result
+/// contains both directions of each link.
+pub(crate) fn link_delays_from_geolocation<P: Prefix, Q>(
+    net: &Network<P, Q>,
+    geo: impl Into<HashMap<RouterId, Location>>,
+) -> HashMap<(RouterId, RouterId), u32> {
+    let mut geo: HashMap<RouterId, Location> = geo.into();
+    geo.retain(|_, v| v.latitude() != 0f64 || v.longitude() != 0f64);
+
+    // set all missing routers to the center of all other points.
+    let center_point = Location::center(&geo.values().collect_vec());
+    net.get_routers().into_iter().for_each(|r| {
+        geo.entry(r).or_insert(center_point);
+    });
+
+    let mut delays = HashMap::new();
+
+    // compute the distance between all nodes
+    for e in net.get_topology().edge_indices() {
+        let (a, b) = net.get_topology().edge_endpoints(e).unwrap();
+        if a > b {
+            continue;
+        }
+
+        // get the distance between a and b
+        let zero = Location::new(0, 0);
+        let a_loc = *geo.get(&a).unwrap_or(&zero);
+        let b_loc = *geo.get(&b).unwrap_or(&zero);
+
+        // check if either a_loc or b_loc is pointing to zero.
+        if a_loc == zero || b_loc == zero {
+            continue;
+        }
+
+        let distance = a_loc
+            .distance_to(&b_loc)
+            .unwrap_or_else(|_| a_loc.haversine_distance_to(&b_loc))
+            .meters();
+        let delay = distance / SPEED_OF_LIGHT;
+        let delay_us = (delay * 1_000_000f64) as u32;
+        delays.insert((a, b), delay_us);
+        delays.insert((b, a), delay_us);
+    }
+
+    delays
+}
+
 impl<'n, P: Prefix, Q> CiscoLab<'n, P, Q, Active> {
     /// Configure the tofino by uploading the controller and running it.
     pub(crate) async fn configure_tofino(&mut self) -> Result<(), CiscoLabError> {
@@ -75,7 +75,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // write to file if feature is enabled
     #[cfg(feature = "export-web")]
     {
-        atomic_bgp::export_web(&net, &spec, decomposition.clone(), "abilene")?;
+        atomic_bgp::export_web(&net, &spec, decomposition.clone(), ".", "abilene")?;
     }
 
     // perform the simulation
@@ -92,7 +92,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let lab = runtime::lab::setup_cisco_lab(&net, Some(TOPO)).await?;
                 let mut lab = lab.connect().await?;
                 lab.wait_for_convergence().await?;
-                runtime::lab::run(net.clone(), &mut lab, decomposition.clone(), None).await?;
+                runtime::lab::run(
+                    net.clone(),
+                    &mut lab,
+                    decomposition.clone(),
+                    None,
+                    "results",
+                )
+                .await?;
 
                 // drop the lab
                 std::mem::drop(lab);
@@ -101,8 +108,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let lab = runtime::lab::setup_cisco_lab(&net, Some(TOPO)).await?;
                 let mut lab = lab.connect().await?;
                 lab.wait_for_convergence().await?;
-                runtime::lab::run_baseline(net.clone(), &mut lab, decomposition.clone(), None)
-                    .await?;
+                runtime::lab::run_baseline(
+                    net.clone(),
+                    &mut lab,
+                    decomposition.clone(),
+                    None,
+                    "results",
+                )
+                .await?;
 
                 Ok::<(), runtime::lab::LabError>(())
             })?;
@@ -82,11 +82,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 lab.wait_for_convergence().await?;
 
                 // set the prefix equivalence classes
-                let mut path =
-                    runtime::lab::run(net.clone(), &mut lab, decomp.clone(), None).await?;
+                let path =
+                    runtime::lab::run(net.clone(), &mut lab, decomp.clone(), None, "results")
+                        .await?;
 
                 // store the experiment
-                path.push("scenario.json");
                 Experiment {
                     net: &net,
                     topo: None,
@@ -97,12 +97,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     rand: false,
                     data: (),
                 }
-                .write_json(&path)?;
-                path.pop();
+                .write_json(&path, "scenario.json")?;
 
                 // generate the web export
-                let web_export_path = format!("{}/web_export", path.to_string_lossy());
-                chameleon::export_web(&net, &spec, decomp.clone(), web_export_path).unwrap();
+                chameleon::export_web(&net, &spec, decomp.clone(), &path, "web_export").unwrap();
 
                 // drop the lab
                 std::mem::drop(lab);
@@ -117,11 +115,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let mut lab = lab.connect().await?;
                 lab.wait_for_convergence().await?;
 
-                let mut path =
-                    runtime::lab::run_baseline(net.clone(), &mut lab, decomp.clone(), None).await?;
+                let path = runtime::lab::run_baseline(
+                    net.clone(),
+                    &mut lab,
+                    decomp.clone(),
+                    None,
+                    "results",
+                )
+                .await?;
 
                 // generate the scenario.json
-                path.push("scenario.json");
                 Experiment {
                     net: &net,
                     topo: None,
@@ -132,7 +135,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     rand: false,
                     data: (),
                 }
-                .write_json(path)?;
+                .write_json(path, "scenario.json")?;
 
                 Ok::<(), runtime::lab::LabError>(())
             })?;
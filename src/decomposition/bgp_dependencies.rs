@@ -18,7 +18,7 @@
 //! This module analyzes the difference in BGP state and computes the high-level dependencies of the
 //! control-plane (for which violations should be minimized).
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use bgpsim::{
     bgp::{BgpRoute, BgpState},
@@ -101,6 +101,65 @@ pub fn find_dependencies<Q>(info: &'_ CommandInfo<'_, Q>) -> HashMap<P, BgpDepen
     result
 }
 
+/// Find all routers that must use at least one temporary BGP session in any schedule, based only
+/// on the BGP dependencies (without solving the ILP).
+///
+/// A router `a` learns the old route from `old_from` and the new route from `new_from`. Any
+/// schedule satisfies `r_old[a] < max(r_old[old_from])` and `r_new[a] > min(r_new[new_from])` (see
+/// [`super::ilp_scheduler`]). If `r_old[o] <= r_new[n]` holds for every `o` in `old_from` and `n` in
+/// `new_from`, then `r_new[a] - r_old[a] >= 2`, and `a` cannot switch its forwarding state at the
+/// same time as it switches from the old to the new route. The result is thus a lower bound on the
+/// routers that need temporary sessions.
+pub fn forced_temp_sessions<Q>(
+    info: &'_ CommandInfo<'_, Q>,
+    bgp_deps: &HashMap<P, BgpDependencies>,
+) -> HashMap<P, BTreeSet<RouterId>> {
+    bgp_deps
+        .iter()
+        .map(|(prefix, deps)| {
+            // the same routers for which the ILP creates variables.
+            let nodes: HashSet<RouterId> = info
+                .fw_diff
+                .get(prefix)
+                .into_iter()
+                .flat_map(|d| d.keys())
+                .chain(deps.keys())
+                .copied()
+                .collect();
+            let single = |r: &BTreeSet<RouterId>, x: RouterId| r.len() == 1 && r.contains(&x);
+            // check if `r_old[o] <= r_new[n]` holds in any schedule
+            let ordered = |o: RouterId, n: RouterId| {
+                o == n
+                    || deps
+                        .get(&n)
+                        .map(|d| single(&d.new_from, o))
+                        .unwrap_or(false)
+                    || deps
+                        .get(&o)
+                        .map(|d| single(&d.old_from, n))
+                        .unwrap_or(false)
+            };
+            let forced = deps
+                .iter()
+                .filter(|(_, d)| !d.old_from.is_empty() && !d.new_from.is_empty())
+                .filter(|(_, d)| {
+                    d.old_from
+                        .iter()
+                        .chain(&d.new_from)
+                        .all(|x| nodes.contains(x))
+                })
+                .filter(|(_, d)| {
+                    d.old_from
+                        .iter()
+                        .all(|o| d.new_from.iter().all(|n| ordered(*o, *n)))
+                })
+                .map(|(r, _)| *r)
+                .collect();
+            (*prefix, forced)
+        })
+        .collect()
+}
+
 /// Compute the list of peers that advertise `route`.
 ///
 /// We use a custom equality here, that ignores `cluster_list` and `from_type`, and compares
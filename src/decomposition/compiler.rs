@@ -40,27 +40,65 @@ use lazy_static::lazy_static;
 use crate::{Decomposition, P};
 
 use super::{
-    bgp_dependencies::BgpDependencies,
-    ilp_scheduler::{FwStateTrace, NodeSchedule, Schedule},
+    bgp_dependencies::{find_dependencies, BgpDependencies},
+    ilp_scheduler::{fw_state_trace_from_schedule, FwStateTrace, NodeSchedule, Schedule},
     CommandInfo, DecompositionError,
 };
 
 /// Type definition for a single stage
 type Stage = Vec<Vec<AtomicCommand<P>>>;
 
+/// Options for building the atomic decomposition of the command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CompilerOptions {
+    /// Coalesce commands of the same round that reconfigure the same router into a single command
+    /// (see [`coalesce_commands`]).
+    pub coalesce: bool,
+}
+
 /// Build the atomic decomposition of the command.
 pub fn build<Q>(
     info: &CommandInfo<'_, Q>,
     bgp_deps: HashMap<P, BgpDependencies>,
     schedules: HashMap<P, (Schedule, FwStateTrace)>,
+) -> Result<Decomposition, DecompositionError> {
+    build_with_options(info, bgp_deps, schedules, CompilerOptions::default())
+}
+
+/// Build the atomic decomposition of the command using the given options.
+pub fn build_with_options<Q>(
+    info: &CommandInfo<'_, Q>,
+    bgp_deps: HashMap<P, BgpDependencies>,
+    schedules: HashMap<P, (Schedule, FwStateTrace)>,
+    options: CompilerOptions,
 ) -> Result<Decomposition, DecompositionError> {
     log::info!("Generate the final decomposition based on the schedule.");
-    match info.command.key() {
+    let mut decomposition = match info.command.key() {
         Some(ConfigExprKey::BgpRouteMap { .. }) | Some(ConfigExprKey::BgpSession { .. }) => {
-            _build(info, bgp_deps, schedules)
+            _build(info, bgp_deps, schedules)?
         }
         _ => unimplemented!(),
+    };
+
+    if options.coalesce {
+        coalesce_commands(&mut decomposition);
     }
+
+    Ok(decomposition)
+}
+
+/// Build the atomic decomposition of the command for a single prefix, using a schedule that was
+/// computed externally (e.g., parsed with `ilp_scheduler::schedule_from_json`). This bypasses the
+/// ILP scheduler entirely. The schedule is not checked; use [`Decomposition::verify_endpoints`] or
+/// the simulator to validate the result.
+pub fn compile_from_schedule<Q>(
+    info: &CommandInfo<'_, Q>,
+    schedule: Schedule,
+    prefix: P,
+) -> Result<Decomposition, DecompositionError> {
+    let bgp_deps = find_dependencies(info);
+    let trace = fw_state_trace_from_schedule(info, &schedule, prefix);
+    build(info, bgp_deps, HashMap::from([(prefix, (schedule, trace))]))
 }
 
 /// Build function with type specialization for each decomposer.
@@ -296,6 +334,7 @@ fn setup_commands<Q>(
                         neighbor: Some(n),
                         weight: Some(OLD_ROUTE_WEIGHT),
                         next_hop: old_nh(info, *r, *p),
+                        originator_id: None,
                     },
                 })
             }
@@ -502,6 +541,7 @@ fn prefer_new_route_in_r_new<Q>(
             neighbor: Some(new_n),
             weight: Some(NEW_ROUTE_WEIGHT),
             next_hop: new_egress,
+            originator_id: None,
         },
     });
 }
@@ -531,6 +571,7 @@ fn apply_rule_2<Q>(
             neighbor: Some(old_egress),
             weight: Some(TMP_ROUTE_WEIGHT),
             next_hop: Some(old_egress),
+            originator_id: None,
         },
     });
 
@@ -544,6 +585,7 @@ fn apply_rule_2<Q>(
             neighbor: Some(new_n),
             weight: Some(NEW_ROUTE_WEIGHT),
             next_hop: Some(new_egress),
+            originator_id: None,
         },
         postcondition: AtomicCondition::None,
     });
@@ -572,6 +614,7 @@ fn apply_rule_3<Q>(
             neighbor: Some(new_egress),
             weight: Some(TMP_ROUTE_WEIGHT),
             next_hop: Some(new_egress),
+            originator_id: None,
         },
     });
 
@@ -584,6 +627,7 @@ fn apply_rule_3<Q>(
             neighbor: Some(new_n),
             weight: Some(NEW_ROUTE_WEIGHT),
             next_hop: Some(new_egress),
+            originator_id: None,
         },
         postcondition: AtomicCondition::None,
     });
@@ -617,6 +661,7 @@ fn apply_rule_4<Q>(
             neighbor: Some(old_egress),
             weight: Some(TMP_ROUTE_WEIGHT),
             next_hop: Some(old_egress),
+            originator_id: None,
         },
     });
 
@@ -661,6 +706,7 @@ fn apply_rule_4<Q>(
             neighbor: Some(new_n),
             weight: Some(NEW_ROUTE_WEIGHT),
             next_hop: Some(new_egress),
+            originator_id: None,
         },
         postcondition: AtomicCondition::None,
     });
@@ -689,6 +735,7 @@ fn apply_rule_4_same_egress<Q>(
             neighbor: Some(egress),
             weight: Some(TMP_ROUTE_WEIGHT),
             next_hop: Some(egress),
+            originator_id: None,
         },
     });
 
@@ -704,6 +751,7 @@ fn apply_rule_4_same_egress<Q>(
             neighbor: Some(new_n),
             weight: Some(NEW_ROUTE_WEIGHT),
             next_hop: Some(egress),
+            originator_id: None,
         },
         postcondition: AtomicCondition::None,
     });
@@ -856,6 +904,75 @@ fn get_temp_sessions<Q>(
     Ok(sessions)
 }
 
+/// Coalesce commands within the same round that reconfigure the same (single) router, such that
+/// they are applied to the router at once. A `ChangePreference` or `ClearPreference` command is
+/// merged into an earlier `ChangePreference` or `ClearPreference` command on the same router, as
+/// long as their pre- and postconditions are either equal or `None`. The merged command waits for
+/// the conditions of both commands. All other commands (e.g., those that add or use temporary
+/// sessions) are never merged, such that their kind is preserved.
+pub fn coalesce_commands(decomp: &mut Decomposition) {
+    coalesce_commands_of_stage(&mut decomp.setup_commands);
+    coalesce_commands_of_stage(&mut decomp.main_commands);
+    coalesce_commands_of_stage(&mut decomp.cleanup_commands);
+    decomp
+        .atomic_before
+        .values_mut()
+        .for_each(coalesce_commands_of_stage);
+    decomp
+        .atomic_after
+        .values_mut()
+        .for_each(coalesce_commands_of_stage);
+}
+
+/// Coalesce commands of each round in the stage (see [`coalesce_commands`]).
+#[allow(clippy::ptr_arg)]
+fn coalesce_commands_of_stage(stage: &mut Vec<Vec<AtomicCommand<P>>>) {
+    /// Combine two conditions, if possible.
+    fn combine(a: &AtomicCondition<P>, b: &AtomicCondition<P>) -> Option<AtomicCondition<P>> {
+        if b.is_none() || a == b {
+            Some(a.clone())
+        } else if a.is_none() {
+            Some(b.clone())
+        } else {
+            None
+        }
+    }
+
+    for step in stage.iter_mut() {
+        let mut result: Vec<AtomicCommand<P>> = Vec::with_capacity(step.len());
+        'cmd: for cmd in std::mem::take(step) {
+            let routers = cmd.command.routers();
+            let is_preference = matches!(
+                cmd.command,
+                AtomicModifier::ChangePreference { .. } | AtomicModifier::ClearPreference { .. }
+            );
+            if is_preference && routers.len() == 1 {
+                for target in result.iter_mut() {
+                    if target.command.routers() != routers {
+                        continue;
+                    }
+                    let raw = match &mut target.command {
+                        AtomicModifier::ChangePreference { raw, .. }
+                        | AtomicModifier::ClearPreference { raw, .. } => raw,
+                        _ => continue,
+                    };
+                    if let (Some(pre), Some(post)) = (
+                        combine(&target.precondition, &cmd.precondition),
+                        combine(&target.postcondition, &cmd.postcondition),
+                    ) {
+                        raw.extend(Vec::<ConfigModifier<P>>::from(cmd.command));
+                        target.precondition = pre;
+                        target.postcondition = post;
+                        continue 'cmd;
+                    }
+                }
+            }
+            result.push(cmd);
+        }
+        *step = result;
+    }
+}
+
 /// Batch together all similar route-map updates of all commands in the decomposition
 fn batch_route_map_updates(decomp: &mut Decomposition) {
     batch_route_map_updates_of_stage(&mut decomp.setup_commands);
@@ -246,6 +246,35 @@ pub(super) fn temp_session_needed_constraints(problem: &mut impl SolverModel, va
     }
 }
 
+/// Require that no router needs a temporary session, i.e., `r_old == r == r_new` for all routers.
+pub(super) fn forbid_temp_sessions_constraints(problem: &mut impl SolverModel, vars: &IlpVars) {
+    for router in vars.session_needed.keys() {
+        let r_old = vars.r_old[router];
+        let r_fw = vars.r[router];
+        let r_new = vars.r_new[router];
+
+        problem.add_constraint(constraint!(r_old == r_fw));
+        problem.add_constraint(constraint!(r_fw == r_new));
+    }
+}
+
+/// Require that each listed router performs all its changes within the given number of rounds. The
+/// router participates in all rounds from `r_old` up to and including `r_new`, so we require
+/// `r_new - r_old + 1 <= limit`. Routers that are not part of the schedule are ignored.
+pub(super) fn max_rounds_per_router_constraints(
+    problem: &mut impl SolverModel,
+    vars: &IlpVars,
+    limits: &HashMap<RouterId, usize>,
+) {
+    for (router, limit) in limits {
+        if let (Some(r_old), Some(r_new)) = (vars.r_old.get(router), vars.r_new.get(router)) {
+            let (r_old, r_new) = (*r_old, *r_new);
+            let limit = i32::try_from(*limit).unwrap_or(i32::MAX);
+            problem.add_constraint(constraint!(r_new - r_old + 1 <= limit));
+        }
+    }
+}
+
 /// Description of the type of BGP constraint.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub(super) enum ConstraintType {
@@ -30,6 +30,13 @@
 //! reachability with sending to a terminal), or to be equal to the next hop. In case this router
 //! may change its forwarding decision, we add an if-then-else clause, which uses the old or the new
 //! next-hop.
+//!
+//! # Loop Freedom
+//! The recursive model cannot detect forwarding loops, as the equations along a cycle are satisfied
+//! by any value. Without the explicit loop checker, we therefore add a rank variable for each router
+//! and step. If a router is loop-free and its property depends on its next-hop, then its rank must
+//! be strictly larger than the rank of that next-hop. As the ranks are bounded, no cycle can be
+//! loop-free.
 
 use std::{
     collections::{HashMap, HashSet},
@@ -52,6 +59,9 @@ use super::{or_tools::*, IlpVars};
 pub(super) type CondsType = HashMap<Property, HashMap<RouterId, Vec<Variable>>>;
 /// Type to represent variables for the specification (LTL).
 pub(super) type SpecExprType = HashMap<SpecExprExt, HashMap<usize, Variable>>;
+/// Type to represent the rank variables used to encode loop freedom.
+#[cfg(not(feature = "explicit-loop-checker"))]
+pub(super) type LoopFreeType = HashMap<RouterId, Vec<Variable>>;
 
 /// Create all variables needed for the specification, i.e., to satisfy the forwarding policies.
 pub(super) fn spec_variables<Q>(
@@ -89,6 +99,30 @@ pub(super) fn spec_variables<Q>(
     (prop_vars, spec_vars)
 }
 
+/// Create the rank variables used to encode loop freedom. The variables are only created if the
+/// conditions contain [`Property::LoopFree`].
+#[cfg(not(feature = "explicit-loop-checker"))]
+pub(super) fn loop_free_variables(
+    p: &mut ProblemVariables,
+    conds: &CondsType,
+    all_nodes: &HashSet<RouterId>,
+    max_steps: usize,
+) -> LoopFreeType {
+    if !conds.contains_key(&Property::LoopFree) {
+        return LoopFreeType::new();
+    }
+    let max_rank = all_nodes.len() as f64;
+    all_nodes
+        .iter()
+        .copied()
+        .zip(repeat_with(|| {
+            repeat_with(|| p.add(variable().min(0).max(max_rank)))
+                .take(max_steps)
+                .collect_vec()
+        }))
+        .collect()
+}
+
 /// recursive function to build the spec expr variables
 fn build_spec_vars(
     p: &mut ProblemVariables,
@@ -178,7 +212,10 @@ pub(super) fn prop_constraints<Q>(
         let c = vars.get_c(prop, r, round);
         match prop {
             // Special case for external routers
-            Property::Reachability | Property::Waypoint(_)
+            Property::Reachability
+            | Property::Waypoint(_)
+            | Property::MaxPathLength(_)
+            | Property::LoopFree
                 if info.net_before.get_device(r).is_external() =>
             {
                 match prop.local_sat(r, None, &info.fw_before, prefix) {
@@ -187,12 +224,15 @@ pub(super) fn prop_constraints<Q>(
                     None => unreachable!(),
                 };
             }
-            // Reachability and waypoint properties depend on the next-hop that they have.
-            Property::Reachability | Property::Waypoint(_) => {
-                // TODO deal with black holes here!
-                //
+            // Reachability, waypoint, path length, and loop freedom properties depend on the next-hop
+            // that they have.
+            Property::Reachability
+            | Property::Waypoint(_)
+            | Property::MaxPathLength(_)
+            | Property::LoopFree => {
                 // extract the next hop. Notice, that z is the state before the update (i.e., b = 0), and
-                // y is the state after the update (i.e., b = 1).
+                // y is the state after the update (i.e., b = 1). A missing next-hop means that the
+                // router is either a terminal or a black hole.
                 let nh_old = info.fw_before.get_next_hops(r, prefix).last().copied();
                 let nh_new = info.fw_after.get_next_hops(r, prefix).last().copied();
 
@@ -200,9 +240,13 @@ pub(super) fn prop_constraints<Q>(
                 let sat_old = prop.local_sat(r, nh_old, &info.fw_before, prefix);
                 let sat_new = prop.local_sat(r, nh_new, &info.fw_after, prefix);
 
-                // prepare the c variable to be constrained in this round.
-                let c_nh_new = vars.get_c(prop, nh_new.unwrap(), round);
-                let c_nh_old = vars.get_c(prop, nh_old.unwrap(), round);
+                // prepare the c variable to be constrained in this round. `local_sat` always
+                // returns `Some(_)` for routers without a next-hop, so the variable of the next-hop
+                // is only needed (and only looked up) if the router has a next-hop.
+                let nh_prop = prop.next_hop_prop();
+                let c_nh = |nh: Option<RouterId>| {
+                    vars.get_c(&nh_prop, nh.expect("next-hop must exist"), round)
+                };
 
                 // check if there was a change
                 if nh_old == nh_new {
@@ -212,7 +256,7 @@ pub(super) fn prop_constraints<Q>(
                     match sat_old {
                         Some(true) => problem.add_constraint(constraint!(c == 1)),
                         Some(false) => problem.add_constraint(constraint!(c == 0)),
-                        None => problem.add_constraint(constraint!(c == c_nh_old)),
+                        None => problem.add_constraint(constraint!(c == c_nh(nh_old))),
                     };
                 } else {
                     // helper function to turn a boolean into a float.
@@ -222,19 +266,38 @@ pub(super) fn prop_constraints<Q>(
                     let has_changed = vars.get_b(r, round);
                     match (sat_old, sat_new) {
                         (None, None) => {
-                            c_if_then_else(problem, has_changed, c, c_nh_new, c_nh_old);
+                            let (y, z) = (c_nh(nh_new), c_nh(nh_old));
+                            c_if_then_else(problem, has_changed, c, y, z);
                         }
                         (None, Some(nh_new_sat)) => {
-                            c_if_then_else(problem, has_changed, c, b2f(nh_new_sat), c_nh_old);
+                            let z = c_nh(nh_old);
+                            c_if_then_else(problem, has_changed, c, b2f(nh_new_sat), z);
                         }
                         (Some(nh_old_sat), None) => {
-                            c_if_then_else(problem, has_changed, c, c_nh_new, b2f(nh_old_sat));
+                            let y = c_nh(nh_new);
+                            c_if_then_else(problem, has_changed, c, y, b2f(nh_old_sat));
                         }
                         (Some(nh_old_sat), Some(nh_new_sat)) => {
                             c_if_then_else_yz(problem, has_changed, c, nh_new_sat, nh_old_sat)
                         }
                     }
                 }
+
+                // With the explicit loop checker, all loops are already excluded by
+                // `loop_protection_constraints`. Otherwise, exclude loops using the rank variables.
+                #[cfg(not(feature = "explicit-loop-checker"))]
+                if *prop == Property::LoopFree {
+                    let nh_old = nh_old.filter(|_| sat_old.is_none());
+                    let nh_new = nh_new.filter(|_| sat_new.is_none());
+                    if nh_old == nh_new {
+                        loop_free_rank_constraints(problem, vars, r, round, nh_old, None);
+                    } else {
+                        let b = vars.get_b(r, round);
+                        for (nh, x) in [(nh_old, false), (nh_new, true)] {
+                            loop_free_rank_constraints(problem, vars, r, round, nh, Some((b, x)));
+                        }
+                    }
+                }
             }
             Property::All(ps) => {
                 let ys = ps.iter().map(|p| vars.get_c(p, r, round)).collect_vec();
@@ -251,10 +314,46 @@ pub(super) fn prop_constraints<Q>(
             Property::True => {
                 problem.add_constraint(constraint!(c == 1));
             }
+            // Isolation is satisfied exactly if the router cannot reach the prefix.
+            Property::Isolation => {
+                let y = vars.get_c(&Property::Reachability, r, round);
+                problem.add_constraint(constraint!(c == 1 - y));
+            }
         }
     }
 }
 
+/// Require that the rank of router `r` is strictly larger than the rank of its next-hop `nh`, if
+/// `r` satisfies [`Property::LoopFree`]. `nh` is `None` if loop freedom does not depend on the
+/// next-hop. If `changed` is `Some((b, x))`, then the constraint is only enforced if the router
+/// has changed its next-hop (`b == 1`) exactly when `x` is `true`.
+#[cfg(not(feature = "explicit-loop-checker"))]
+fn loop_free_rank_constraints(
+    problem: &mut impl SolverModel,
+    vars: &IlpVars,
+    r: RouterId,
+    round: usize,
+    nh: Option<RouterId>,
+    changed: Option<(Variable, bool)>,
+) {
+    let nh = match nh {
+        Some(nh) => nh,
+        None => return,
+    };
+    let c = vars.get_c(&Property::LoopFree, r, round);
+    let d = vars.loop_free[&r][round];
+    let d_nh = vars.loop_free[&nh][round];
+    // The ranks are bounded by the number of routers, so `m` relaxes the constraint entirely.
+    let m = vars.loop_free.len() as f64 + 1.0;
+    // `relax` is zero if the constraint must hold, and at least one otherwise.
+    let relax = match changed {
+        None => 1 - c,
+        Some((b, false)) => 1 - c + b,
+        Some((b, true)) => 2 - c - b,
+    };
+    problem.add_constraint(constraint!(d >= d_nh + 1.0 - m * relax));
+}
+
 /// Create the constraints for all specifications. Further, assert that the root specificatoin is
 /// satisfied in round 0.
 pub(super) fn spec_constraints<Q>(
@@ -342,7 +441,9 @@ pub(super) fn spec_constraints<Q>(
 impl Property {
     /// Returns `Some(true)` or `Some(false)` if the router with the selected next hop already
     /// satisfies of violates the condition. If this cannot be determined only by considering this
-    /// local view, return `None`.
+    /// local view, return `None`. If the router has no next hop (i.e., `nh` is `None`), this
+    /// function never returns `None`. A router without next-hop is either a terminal or a black
+    /// hole.
     fn local_sat(
         &self,
         r: RouterId,
@@ -370,7 +471,34 @@ impl Property {
                     }
                 }
             }
-            _ => unreachable!("local_set should only be called on Reachability or Waypoint!"),
+            Property::MaxPathLength(max) => match nh {
+                // the path from a terminal has length 0, and black holes satisfy any bound.
+                None => Some(true),
+                Some(nh) if fw.is_terminal(nh, prefix) => Some(*max >= 1),
+                // The next-hop must satisfy the bound `max - 1`. For `max == 0`, the next hop must
+                // satisfy the bound `0`, which (as it is not a terminal) requires that its traffic
+                // ends in a black hole.
+                Some(_) => None,
+            },
+            Property::LoopFree => match nh {
+                // Neither terminals nor black holes forward traffic into a loop.
+                None => Some(true),
+                Some(nh) if fw.is_terminal(nh, prefix) => Some(true),
+                Some(_) => None,
+            },
+            _ => unreachable!(
+                "local_set should only be called on Reachability, Waypoint, MaxPathLength, or \
+                 LoopFree!"
+            ),
+        }
+    }
+
+    /// Get the property that the next-hop must satisfy, such that the router satisfies `self`. For
+    /// a path length bound of `k`, the next-hop must satisfy the bound `k - 1`.
+    fn next_hop_prop(&self) -> Property {
+        match self {
+            Property::MaxPathLength(max) => Property::MaxPathLength(max.saturating_sub(1)),
+            p => p.clone(),
         }
     }
 }
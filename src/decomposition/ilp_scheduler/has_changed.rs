@@ -90,7 +90,7 @@ use itertools::Itertools;
 
 use crate::{decomposition::CommandInfo, P};
 
-use super::{or_tools::*, IlpVars};
+use super::{or_tools::*, IlpVars, Schedule};
 
 /// Type definition for `changed` and `changed_step` variables
 pub(super) type HasChangedType = HashMap<RouterId, Vec<Variable>>;
@@ -156,6 +156,33 @@ pub(super) fn has_changed_constraints(problem: &mut impl SolverModel, vars: &Ilp
     }
 }
 
+/// Add a (no-good) constraint that excludes the forwarding rounds of the given schedule.
+///
+/// The round of each router is encoded in the variables `b`, where `b[i] = 1` if and only if `i >=
+/// round`. The constraint requires that at least one of these variables takes a different value
+/// than in the given schedule.
+pub(super) fn exclude_fw_schedule_constraint(
+    problem: &mut impl SolverModel,
+    vars: &IlpVars,
+    schedule: &Schedule,
+) {
+    let mut diff = Expression::from(0);
+    for (node, bs) in vars.b.iter() {
+        let round = match schedule.get(node) {
+            Some(s) => s.fw_state,
+            None => continue,
+        };
+        for (i, b) in bs.iter().copied().enumerate() {
+            if i >= round {
+                diff += 1 - b;
+            } else {
+                diff += b;
+            }
+        }
+    }
+    problem.add_constraint(constraint!(diff >= 1));
+}
+
 /// Setup all constraints for the boolean variable encoding if a router has already changed its
 /// decision at this point.
 pub(super) fn has_changed_path_constraints<Q>(
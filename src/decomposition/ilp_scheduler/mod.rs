@@ -103,9 +103,44 @@ impl std::fmt::Display for NodeSchedule {
 
 /// Schedule, that is, a mapping from each router to its schedule.
 pub type Schedule = HashMap<RouterId, NodeSchedule>;
-/// The forwarding state trace, that is, a sequence of forwarding state changes.
+/// The forwarding state trace, that is, a sequence of forwarding state changes, one for each round
+/// of the schedule. Rounds in which no router changes its next-hop are empty.
 pub type FwStateTrace = Vec<HashSet<(RouterId, Vec<RouterId>)>>;
 
+/// Parse a schedule from JSON of the form `{"router": {"fw_state": _, "old_route": _, "new_route":
+/// _}}`, where `router` is the index of the router.
+#[cfg(feature = "serde")]
+pub fn schedule_from_json(json: &str) -> Result<Schedule, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// Compute the forwarding state trace of a given schedule, without checking any property. Each
+/// step contains all routers that change their next-hops in that round. Rounds in which no router
+/// changes its next-hop are kept as empty steps, such that the index of each step matches the
+/// `fw_state` of the schedule.
+pub fn fw_state_trace_from_schedule<Q>(
+    info: &CommandInfo<'_, Q>,
+    schedule: &Schedule,
+    prefix: P,
+) -> FwStateTrace {
+    let num_rounds = schedule.values().map(|s| s.fw_state + 1).max().unwrap_or(0);
+    (0..num_rounds)
+        .map(|round| {
+            schedule
+                .iter()
+                .filter(|(_, s)| s.fw_state == round)
+                .map(|(router, _)| {
+                    (
+                        *router,
+                        info.fw_after.get_next_hops(*router, prefix).to_vec(),
+                    )
+                })
+                .filter(|(router, nhs)| info.fw_before.get_next_hops(*router, prefix) != nhs)
+                .collect::<HashSet<_>>()
+        })
+        .collect()
+}
+
 /// Find the optimal schedule for a given prefix. We are using the maximal number of steps here.
 pub fn schedule<Q>(
     info: &CommandInfo<'_, Q>,
@@ -120,12 +155,42 @@ pub fn schedule<Q>(
         prefix,
         Duration::from_secs(24 * 60 * 60),
         usize::MAX,
+        &ScheduleOptions::default(),
     )
     .0
 }
 
+/// Additional constraints on the schedule of a prefix (see [`schedule_with_max_steps`]). The
+/// default options allow temporary sessions and do not constrain any router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleOptions {
+    /// If `false`, then the schedule is constrained such that `old_route == fw_state == new_route`
+    /// for all nodes, i.e., no temporary session is needed.
+    pub allow_temp_sessions: bool,
+    /// Bound the number of rounds in which each listed router participates, i.e.,
+    /// `new_route - old_route + 1 <= limit`. A limit of `1` requires the router to perform all its
+    /// changes in a single round, while a limit of `0` makes the problem infeasible if the router is
+    /// part of the schedule.
+    pub max_rounds_per_router: HashMap<RouterId, usize>,
+    /// If given, the routers must change their forwarding state in that order, i.e., `fw_state` is
+    /// non-decreasing along `fixed_order`. Routers that do not change for the scheduled prefix are
+    /// ignored, while routers that do not exist in the network are rejected.
+    pub fixed_order: Option<Vec<RouterId>>,
+}
+
+impl Default for ScheduleOptions {
+    fn default() -> Self {
+        Self {
+            allow_temp_sessions: true,
+            max_rounds_per_router: HashMap::new(),
+            fixed_order: None,
+        }
+    }
+}
+
 /// Structure containing the resulting problem size before solving.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct ProblemSize {
     /// Number of constraints (equations)
     pub rows: usize,
@@ -144,19 +209,23 @@ impl std::fmt::Display for ProblemSize {
 /// Find the optimal schedule for a given prefix in a smart way. We increase the number of steps
 /// until either we use less than the allowed number of temporary sessions, or we exceed the time
 /// budget.
+///
+/// Each model is constrained by `options` (see [`ScheduleOptions`]). If no schedule satisfies them,
+/// `ResolutionError::Infeasible` is returned.
 pub fn schedule_smart<Q>(
     info: &CommandInfo<'_, Q>,
     bgp_deps: &HashMap<P, BgpDependencies>,
     prefix: P,
     time_budget: Duration,
     allowed_temp_sessions: usize,
+    options: &ScheduleOptions,
 ) -> (
     Result<(Schedule, FwStateTrace), ResolutionError>,
     ProblemSize,
 ) {
     let max_steps: usize = info.fw_diff.get(&prefix).map(|x| x.len()).unwrap_or(0);
     if max_steps == 0 {
-        return schedule_with_max_steps(info, bgp_deps, prefix, max_steps, None);
+        return schedule_with_max_steps(info, bgp_deps, prefix, max_steps, None, options);
     }
 
     let mut largest_size = Default::default();
@@ -164,10 +233,26 @@ pub fn schedule_smart<Q>(
     let deadline = start_time + time_budget;
 
     for num_steps in 1..=max_steps {
-        let remaining_budget = deadline.duration_since(Instant::now());
+        let remaining_budget = deadline.saturating_duration_since(Instant::now());
+        if remaining_budget.is_zero() {
+            // the solver would not stop immediately, so do not even start it.
+            return (
+                Err(ResolutionError::Str(format!(
+                    "Time budget is not large enough! Explored {}/{max_steps} steps",
+                    num_steps - 1
+                ))),
+                largest_size,
+            );
+        }
         log::info!("Solving model with {num_steps}/{max_steps} steps");
-        let (result, size) =
-            schedule_with_max_steps(info, bgp_deps, prefix, num_steps, Some(remaining_budget));
+        let (result, size) = schedule_with_max_steps(
+            info,
+            bgp_deps,
+            prefix,
+            num_steps,
+            Some(remaining_budget),
+            options,
+        );
         match result {
             Ok(x) => {
                 log::info!("Found a solution!");
@@ -202,51 +287,139 @@ pub fn schedule_smart<Q>(
     (Err(ResolutionError::Infeasible), largest_size)
 }
 
+/// Find the schedule for a given prefix that minimizes `weight * steps + temp_sessions`, i.e., the
+/// number of steps is scaled by `weight`, while each temporary session costs one. A large weight
+/// results in the schedule with the fewest steps (like [`schedule_smart`]), while a weight of zero
+/// results in the schedule with the fewest temporary sessions. The `weight` must be finite and not
+/// negative. Otherwise, an error is returned.
+///
+/// We increase the number of steps until adding more steps can no longer reduce the cost, or until
+/// we exceed the time budget. In the latter case, the best schedule found so far is returned.
+///
+/// Each model is constrained by `options` (see [`ScheduleOptions`]). If no schedule satisfies them,
+/// `ResolutionError::Infeasible` is returned.
+pub fn schedule_weighted<Q>(
+    info: &CommandInfo<'_, Q>,
+    bgp_deps: &HashMap<P, BgpDependencies>,
+    prefix: P,
+    time_budget: Duration,
+    weight: f64,
+    options: &ScheduleOptions,
+) -> (
+    Result<(Schedule, FwStateTrace), ResolutionError>,
+    ProblemSize,
+) {
+    if !weight.is_finite() || weight < 0.0 {
+        return (
+            Err(ResolutionError::Str(format!(
+                "Invalid weight {weight}! The weight must be finite and not negative."
+            ))),
+            Default::default(),
+        );
+    }
+
+    let max_steps: usize = info.fw_diff.get(&prefix).map(|x| x.len()).unwrap_or(0);
+    if max_steps == 0 {
+        return schedule_with_max_steps(info, bgp_deps, prefix, max_steps, None, options);
+    }
+
+    let mut largest_size = Default::default();
+    let deadline = Instant::now() + time_budget;
+    let mut best: Option<(f64, (Schedule, FwStateTrace), ProblemSize)> = None;
+
+    for num_steps in 1..=max_steps {
+        // temporary sessions cannot be negative, so more steps can never be cheaper.
+        if best
+            .as_ref()
+            .map(|(cost, _, _)| weight * num_steps as f64 >= *cost)
+            .unwrap_or(false)
+        {
+            break;
+        }
+        let remaining_budget = deadline.saturating_duration_since(Instant::now());
+        if remaining_budget.is_zero() {
+            break;
+        }
+        log::info!("Solving model with {num_steps}/{max_steps} steps (weight {weight})");
+        let (result, size) = schedule_with_max_steps(
+            info,
+            bgp_deps,
+            prefix,
+            num_steps,
+            Some(remaining_budget),
+            options,
+        );
+        largest_size = size;
+        match result {
+            Ok(x) => {
+                let temp_sessions: usize = x.0.values().map(NodeSchedule::cost).sum();
+                let cost = weight * num_steps as f64 + temp_sessions as f64;
+                if best.as_ref().map(|(c, _, _)| cost < *c).unwrap_or(true) {
+                    best = Some((cost, x, size));
+                }
+            }
+            Err(_) if Instant::now() >= deadline => break,
+            Err(_) => log::info!("No solution yet! try with more steps."),
+        }
+    }
+
+    match best {
+        Some((_, x, size)) => (Ok(x), size),
+        None if Instant::now() >= deadline => (
+            Err(ResolutionError::Str(String::from(
+                "Time budget is not large enough!",
+            ))),
+            largest_size,
+        ),
+        None => (Err(ResolutionError::Infeasible), largest_size),
+    }
+}
+
 /// Find the optimal schedule for a given prefix
+///
+/// The schedule is constrained by `options` (see [`ScheduleOptions`]). If no schedule satisfies
+/// them, `ResolutionError::Infeasible` is returned. If `options.fixed_order` contains a router that
+/// does not exist in the network, an error is returned.
 pub fn schedule_with_max_steps<Q>(
     info: &CommandInfo<'_, Q>,
     bgp_deps: &HashMap<P, BgpDependencies>,
     prefix: P,
     num_steps: usize,
     timeout: Option<Duration>,
+    options: &ScheduleOptions,
 ) -> (
     Result<(Schedule, FwStateTrace), ResolutionError>,
     ProblemSize,
 ) {
-    // check if the update is empty
-    info!("Prepare the ILP problem to schedule {}", prefix);
-    if bgp_deps.get(&prefix).map(|x| x.is_empty()).unwrap_or(true)
-        && info
-            .fw_diff
-            .get(&prefix)
-            .map(|x| x.is_empty())
-            .unwrap_or(true)
+    // check that all routers in the fixed order exist
+    if let Some(r) = options
+        .fixed_order
+        .iter()
+        .flatten()
+        .find(|r| info.net_before.get_device(**r).is_none())
     {
-        return (Ok(Default::default()), Default::default());
+        return (
+            Err(ResolutionError::Str(format!(
+                "Router {} in the fixed order does not exist!",
+                r.index()
+            ))),
+            Default::default(),
+        );
     }
 
-    // create the variables
-    let (problem, vars) = setup_vars(info, bgp_deps.get(&prefix), prefix, num_steps);
-
-    // create the coin_cbc problem
-    let mut problem = create_solver(problem.minimise(vars.cost));
-
-    // disable logging during tests
-    #[cfg(any(test, feature = "hide-cbc-output"))]
-    {
-        problem.set_parameter("logLevel", "0");
+    // check if the update is empty
+    info!("Prepare the ILP problem to schedule {}", prefix);
+    if is_empty_update(info, bgp_deps, prefix) {
+        return (Ok(Default::default()), Default::default());
     }
 
-    #[cfg(feature = "cbc-parallel")]
-    problem.set_parameter("threads", &format!("{}", num_cpus::get()));
+    // create the problem with all variables and constraints
+    let (mut problem, vars) = setup_problem(info, bgp_deps, prefix, num_steps, options);
 
     if let Some(t) = timeout {
         problem.set_parameter("seconds", &t.as_secs().to_string());
     }
 
-    // create all constraints
-    setup_constraints(&mut problem, &vars, info, bgp_deps.get(&prefix), prefix);
-
     let model = problem.as_inner();
     let size = ProblemSize {
         cols: model.num_cols() as usize,
@@ -267,8 +440,123 @@ pub fn schedule_with_max_steps<Q>(
     let fw_state_trace = check_properties(info, &vars, &solution, prefix);
 
     // build the schedule
-    let schedule = vars
-        .r
+    let schedule = extract_schedule(&vars, &solution);
+
+    (Ok((schedule, fw_state_trace)), size)
+}
+
+/// Enumerate up to `max_solutions` distinct feasible schedules for a given prefix, using
+/// `max_steps` rounds.
+///
+/// After each solution, the problem is solved again with an additional (no-good) constraint that
+/// excludes the forwarding rounds of all previously found solutions. Hence, two schedules differ in
+/// the round in which at least one router changes its forwarding state. For each such ordering, the
+/// solver picks the cheapest `old_route` and `new_route`. The enumeration stops early as soon as no
+/// further solution exists.
+pub fn enumerate_schedules<Q>(
+    info: &CommandInfo<'_, Q>,
+    bgp_deps: &HashMap<P, BgpDependencies>,
+    prefix: P,
+    max_solutions: usize,
+    max_steps: usize,
+) -> Vec<(Schedule, FwStateTrace)> {
+    // check if the update is empty
+    if is_empty_update(info, bgp_deps, prefix) {
+        return std::iter::once(Default::default())
+            .take(max_solutions)
+            .collect();
+    }
+
+    let mut results: Vec<(Schedule, FwStateTrace)> = Vec::new();
+
+    while results.len() < max_solutions {
+        info!(
+            "Searching solution {} for prefix {}",
+            results.len() + 1,
+            prefix
+        );
+
+        // the variables are created anew every time, so we must add all constraints again.
+        let options = ScheduleOptions::default();
+        let (mut problem, vars) = setup_problem(info, bgp_deps, prefix, max_steps, &options);
+
+        // exclude all previous solutions
+        for (schedule, _) in results.iter() {
+            exclude_fw_schedule_constraint(&mut problem, &vars, schedule);
+        }
+
+        let solution = match problem.solve() {
+            Ok(s) => s,
+            Err(e) => {
+                info!("No more solutions: {e}");
+                break;
+            }
+        };
+
+        validate_solution(&vars, &solution);
+        let fw_state_trace = check_properties(info, &vars, &solution, prefix);
+        let schedule = extract_schedule(&vars, &solution);
+        results.push((schedule, fw_state_trace));
+    }
+
+    results
+}
+
+/// Check if the update of `prefix` is empty, i.e., no router changes its forwarding or its BGP
+/// route.
+fn is_empty_update<Q>(
+    info: &CommandInfo<'_, Q>,
+    bgp_deps: &HashMap<P, BgpDependencies>,
+    prefix: P,
+) -> bool {
+    bgp_deps.get(&prefix).map(|x| x.is_empty()).unwrap_or(true)
+        && info
+            .fw_diff
+            .get(&prefix)
+            .map(|x| x.is_empty())
+            .unwrap_or(true)
+}
+
+/// Create the ILP model to schedule `prefix` in `num_steps` rounds, including all variables and
+/// constraints.
+fn setup_problem<Q>(
+    info: &CommandInfo<'_, Q>,
+    bgp_deps: &HashMap<P, BgpDependencies>,
+    prefix: P,
+    num_steps: usize,
+    options: &ScheduleOptions,
+) -> (CoinCbcProblem, IlpVars) {
+    // create the variables
+    let (problem, vars) = setup_vars(info, bgp_deps.get(&prefix), prefix, num_steps);
+
+    // create the coin_cbc problem
+    let mut problem = create_solver(problem.minimise(vars.cost));
+
+    // disable logging during tests
+    #[cfg(any(test, feature = "hide-cbc-output"))]
+    {
+        problem.set_parameter("logLevel", "0");
+    }
+
+    #[cfg(feature = "cbc-parallel")]
+    problem.set_parameter("threads", &format!("{}", num_cpus::get()));
+
+    // create all constraints
+    setup_constraints(
+        &mut problem,
+        &vars,
+        info,
+        bgp_deps.get(&prefix),
+        prefix,
+        options,
+    );
+
+    (problem, vars)
+}
+
+/// Build the schedule out of a solution.
+fn extract_schedule(vars: &IlpVars, solution: &impl Solution) -> Schedule {
+    vars.r
         .keys()
         .map(|r_id| {
             (
@@ -280,9 +568,7 @@ pub fn schedule_with_max_steps<Q>(
                 },
             )
         })
-        .collect();
-
-    (Ok((schedule, fw_state_trace)), size)
+        .collect()
 }
 
 /// Setup all variables needed for the ILP thing to work.
@@ -312,6 +598,8 @@ fn setup_vars<Q>(
     let max_f = max_steps as f64;
 
     let (c, s) = spec_variables(p, info, &all_nodes, max_steps, prefix);
+    #[cfg(not(feature = "explicit-loop-checker"))]
+    let loop_free = loop_free_variables(p, &c, &all_nodes, max_steps);
 
     // Create a variable that tracks the maximum round.
     let vars = IlpVars {
@@ -330,6 +618,8 @@ fn setup_vars<Q>(
         min_max: min_max_variables(p, bgp_deps, max_steps),
         #[cfg(feature = "explicit-loop-checker")]
         loop_protection: loop_protection_variables(p, &all_nodes, max_steps),
+        #[cfg(not(feature = "explicit-loop-checker"))]
+        loop_free,
     };
 
     (problem, vars)
@@ -372,18 +662,29 @@ fn setup_constraints<Q>(
     info: &CommandInfo<'_, Q>,
     bgp_deps: Option<&BgpDependencies>,
     prefix: P,
+    options: &ScheduleOptions,
 ) {
     // setup the cost constraint
     let mut rows = problem.as_inner().num_rows();
     log::debug!("{rows} equations before start");
 
-    setup_cost_constraints(problem, vars);
+    setup_cost_constraints(problem, vars, options);
 
     let new_rows = problem.as_inner().num_rows();
     let delta = new_rows - rows;
     rows = new_rows;
     log::debug!("{delta} equations for `setup_cost_constraints`");
 
+    // enforce the fixed order of routers
+    if let Some(order) = options.fixed_order.as_ref() {
+        fixed_order_constraints(problem, vars, order);
+    }
+
+    let new_rows = problem.as_inner().num_rows();
+    let delta = new_rows - rows;
+    rows = new_rows;
+    log::debug!("{delta} equations for `fixed_order_constraints`");
+
     // setup the bgp propagation constraints
     bgp_propagation_constraints(problem, vars, bgp_deps);
 
@@ -455,8 +756,22 @@ fn setup_constraints<Q>(
     log::debug!("{rows} total equations");
 }
 
+/// Require that the routers in `order` change their forwarding state in that order, i.e.,
+/// `r[a] <= r[b]` for each two consecutive routers `a` and `b` in `order`. Routers that are not part
+/// of the schedule are skipped. Routers that do not exist must be rejected before.
+fn fixed_order_constraints(problem: &mut impl SolverModel, vars: &IlpVars, order: &[RouterId]) {
+    for (a, b) in order.iter().filter_map(|r| vars.r.get(r)).tuple_windows() {
+        let (a, b) = (*a, *b);
+        problem.add_constraint(constraint!(a <= b));
+    }
+}
+
 /// Setup the cost function constraints
-fn setup_cost_constraints(problem: &mut impl SolverModel, vars: &IlpVars) {
+fn setup_cost_constraints(
+    problem: &mut impl SolverModel,
+    vars: &IlpVars,
+    options: &ScheduleOptions,
+) {
     // add the constraints to make max_steps_v be the biggest of all rounds used..
     for a in vars.r.values() {
         problem.add_constraint(constraint!(*a <= vars.max_steps_v));
@@ -465,6 +780,14 @@ fn setup_cost_constraints(problem: &mut impl SolverModel, vars: &IlpVars) {
     // add constraints to build the temporary sessions needed variables.
     temp_session_needed_constraints(problem, vars);
 
+    // forbid any temporary session if requested
+    if !options.allow_temp_sessions {
+        forbid_temp_sessions_constraints(problem, vars);
+    }
+
+    // bound the number of rounds of individual routers
+    max_rounds_per_router_constraints(problem, vars, &options.max_rounds_per_router);
+
     // compute the value for the cost
     let bgp_cost = bgp_cost_expression(vars);
 
@@ -651,14 +974,17 @@ fn check_properties<Q>(
         panic!("Specification violated on complete trace");
     }
 
-    // transfor te plan into an FwStateTrace
-    let plan: FwStateTrace = plan
-        .into_iter()
-        .sorted_by_key(|(i, _)| *i)
-        .map(|(_, x)| x)
-        .collect();
-
-    plan
+    // transfor te plan into an FwStateTrace. Keep empty rounds, such that the index of each step
+    // matches the round of the schedule.
+    let num_rounds = vars
+        .r
+        .values()
+        .map(|r| solution.value(*r).round() as usize + 1)
+        .max()
+        .unwrap_or(0);
+    (0..num_rounds)
+        .map(|i| plan.remove(&i).unwrap_or_default())
+        .collect()
 }
 
 /// Structure for maintaining all variables of the ILP Scheduler
@@ -696,6 +1022,9 @@ pub(self) struct IlpVars {
     // /// Variables to protect against loops.
     #[cfg(feature = "explicit-loop-checker")]
     loop_protection: LoopProtectionType,
+    /// Rank variables to encode loop freedom.
+    #[cfg(not(feature = "explicit-loop-checker"))]
+    loop_free: LoopFreeType,
 }
 
 impl IlpVars {
@@ -0,0 +1,123 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Export the schedule of a decomposition as a TikZ picture.
+
+use bgpsim::prelude::{Network, NetworkFormatter};
+use itertools::Itertools;
+
+use super::Decomposition;
+use crate::P;
+
+const TIKZ_TEMPLATE: &str = r"
+% This file was automatically generated by Chameleon
+\documentclass{standalone}
+
+% latex packages
+\usepackage{tikz}
+
+% color definitions
+\usepackage{xcolor}
+\definecolor{gray-300}{HTML}{D1D5DB}
+\definecolor{gray-700}{HTML}{374151}
+\definecolor{blue-500}{HTML}{3B82F6}
+
+% tikzset styles
+\tikzset{
+  phase/.style = {draw=gray-700, fill=gray-300},
+  route window/.style = {very thick, gray-700},
+  fw bar/.style = {draw=gray-700, fill=blue-500},
+}
+
+\begin{document}
+\begin{tikzpicture}[xscale=1, yscale=0.5]
+{{PHASES}}
+
+{{SCHEDULE}}
+\end{tikzpicture}
+\end{document}
+";
+
+impl Decomposition {
+    /// Export the schedule as a Gantt-like TikZ chart. The top row shows the phases of the
+    /// migration, and every following row shows the schedule of a single router for a single
+    /// prefix. The thick line spans from the round up to which the router knows the old route
+    /// until the round from which it knows the new route, and the bar marks the round in which the
+    /// router changes its forwarding.
+    pub fn to_tikz<Q>(&self, net: &Network<P, Q>) -> String {
+        let num_rounds = self
+            .schedule
+            .values()
+            .flat_map(|s| s.values())
+            .map(|s| s.fw_state.max(s.new_route) + 1)
+            .max()
+            .unwrap_or(1) as isize;
+
+        let phases = [
+            ("setup", -1, 0),
+            ("main", 0, num_rounds),
+            ("cleanup", num_rounds, num_rounds + 1),
+        ]
+        .into_iter()
+        .map(|(name, from, to)| {
+            format!("  \\draw[phase] ({from}, 0.4) rectangle ({to}, -0.4) node[midway] {{{name}}};")
+        })
+        .join("\n");
+
+        let schedule = self
+            .schedule
+            .iter()
+            .sorted_by_key(|(p, _)| **p)
+            .flat_map(|(p, schedule)| {
+                schedule
+                    .iter()
+                    .sorted_by_key(|(r, s)| (s.fw_state, s.old_route, s.new_route, **r))
+                    .map(move |(r, s)| (p, r, s))
+            })
+            .enumerate()
+            .map(|(i, (p, r, s))| {
+                let y = -(i as f64) - 1.0;
+                format!(
+                    "  \\node[anchor=east] at (-1, {y:.1}) {{{} ({p})}};\n  \
+                     \\draw[route window] ({}, {y:.1}) -- ({}, {y:.1});\n  \
+                     \\fill[fw bar] ({}, {:.1}) rectangle ({}, {:.1});",
+                    escape(r.fmt(net)),
+                    s.old_route,
+                    s.new_route + 1,
+                    s.fw_state,
+                    y - 0.3,
+                    s.fw_state + 1,
+                    y + 0.3,
+                )
+            })
+            .join("\n");
+
+        TIKZ_TEMPLATE
+            .replace("{{PHASES}}", &phases)
+            .replace("{{SCHEDULE}}", &schedule)
+    }
+}
+
+/// Escape special LaTeX characters.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\textbackslash{}")
+        .replace('_', "\\_")
+        .replace('&', "\\&")
+        .replace('%', "\\%")
+        .replace('#', "\\#")
+        .replace('$', "\\$")
+}
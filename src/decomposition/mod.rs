@@ -18,7 +18,10 @@
 //! This module is responsible for decomposing a command into atomic commands, as well as finding an
 //! ordering in which to apply them.
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 use bgpsim::{
     bgp::BgpState,
@@ -29,16 +32,19 @@ use bgpsim::{
     types::{NetworkError, RouterId},
 };
 use good_lp::ResolutionError;
-use log::info;
+use itertools::Itertools;
+use log::{info, warn};
 use thiserror::Error;
 
 use crate::{
-    decomposition::ilp_scheduler::{FwStateTrace, NodeSchedule, Schedule},
-    specification::Specification,
+    decomposition::ilp_scheduler::{
+        FwStateTrace, NodeSchedule, ProblemSize, Schedule, ScheduleOptions,
+    },
+    specification::{Checker, Specification},
     P,
 };
 
-use self::bgp_dependencies::BgpDependencies;
+use self::{bgp_dependencies::BgpDependencies, compiler::CompilerOptions};
 
 #[cfg(feature = "explicit-loop-checker")]
 pub(self) mod all_loops;
@@ -46,6 +52,8 @@ pub(self) mod all_loops;
 pub mod bgp_dependencies;
 pub mod compiler;
 pub mod ilp_scheduler;
+#[cfg(feature = "latex-export")]
+mod latex_export;
 
 use atomic_command::{AtomicCommand, AtomicCondition, AtomicModifier};
 
@@ -102,6 +110,86 @@ impl Decomposition {
             atomic_after: Default::default(),
         }
     }
+
+    /// Iterate over all atomic commands in the decomposition, in all phases and for all prefixes.
+    fn all_commands(&self) -> impl Iterator<Item = &AtomicCommand<P>> {
+        self.setup_commands
+            .iter()
+            .chain(self.atomic_before.values().flatten())
+            .chain(self.main_commands.iter())
+            .chain(self.atomic_after.values().flatten())
+            .chain(self.cleanup_commands.iter())
+            .flatten()
+    }
+
+    /// Count the number of commands that either establish or use a temporary BGP session, i.e.,
+    /// `AddTempSession` and `UseTempSession`, without solving the scheduling problem again.
+    pub fn temp_session_count(&self) -> usize {
+        self.all_commands()
+            .filter(|c| {
+                matches!(
+                    c.command,
+                    AtomicModifier::AddTempSession { .. } | AtomicModifier::UseTempSession { .. }
+                )
+            })
+            .count()
+    }
+
+    /// Count the number of rounds across all phases. Prefixes are updated in parallel in the
+    /// `atomic_before` and `atomic_after` phases, so those phases take as many rounds as the prefix
+    /// with the most rounds.
+    pub fn step_count(&self) -> usize {
+        let max_rounds = |stage: &HashMap<P, Vec<Vec<AtomicCommand<P>>>>| {
+            stage.values().map(Vec::len).max().unwrap_or_default()
+        };
+        self.setup_commands.len()
+            + max_rounds(&self.atomic_before)
+            + self.main_commands.len()
+            + max_rounds(&self.atomic_after)
+            + self.cleanup_commands.len()
+    }
+
+    /// Verify that applying all atomic commands of the decomposition (in all phases) yields the same
+    /// forwarding state for every prefix as applying the original command directly. Further, the
+    /// final forwarding state must satisfy the specification.
+    ///
+    /// Both are applied to separate clones of `net`, which must be in its initial state.
+    pub fn verify_endpoints<Q>(
+        &self,
+        net: &Network<P, Q>,
+        spec: &Specification,
+    ) -> Result<(), EndpointMismatch>
+    where
+        Q: EventQueue<P> + Clone,
+    {
+        let mut net_decomp = net.clone();
+        for cmd in self.all_commands() {
+            cmd.command.apply(&mut net_decomp)?;
+        }
+        let mut net_direct = net.clone();
+        net_direct.apply_modifier(&self.original_command)?;
+
+        let mut fw_decomp = net_decomp.get_forwarding_state();
+        let fw_direct = net_direct.get_forwarding_state();
+        let prefixes: HashSet<P> = net_decomp
+            .get_known_prefixes()
+            .chain(net_direct.get_known_prefixes())
+            .copied()
+            .collect();
+        for p in prefixes.into_iter().sorted() {
+            for r in net.get_routers() {
+                if fw_decomp.get_next_hops(r, p) != fw_direct.get_next_hops(r, p) {
+                    return Err(EndpointMismatch::ForwardingState(p, r));
+                }
+            }
+        }
+
+        if !Checker::new(spec).step(&mut fw_decomp) {
+            return Err(EndpointMismatch::Violation);
+        }
+
+        Ok(())
+    }
 }
 
 /// Decompose the command and return a [`Decomposition`].
@@ -110,19 +198,218 @@ pub fn decompose<Q>(
     command: ConfigModifier<P>,
     spec: &Specification,
 ) -> Result<Decomposition, DecompositionError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    decompose_with_metrics(net, command, spec).map(|(decomp, _)| decomp)
+}
+
+/// Metrics describing the problems solved while decomposing a command, for each prefix.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DecompositionMetrics {
+    /// Size of the ILP that was solved to find the schedule.
+    pub problem_size: HashMap<P, ProblemSize>,
+    /// Number of steps of the schedule.
+    pub num_steps: HashMap<P, usize>,
+    /// Number of temporary BGP sessions required by the schedule.
+    pub temp_sessions: HashMap<P, usize>,
+    /// Time spent to find the schedule.
+    pub solve_time: HashMap<P, Duration>,
+}
+
+/// Decompose the command and return a [`Decomposition`], together with the
+/// [`DecompositionMetrics`] of the scheduling problem for each prefix.
+pub fn decompose_with_metrics<Q>(
+    net: &Network<P, Q>,
+    command: ConfigModifier<P>,
+    spec: &Specification,
+) -> Result<(Decomposition, DecompositionMetrics), DecompositionError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    decompose_with_options(net, command, spec, CompilerOptions::default())
+}
+
+/// Decompose the command like [`decompose_with_metrics`], but build the atomic commands using the
+/// given [`CompilerOptions`].
+pub fn decompose_with_options<Q>(
+    net: &Network<P, Q>,
+    command: ConfigModifier<P>,
+    spec: &Specification,
+    options: CompilerOptions,
+) -> Result<(Decomposition, DecompositionMetrics), DecompositionError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    let budget = Duration::from_secs(24 * 60 * 60);
+    decompose_budget(net, command, spec, budget, false, None, options)
+        .map(|(decomp, metrics, _)| (decomp, metrics))
+}
+
+/// Result of [`decompose_with_budget`].
+#[derive(Debug)]
+pub enum BudgetedDecomposition {
+    /// All prefixes were scheduled within the time budget.
+    Complete(Decomposition),
+    /// Some prefixes could not be scheduled within the time budget. The decomposition contains no
+    /// atomic commands for those prefixes, i.e., their forwarding state changes while applying the
+    /// main command without any guarantees. The error describes which prefixes failed.
+    BestEffort(Decomposition, DecompositionError),
+}
+
+impl BudgetedDecomposition {
+    /// Create a complete decomposition if no prefix failed, and a best-effort one otherwise.
+    fn new(decomp: Decomposition, failed: Vec<P>) -> Self {
+        if failed.is_empty() {
+            BudgetedDecomposition::Complete(decomp)
+        } else {
+            BudgetedDecomposition::BestEffort(decomp, DecompositionError::Unscheduled(failed))
+        }
+    }
+}
+
+/// Decompose the command within the given time budget (shared among all prefixes). If some
+/// prefixes cannot be scheduled in time, return a best-effort decomposition that ignores those
+/// prefixes.
+pub fn decompose_with_budget<Q>(
+    net: &Network<P, Q>,
+    command: ConfigModifier<P>,
+    spec: &Specification,
+    budget: Duration,
+) -> Result<BudgetedDecomposition, DecompositionError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    let (decomp, _, failed) = decompose_budget(
+        net,
+        command,
+        spec,
+        budget,
+        true,
+        None,
+        CompilerOptions::default(),
+    )?;
+    Ok(BudgetedDecomposition::new(decomp, failed))
+}
+
+/// Decompose the command within the given time budget (like [`decompose_with_budget`]), but weigh
+/// the cost of each prefix's schedule. For each prefix, the scheduler minimizes the number of steps
+/// scaled by its weight plus the number of temporary sessions (see
+/// [`ilp_scheduler::schedule_weighted`]). Heavy prefixes thus get schedules with fewer steps, while
+/// light prefixes get schedules with fewer temporary sessions. Prefixes missing in
+/// `prefix_weights` have a weight of `1.0`.
+pub fn decompose_with_prefix_weights<Q>(
+    net: &Network<P, Q>,
+    command: ConfigModifier<P>,
+    spec: &Specification,
+    budget: Duration,
+    prefix_weights: &HashMap<P, f64>,
+) -> Result<(BudgetedDecomposition, DecompositionMetrics), DecompositionError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    let (decomp, metrics, failed) = decompose_budget(
+        net,
+        command,
+        spec,
+        budget,
+        true,
+        Some(prefix_weights),
+        CompilerOptions::default(),
+    )?;
+    Ok((BudgetedDecomposition::new(decomp, failed), metrics))
+}
+
+/// Compute a lower bound on the routers that need temporary BGP sessions, for each prefix. This
+/// only uses the BGP dependencies and does not solve any ILP, so it is much faster than
+/// [`decompose`]. Every returned router uses a temporary session in any valid schedule (see
+/// [`bgp_dependencies::forced_temp_sessions`]), but other routers may need them as well.
+pub fn min_temp_sessions<Q>(
+    net: &Network<P, Q>,
+    command: ConfigModifier<P>,
+    spec: &Specification,
+) -> Result<HashMap<P, BTreeSet<RouterId>>, DecompositionError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    let info = CommandInfo::new(net, command, spec)?;
+    let bgp_deps = bgp_dependencies::find_dependencies(&info);
+    Ok(bgp_dependencies::forced_temp_sessions(&info, &bgp_deps))
+}
+
+/// Decompose the command within the time budget. If `best_effort` is set, then prefixes that
+/// cannot be scheduled are skipped and returned. Otherwise, the first scheduling error is
+/// returned. If `prefix_weights` is given, each prefix is scheduled with
+/// [`ilp_scheduler::schedule_weighted`] instead of [`ilp_scheduler::schedule_smart`]. The atomic
+/// commands are built using `options`.
+fn decompose_budget<Q>(
+    net: &Network<P, Q>,
+    command: ConfigModifier<P>,
+    spec: &Specification,
+    budget: Duration,
+    best_effort: bool,
+    prefix_weights: Option<&HashMap<P, f64>>,
+    options: CompilerOptions,
+) -> Result<(Decomposition, DecompositionMetrics, Vec<P>), DecompositionError>
 where
     Q: EventQueue<P> + Clone,
 {
     let info = CommandInfo::new(net, command, spec)?;
     let bgp_deps = bgp_dependencies::find_dependencies(&info);
+    let deadline = Instant::now() + budget;
 
-    let schedules: HashMap<P, (Schedule, FwStateTrace)> = info
-        .prefixes
-        .iter()
-        .map(|p| Ok((*p, ilp_scheduler::schedule(&info, &bgp_deps, *p)?)))
-        .collect::<Result<HashMap<_, _>, DecompositionError>>()?;
+    let mut metrics = DecompositionMetrics::default();
+    let mut failed = Vec::new();
+    let mut schedules: HashMap<P, (Schedule, FwStateTrace)> = HashMap::new();
+    for p in info.prefixes.iter().copied().sorted() {
+        let start_time = Instant::now();
+        let time_budget = deadline.saturating_duration_since(start_time);
+        let (result, size) = match prefix_weights {
+            Some(weights) => ilp_scheduler::schedule_weighted(
+                &info,
+                &bgp_deps,
+                p,
+                time_budget,
+                weights.get(&p).copied().unwrap_or(1.0),
+                &ScheduleOptions::default(),
+            ),
+            None => ilp_scheduler::schedule_smart(
+                &info,
+                &bgp_deps,
+                p,
+                time_budget,
+                usize::MAX,
+                &ScheduleOptions::default(),
+            ),
+        };
+        let (schedule, trace) = match result {
+            Ok(x) => x,
+            Err(e) if best_effort => {
+                warn!("Could not schedule prefix {p}: {e}");
+                failed.push(p);
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        metrics.solve_time.insert(p, start_time.elapsed());
+        metrics.problem_size.insert(p, size);
+        metrics.num_steps.insert(
+            p,
+            schedule
+                .values()
+                .map(|s| s.fw_state + 1)
+                .max()
+                .unwrap_or_default(),
+        );
+        metrics
+            .temp_sessions
+            .insert(p, schedule.values().map(NodeSchedule::cost).sum());
+        schedules.insert(p, (schedule, trace));
+    }
 
-    compiler::build(&info, bgp_deps, schedules)
+    let decomp = compiler::build_with_options(&info, bgp_deps, schedules, options)?;
+    Ok((decomp, metrics, failed))
 }
 
 /// A single forwarding delta, storing the old and the new next-hop
@@ -250,6 +537,21 @@ impl<'n, Q> CommandInfo<'n, Q> {
     }
 }
 
+/// Error when the final state of a decomposition differs from applying the original command (see
+/// [`Decomposition::verify_endpoints`]).
+#[derive(Debug, Error)]
+pub enum EndpointMismatch {
+    /// Error while operating with the Network.
+    #[error("Network Error: {0}")]
+    NetworkError(#[from] NetworkError),
+    /// The forwarding state differs for a given prefix at a given router.
+    #[error("Forwarding state for prefix {0} differs at router {1:?}.")]
+    ForwardingState(P, RouterId),
+    /// The final forwarding state violates the specification.
+    #[error("The final forwarding state violates the specification.")]
+    Violation,
+}
+
 /// Error when decomposing a command
 #[derive(Debug, Error)]
 pub enum DecompositionError {
@@ -268,4 +570,7 @@ pub enum DecompositionError {
     /// The round at which to apply the main command could not be determined
     #[error("Illdefined round at which to apply the main command for prefix {0}: {1}")]
     InconsistentMainCommandRound(P, &'static str),
+    /// Some prefixes could not be scheduled within the time budget.
+    #[error("Could not schedule the prefixes {0:?} within the time budget.")]
+    Unscheduled(Vec<P>),
 }
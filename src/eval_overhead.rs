@@ -31,7 +31,7 @@ use chameleon::{
     decomposition::{
         bgp_dependencies::find_dependencies,
         compiler::build,
-        ilp_scheduler::{schedule_smart, NodeSchedule},
+        ilp_scheduler::{schedule_smart, NodeSchedule, ScheduleOptions},
         CommandInfo,
     },
     experiment::{Experiment, Scenario, _TopologyZoo},
@@ -141,7 +141,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let args = Cli::parse();
 
-    let mut path = generate_folder()?;
+    let path = generate_folder()?;
 
     let topos: Vec<_> = if args.topologies.is_empty() {
         TopologyZoo::topologies_increasing_nodes()
@@ -203,6 +203,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     p,
                     Duration::from_secs(args.timeout),
                     (args.num_allowed_temp_sessions * net.num_devices() as f64).round() as usize,
+                    &ScheduleOptions::default(),
                 );
 
                 let path_len = compute_avg_path_length(&info);
@@ -283,8 +284,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 };
 
-                path.push(format!("{i}_{topo:?}_{spec_kind:?}"));
-
                 // store the experiment
                 Experiment {
                     net: &net,
@@ -305,9 +304,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         fw_state_after: &info.fw_after,
                     },
                 }
-                .write_json(&path)?;
-
-                path.pop();
+                .write_json(&path, format!("{i}_{topo:?}_{spec_kind:?}"))?;
             } // for each repetition
         } // for each scenario
     } // for each topology
@@ -25,7 +25,7 @@ use std::{
 use chameleon::{
     decomposition::{
         bgp_dependencies::find_dependencies,
-        ilp_scheduler::{schedule_with_max_steps, NodeSchedule},
+        ilp_scheduler::{schedule_with_max_steps, NodeSchedule, ScheduleOptions},
         CommandInfo,
     },
     experiment::{Scenario as Event, _TopologyZoo},
@@ -175,6 +175,7 @@ fn run(
                 prefix,
                 steps,
                 timeout.map(|x| Duration::from_secs(x as u64)),
+                &ScheduleOptions::default(),
             );
             let cost: String = match result.as_ref() {
                 Ok((r, _)) => r
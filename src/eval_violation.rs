@@ -138,10 +138,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 rand: args.rand,
                 data: violation_times,
             }
-            .write_json_with_timestamp(format!(
-                "{}/{topo:?}_{scenario:?}_{spec_kind:?}",
-                args.output
-            ))?;
+            .write_json_with_timestamp(
+                &args.output,
+                format!("{topo:?}_{scenario:?}_{spec_kind:?}"),
+            )?;
         }
     }
 
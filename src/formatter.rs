@@ -169,6 +169,50 @@ impl<'a, 'n, Q> NetworkFormatter<'a, 'n, P, Q> for Decomposition {
     }
 }
 
+impl Decomposition {
+    /// Format the decomposition as a human-readable plan. Each line describes a single atomic
+    /// command, together with the phase and step (starting at 1) in which it is applied. Commands of
+    /// the same step can be applied simultaneously.
+    pub fn fmt_plan<Q>(&self, net: &Network<P, Q>) -> String {
+        let fmt_phase = |phase: &str, cmdss: &[Vec<AtomicCommand<P>>]| {
+            cmdss
+                .iter()
+                .enumerate()
+                .flat_map(|(i, cmds)| {
+                    cmds.iter().map(move |c| {
+                        format!(
+                            "Phase {phase}, step {}: {} [pre: {}] [post: {}]",
+                            i + 1,
+                            c.command.fmt(net),
+                            c.precondition.fmt(net),
+                            c.postcondition.fmt(net),
+                        )
+                    })
+                })
+                .collect_vec()
+        };
+
+        let before = self
+            .atomic_before
+            .iter()
+            .sorted_by_key(|(p, _)| **p)
+            .flat_map(|(p, cmdss)| fmt_phase(&format!("before ({p})"), cmdss));
+        let after = self
+            .atomic_after
+            .iter()
+            .sorted_by_key(|(p, _)| **p)
+            .flat_map(|(p, cmdss)| fmt_phase(&format!("after ({p})"), cmdss));
+
+        fmt_phase("setup", &self.setup_commands)
+            .into_iter()
+            .chain(before)
+            .chain(fmt_phase("main", &self.main_commands))
+            .chain(after)
+            .chain(fmt_phase("cleanup", &self.cleanup_commands))
+            .join("\n")
+    }
+}
+
 impl<'a, 'n, Q> NetworkFormatter<'a, 'n, P, Q> for Controller {
     type Formatter = String;
 
@@ -281,7 +325,10 @@ impl<'a, 'n, Q> NetworkFormatter<'a, 'n, P, Q> for Property {
             Property::Any(x) => format!("({})", x.iter().map(|p| p.fmt(net)).join(" || ")),
             Property::Not(x) => format!("!{}", x.fmt(net)),
             Property::Waypoint(wp) => wp.fmt(net).to_string(),
+            Property::MaxPathLength(max) => format!("len<={max}"),
+            Property::LoopFree => String::from("loop-free"),
             Property::Reachability => String::from("reach"),
+            Property::Isolation => String::from("isolated"),
             Property::True => String::from('t'),
         }
     }
@@ -90,17 +90,23 @@ pub mod specification;
 mod test;
 
 pub use bgpsim::types::{RouterId, SimplePrefix as P};
-pub use decomposition::{decompose, Decomposition};
+pub use decomposition::{
+    compiler::CompilerOptions, decompose, decompose_with_budget, decompose_with_metrics,
+    decompose_with_options, decompose_with_prefix_weights, BudgetedDecomposition, Decomposition,
+    DecompositionMetrics,
+};
 
 #[cfg(feature = "experiment")]
 #[cfg_attr(docsrs, doc(cfg(feature = "experiment")))]
 /// Export an experiment result to a json file, including all metadata.
 pub mod experiment {
     use std::{
+        collections::HashSet,
         ffi::OsStr,
-        fs::{remove_file, OpenOptions},
+        fs::{create_dir_all, read_to_string, remove_file, OpenOptions},
         io::Write,
-        path::{Path, PathBuf},
+        iter::once,
+        path::Path,
     };
 
     use crate::{
@@ -111,15 +117,20 @@ pub mod experiment {
     use super::P;
     use bgpsim::{
         builder::{
-            constant_link_weight, k_random_nodes, uniform_integer_link_weight, NetworkBuilder,
+            constant_link_weight, k_random_nodes_seeded, uniform_integer_link_weight_seeded,
+            NetworkBuilder,
         },
         config::{ConfigExpr, ConfigModifier, NetworkConfig},
         event::{EventQueue, FmtPriority},
+        forwarding_state::ForwardingState,
         prelude::{BgpSessionType, Network},
-        topology_zoo::TopologyZoo,
+        topology_zoo::{TopologyZoo, TopologyZooError, TopologyZooParser},
         types::{NetworkError, RouterId},
     };
     use clap::ValueEnum;
+    use itertools::{iproduct, Itertools};
+    use rand::{seq::SliceRandom, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
     use serde::{Deserialize, Serialize};
     use thiserror::Error;
     use time::{format_description, OffsetDateTime};
@@ -131,6 +142,12 @@ pub mod experiment {
         NewBestRoute,
         /// Withdraw the old best route.
         DelBestRoute,
+        /// Change the weight of an internal link, such that some routers select a different egress
+        /// (due to hot-potato routing).
+        ChangeLinkWeight,
+        /// Replace one route reflector by a router that was previously a route-reflector client.
+        /// This scenario requires multiple commands, see [`Scenario::build_commands`].
+        MoveRouteReflector,
     }
 
     /// Error thrown while building a scenario
@@ -139,16 +156,36 @@ pub mod experiment {
         /// Network error occurred.
         #[error("{0}")]
         Network(#[from] NetworkError),
+        /// The GraphML topology could not be parsed.
+        #[error("{0}")]
+        TopologyZoo(#[from] TopologyZooError),
         /// external BGP session missing
         #[error("Missing an eBGP session with the external router {0:?}")]
         NoBgpSession(RouterId),
         /// Initial or final forwarding state violate reachability
         #[error("Initial or final forwarding state violate reachability.")]
         ConvergenceViolated,
+        /// The network has no internal link whose weight could be changed
+        #[error("The network has no internal link.")]
+        NoInternalLink,
+        /// Changing the weight of any internal link does not cause any router to change its egress
+        #[error("Changing the weight of any internal link does not change any egress.")]
+        NoEgressChange,
+        /// The network has no router that can become a route reflector
+        #[error("The network has no router that can become a route reflector.")]
+        NoRouteReflectorCandidate,
+        /// The scenario requires multiple commands.
+        #[error("The scenario requires {0} commands, but only a single command is supported.")]
+        MultipleCommands(usize),
+        /// The scenario must contain at least one prefix.
+        #[error("The scenario must contain at least one prefix.")]
+        NoPrefixes,
     }
 
     impl Scenario {
         /// Generate and configure the network appropriately, and generate the reconfiguration command.
+        /// This function fails with [`ScenarioBuildError::MultipleCommands`] for scenarios that
+        /// require multiple commands.
         #[allow(clippy::type_complexity)]
         pub fn build<Q>(
             &self,
@@ -160,20 +197,139 @@ pub mod experiment {
             Q: EventQueue<P> + Clone,
             Q::Priority: FmtPriority,
         {
-            let p = P::from(1);
-            let mut net = topo.build(queue);
+            let (net, p, mut cs) = self.build_commands(topo, queue, randomized)?;
+            if cs.len() != 1 {
+                return Err(ScenarioBuildError::MultipleCommands(cs.len()));
+            }
+            Ok((net, p, cs.pop().unwrap()))
+        }
 
-            let ads = if randomized {
-                let ext = net.build_external_routers(k_random_nodes, 3)?;
-                net.build_link_weights(uniform_integer_link_weight, (10, 100))?;
-                net.build_ibgp_route_reflection(k_random_nodes, 3)?;
+        /// Generate and configure the network appropriately, and generate the sequence of
+        /// reconfiguration commands.
+        #[allow(clippy::type_complexity)]
+        pub fn build_commands<Q>(
+            &self,
+            topo: TopologyZoo,
+            queue: Q,
+            randomized: bool,
+        ) -> Result<(Network<P, Q>, P, Vec<ConfigModifier<P>>), ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+            Q::Priority: FmtPriority,
+        {
+            let rng = randomized.then(ChaCha8Rng::from_entropy);
+            let (net, mut ps, cs) = self.build_prefixes(topo.build(queue), Some(topo), rng, 1)?;
+            Ok((net, ps.pop().unwrap(), cs))
+        }
+
+        /// Generate and configure the network with random egresses, route reflectors and link
+        /// weights (like [`Scenario::build`] with `randomized` set), using a `ChaCha8Rng`
+        /// initialized with the given `seed`. Building a scenario twice with the same seed results
+        /// in the same network and command, also across platforms and versions of `rand`.
+        #[allow(clippy::type_complexity)]
+        pub fn build_seeded<Q>(
+            &self,
+            topo: TopologyZoo,
+            queue: Q,
+            seed: u64,
+        ) -> Result<(Network<P, Q>, P, ConfigModifier<P>), ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+            Q::Priority: FmtPriority,
+        {
+            let rng = Some(ChaCha8Rng::seed_from_u64(seed));
+            let (net, mut ps, mut cs) =
+                self.build_prefixes(topo.build(queue), Some(topo), rng, 1)?;
+            if cs.len() != 1 {
+                return Err(ScenarioBuildError::MultipleCommands(cs.len()));
+            }
+            Ok((net, ps.pop().unwrap(), cs.pop().unwrap()))
+        }
+
+        /// Generate and configure the network appropriately with `num_prefixes` prefixes, and
+        /// generate the reconfiguration command. The first prefix uses the same preferences as
+        /// [`Scenario::build`]. For all other prefixes, the order of the egresses is rotated, or
+        /// shuffled if `randomized` is set.
+        #[allow(clippy::type_complexity)]
+        pub fn build_multi<Q>(
+            &self,
+            topo: TopologyZoo,
+            queue: Q,
+            num_prefixes: usize,
+            randomized: bool,
+        ) -> Result<(Network<P, Q>, Vec<P>, ConfigModifier<P>), ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+            Q::Priority: FmtPriority,
+        {
+            let rng = randomized.then(ChaCha8Rng::from_entropy);
+            let (net, ps, mut cs) =
+                self.build_prefixes(topo.build(queue), Some(topo), rng, num_prefixes)?;
+            if cs.len() != 1 {
+                return Err(ScenarioBuildError::MultipleCommands(cs.len()));
+            }
+            Ok((net, ps, cs.pop().unwrap()))
+        }
+
+        /// Generate and configure the network from the content of an arbitrary GraphML file (in
+        /// the format of the topology zoo), and generate the reconfiguration command. This function
+        /// behaves like [`Scenario::build`], but it uses the given topology instead of one from
+        /// [`TopologyZoo`].
+        #[allow(clippy::type_complexity)]
+        pub fn build_from_graphml<Q>(
+            &self,
+            graphml_content: &str,
+            queue: Q,
+            randomized: bool,
+        ) -> Result<(Network<P, Q>, P, ConfigModifier<P>), ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+            Q::Priority: FmtPriority,
+        {
+            let net = TopologyZooParser::new(graphml_content)?.get_network(queue)?;
+            let rng = randomized.then(ChaCha8Rng::from_entropy);
+            let (net, mut ps, mut cs) = self.build_prefixes(net, None, rng, 1)?;
+            if cs.len() != 1 {
+                return Err(ScenarioBuildError::MultipleCommands(cs.len()));
+            }
+            Ok((net, ps.pop().unwrap(), cs.pop().unwrap()))
+        }
+
+        /// Configure the network `net` with `num_prefixes` prefixes, and generate the sequence of
+        /// reconfiguration commands. If `rng` is given, then the network is configured randomly.
+        /// `topo` is the topology from which `net` was built (if any).
+        #[allow(clippy::type_complexity)]
+        fn build_prefixes<Q>(
+            &self,
+            mut net: Network<P, Q>,
+            topo: Option<TopologyZoo>,
+            mut rng: Option<ChaCha8Rng>,
+            num_prefixes: usize,
+        ) -> Result<(Network<P, Q>, Vec<P>, Vec<ConfigModifier<P>>), ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+            Q::Priority: FmtPriority,
+        {
+            if num_prefixes == 0 {
+                return Err(ScenarioBuildError::NoPrefixes);
+            }
+            let prefixes = (1..=num_prefixes).map(P::from).collect::<Vec<_>>();
+            let p = prefixes[0];
+
+            let (ext, rrs) = if let Some(rng) = rng.as_mut() {
+                let ext = net.build_external_routers(k_random_nodes_seeded, (&mut *rng, 3))?;
+                net.build_link_weights_seeded(
+                    &mut *rng,
+                    uniform_integer_link_weight_seeded,
+                    (10, 100),
+                )?;
+                let rrs = net.build_ibgp_route_reflection(k_random_nodes_seeded, (&mut *rng, 3))?;
                 net.build_ebgp_sessions()?;
-                let preferences = vec![vec![ext[0]], vec![ext[1], ext[2]]];
-                net.build_advertisements(p, |_, _| preferences, ())?
+                (ext, rrs)
             } else {
                 let mut r = net.get_routers();
                 r.sort();
-                let (egresses, rrs) = if topo == TopologyZoo::Abilene {
+                let (egresses, rrs) = if topo == Some(TopologyZoo::Abilene) {
                     (
                         vec![
                             net.get_router_id("NewYork")?,
@@ -201,12 +357,24 @@ pub mod experiment {
                 };
                 let ext = net.build_external_routers(|_, _| egresses, ())?;
                 net.build_link_weights(constant_link_weight, 1.0)?;
-                net.build_ibgp_route_reflection(|_, _| rrs, ())?;
+                let rrs = net.build_ibgp_route_reflection(|_, _| rrs, ())?;
                 net.build_ebgp_sessions()?;
-                let preferences = vec![vec![ext[0]], vec![ext[1], ext[2]]];
-                net.build_advertisements(p, |_, _| preferences, ())?
+                (ext, rrs)
             };
 
+            let preferences = self.preferences(&ext);
+            let ads = net.build_advertisements(p, |_, _| preferences, ())?;
+            for (i, p) in prefixes.iter().enumerate().skip(1) {
+                let mut ext = ext.clone();
+                if let Some(rng) = rng.as_mut() {
+                    ext.shuffle(rng);
+                } else {
+                    ext.rotate_left(i % ext.len());
+                }
+                let preferences = self.preferences(&ext);
+                net.build_advertisements(*p, |_, _| preferences, ())?;
+            }
+
             let e = ads[0][0];
             let r = match net
                 .get_device(e)
@@ -219,36 +387,149 @@ pub mod experiment {
                 None => return Err(ScenarioBuildError::NoBgpSession(e)),
             };
 
-            let c = match self {
+            let cs = match self {
                 Scenario::NewBestRoute => {
                     net.set_bgp_session(r, e, None)?;
-                    ConfigModifier::Insert(ConfigExpr::<P>::BgpSession {
+                    vec![ConfigModifier::Insert(ConfigExpr::<P>::BgpSession {
                         source: r,
                         target: e,
                         session_type: BgpSessionType::EBgp,
-                    })
+                    })]
+                }
+                Scenario::DelBestRoute => {
+                    vec![ConfigModifier::Remove(ConfigExpr::<P>::BgpSession {
+                        source: r,
+                        target: e,
+                        session_type: BgpSessionType::EBgp,
+                    })]
                 }
-                Scenario::DelBestRoute => ConfigModifier::Remove(ConfigExpr::<P>::BgpSession {
-                    source: r,
-                    target: e,
-                    session_type: BgpSessionType::EBgp,
-                }),
+                Scenario::ChangeLinkWeight => vec![Self::link_weight_command(&net, p)?],
+                Scenario::MoveRouteReflector => Self::move_rr_commands(&net, &rrs)?,
             };
 
             // check the initial and final states.
             let mut initial_fw = net.get_forwarding_state();
             let mut tmp_net = net.clone();
-            tmp_net.apply_modifier(&c)?;
+            for c in cs.iter() {
+                tmp_net.apply_modifier(c)?;
+            }
             let mut final_fw = tmp_net.get_forwarding_state();
-            if net
-                .get_routers()
-                .into_iter()
-                .any(|r| initial_fw.get_paths(r, p).is_err() || final_fw.get_paths(r, p).is_err())
-            {
+            if iproduct!(net.get_routers(), prefixes.iter().copied()).any(|(r, p)| {
+                initial_fw.get_paths(r, p).is_err() || final_fw.get_paths(r, p).is_err()
+            }) {
                 return Err(ScenarioBuildError::ConvergenceViolated);
             }
 
-            Ok((net, p, c))
+            Ok((net, prefixes, cs))
+        }
+
+        /// Get the preferences of the advertisements of the three external routers. For
+        /// `ChangeLinkWeight`, all routes are equally preferred, such that each router selects the
+        /// closest egress.
+        fn preferences(&self, ext: &[RouterId]) -> Vec<Vec<RouterId>> {
+            match self {
+                Scenario::NewBestRoute | Scenario::DelBestRoute | Scenario::MoveRouteReflector => {
+                    vec![vec![ext[0]], vec![ext[1], ext[2]]]
+                }
+                Scenario::ChangeLinkWeight => vec![ext[0..3].to_vec()],
+            }
+        }
+
+        /// Generate the command that increases the weight of an internal link by a factor of 10.
+        /// Links are tried in a deterministic order, and the first one that causes any router to
+        /// change its egress is chosen. If no link causes a change of egress, this function returns
+        /// [`ScenarioBuildError::NoEgressChange`].
+        ///
+        /// Link weights are directional, and only the weight in the direction that changes the
+        /// egress is updated. This keeps the scenario a single command (as required by
+        /// [`Scenario::build`]), and the reverse direction is not needed to cause the change.
+        fn link_weight_command<Q>(
+            net: &Network<P, Q>,
+            p: P,
+        ) -> Result<ConfigModifier<P>, ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+        {
+            let routers = net.get_routers();
+            let g = net.get_topology();
+            let links = g
+                .edge_indices()
+                .filter_map(|e| g.edge_endpoints(e))
+                .filter(|(a, b)| routers.contains(a) && routers.contains(b))
+                .sorted()
+                .collect::<Vec<_>>();
+
+            let egress = |fw: &mut ForwardingState<P>, r: RouterId| {
+                fw.get_paths(r, p)
+                    .ok()
+                    .and_then(|paths| paths.first().and_then(|path| path.last().copied()))
+            };
+
+            if links.is_empty() {
+                return Err(ScenarioBuildError::NoInternalLink);
+            }
+
+            let mut initial_fw = net.get_forwarding_state();
+            for (source, target) in links {
+                let weight = net.get_link_weigth(source, target)?;
+                let c = ConfigModifier::Update {
+                    from: ConfigExpr::IgpLinkWeight {
+                        source,
+                        target,
+                        weight,
+                    },
+                    to: ConfigExpr::IgpLinkWeight {
+                        source,
+                        target,
+                        weight: weight * 10.0,
+                    },
+                };
+                let mut tmp_net = net.clone();
+                tmp_net.apply_modifier(&c)?;
+                let mut final_fw = tmp_net.get_forwarding_state();
+                if routers
+                    .iter()
+                    .any(|r| egress(&mut initial_fw, *r) != egress(&mut final_fw, *r))
+                {
+                    return Ok(c);
+                }
+            }
+
+            Err(ScenarioBuildError::NoEgressChange)
+        }
+
+        /// Generate the commands that replace the route reflector with the smallest router id by
+        /// the route-reflector client with the smallest router id.
+        fn move_rr_commands<Q>(
+            net: &Network<P, Q>,
+            rrs: &HashSet<RouterId>,
+        ) -> Result<Vec<ConfigModifier<P>>, ScenarioBuildError>
+        where
+            Q: EventQueue<P> + Clone,
+        {
+            let routers = net.get_routers().into_iter().sorted().collect::<Vec<_>>();
+            let old_rr = routers.iter().find(|r| rrs.contains(r));
+            let new_rr = routers.iter().find(|r| !rrs.contains(r));
+            let (old_rr, new_rr) = match (old_rr, new_rr) {
+                (Some(old_rr), Some(new_rr)) => (*old_rr, *new_rr),
+                _ => return Err(ScenarioBuildError::NoRouteReflectorCandidate),
+            };
+            let new_rrs = rrs
+                .iter()
+                .copied()
+                .filter(|r| *r != old_rr)
+                .chain(once(new_rr))
+                .collect::<Vec<_>>();
+
+            let mut new_net = net.clone();
+            new_net.build_ibgp_route_reflection(|_, _| new_rrs, ())?;
+            Ok(net
+                .get_config()?
+                .get_diff(&new_net.get_config()?)
+                .modifiers
+                .into_iter()
+                .sorted_by_key(|c| c.routers())
+                .collect())
         }
     }
 
@@ -278,10 +559,11 @@ pub mod experiment {
         T: Serialize,
         Q: EventQueue<P> + Serialize,
     {
-        /// Write the json file adding to the filename `_DATE.json`. If the file already exists,
-        /// append an increasing number to the filename.
+        /// Write the json file into `output_dir`, adding to the filename `_DATE.json`. If the file
+        /// already exists, append an increasing number to the filename.
         pub fn write_json_with_timestamp(
             &self,
+            output_dir: impl AsRef<Path>,
             file: impl AsRef<str>,
         ) -> Result<(), std::io::Error> {
             let cur_time = OffsetDateTime::now_local()
@@ -298,20 +580,24 @@ pub mod experiment {
                 } else {
                     format!("{}_{cur_time}.json", file.as_ref())
                 };
-                let file = PathBuf::from(filename);
-                if !file.exists() {
-                    break file;
+                if !output_dir.as_ref().join(&filename).exists() {
+                    break filename;
                 }
                 offset = Some(offset.unwrap_or_default() + 1);
             };
 
-            self.write_json(file)
+            self.write_json(output_dir, file)
         }
 
-        /// Write the content of the experiment to a json file.
+        /// Write the content of the experiment to a json file in `output_dir`. The directory is
+        /// created if it does not exist yet.
         ///
         /// This function will overwrite any existing file.
-        pub fn write_json(&self, file: impl AsRef<OsStr>) -> Result<(), std::io::Error> {
+        pub fn write_json(
+            &self,
+            output_dir: impl AsRef<Path>,
+            file: impl AsRef<OsStr>,
+        ) -> Result<(), std::io::Error> {
             #[derive(Debug, Serialize)]
             #[allow(clippy::missing_docs_in_private_items)]
             pub struct ExportExperiment<'a, 'b, S, T> {
@@ -335,14 +621,56 @@ pub mod experiment {
             })
             .unwrap();
 
-            let file = Path::new(file.as_ref());
-            if file.exists() {
-                remove_file(file)?;
-            }
-            let mut file = OpenOptions::new().create(true).write(true).open(file)?;
-            writeln!(file, "{exp_str}")?;
-            Ok(())
+            write_file(output_dir, file, exp_str)
+        }
+    }
+
+    /// A decomposition together with its specification, stored to inspect it offline without
+    /// running it.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct DecompositionExport {
+        /// Specification for which the decomposition was computed
+        pub spec: Specification,
+        /// The decomposition
+        pub decomp: Decomposition,
+    }
+
+    impl DecompositionExport {
+        /// Write the decomposition and the specification to a json file in `output_dir`. The
+        /// directory is created if it does not exist yet.
+        ///
+        /// This function will overwrite any existing file.
+        pub fn write_json(
+            &self,
+            output_dir: impl AsRef<Path>,
+            file: impl AsRef<OsStr>,
+        ) -> Result<(), std::io::Error> {
+            let export_str = serde_json::to_string_pretty(self)?;
+            write_file(output_dir, file, export_str)
         }
+
+        /// Read the decomposition and the specification from a json file.
+        pub fn read_json(file: impl AsRef<OsStr>) -> Result<Self, std::io::Error> {
+            let export_str = read_to_string(Path::new(file.as_ref()))?;
+            Ok(serde_json::from_str(&export_str)?)
+        }
+    }
+
+    /// Write the content into the file `output_dir/file`, creating `output_dir` if necessary and
+    /// overwriting any existing file.
+    fn write_file(
+        output_dir: impl AsRef<Path>,
+        file: impl AsRef<OsStr>,
+        content: String,
+    ) -> Result<(), std::io::Error> {
+        create_dir_all(output_dir.as_ref())?;
+        let file = output_dir.as_ref().join(file.as_ref());
+        if file.exists() {
+            remove_file(&file)?;
+        }
+        let mut file = OpenOptions::new().create(true).write(true).open(file)?;
+        writeln!(file, "{content}")?;
+        Ok(())
     }
 
     /// Wrapping type for TopologyZoo that implements ValueEnum
@@ -376,7 +704,10 @@ pub mod experiment {
 }
 
 #[cfg(feature = "export-web")]
-pub use export_web::export_web;
+pub use export_web::{
+    export_web, export_web_bundle, web_bundle, WebBundle, WebMigration, WebPhasePolicies,
+    WebPolicies,
+};
 
 /// Module for exporting the migration for `bgpsim-web`.
 #[cfg(feature = "export-web")]
@@ -392,20 +723,132 @@ mod export_web {
         policies::{FwPolicy, Policy, PolicyError},
         prelude::*,
     };
-    use serde::ser::Serialize;
+    use serde::{Deserialize, Serialize};
     use serde_json::Value;
-    use std::{collections::HashMap, fs::OpenOptions, io::Write};
+    use std::{
+        collections::HashMap,
+        fs::{create_dir_all, OpenOptions},
+        io::Write,
+        path::Path,
+    };
+
+    /// A migration as understood by `bgpsim-web`, i.e., a sequence of phases, each consisting of a
+    /// sequence of steps, each containing commands that can be executed simultaneously.
+    pub type WebMigration = Vec<Vec<Vec<AtomicCommand<P>>>>;
+
+    /// Policies as understood by `bgpsim-web`.
+    pub type WebPolicies = HashMap<RouterId, Vec<(FwPolicy<P>, Result<(), PolicyError<P>>)>>;
+
+    /// Status of all policies after each phase of a migration (in the same order as the phases).
+    pub type WebPhasePolicies = Vec<WebPolicies>;
+
+    /// Bundle containing the network, the policies, and both the atomic and the naive migration,
+    /// such that `bgpsim-web` can compare both migrations.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct WebBundle {
+        /// The network, as exported by [`Network::as_json_str`].
+        pub net: Value,
+        /// The specification, expressed as forwarding policies.
+        pub spec: WebPolicies,
+        /// The migration computed by Chameleon.
+        pub atomic: WebMigration,
+        /// The naive migration, applying the original command at once.
+        pub naive: WebMigration,
+        /// Status of the specification after each phase of the atomic migration.
+        pub atomic_spec: WebPhasePolicies,
+        /// Status of the specification after each phase of the naive migration.
+        pub naive_spec: WebPhasePolicies,
+    }
 
-    /// Export the network, the policies and the decomposition to a json file to import into `bgpsim-web`.
+    /// Export the network, the policies and the decomposition to the json files
+    /// `{output_dir}/{filename}_atomic.json` and `{output_dir}/{filename}_naive.json` to import into
+    /// `bgpsim-web`. Each file also contains the status of all policies after each phase of the
+    /// migration (`phase_spec`).
     pub fn export_web<Q>(
         net: &Network<P, Q>,
         spec: &Specification,
         decomp: Decomposition,
+        output_dir: impl AsRef<Path>,
         filename: impl AsRef<str>,
     ) -> Result<(), Box<dyn std::error::Error>>
     where
-        Q: EventQueue<P> + Serialize,
+        Q: EventQueue<P> + Clone + Serialize,
+    {
+        let (atomic_migration, instant_migration) = migrations(decomp);
+        let policies = web_policies(net, spec);
+        let atomic_spec = phase_policies(net, &policies, &atomic_migration)?;
+        let instant_spec = phase_policies(net, &policies, &instant_migration)?;
+
+        let mut json_obj = serde_json::from_str::<Value>(&net.as_json_str())?;
+        let obj = json_obj.as_object_mut().unwrap();
+        obj.insert("spec".to_string(), serde_json::to_value(policies)?);
+        obj.insert(
+            "migration".to_string(),
+            serde_json::to_value(atomic_migration)?,
+        );
+        obj.insert("phase_spec".to_string(), serde_json::to_value(atomic_spec)?);
+        let s = serde_json::to_string(&json_obj).unwrap();
+        write_file(&output_dir, format!("{}_atomic.json", filename.as_ref()), s)?;
+
+        let obj = json_obj.as_object_mut().unwrap();
+        obj.insert(
+            "migration".to_string(),
+            serde_json::to_value(instant_migration)?,
+        );
+        obj.insert(
+            "phase_spec".to_string(),
+            serde_json::to_value(instant_spec)?,
+        );
+        let s = serde_json::to_string(&json_obj).unwrap();
+        write_file(&output_dir, format!("{}_naive.json", filename.as_ref()), s)?;
+
+        Ok(())
+    }
+
+    /// Export the network, the policies, and both the atomic and the naive migration into a single
+    /// json file (`{output_dir}/{filename}_bundle.json`) to import into `bgpsim-web`.
+    pub fn export_web_bundle<Q>(
+        net: &Network<P, Q>,
+        spec: &Specification,
+        decomp: Decomposition,
+        output_dir: impl AsRef<Path>,
+        filename: impl AsRef<str>,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        Q: EventQueue<P> + Clone + Serialize,
+    {
+        let bundle = web_bundle(net, spec, decomp)?;
+        let s = serde_json::to_string(&bundle)?;
+        write_file(&output_dir, format!("{}_bundle.json", filename.as_ref()), s)?;
+        Ok(())
+    }
+
+    /// Create the bundle containing the network, the policies, and both migrations. The policies
+    /// are evaluated after each phase by replaying both migrations in the simulator.
+    pub fn web_bundle<Q>(
+        net: &Network<P, Q>,
+        spec: &Specification,
+        decomp: Decomposition,
+    ) -> Result<WebBundle, Box<dyn std::error::Error>>
+    where
+        Q: EventQueue<P> + Clone + Serialize,
     {
+        let (atomic, naive) = migrations(decomp);
+        let spec = web_policies(net, spec);
+        let atomic_spec = phase_policies(net, &spec, &atomic)?;
+        let naive_spec = phase_policies(net, &spec, &naive)?;
+        Ok(WebBundle {
+            net: serde_json::from_str(&net.as_json_str())?,
+            spec,
+            atomic,
+            naive,
+            atomic_spec,
+            naive_spec,
+        })
+    }
+
+    /// Get the atomic and the naive migration from the decomposition.
+    fn migrations(decomp: Decomposition) -> (WebMigration, WebMigration) {
         let Decomposition {
             setup_commands,
             cleanup_commands,
@@ -416,7 +859,7 @@ mod export_web {
             ..
         } = decomp;
 
-        let atomic_migration: Vec<Vec<Vec<AtomicCommand<P>>>> = vec![
+        let atomic_migration: WebMigration = vec![
             setup_commands,
             atomic_before.into_values().flatten().collect(),
             main_commands,
@@ -424,17 +867,18 @@ mod export_web {
             cleanup_commands,
         ];
 
-        let instant_migration: Vec<Vec<Vec<AtomicCommand<P>>>> = vec![vec![vec![AtomicCommand {
+        let instant_migration: WebMigration = vec![vec![vec![AtomicCommand {
             command: AtomicModifier::Raw(original_command),
             precondition: AtomicCondition::None,
             postcondition: AtomicCondition::None,
         }]]];
 
-        #[allow(clippy::type_complexity)]
-        let mut policies: HashMap<
-            RouterId,
-            Vec<(FwPolicy<P>, Result<(), PolicyError<P>>)>,
-        > = HashMap::new();
+        (atomic_migration, instant_migration)
+    }
+
+    /// Transform the specification into forwarding policies for each router.
+    fn web_policies<Q>(net: &Network<P, Q>, spec: &Specification) -> WebPolicies {
+        let mut policies: WebPolicies = HashMap::new();
         for (prefix, expr) in spec.clone() {
             for invariant in expr.as_global_invariants(net) {
                 for policy in invariant.as_fw_policies(net, prefix) {
@@ -446,35 +890,54 @@ mod export_web {
                 }
             }
         }
+        policies
+    }
 
-        let mut json_obj = serde_json::from_str::<Value>(&net.as_json_str())?;
-        let obj = json_obj.as_object_mut().unwrap();
-        obj.insert("spec".to_string(), serde_json::to_value(policies)?);
-        obj.insert(
-            "migration".to_string(),
-            serde_json::to_value(atomic_migration)?,
-        );
-        let s = serde_json::to_string(&json_obj).unwrap();
-        let mut f = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(format!("./{}_atomic.json", filename.as_ref()))?;
-        f.write_all(s.as_bytes())?;
+    /// Replay the migration on a copy of the network, and evaluate all policies after each phase.
+    fn phase_policies<Q>(
+        net: &Network<P, Q>,
+        policies: &WebPolicies,
+        migration: &WebMigration,
+    ) -> Result<WebPhasePolicies, NetworkError>
+    where
+        Q: EventQueue<P> + Clone,
+    {
+        let mut net = net.clone();
+        let mut result = Vec::with_capacity(migration.len());
+        for phase in migration {
+            for cmd in phase.iter().flatten() {
+                cmd.command.apply(&mut net)?;
+            }
+            let mut fw_state = net.get_forwarding_state();
+            result.push(
+                policies
+                    .iter()
+                    .map(|(r, ps)| {
+                        let ps = ps
+                            .iter()
+                            .map(|(policy, _)| (policy.clone(), policy.check(&mut fw_state)))
+                            .collect();
+                        (*r, ps)
+                    })
+                    .collect(),
+            );
+        }
+        Ok(result)
+    }
 
-        let obj = json_obj.as_object_mut().unwrap();
-        obj.insert(
-            "migration".to_string(),
-            serde_json::to_value(instant_migration)?,
-        );
-        let s = serde_json::to_string(&json_obj).unwrap();
+    /// Write the string into the file `output_dir/filename`, creating `output_dir` if necessary and
+    /// overwriting any existing content.
+    fn write_file(
+        output_dir: impl AsRef<Path>,
+        filename: String,
+        content: String,
+    ) -> Result<(), std::io::Error> {
+        create_dir_all(output_dir.as_ref())?;
         let mut f = OpenOptions::new()
             .create(true)
             .truncate(true)
             .write(true)
-            .open(format!("./{}_naive.json", filename.as_ref()))?;
-        f.write_all(s.as_bytes())?;
-
-        Ok(())
+            .open(output_dir.as_ref().join(filename))?;
+        f.write_all(content.as_bytes())
     }
 }
@@ -17,28 +17,52 @@
 
 use bgpsim::export::Addressor;
 use cisco_lab::{CiscoLab, Inactive};
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use ipnet::Ipv4Net;
 use itertools::Itertools;
 use rand::prelude::*;
 use serde::Serialize;
-use std::{collections::HashMap, net::Ipv4Addr};
+use std::{collections::HashMap, net::Ipv4Addr, path::PathBuf};
 
 use chameleon::{
-    decompose,
-    experiment::{Experiment, Scenario, _TopologyZoo},
+    decompose, decompose_with_metrics,
+    experiment::{DecompositionExport, Experiment, Scenario, _TopologyZoo},
     runtime::{self, lab::ExternalEvent},
-    specification::SpecificationBuilder,
+    specification::{Specification, SpecificationBuilder},
     P,
 };
-use bgpsim::{prelude::*, topology_zoo::TopologyZoo};
+use bgpsim::{config::ConfigModifier, prelude::*, topology_zoo::TopologyZoo};
 
 /// The topology to test things on.
 const TOPO: TopologyZoo = TopologyZoo::Abilene;
 
-/// Run the system in simulation and in the testbed.
+/// Run the system in simulation and in the testbed, or inspect the decomposition.
 #[derive(Debug, Parser)]
 struct Cli {
+    /// What to do
+    #[clap(subcommand)]
+    command: Command,
+    /// Directory in which all results and exported files are stored.
+    #[clap(long = "out", global = true, default_value = "results")]
+    out: PathBuf,
+}
+
+/// Subcommands of the main program.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Decompose the scenario, and run it in simulation (and optionally in the testbed).
+    Run(RunArgs),
+    /// Decompose the scenario, and write the decomposition and the specification to a file.
+    Export(ExportArgs),
+    /// Decompose the scenario, and print the size of the scheduling problems.
+    Analyze(ScenarioArgs),
+    /// List all topologies whose number of internal routers lies within the given range.
+    List(ListArgs),
+}
+
+/// Arguments describing the scenario to generate.
+#[derive(Debug, Args)]
+struct ScenarioArgs {
     /// Topology to use. If you choose a topology with more than 11 routers, you cannot run it on
     /// the testbed.
     #[clap(long = "topo", short = 't', default_value = "Abilene")]
@@ -49,6 +73,17 @@ struct Cli {
     /// Event (scenario) to generate.
     #[clap(long = "event", short = 'e', default_value = "del-best-route")]
     event: Scenario,
+    /// Use a randomized configuration
+    #[clap(short, long)]
+    rand: bool,
+}
+
+/// Arguments of the `run` command.
+#[derive(Debug, Args)]
+struct RunArgs {
+    /// The scenario to run
+    #[clap(flatten)]
+    scenario: ScenarioArgs,
     /// Unexpected event that disturbs the simulation
     #[clap(long = "failure", short = 'f')]
     failure: Option<UnexpectedEvent>,
@@ -58,20 +93,110 @@ struct Cli {
     /// Specifiy the number of prefixes (Prefix Equivalence Class) to simulate
     #[clap(long = "pecs", short = 'p')]
     pecs: Option<u32>,
-    /// Use a randomized configuration
-    #[clap(short, long)]
-    rand: bool,
+}
+
+/// Arguments of the `export` command.
+#[derive(Debug, Args)]
+struct ExportArgs {
+    /// The scenario to decompose
+    #[clap(flatten)]
+    scenario: ScenarioArgs,
+    /// Filename (within the output directory) to which the decomposition is written.
+    #[clap(long = "output", short = 'o', default_value = "decomposition.json")]
+    output: String,
+}
+
+/// Arguments of the `list` command.
+#[derive(Debug, Args)]
+struct ListArgs {
+    /// Minimum number of internal routers (inclusive).
+    #[clap(long = "min-nodes", default_value_t = 0)]
+    min_nodes: usize,
+    /// Maximum number of internal routers (inclusive).
+    #[clap(long = "max-nodes", default_value_t = usize::MAX)]
+    max_nodes: usize,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     pretty_env_logger::init_timed();
+    execute(Cli::parse())
+}
 
-    let args = Cli::parse();
+/// Execute the command given on the command line.
+fn execute(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    match cli.command {
+        Command::Run(args) => run(args, cli.out),
+        Command::Export(args) => export(args, cli.out),
+        Command::Analyze(args) => analyze(args),
+        Command::List(args) => {
+            list(args);
+            Ok(())
+        }
+    }
+}
 
-    let (mut net, p, command) = args
+/// Build the network, the command and the specification of the scenario.
+#[allow(clippy::type_complexity)]
+fn build_scenario(
+    args: &ScenarioArgs,
+) -> Result<
+    (
+        Network<P, BasicEventQueue<P>>,
+        P,
+        ConfigModifier<P>,
+        Specification,
+    ),
+    Box<dyn std::error::Error>,
+> {
+    let (net, p, command) = args
         .event
         .build(args.topo.0, BasicEventQueue::new(), args.rand)?;
     let spec = args.spec_builder.build_all(&net, Some(&command), [p]);
+    Ok((net, p, command, spec))
+}
+
+/// Write the decomposition and the specification to a file in `out` without running it.
+fn export(args: ExportArgs, out: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let (net, _, command, spec) = build_scenario(&args.scenario)?;
+    let decomp = decompose(&net, command, &spec)?;
+    DecompositionExport { spec, decomp }.write_json(&out, &args.output)?;
+    log::info!(
+        "Decomposition written to {}",
+        out.join(&args.output).display()
+    );
+    Ok(())
+}
+
+/// Print the metrics of the scheduling problem of each prefix.
+fn analyze(args: ScenarioArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (net, _, command, spec) = build_scenario(&args)?;
+    let (_, metrics) = decompose_with_metrics(&net, command, &spec)?;
+    for p in metrics.problem_size.keys().sorted() {
+        println!(
+            "{p}: problem size {}, {} steps, {} temporary sessions, solved in {:?}",
+            metrics.problem_size[p],
+            metrics.num_steps[p],
+            metrics.temp_sessions[p],
+            metrics.solve_time[p],
+        );
+    }
+    Ok(())
+}
+
+/// Print all topologies whose number of internal routers lies within the given range.
+fn list(args: ListArgs) {
+    for topo in TopologyZoo::topologies_in_range(args.min_nodes, args.max_nodes) {
+        println!(
+            "{topo:?}: {} internal routers, {} internal edges",
+            topo.num_internals(),
+            topo.num_internal_edges()
+        );
+    }
+}
+
+/// Run the scenario in simulation, and (optionally) in the testbed. All results are stored in `out`.
+fn run(args: RunArgs, out: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut net, p, command, spec) = build_scenario(&args.scenario)?;
     let decomp = decompose(&net, command, &spec)?;
 
     let failure = args.failure.map(|x| x.build(&mut net, p));
@@ -79,7 +204,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // perform the simulation
     runtime::sim::run(net.clone(), decomp.clone(), &spec)?;
 
-    if args.cisco_lab && args.topo.0.num_internals() <= 12 {
+    if args.cisco_lab && args.scenario.topo.0.num_internals() <= 12 {
         let pecs = args.pecs.map(|p| {
             (0..p)
                 .map(|x| Ipv4Addr::from((200u32 << 24) + (x << 8)))
@@ -93,7 +218,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap()
             .block_on(async move {
                 // normal run
-                let mut lab = runtime::lab::setup_cisco_lab(&net, Some(args.topo.0)).await?;
+                let mut lab =
+                    runtime::lab::setup_cisco_lab(&net, Some(args.scenario.topo.0)).await?;
                 let event = failure.as_ref().map(|f| f.build(&mut lab));
                 if let Some(pecs) = pecs.clone() {
                     lab.addressor_mut().register_pec(p, pecs);
@@ -104,30 +230,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 lab.wait_for_convergence().await?;
 
                 // set the prefix equivalence classes
-                let mut path =
-                    runtime::lab::run(net.clone(), &mut lab, decomp.clone(), event).await?;
+                let path =
+                    runtime::lab::run(net.clone(), &mut lab, decomp.clone(), event, &out).await?;
 
                 // store the experiment
-                path.push("scenario.json");
                 Experiment {
                     net: &net,
                     topo: Some(TOPO),
-                    scenario: Some(args.event),
-                    spec_builder: Some(args.spec_builder),
+                    scenario: Some(args.scenario.event),
+                    spec_builder: Some(args.scenario.spec_builder),
                     spec: &spec,
                     decomp: Some(&decomp),
-                    rand: args.rand,
+                    rand: args.scenario.rand,
                     data: Parameters {
                         failure: failure.clone(),
                         pecs: args.pecs,
                     },
                 }
-                .write_json(&path)?;
-                path.pop();
+                .write_json(&path, "scenario.json")?;
 
                 // generate the web export
-                let web_export_path = format!("{}/web_export", path.to_string_lossy());
-                chameleon::export_web(&net, &spec, decomp.clone(), web_export_path).unwrap();
+                chameleon::export_web(&net, &spec, decomp.clone(), &path, "web_export").unwrap();
 
                 // drop the lab
                 std::mem::drop(lab);
@@ -143,26 +266,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let mut lab = lab.connect().await?;
                 lab.wait_for_convergence().await?;
 
-                let mut path =
-                    runtime::lab::run_baseline(net.clone(), &mut lab, decomp.clone(), event)
+                let path =
+                    runtime::lab::run_baseline(net.clone(), &mut lab, decomp.clone(), event, &out)
                         .await?;
 
                 // generate the scenario.json
-                path.push("scenario.json");
                 Experiment {
                     net: &net,
                     topo: Some(TOPO),
-                    scenario: Some(args.event),
-                    spec_builder: Some(args.spec_builder),
+                    scenario: Some(args.scenario.event),
+                    spec_builder: Some(args.scenario.spec_builder),
                     spec: &spec,
                     decomp: Some(&decomp),
-                    rand: args.rand,
+                    rand: args.scenario.rand,
                     data: Parameters {
                         failure,
                         pecs: args.pecs,
                     },
                 }
-                .write_json(path)?;
+                .write_json(path, "scenario.json")?;
 
                 Ok::<(), runtime::lab::LabError>(())
             })?;
@@ -269,3 +391,44 @@ struct Parameters {
     failure: Option<ExternalEventPrepared>,
     pecs: Option<u32>,
 }
+
+#[cfg(test)]
+mod test {
+    use bgpsim::topology_zoo::TopologyZoo;
+    use chameleon::experiment::{DecompositionExport, Scenario, _TopologyZoo};
+    use chameleon::specification::SpecificationBuilder;
+    use clap::Parser;
+    use test_log::test;
+
+    use super::{build_scenario, execute, Cli, ScenarioArgs};
+
+    #[test]
+    fn export_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        let cli = Cli::try_parse_from([
+            "main",
+            "export",
+            "--out",
+            dir.path().to_str().unwrap(),
+            "--output",
+            "decomposition.json",
+        ])
+        .unwrap();
+        execute(cli).unwrap();
+
+        let file = dir.path().join("decomposition.json");
+        assert!(file.exists());
+        let export = DecompositionExport::read_json(&file).unwrap();
+
+        // the export contains the decomposition of the default scenario
+        let (_, _, command, spec) = build_scenario(&ScenarioArgs {
+            topo: _TopologyZoo(TopologyZoo::Abilene),
+            spec_builder: SpecificationBuilder::Reachability,
+            event: Scenario::DelBestRoute,
+            rand: false,
+        })
+        .unwrap();
+        assert_eq!(export.decomp.original_command, command);
+        assert_eq!(export.spec, spec);
+    }
+}
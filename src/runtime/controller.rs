@@ -42,6 +42,42 @@ impl Controller {
         Self { decomp, state }
     }
 
+    /// Create a new controller that resumes a migration that was paused after `phase`, i.e., the
+    /// controller starts with the phase directly following `phase`.
+    pub fn resume_from(decomp: Decomposition, phase: Phase) -> Self {
+        let mut controller = Self::new(decomp);
+        while !controller.is_past(phase) {
+            controller.next_stage();
+        }
+        controller
+    }
+
+    /// Move the controller to the next stage, irrespective of whether the current stage is done.
+    pub(crate) fn next_stage(&mut self) {
+        self.state = match self.state {
+            ControllerStage::Setup(_) => {
+                ControllerStage::update_before(take(&mut self.decomp.atomic_before))
+            }
+            ControllerStage::UpdateBefore(_) => {
+                ControllerStage::main(take(&mut self.decomp.main_commands))
+            }
+            ControllerStage::Main(_) => {
+                ControllerStage::update_after(take(&mut self.decomp.atomic_after))
+            }
+            ControllerStage::UpdateAfter(_) => {
+                ControllerStage::cleanup(take(&mut self.decomp.cleanup_commands))
+            }
+            ControllerStage::Cleanup(_) => ControllerStage::Finished,
+            ControllerStage::Finished => ControllerStage::Finished,
+        };
+    }
+
+    /// Returns `true` if the controller has completed `phase`, i.e., it is in a later phase or
+    /// finished.
+    pub fn is_past(&self, phase: Phase) -> bool {
+        self.state.phase().map(|p| p > phase).unwrap_or(true)
+    }
+
     /// Get the decomposition of the command
     pub fn decomposition(&self) -> &Decomposition {
         &self.decomp
@@ -93,6 +129,21 @@ impl Controller {
     }
 }
 
+/// Phase of the migration, in the order in which they are executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// Setting up the network (see [`Decomposition::setup_commands`])
+    Setup,
+    /// Updates before the main command (see [`Decomposition::atomic_before`])
+    UpdateBefore,
+    /// The main command (see [`Decomposition::main_commands`])
+    Main,
+    /// Updates after the main command (see [`Decomposition::atomic_after`])
+    UpdateAfter,
+    /// Cleaning up the network (see [`Decomposition::cleanup_commands`])
+    Cleanup,
+}
+
 /// In which state is the controller currently in.
 #[derive(Debug)]
 pub enum ControllerStage {
@@ -228,6 +279,18 @@ impl ControllerStage {
         }
     }
 
+    /// Return the phase of the current stage, or `None` if the controller has finished.
+    pub fn phase(&self) -> Option<Phase> {
+        match self {
+            ControllerStage::Setup(_) => Some(Phase::Setup),
+            ControllerStage::UpdateBefore(_) => Some(Phase::UpdateBefore),
+            ControllerStage::Main(_) => Some(Phase::Main),
+            ControllerStage::UpdateAfter(_) => Some(Phase::UpdateAfter),
+            ControllerStage::Cleanup(_) => Some(Phase::Cleanup),
+            ControllerStage::Finished => None,
+        }
+    }
+
     /// Count the number of commands stored within this stage..
     #[cfg(feature = "cisco-lab")]
     pub(crate) fn count_commands(&self) -> usize {
@@ -242,6 +305,20 @@ impl ControllerStage {
             ControllerStage::Finished => 0,
         }
     }
+
+    /// Get all commands of the stage (of all prefixes and all rounds).
+    pub(crate) fn commands(&self) -> Vec<&AtomicCommand<P>> {
+        match self {
+            ControllerStage::Setup(s) | ControllerStage::Main(s) | ControllerStage::Cleanup(s) => {
+                s.commands.iter().flatten().collect()
+            }
+            ControllerStage::UpdateAfter(ss) | ControllerStage::UpdateBefore(ss) => ss
+                .values()
+                .flat_map(|s| s.commands.iter().flatten())
+                .collect(),
+            ControllerStage::Finished => Vec::new(),
+        }
+    }
 }
 
 /// The state of a `Vec<Vec<AtomicCommand>>`
@@ -271,7 +348,7 @@ impl StateItem {
                     AtomicCommandState::Done => continue,
                 };
 
-                let net_state = match cond {
+                let net_state = match cond.without_stability().clone() {
                     AtomicCondition::None => "()".to_string(),
                     AtomicCondition::SelectedRoute { router, prefix, .. } => {
                         if let Some(rib) = net
@@ -285,6 +362,7 @@ impl StateItem {
                         }
                     }
                     AtomicCondition::AvailableRoute { router, prefix, .. }
+                    | AtomicCondition::AllRoutesMatch { router, prefix, .. }
                     | AtomicCondition::RoutesLessPreferred { router, prefix, .. } => net
                         .get_device(router)
                         .unwrap_internal()
@@ -297,6 +375,7 @@ impl StateItem {
                     AtomicCondition::BgpSessionEstablished { .. } => {
                         String::from("not established")
                     }
+                    AtomicCondition::Stable { .. } => unreachable!("Stability was removed"),
                 };
 
                 result.push(format!(
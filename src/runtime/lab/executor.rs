@@ -25,11 +25,10 @@ use std::{
     time::Duration,
 };
 
-use atomic_command::{AtomicCommand, AtomicCondition};
+use atomic_command::{AtomicCommand, AtomicCondition, StabilityTracker};
 use bgpsim::{
     bgp::BgpRibEntry,
-    config::ConfigModifier,
-    export::{Addressor, DefaultAddressor, ExportError, InternalCfgGen, MaybePec},
+    export::{Addressor, DefaultAddressor, ExportError, MaybePec},
     prelude::*,
 };
 use cisco_lab::{
@@ -54,8 +53,9 @@ use tokio::{
     time::{sleep_until, Instant},
 };
 
+use super::generate_commands;
 use crate::{
-    runtime::controller::{Controller, ControllerStage, StateItem},
+    runtime::controller::{Controller, ControllerStage, Phase, StateItem},
     P,
 };
 
@@ -149,16 +149,36 @@ impl Controller {
         self,
         lab: &'a mut CiscoLab<'n, P, Q, Active>,
         net: &Network<P, Q>,
+    ) -> Result<Vec<Event>, LabError> {
+        self.execute_lab_until(lab, net, None).await
+    }
+
+    /// Perform the migration in parallel using the parallel executor, up to and including the
+    /// phase `until`. If `until` is `None`, then perform all remaining stages.
+    pub async fn execute_lab_until<'a, 'n: 'a, Q>(
+        self,
+        lab: &'a mut CiscoLab<'n, P, Q, Active>,
+        net: &Network<P, Q>,
+        until: Option<Phase>,
     ) -> Result<Vec<Event>, LabError> {
         // clear the event log.
         EVENT_LOG.lock().await.clear();
 
-        let stages = self.into_remaining_states();
+        let stages = self
+            .into_remaining_states()
+            .into_iter()
+            .filter(|s| match (s.phase(), until) {
+                (Some(phase), Some(until)) => phase <= until,
+                (_, None) => true,
+                (None, Some(_)) => false,
+            })
+            .collect::<Vec<_>>();
 
         let num_routers = net.get_routers().len();
         let queue_size = stages.iter().map(|s| s.count_commands()).sum();
         let mut idx = 0;
 
+        let configured = ConfiguredJobs::default();
         let c_kill = KillChannel::new(num_routers);
         let (c_jobs_tx, c_jobs_rx) = broadcast::channel(queue_size);
         let (c_done_tx, c_done_rx) = broadcast::channel(queue_size);
@@ -181,6 +201,9 @@ impl Controller {
             })
             .collect();
 
+        // make sure that all conditions can be checked before reconfiguring any router.
+        check_conditions(net, lab, &stages, &pec_addresses)?;
+
         // start all runners
         info!("Connecting to all routers...");
         let runners = start_runners(
@@ -203,6 +226,7 @@ impl Controller {
                         s,
                         None,
                         &pec_addresses,
+                        &configured,
                         &mut idx,
                         c_jobs_tx.clone(),
                         c_done_rx.resubscribe(),
@@ -217,6 +241,7 @@ impl Controller {
                         lab,
                         s,
                         &pec_addresses,
+                        &configured,
                         &mut idx,
                         c_jobs_tx.clone(),
                         c_done_rx.resubscribe(),
@@ -244,6 +269,27 @@ impl Controller {
     }
 }
 
+/// Translate the conditions of all commands in `stages` without executing them. This returns an
+/// error if any condition cannot be checked in the lab.
+fn check_conditions<Q>(
+    net: &Network<P, Q>,
+    lab: &mut CiscoLab<'_, P, Q, Active>,
+    stages: &[ControllerStage],
+    pec_addresses: &HashMap<P, Vec<Ipv4Net>>,
+) -> Result<(), LabError> {
+    for cmd in stages.iter().flat_map(ControllerStage::commands) {
+        for r in cmd.command.routers() {
+            if net.get_device(r).is_external() {
+                continue;
+            }
+            let (_, addressor) = lab.get_router_cfg_gen(r)?;
+            LabCondition::translate(&cmd.precondition, r, net, addressor, pec_addresses)?;
+            LabCondition::translate(&cmd.postcondition, r, net, addressor, pec_addresses)?;
+        }
+    }
+    Ok(())
+}
+
 /// Start all shells and return a vector of join handles.
 fn start_runners<Q>(
     net: &Network<P, Q>,
@@ -273,6 +319,7 @@ async fn execute_prefix_stage<'a, 'n: 'a, Q>(
     lab: &'a mut CiscoLab<'n, P, Q, Active>,
     stage: HashMap<P, StateItem>,
     pec_addresses: &HashMap<P, Vec<Ipv4Net>>,
+    configured: &ConfiguredJobs,
     idx: &mut usize,
     c_jobs: broadcast::Sender<Job>,
     c_done: broadcast::Receiver<JobId>,
@@ -286,6 +333,7 @@ async fn execute_prefix_stage<'a, 'n: 'a, Q>(
             stage,
             Some(p),
             pec_addresses,
+            configured,
             idx,
             c_jobs.clone(),
             c_done.resubscribe(),
@@ -308,6 +356,7 @@ fn execute_stage<'a, 'n: 'a, Q>(
     stage: StateItem,
     prefix: Option<P>,
     pec_addresses: &HashMap<P, Vec<Ipv4Net>>,
+    configured: &ConfiguredJobs,
     idx: &mut usize,
     c_jobs: broadcast::Sender<Job>,
     mut c_done: broadcast::Receiver<JobId>,
@@ -319,23 +368,18 @@ fn execute_stage<'a, 'n: 'a, Q>(
         let mut jobs = Vec::new();
         // iterate ovewr all commands of that step
         for cmd in step {
-            // iterate over all routers for that command
-            for r in cmd.command.routers() {
-                if net.get_device(r).is_external() {
-                    continue;
-                }
-                *idx += 1;
+            // iterate over all routers for that command, in the order they must be configured.
+            for (id, after) in command_jobs(net, cmd, prefix, idx) {
+                let r = id.0;
 
                 // get the generator and addressor to create the command.
                 let (gen, addressor) = lab.get_router_cfg_gen(r).map_err(|e| (e, &c_kill))?;
 
                 jobs.push(Job {
-                    id: (r, prefix, *idx),
-                    cmd: Vec::<ConfigModifier<P>>::from(cmd.command.clone())
-                        .into_iter()
-                        .filter(|c| c.routers().contains(&r))
-                        .map(|c| gen.generate_command(net, addressor, c))
-                        .collect::<Result<_, _>>()
+                    id,
+                    after,
+                    configured: configured.clone(),
+                    cmd: generate_commands(net, gen, addressor, r, &cmd.command)
                         .map_err(|e| (e, &c_kill))?,
                     cmd_repr: cmd.command.fmt(net),
                     pre: LabCondition::translate(
@@ -354,6 +398,7 @@ fn execute_stage<'a, 'n: 'a, Q>(
                         pec_addresses,
                     )
                     .map_err(|e| (e, &c_kill))?,
+                    post_stable: cmd.postcondition.stability_tracker(),
                     state: JobState::Pre,
                     command: cmd.clone(),
                 });
@@ -510,13 +555,57 @@ async fn _runner(
 }
 
 /// Job Identification
-type JobId = (RouterId, Option<P>, usize);
+pub(crate) type JobId = (RouterId, Option<P>, usize);
+
+/// Create the IDs of the jobs of a command, one for each internal router, in the order in which
+/// they must be configured. Each ID is paired with the ID of the job that must be configured
+/// before it.
+pub(crate) fn command_jobs<Q>(
+    net: &Network<P, Q>,
+    cmd: &AtomicCommand<P>,
+    prefix: Option<P>,
+    idx: &mut usize,
+) -> Vec<(JobId, Option<JobId>)> {
+    let mut after = None;
+    cmd.command
+        .routers()
+        .into_iter()
+        .filter(|r| !net.get_device(*r).is_external())
+        .map(|r| {
+            *idx += 1;
+            let id = (r, prefix, *idx);
+            (id, after.replace(id))
+        })
+        .collect()
+}
+
+/// Jobs whose command was already pushed to the router. The set is shared among all jobs of a
+/// single execution.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ConfiguredJobs(Arc<std::sync::Mutex<HashSet<JobId>>>);
+
+impl ConfiguredJobs {
+    /// Remember that the command of the job was pushed to the router.
+    pub(crate) fn insert(&self, id: JobId) {
+        self.0.lock().unwrap().insert(id);
+    }
+
+    /// Check if a job may push its command, i.e., if the job that must be configured before it (if
+    /// any) was already configured.
+    pub(crate) fn may_configure(&self, after: Option<JobId>) -> bool {
+        after.map_or(true, |id| self.0.lock().unwrap().contains(&id))
+    }
+}
 
 /// Arguments to the job
 #[derive(Clone, Debug)]
 struct Job {
     /// The job identification
     id: JobId,
+    /// The job (of the same command) that must be configured before this job can be configured.
+    after: Option<JobId>,
+    /// All jobs of the current execution that were already configured.
+    configured: ConfiguredJobs,
     /// Command to apply (as a string)
     cmd: String,
     /// Representation of the command as a string, for logging
@@ -525,6 +614,8 @@ struct Job {
     pre: LabCondition,
     /// Postcondition after applying the command.
     post: LabCondition,
+    /// Tracker for the postcondition, if it must hold continuously for some duration.
+    post_stable: Option<StabilityTracker>,
     /// State of the job.
     state: JobState,
     /// The original command
@@ -540,6 +631,7 @@ impl Job {
     ) -> Result<bool, LabError> {
         // check precondition
         if self.state == JobState::Pre
+            && self.configured.may_configure(self.after)
             && self
                 .pre
                 .check(shell, cache)
@@ -552,17 +644,12 @@ impl Job {
                 .configure(&self.cmd)
                 .await
                 .map_err(CiscoLabError::CiscoShell)?;
+            self.configured.insert(self.id);
         } else {
             log::trace!("[{}] Waiting for precondition {}", shell.name(), self.pre);
         }
         // check postcondition
-        if self.state == JobState::Post
-            && self
-                .post
-                .check(shell, cache)
-                .await
-                .map_err(CiscoLabError::CiscoShell)?
-        {
+        if self.state == JobState::Post && self.check_post(shell, cache).await? {
             self.log_postcond(shell.name()).await;
             self.state = JobState::Done;
         } else {
@@ -571,6 +658,24 @@ impl Job {
 
         Ok(self.state == JobState::Done)
     }
+
+    /// Check the postcondition. If the postcondition must be stable, then this function only
+    /// returns `true` once it has held continuously for the required duration.
+    async fn check_post(
+        &mut self,
+        shell: &mut CiscoShell,
+        cache: &mut HashMap<Ipv4Net, Vec<BgpRoute>>,
+    ) -> Result<bool, LabError> {
+        let holds = self
+            .post
+            .check(shell, cache)
+            .await
+            .map_err(CiscoLabError::CiscoShell)?;
+        Ok(match self.post_stable.as_mut() {
+            Some(tracker) => tracker.update(holds, Instant::now().into_std()),
+            None => holds,
+        })
+    }
 }
 
 /// logging helpe rfunctions
@@ -667,7 +772,7 @@ impl Default for JobState {
 
 /// The atomic Condition, translated to a form that can be checked using a [`CiscoShell`];
 #[derive(Debug, Clone)]
-enum LabCondition {
+pub(crate) enum LabCondition {
     /// No condition necessary. This condition is satisfied automatically.
     None,
     /// Condition on the current RIB entry (selected route) of a router and a prefix. This condition
@@ -724,14 +829,16 @@ impl LabCondition {
     /// translate an `AtomicCondition` to an `LabCondition`.
     ///
     /// The `pec_addrsses` is a lookup for prefix equivalence classes, and which networks to
-    /// actually check for those.
-    fn translate<Q>(
+    /// actually check for those. Conditions that cannot be checked in the lab (like those on the
+    /// originator ID of the selected route, or on all known routes) result in
+    /// `LabError::UnsupportedCondition`.
+    pub(crate) fn translate<Q>(
         from: &AtomicCondition<P>,
         r: RouterId,
         net: &Network<P, Q>,
         addressor: &mut DefaultAddressor<'_, P, Q>,
         pec_addresses: &HashMap<P, Vec<Ipv4Net>>,
-    ) -> Result<Self, ExportError> {
+    ) -> Result<Self, LabError> {
         /// compute the prefix from the addressor
         fn get_prefixes<Q>(
             prefix: &P,
@@ -779,12 +886,28 @@ impl LabCondition {
 
         Ok(match from {
             AtomicCondition::None => LabCondition::None,
+            // stability is tracked by the job itself.
+            AtomicCondition::Stable { inner, .. } => {
+                Self::translate(inner, r, net, addressor, pec_addresses)?
+            }
+            // the BGP table parser does not expose the originator-id, so it cannot be checked.
+            AtomicCondition::SelectedRoute {
+                router,
+                originator_id: Some(_),
+                ..
+            } if r == *router => {
+                return Err(LabError::UnsupportedCondition(format!(
+                    "{} (the originator ID is not known)",
+                    from.fmt(net)
+                )))
+            }
             AtomicCondition::SelectedRoute {
                 router,
                 prefix,
                 neighbor,
                 weight,
                 next_hop,
+                originator_id: None,
             } if r == *router => LabCondition::SelectedRoute {
                 prefixes: get_prefixes(prefix, addressor, pec_addresses)?,
                 neighbor: get_router_addr(r, *neighbor, net, addressor)?,
@@ -829,6 +952,12 @@ impl LabCondition {
                 route: route.clone(),
                 next_hop: get_router_addr(r, Some(route.route.next_hop), net, addressor)?.unwrap(),
             },
+            AtomicCondition::AllRoutesMatch { router, .. } if r == *router => {
+                return Err(LabError::UnsupportedCondition(format!(
+                    "{} (conditions on all known routes are not supported)",
+                    from.fmt(net)
+                )))
+            }
             _ => unreachable!("Condition is on a different device!"),
         })
     }
@@ -857,65 +986,81 @@ impl LabCondition {
             Ok(cache.get(net).unwrap())
         }
 
-        Ok(match self {
-            LabCondition::None => true,
+        if let LabCondition::BgpSessionEstablished { neighbor } = self {
+            return Ok(shell
+                .get_bgp_neighbors()
+                .await?
+                .iter()
+                .any(|n| n.connected && n.id == *neighbor));
+        }
+
+        if let Some(prefixes) = self.prefixes() {
+            for p in prefixes.iter() {
+                if !self.check_routes(get(shell, p, cache).await?) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check if the condition is satisfied by the given BGP routes of the router, as parsed from
+    /// `show bgp ipv4 unicast detail` (see [`BgpRoute::from_detail`]), without issuing any
+    /// commands to the router. Prefixes that are missing in `routes` are treated as having no
+    /// routes at all.
+    ///
+    /// Returns `None` if the condition cannot be evaluated on BGP routes alone, i.e., for
+    /// `BgpSessionEstablished`.
+    pub(crate) fn check_raw(&self, routes: &HashMap<Ipv4Net, Vec<BgpRoute>>) -> Option<bool> {
+        match self {
+            LabCondition::BgpSessionEstablished { .. } => None,
+            _ => Some(self.prefixes().into_iter().all(|prefixes| {
+                prefixes.iter().all(|p| {
+                    self.check_routes(routes.get(p).map(Vec::as_slice).unwrap_or_default())
+                })
+            })),
+        }
+    }
+
+    /// Get the prefixes that are checked by this condition.
+    fn prefixes(&self) -> Option<&MaybePec<Ipv4Net>> {
+        match self {
+            LabCondition::None | LabCondition::BgpSessionEstablished { .. } => None,
+            LabCondition::SelectedRoute { prefixes, .. }
+            | LabCondition::AvailableRoute { prefixes, .. }
+            | LabCondition::RoutesLessPreferred { prefixes, .. } => Some(prefixes),
+        }
+    }
+
+    /// Check the condition on all routes known for a single prefix.
+    fn check_routes(&self, routes: &[BgpRoute]) -> bool {
+        match self {
+            LabCondition::None | LabCondition::BgpSessionEstablished { .. } => true,
             LabCondition::SelectedRoute {
-                prefixes,
                 neighbor,
                 weight,
                 next_hop,
-            } => {
-                for p in prefixes.iter() {
-                    if !get(shell, p, cache)
-                        .await?
-                        .iter()
-                        .any(|r| r.selected && check_route(r, *weight, *next_hop, *neighbor))
-                    {
-                        return Ok(false);
-                    }
-                }
-                true
-            }
+                ..
+            } => routes
+                .iter()
+                .any(|r| r.selected && check_route(r, *weight, *next_hop, *neighbor)),
             LabCondition::AvailableRoute {
-                prefixes,
                 neighbor,
                 weight,
                 next_hop,
-            } => {
-                for p in prefixes.iter() {
-                    if !get(shell, p, cache)
-                        .await?
-                        .iter()
-                        .any(|r| check_route(r, *weight, *next_hop, *neighbor))
-                    {
-                        return Ok(false);
-                    }
-                }
-                true
-            }
-            LabCondition::BgpSessionEstablished { neighbor } => shell
-                .get_bgp_neighbors()
-                .await?
+                ..
+            } => routes
                 .iter()
-                .any(|n| n.connected && n.id == *neighbor),
+                .any(|r| check_route(r, *weight, *next_hop, *neighbor)),
             LabCondition::RoutesLessPreferred {
-                prefixes,
                 good_neighbors,
                 route,
                 next_hop,
-            } => {
-                for p in prefixes.iter() {
-                    if get(shell, p, cache)
-                        .await?
-                        .iter()
-                        .any(|r| !check_route_preference(r, route, good_neighbors, *next_hop))
-                    {
-                        return Ok(false);
-                    }
-                }
-                true
-            }
-        })
+                ..
+            } => routes
+                .iter()
+                .all(|r| check_route_preference(r, route, good_neighbors, *next_hop)),
+        }
     }
 }
 
@@ -17,21 +17,33 @@
 
 //! Runtime for the real-world system in the [`cisco_lab`]
 
-use std::{fs::OpenOptions, io::Write, path::PathBuf, time::Duration};
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::Write,
+    iter::once,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use atomic_command::{AtomicCommand, AtomicCondition, AtomicModifier};
 use bgpsim::{
-    config::NetworkConfig, event::EventQueue, export::ExportError, prelude::*,
+    config::{ConfigModifier, NetworkConfig},
+    event::EventQueue,
+    export::{Addressor, CiscoFrrCfgGen, ExportError, InternalCfgGen},
+    prelude::*,
     topology_zoo::TopologyZoo,
 };
 use cisco_lab::{export_capture_to_csv, Active, CiscoLab, CiscoLabError, Inactive};
 use thiserror::Error;
 use tokio::{sync::broadcast::error::RecvError, task::JoinError};
 
-use super::controller::Controller;
+use super::controller::{Controller, Phase};
 use crate::{decomposition::Decomposition, P};
 
 mod executor;
+#[cfg(test)]
+pub(crate) use executor::{command_jobs, ConfiguredJobs, LabCondition};
 pub use executor::{Event, EventKind};
 
 /// Number of pings per second per flow.
@@ -55,13 +67,137 @@ where
     Ok(lab)
 }
 
-/// Perform the decomposed update on the network using the cisco lab. This function returns the
-/// folder where the experiment results were stored.
+/// Generate the configuration commands that apply `modifier` on `router`. The result contains the
+/// commands of all raw [`ConfigModifier`]s of `modifier` that affect `router`, in order.
+///
+/// Temporary sessions are controlled with route-maps, exactly as in the simulation:
+/// [`AtomicModifier::UseTempSession`] installs a route-map item on the temporary session that
+/// prefers its route for the prefix, and [`AtomicModifier::IgnoreTempSession`] removes that item
+/// again. No static routes are required, as the temporary session itself is established by
+/// [`AtomicModifier::AddTempSession`].
+pub fn generate_commands<Q, A: Addressor<P>>(
+    net: &Network<P, Q>,
+    gen: &mut CiscoFrrCfgGen<P>,
+    addressor: &mut A,
+    router: RouterId,
+    modifier: &AtomicModifier<P>,
+) -> Result<String, ExportError> {
+    Vec::<ConfigModifier<P>>::from(modifier.clone())
+        .into_iter()
+        .filter(|c| c.routers().contains(&router))
+        .map(|c| gen.generate_command(net, addressor, c))
+        .collect()
+}
+
+/// Set the effective BGP router-IDs of all routers in `net` to the loopback addresses assigned by
+/// `addressor`, such that the final tie-breaks of the BGP decision process in the simulation match
+/// those on the hardware. Routers without an assigned address are not changed.
+pub fn set_bgp_router_ids<Q, A>(net: &mut Network<P, Q>, addressor: &A) -> Result<(), NetworkError>
+where
+    Q: EventQueue<P>,
+    A: Addressor<P>,
+{
+    for router in net
+        .get_routers()
+        .into_iter()
+        .chain(net.get_external_routers())
+    {
+        if let Some(id) = addressor.try_get_router_address(router) {
+            net.set_bgp_router_id(router, Some(id))?;
+        }
+    }
+    Ok(())
+}
+
+impl Decomposition {
+    /// Export the migration as a script of CLI commands that operators can apply manually. For
+    /// each atomic command (in the order in which the [`Controller`] applies them), the script
+    /// contains one commented block per affected internal router with the generated configuration
+    /// commands. The precondition to wait for is given as a comment before the commands of the
+    /// first router, and the postcondition after the commands of the last router. Commands of different
+    /// prefixes within the same phase are independent, and are listed one prefix after another.
+    ///
+    /// `cfg_gens` must contain the configuration generator of each internal router that is
+    /// affected by the migration.
+    pub fn to_cli_script<Q, A: Addressor<P>>(
+        &self,
+        net: &Network<P, Q>,
+        cfg_gens: &mut HashMap<RouterId, CiscoFrrCfgGen<P>>,
+        addressor: &mut A,
+    ) -> Result<String, ExportError> {
+        let mut before = self.atomic_before.iter().collect::<Vec<_>>();
+        before.sort_by_key(|(p, _)| **p);
+        let mut after = self.atomic_after.iter().collect::<Vec<_>>();
+        after.sort_by_key(|(p, _)| **p);
+
+        let stages = once((String::from("setup"), &self.setup_commands))
+            .chain(before.into_iter().map(|(p, c)| (format!("before, {p}"), c)))
+            .chain(once((String::from("main"), &self.main_commands)))
+            .chain(after.into_iter().map(|(p, c)| (format!("after, {p}"), c)))
+            .chain(once((String::from("cleanup"), &self.cleanup_commands)));
+
+        let mut script = String::new();
+        for (stage, steps) in stages {
+            for (i, step) in steps.iter().enumerate() {
+                script.push_str(&format!("# ===== {stage}: step {} =====\n", i + 1));
+                for cmd in step {
+                    let routers = cmd
+                        .command
+                        .routers()
+                        .into_iter()
+                        .filter(|r| !net.get_device(*r).is_external())
+                        .collect::<Vec<_>>();
+                    for (i, router) in routers.iter().copied().enumerate() {
+                        let gen = cfg_gens.get_mut(&router).ok_or_else(|| {
+                            ExportError::InternalCfgGenError(
+                                router,
+                                String::from("No configuration generator given"),
+                            )
+                        })?;
+                        script.push_str(&format!(
+                            "\n# router {}: {}\n",
+                            router.fmt(net),
+                            cmd.command.fmt(net)
+                        ));
+                        // wait for the precondition before configuring the first router.
+                        if i == 0 && !cmd.precondition.is_none() {
+                            script.push_str(&format!(
+                                "# wait until: {}\n",
+                                cmd.precondition.fmt(net)
+                            ));
+                        }
+                        script.push_str(&generate_commands(
+                            net,
+                            gen,
+                            addressor,
+                            router,
+                            &cmd.command,
+                        )?);
+                    }
+                    // wait for the postcondition after configuring the last router.
+                    if !routers.is_empty() && !cmd.postcondition.is_none() {
+                        script.push_str(&format!(
+                            "# then wait until: {}\n",
+                            cmd.postcondition.fmt(net)
+                        ));
+                    }
+                }
+                script.push('\n');
+            }
+        }
+        Ok(script)
+    }
+}
+
+/// Perform the decomposed update on the network using the cisco lab. The results are stored in a new
+/// folder within `output_dir`. This function returns the folder where the experiment results were
+/// stored.
 pub async fn run<'a, 'n: 'a, Q>(
     net: Network<P, Q>,
     lab: &'a mut CiscoLab<'n, P, Q, Active>,
     decomp: Decomposition,
     event: Option<ExternalEvent>,
+    output_dir: impl AsRef<Path>,
 ) -> Result<PathBuf, LabError>
 where
     Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
@@ -71,6 +207,7 @@ where
         lab,
         decomp,
         event.map(|x| (x, Duration::from_secs(30))),
+        output_dir,
         "lab_chameleon",
     )
     .await
@@ -83,12 +220,14 @@ async fn run_and_save_results<'a, 'n: 'a, Q>(
     lab: &'a mut CiscoLab<'n, P, Q, Active>,
     decomp: Decomposition,
     event: Option<(ExternalEvent, Duration)>,
+    output_dir: impl AsRef<Path>,
     target_dir_base: impl AsRef<str>,
 ) -> Result<PathBuf, LabError>
 where
     Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
 {
-    // do the update on the simulated net
+    // use the same router-IDs as the lab, and do the update on the simulated net
+    set_bgp_router_ids(&mut net, lab.addressor())?;
     net.apply_modifier(&decomp.original_command)?;
 
     // create the controller
@@ -115,7 +254,7 @@ where
     let result = lab.stop_capture(meas_handle).await?;
 
     // store the capture to disk
-    let mut folder = export_capture_to_csv(&net, &result, "results", target_dir_base)?;
+    let mut folder = export_capture_to_csv(&net, &result, output_dir.as_ref(), target_dir_base)?;
 
     // create the logfile
     folder.push("event.log");
@@ -155,12 +294,60 @@ where
     Ok(folder)
 }
 
-/// run the baseline, which is simply applying the command on the live network.
+/// Perform the decomposed update on the cisco lab, but only up to and including `phase`. The lab
+/// remains in the intermediate state afterwards, and the update can be continued with
+/// [`resume_from`]. This function does not capture any traffic, and returns the event log.
+pub async fn run_until<'a, 'n: 'a, Q>(
+    mut net: Network<P, Q>,
+    lab: &'a mut CiscoLab<'n, P, Q, Active>,
+    decomp: Decomposition,
+    phase: Phase,
+) -> Result<Vec<Event>, LabError>
+where
+    Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
+{
+    set_bgp_router_ids(&mut net, lab.addressor())?;
+    net.apply_modifier(&decomp.original_command)?;
+
+    Controller::new(decomp)
+        .execute_lab_until(lab, &net, Some(phase))
+        .await
+}
+
+/// Continue the decomposed update on the cisco lab that was paused after `phase` (see
+/// [`run_until`]), and check that the final BGP state matches the simulated network. This function
+/// does not capture any traffic, and returns the event log of the remaining phases.
+pub async fn resume_from<'a, 'n: 'a, Q>(
+    mut net: Network<P, Q>,
+    lab: &'a mut CiscoLab<'n, P, Q, Active>,
+    decomp: Decomposition,
+    phase: Phase,
+) -> Result<Vec<Event>, LabError>
+where
+    Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
+{
+    set_bgp_router_ids(&mut net, lab.addressor())?;
+    net.apply_modifier(&decomp.original_command)?;
+
+    let event_log = Controller::resume_from(decomp, phase)
+        .execute_lab(lab, &net)
+        .await?;
+
+    log::debug!("Comparing the final state...");
+    if !lab.equal_bgp_state(&net).await? {
+        return Err(LabError::WrongFinalState);
+    }
+    Ok(event_log)
+}
+
+/// run the baseline, which is simply applying the command on the live network. The results are
+/// stored in a new folder within `output_dir`.
 pub async fn run_baseline<'a, 'n: 'a, Q>(
     net: Network<P, Q>,
     lab: &'a mut CiscoLab<'n, P, Q, Active>,
     decomp: Decomposition,
     event: Option<ExternalEvent>,
+    output_dir: impl AsRef<Path>,
 ) -> Result<PathBuf, LabError>
 where
     Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
@@ -187,6 +374,7 @@ where
         lab,
         tmp_decomp,
         event.map(|x| (x, Duration::from_secs_f64(5.0))),
+        output_dir,
         "lab_baseline",
     )
     .await
@@ -246,4 +434,7 @@ pub enum LabError {
     /// Error while dealing with IO
     #[error("IO Error: {0}")]
     IoError(#[from] std::io::Error),
+    /// A condition of the decomposition cannot be checked in the lab.
+    #[error("Cannot check the condition in the lab: {0}")]
+    UnsupportedCondition(String),
 }
@@ -0,0 +1,127 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Compare the atomic migration with the naive migration in the simulated environment.
+
+use atomic_command::{AtomicCommand, AtomicCondition, AtomicModifier};
+use bgpsim::{event::EventQueue, prelude::*};
+
+use super::{run_no_checks, SimError, SimStats};
+use crate::{decomposition::Decomposition, specification::Specification, P};
+
+/// Result of comparing the atomic migration with the naive migration, see [`compare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ComparisonReport {
+    /// Number of forwarding states traversed by the atomic migration.
+    pub atomic_steps: usize,
+    /// Number of forwarding states of the atomic migration that violate a global invariant of the
+    /// specification. Other temporal expressions of the specification are not considered.
+    pub atomic_global_violations: usize,
+    /// Number of forwarding states traversed by the naive migration.
+    pub naive_steps: usize,
+    /// Number of forwarding states of the naive migration that violate a global invariant of the
+    /// specification. Other temporal expressions of the specification are not considered.
+    pub naive_global_violations: usize,
+}
+
+impl ComparisonReport {
+    /// Number of forwarding states violating a global invariant that the atomic migration avoids
+    /// compared to the naive migration. This number is negative if the atomic migration causes
+    /// more violations.
+    pub fn avoided_global_violations(&self) -> isize {
+        self.naive_global_violations as isize - self.atomic_global_violations as isize
+    }
+}
+
+/// Run both the atomic migration of `decomp` and the naive migration (applying the original
+/// command at once) on `net`, and count the transient forwarding states in which any global
+/// invariant of `spec` is violated. Only expressions that can be turned into global invariants
+/// (see [`crate::specification::SpecExpr::as_global_invariants`]) are considered.
+pub fn compare<Q>(
+    net: &Network<P, Q>,
+    decomp: Decomposition,
+    spec: &Specification,
+) -> Result<ComparisonReport, SimError>
+where
+    Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
+{
+    let naive = naive_decomposition(&decomp);
+
+    let (_, atomic_stats) = run_no_checks(net.clone(), decomp)?;
+    let (_, naive_stats) = run_no_checks(net.clone(), naive)?;
+
+    let (atomic_steps, atomic_global_violations) = count_violations(net, spec, &atomic_stats);
+    let (naive_steps, naive_global_violations) = count_violations(net, spec, &naive_stats);
+
+    Ok(ComparisonReport {
+        atomic_steps,
+        atomic_global_violations,
+        naive_steps,
+        naive_global_violations,
+    })
+}
+
+/// Create the decomposition that applies the original command at once.
+fn naive_decomposition(decomp: &Decomposition) -> Decomposition {
+    let cmd = decomp.original_command.clone();
+    Decomposition {
+        original_command: cmd.clone(),
+        setup_commands: Default::default(),
+        cleanup_commands: Default::default(),
+        atomic_before: Default::default(),
+        main_commands: vec![vec![AtomicCommand {
+            command: AtomicModifier::Raw(cmd),
+            precondition: AtomicCondition::None,
+            postcondition: AtomicCondition::None,
+        }]],
+        atomic_after: Default::default(),
+        bgp_deps: Default::default(),
+        schedule: Default::default(),
+        fw_state_trace: Default::default(),
+    }
+}
+
+/// Replay the forwarding deltas recorded in `stats`, starting from the forwarding state of `net`.
+/// Returns the number of forwarding states, and the number of those that violate any global
+/// invariant of `spec`.
+fn count_violations<Q>(
+    net: &Network<P, Q>,
+    spec: &Specification,
+    stats: &SimStats,
+) -> (usize, usize) {
+    let invariants = spec
+        .iter()
+        .map(|(p, expr)| (*p, expr.clone().as_global_invariants(net)))
+        .collect::<Vec<_>>();
+
+    let mut fw_state = net.get_forwarding_state();
+    let mut violations = 0;
+    for delta in stats.fw_deltas.iter() {
+        for (r, p, nh) in delta {
+            fw_state.update(*r, *p, nh.clone());
+        }
+        if invariants
+            .iter()
+            .any(|(p, invs)| invs.iter().any(|i| i.check(&mut fw_state, *p).is_err()))
+        {
+            violations += 1;
+        }
+    }
+
+    (stats.fw_deltas.len(), violations)
+}
@@ -17,7 +17,7 @@
 
 //! This module is the executor, that applies the actual atomic commands to the network (in `bgpsim`).
 
-use std::{collections::HashMap, mem::take};
+use std::collections::HashMap;
 
 use bgpsim::{event::EventQueue, forwarding_state::ForwardingState, prelude::*};
 use itertools::Itertools;
@@ -26,7 +26,7 @@ use rand::prelude::*;
 
 use crate::{
     decomposition::ilp_scheduler::FwStateTrace,
-    runtime::controller::{AtomicCommandState, Controller, ControllerStage, StateItem},
+    runtime::controller::{AtomicCommandState, Controller, ControllerStage, Phase, StateItem},
     specification::{Checker, Specification},
     P,
 };
@@ -43,12 +43,38 @@ impl Controller {
     ///
     /// If `check` is set to `false`, then do not perform any kind of checks..
     pub fn execute_sim<Q>(
+        &mut self,
+        net: &mut Network<P, Q>,
+        spec: &Specification,
+        prob_controller_step: f64,
+        expected_fw_trace: HashMap<P, FwStateTrace>,
+        check: bool,
+    ) -> Result<SimStats, SimError>
+    where
+        Q: EventQueue<P>,
+    {
+        self.execute_sim_until(
+            net,
+            spec,
+            prob_controller_step,
+            expected_fw_trace,
+            check,
+            None,
+        )
+    }
+
+    /// Perform the migration on the simulated network up to and including the phase `until`, and
+    /// wait for the network to converge (see [`Controller::execute_sim`]). Afterwards, the
+    /// controller is in the phase directly following `until`, and it can be continued by calling
+    /// this function again. If `until` is `None`, then perform the complete migration.
+    pub fn execute_sim_until<Q>(
         &mut self,
         net: &mut Network<P, Q>,
         spec: &Specification,
         prob_controller_step: f64,
         mut expected_fw_trace: HashMap<P, FwStateTrace>,
         check: bool,
+        until: Option<Phase>,
     ) -> Result<SimStats, SimError>
     where
         Q: EventQueue<P>,
@@ -88,6 +114,15 @@ impl Controller {
                 &mut stats,
             )?;
 
+            // do not call the controller once it has completed the phase `until`.
+            if until.map(|phase| self.is_past(phase)).unwrap_or(false) {
+                if net.queue().is_empty() {
+                    // the network has converged
+                    break;
+                }
+                continue;
+            }
+
             // skip the controller if the queue is not empty and with a certain probability
             if net.queue().is_empty() || thread_rng().gen_bool(prob_controller_step) {
                 // do a step on the controller
@@ -133,22 +168,7 @@ impl Controller {
                 return Ok(false);
             }
             // proceed to the next step
-            self.state = match self.state {
-                ControllerStage::Setup(_) => {
-                    ControllerStage::update_before(take(&mut self.decomp.atomic_before))
-                }
-                ControllerStage::UpdateBefore(_) => {
-                    ControllerStage::main(take(&mut self.decomp.main_commands))
-                }
-                ControllerStage::Main(_) => {
-                    ControllerStage::update_after(take(&mut self.decomp.atomic_after))
-                }
-                ControllerStage::UpdateAfter(_) => {
-                    ControllerStage::cleanup(take(&mut self.decomp.cleanup_commands))
-                }
-                ControllerStage::Cleanup(_) => ControllerStage::Finished,
-                ControllerStage::Finished => ControllerStage::Finished,
-            };
+            self.next_stage();
             info!("Proceed to the next stage: {}.", self.state.name());
             // return true, meaning that there was some change.
             Ok(true)
@@ -193,6 +213,10 @@ fn check_and_update_stats<Q>(
                 let prefix_trace = expected_fw_trace
                     .get_mut(&p)
                     .ok_or_else(err!("FW delta for prefix {p} that should not be affected!"))?;
+                // skip all steps in which no router changes its forwarding state.
+                while prefix_trace.first().map(|s| s.is_empty()).unwrap_or(false) {
+                    prefix_trace.remove(0);
+                }
                 let step = prefix_trace
                     .first_mut()
                     .ok_or_else(err!("FW delta after prefix {p} should be fully migrated!"))?;
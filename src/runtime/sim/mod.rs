@@ -17,15 +17,30 @@
 
 //! Runtime for the simulated system (in [`bgpsim`]).
 
+use std::collections::HashSet;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
 use bgpsim::{config::NetworkConfig, event::EventQueue, prelude::*};
 use log::error;
 use thiserror::Error;
 
 use crate::{decomposition::Decomposition, specification::Specification, P};
 
-use super::controller::Controller;
+use super::controller::{Controller, Phase};
 
+mod compare;
 mod executor;
+mod naive;
+#[cfg(feature = "undo")]
+mod runner;
+
+pub use compare::{compare, ComparisonReport};
+pub use naive::naive_transient_states;
+
+#[cfg(feature = "undo")]
+#[cfg_attr(docsrs, doc(cfg(feature = "undo")))]
+pub use runner::SimRunner;
 
 /// Probability that the controller is called to try making progress in this step of the
 /// convergence.
@@ -91,6 +106,150 @@ where
     }
 }
 
+/// Perform the decomposed update on the network using the simulated environment (bgpsim), but only
+/// up to and including `phase`, and return the intermediate (converged) network. The specification
+/// is checked at every step, exactly as in [`run`]. Use [`resume_from`] to continue the update.
+pub fn run_until<Q>(
+    mut net: Network<P, Q>,
+    decomp: Decomposition,
+    spec: &Specification,
+    phase: Phase,
+) -> Result<(Network<P, Q>, SimStats), SimError>
+where
+    Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
+{
+    let trace = decomp.fw_state_trace.clone();
+    let mut controller = Controller::new(decomp);
+
+    let stats = controller.execute_sim_until(
+        &mut net,
+        spec,
+        PROB_CONTROLLER_STEP,
+        trace,
+        true,
+        Some(phase),
+    )?;
+
+    Ok((net, stats))
+}
+
+/// Continue the decomposed update on a network that was paused after `phase` (see [`run_until`]).
+/// The remaining phases are performed, checking the specification at every step. The expected
+/// forwarding trace is reduced to the forwarding changes that have not yet happened in `net`.
+///
+/// As the initial network is not known, the final network is not compared to the expected one.
+pub fn resume_from<Q>(
+    mut net: Network<P, Q>,
+    decomp: Decomposition,
+    spec: &Specification,
+    phase: Phase,
+) -> Result<(Network<P, Q>, SimStats), SimError>
+where
+    Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug,
+{
+    let fw_state = net.get_forwarding_state();
+    let trace = decomp
+        .fw_state_trace
+        .iter()
+        .map(|(p, trace)| {
+            let remaining = trace
+                .iter()
+                .map(|step| {
+                    step.iter()
+                        .filter(|(r, nh)| fw_state.get_next_hops(*r, *p) != nh.as_slice())
+                        .cloned()
+                        .collect::<HashSet<_>>()
+                })
+                .filter(|step| !step.is_empty())
+                .collect();
+            (*p, remaining)
+        })
+        .collect();
+    let mut controller = Controller::resume_from(decomp, phase);
+
+    let stats = controller.execute_sim(&mut net, spec, PROB_CONTROLLER_STEP, trace, true)?;
+
+    Ok((net, stats))
+}
+
+/// Replay an experiment stored as json (see `Experiment::write_json`) in the simulated environment,
+/// without decomposing the command again. The network, the decomposition, and the specification
+/// are read from the file, and the decomposition is executed with [`run`], checking the
+/// specification at every step.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub fn replay(path: &Path) -> Result<(Network<P, BasicEventQueue<P>>, SimStats), ReplayError> {
+    #[derive(serde::Deserialize)]
+    #[allow(clippy::missing_docs_in_private_items)]
+    struct RecordedExperiment {
+        net: serde_json::Value,
+        spec: Specification,
+        decomp: Option<Decomposition>,
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let exp: RecordedExperiment = serde_json::from_str(&content)?;
+    let net = Network::from_json_str(&exp.net.to_string(), BasicEventQueue::new)?;
+    let decomp = exp.decomp.ok_or(ReplayError::NoDecomposition)?;
+    Ok(run(net, decomp, &exp.spec)?)
+}
+
+/// Perform the decomposed update on the network using [`run`], and write the result to
+/// `output_dir/scenario.json`, using the same schema as the lab (see `Experiment::write_json`). The
+/// `data` field of the export contains the statistics and convergence estimates of the simulation
+/// (see [`SimExport`]), together with the custom `data`.
+#[cfg(feature = "experiment")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experiment")))]
+pub fn run_and_export<Q, T>(
+    net: Network<P, Q>,
+    decomp: Decomposition,
+    spec: &Specification,
+    output_dir: impl AsRef<Path>,
+    data: T,
+) -> Result<(Network<P, Q>, SimStats), SimExportError>
+where
+    Q: Clone + EventQueue<P> + PartialEq + std::fmt::Debug + serde::Serialize,
+    T: serde::Serialize,
+{
+    let initial_net = net.clone();
+    let exp_decomp = decomp.clone();
+    let (net, stats) = run(net, decomp, spec)?;
+
+    crate::experiment::Experiment {
+        net: &initial_net,
+        topo: None,
+        scenario: None,
+        spec_builder: None,
+        spec,
+        decomp: Some(&exp_decomp),
+        rand: false,
+        data: SimExport {
+            num_steps: stats.fw_deltas.len(),
+            num_fw_updates: stats.fw_deltas.iter().map(Vec::len).sum(),
+            stats: stats.clone(),
+            data,
+        },
+    }
+    .write_json(output_dir, "scenario.json")?;
+
+    Ok((net, stats))
+}
+
+/// Data of an experiment exported by [`run_and_export`].
+#[cfg(feature = "experiment")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experiment")))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SimExport<T> {
+    /// Estimated number of steps until convergence, i.e., the number of forwarding deltas.
+    pub num_steps: usize,
+    /// Estimated number of forwarding updates performed during convergence.
+    pub num_fw_updates: usize,
+    /// Statistics collected during simulation.
+    pub stats: SimStats,
+    /// Custom data passed to [`run_and_export`].
+    pub data: T,
+}
+
 /// Statistics collected during simulation.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -124,3 +283,38 @@ pub enum SimError {
     #[error("Simulated trace does not match the scheduled trace! {0}")]
     TraceMismatch(String),
 }
+
+/// Error when simulating and exporting an experiment.
+#[cfg(feature = "experiment")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experiment")))]
+#[derive(Debug, Error)]
+pub enum SimExportError {
+    /// Cannot write the file.
+    #[error("Cannot write the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The simulation of the decomposition failed.
+    #[error("{0}")]
+    Sim(#[from] SimError),
+}
+
+/// Error when replaying a recorded experiment.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    /// Cannot read the file.
+    #[error("Cannot read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// Cannot parse the experiment.
+    #[error("Cannot parse the experiment: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Cannot reconstruct the network.
+    #[error("Cannot reconstruct the network: {0}")]
+    Network(#[from] NetworkError),
+    /// The experiment does not contain a decomposition.
+    #[error("The experiment does not contain a decomposition")]
+    NoDecomposition,
+    /// The simulation of the recorded decomposition failed.
+    #[error("{0}")]
+    Sim(#[from] SimError),
+}
@@ -0,0 +1,167 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Explore the transient forwarding states of a naive migration under BGP message reordering.
+
+use std::collections::{HashMap, HashSet};
+
+use bgpsim::{
+    config::{ConfigModifier, NetworkConfig},
+    event::{Event, EventQueue},
+    forwarding_state::ForwardingState,
+    prelude::*,
+    router::Router,
+    types::IgpNetwork,
+};
+
+use crate::P;
+
+/// Maximum number of network states explored by [`naive_transient_states`].
+const MAX_EXPLORED_STATES: usize = 10_000;
+
+/// Event queue in which the next event is chosen explicitly (by setting `next`). After each
+/// `pop`, `next` is reset to the first event.
+#[derive(Debug, Clone, Default)]
+struct ChoiceQueue {
+    events: Vec<Event<P, ()>>,
+    next: usize,
+}
+
+impl ChoiceQueue {
+    /// Get the indices of all events that may be processed next. Messages on the same BGP session
+    /// are delivered in order, so only the first event of each session can be chosen.
+    fn choices(&self) -> Vec<usize> {
+        let mut sessions = HashSet::new();
+        self.events
+            .iter()
+            .enumerate()
+            .filter(|(_, Event::Bgp(_, src, dst, _))| sessions.insert((*src, *dst)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl EventQueue<P> for ChoiceQueue {
+    type Priority = ();
+
+    fn push(
+        &mut self,
+        event: Event<P, Self::Priority>,
+        _routers: &HashMap<RouterId, Router<P>>,
+        _net: &IgpNetwork,
+    ) {
+        self.events.push(event)
+    }
+
+    fn pop(&mut self) -> Option<Event<P, Self::Priority>> {
+        if self.next < self.events.len() {
+            let event = self.events.remove(self.next);
+            self.next = 0;
+            Some(event)
+        } else {
+            None
+        }
+    }
+
+    fn peek(&self) -> Option<&Event<P, Self::Priority>> {
+        self.events.get(self.next)
+    }
+
+    fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.events.clear();
+        self.next = 0;
+    }
+
+    fn update_params(&mut self, _: &HashMap<RouterId, Router<P>>, _: &IgpNetwork) {}
+
+    fn get_time(&self) -> Option<f64> {
+        None
+    }
+
+    unsafe fn clone_events(&self, _: Self) -> Self {
+        self.clone()
+    }
+}
+
+/// Enumerate the distinct forwarding states (for `prefix`) that the network may traverse when
+/// applying `command` at once, considering all possible orderings in which BGP messages of
+/// different sessions may be processed. Messages on the same session are always processed in
+/// order. The returned states include the final, converged state.
+///
+/// Each pair of forwarding state and queued messages is explored only once. The exploration is
+/// bounded by [`MAX_EXPLORED_STATES`]; if that bound is reached, only the states found so far are
+/// returned. The network `net` must have converged, i.e., its queue must be empty. Otherwise,
+/// `NetworkError::QueueNotEmpty` is returned.
+pub fn naive_transient_states<Q>(
+    net: &Network<P, Q>,
+    command: &ConfigModifier<P>,
+    prefix: P,
+) -> Result<Vec<ForwardingState<P>>, NetworkError>
+where
+    Q: EventQueue<P> + Clone,
+{
+    let mut net = net
+        .clone()
+        .swap_queue(ChoiceQueue::default())
+        .map_err(|net| NetworkError::QueueNotEmpty(net.queue().len()))?;
+    net.manual_simulation();
+    net.apply_modifier(command)?;
+
+    let routers = net.get_routers();
+    let mut visited: HashSet<Vec<Vec<RouterId>>> = HashSet::new();
+    let mut explored: HashSet<(Vec<Vec<RouterId>>, Vec<Event<P, ()>>)> = HashSet::new();
+    let mut states = Vec::new();
+
+    let mut stack = vec![net];
+    while let Some(net) = stack.pop() {
+        let fw_state = net.get_forwarding_state();
+        let next_hops: Vec<Vec<RouterId>> = routers
+            .iter()
+            .map(|r| fw_state.get_next_hops(*r, prefix).to_vec())
+            .collect();
+
+        // different orderings often lead to the same state, which we only need to explore once.
+        if !explored.insert((next_hops.clone(), net.queue().events.clone())) {
+            continue;
+        }
+        if explored.len() > MAX_EXPLORED_STATES {
+            log::warn!("Reached the bound of {MAX_EXPLORED_STATES} explored states.");
+            break;
+        }
+
+        if visited.insert(next_hops) {
+            states.push(fw_state);
+        }
+
+        for i in net.queue().choices() {
+            let mut next = net.clone();
+            next.queue_mut().next = i;
+            next.simulate_step()?;
+            stack.push(next);
+        }
+    }
+
+    Ok(states)
+}
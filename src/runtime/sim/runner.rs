@@ -0,0 +1,85 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Interactive runner for the simulated system, which applies atomic modifiers one by one and can
+//! roll them back.
+
+use atomic_command::AtomicModifier;
+use bgpsim::{event::EventQueue, network::UndoMark, prelude::*};
+
+use crate::P;
+
+use super::SimError;
+
+/// Runner that applies atomic modifiers on a simulated network one at a time. Each applied
+/// modifier can be reverted using [`SimRunner::undo`], which uses the undo functionality of
+/// [`bgpsim`].
+#[derive(Debug, Clone)]
+pub struct SimRunner<Q> {
+    /// The simulated network
+    net: Network<P, Q>,
+    /// Undo mark taken before applying each modifier.
+    marks: Vec<UndoMark>,
+}
+
+impl<Q> SimRunner<Q>
+where
+    Q: EventQueue<P>,
+{
+    /// Create a new runner on the given network.
+    pub fn new(net: Network<P, Q>) -> Self {
+        Self {
+            net,
+            marks: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the simulated network.
+    pub fn net(&self) -> &Network<P, Q> {
+        &self.net
+    }
+
+    /// Consume the runner and return the simulated network.
+    pub fn into_net(self) -> Network<P, Q> {
+        self.net
+    }
+
+    /// Number of modifiers that were applied and that can be undone.
+    pub fn num_applied(&self) -> usize {
+        self.marks.len()
+    }
+
+    /// Apply the modifier to the network and wait until the network has converged. If applying
+    /// the modifier fails, then all of its effects are reverted before returning the error.
+    pub fn apply(&mut self, modifier: &AtomicModifier<P>) -> Result<(), SimError> {
+        let mark = self.net.get_undo_mark();
+        if let Err(e) = modifier.apply(&mut self.net) {
+            self.net.undo_to_mark(mark)?;
+            return Err(e.into());
+        }
+        self.marks.push(mark);
+        Ok(())
+    }
+
+    /// Revert the last applied modifier, restoring the network (and its forwarding state) as it
+    /// was before applying that modifier. Returns an error if no modifier was applied.
+    pub fn undo(&mut self) -> Result<(), SimError> {
+        let mark = self.marks.pop().ok_or(NetworkError::EmptyUndoStack)?;
+        self.net.undo_to_mark(mark)?;
+        Ok(())
+    }
+}
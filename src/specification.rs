@@ -41,7 +41,7 @@ use bgpsim::{
 use crate::P;
 
 /// Structure to check a Specification
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Checker<'a> {
     /// Specification that is checked
     spec: &'a Specification,
@@ -74,6 +74,13 @@ impl<'a> Checker<'a> {
         }
     }
 
+    /// Fork the checker, such that both the original and the forked checker can be continued with
+    /// different forwarding states independently. This avoids re-checking the common prefix of
+    /// alternative schedules.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
     /// Perform a step by adding the next forwarding state to the checker. Then, check if there may
     /// be a futhre in which the specification is safisfied. If so, return `true`.
     pub fn step(&mut self, fw_state: &mut ForwardingState<P>) -> bool {
@@ -247,9 +254,114 @@ fn check_rec(
 /// specification expression states a single expression for all properties.
 pub type Specification = HashMap<P, SpecExpr>;
 
+/// Operations to combine two specifications.
+pub trait SpecificationExt {
+    /// Combine two specifications, such that both must be satisfied. For prefixes constrained by
+    /// both specifications, the expressions are joined using [`SpecExpr::All`].
+    fn merge(self, other: Self) -> Self;
+
+    /// Combine two specifications, such that either of them must be satisfied. Prefixes that are
+    /// only constrained by one of the specifications are unconstrained in the result. For prefixes
+    /// constrained by both specifications, the expressions are joined using [`SpecExpr::Any`].
+    fn intersect(self, other: Self) -> Self;
+
+    /// Compute the difference between two specifications, i.e., the invariants that are added or
+    /// removed for each prefix when going from `self` to `other`, and the prefixes whose expression
+    /// changed. A prefix may have a changed expression without any added or removed invariant if
+    /// only the modal or logical operators that combine them differ.
+    fn diff(&self, other: &Self) -> SpecDiff;
+
+    /// Serialize the specification as a JSON string.
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> Result<String, serde_json::Error>;
+
+    /// Deserialize the specification from a JSON string generated by [`Self::to_json`].
+    #[cfg(feature = "serde")]
+    fn from_json(s: &str) -> Result<Self, serde_json::Error>
+    where
+        Self: Sized;
+}
+
+impl SpecificationExt for Specification {
+    fn merge(mut self, other: Self) -> Self {
+        for (p, b) in other {
+            let expr = match self.remove(&p) {
+                Some(a) => SpecExpr::All(vec![a, b]),
+                None => b,
+            };
+            self.insert(p, expr);
+        }
+        self
+    }
+
+    fn intersect(mut self, other: Self) -> Self {
+        other
+            .into_iter()
+            .filter_map(|(p, b)| self.remove(&p).map(|a| (p, SpecExpr::Any(vec![a, b]))))
+            .collect()
+    }
+
+    fn diff(&self, other: &Self) -> SpecDiff {
+        let invariants = |spec: &Self, p: &P| {
+            spec.get(p)
+                .map(SpecExpr::get_invariants)
+                .unwrap_or_default()
+        };
+        let mut diff = SpecDiff::default();
+        for p in self.keys().chain(other.keys()).collect::<HashSet<_>>() {
+            let old = invariants(self, p);
+            let new = invariants(other, p);
+            let added: HashSet<Invariant> = new.difference(&old).cloned().collect();
+            let removed: HashSet<Invariant> = old.difference(&new).cloned().collect();
+            if !added.is_empty() {
+                diff.added.insert(*p, added);
+            }
+            if !removed.is_empty() {
+                diff.removed.insert(*p, removed);
+            }
+            if self.get(p) != other.get(p) {
+                diff.changed.insert(*p);
+            }
+        }
+        diff
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    #[cfg(feature = "serde")]
+    fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+/// Difference between two specifications, computed using [`SpecificationExt::diff`]. Prefixes
+/// without any added or removed invariants are not present in `added` or `removed`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecDiff {
+    /// Invariants that are only present in the new specification, for each prefix.
+    pub added: HashMap<P, HashSet<Invariant>>,
+    /// Invariants that are only present in the old specification, for each prefix.
+    pub removed: HashMap<P, HashSet<Invariant>>,
+    /// Prefixes whose expression differs in both specifications, including prefixes that are only
+    /// present in one of them.
+    pub changed: HashSet<P>,
+}
+
+impl SpecDiff {
+    /// Check if both specifications have the same expressions.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
 /// Modal and Logical Operators to build a specification.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(from = "compat::SpecExprRepr"))]
 pub enum SpecExpr {
     /// *Logical Operator*: Always true.
     True,
@@ -365,6 +477,8 @@ pub struct Invariant {
 /// Enumeration of different kinds of properties
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(from = "compat::PropertyRepr"))]
 pub enum Property {
     /// Conjunction of different properties
     All(Vec<Property>),
@@ -374,8 +488,16 @@ pub enum Property {
     Not(Box<Property>),
     /// Waypoint property
     Waypoint(RouterId),
+    /// The path may contain at most the given number of hops (including the hop to the external
+    /// router). Traffic that is dropped (in a black hole or a forwarding loop) satisfies any bound.
+    MaxPathLength(usize),
+    /// The router must not forward traffic into a forwarding loop. Black holes are allowed.
+    LoopFree,
     /// Reachability
     Reachability,
+    /// The router must not be able to reach the prefix, i.e., its traffic must end in a black hole
+    /// (or a forwarding loop) instead of reaching an egress.
+    Isolation,
     /// property is always satisfied.
     True,
 }
@@ -418,7 +540,10 @@ impl Property {
             Self::Any(ps) => ps.iter().any(|p| p.check(path, reachable)),
             Self::Not(p) => !p.check(path, reachable),
             Self::Waypoint(w) => !reachable || path.contains(w),
+            Self::MaxPathLength(max) => !reachable || path.len() <= max + 1,
+            Self::LoopFree => reachable || path.iter().collect::<HashSet<_>>().len() == path.len(),
             Self::Reachability => reachable,
+            Self::Isolation => !reachable,
             Self::True => true,
         }
     }
@@ -428,7 +553,13 @@ impl Property {
         let mut props: HashSet<Self> = match self {
             Self::All(xs) | Self::Any(xs) => xs.iter().flat_map(Self::get_subprops).collect(),
             Self::Not(x) => x.get_subprops(),
-            Self::True | Self::Waypoint(_) | Self::Reachability => HashSet::with_capacity(1),
+            Self::True | Self::Waypoint(_) | Self::Reachability | Self::LoopFree => {
+                HashSet::with_capacity(1)
+            }
+            // the ILP encodes isolation as the negation of reachability.
+            Self::Isolation => once(Self::Reachability).collect(),
+            // the ILP encodes the path length recursively using the bound of the next hop.
+            Self::MaxPathLength(max) => (0..*max).map(Self::MaxPathLength).collect(),
         };
         props.insert(self.clone());
         props
@@ -467,14 +598,7 @@ impl SpecificationBuilder {
         command: Option<&ConfigModifier<P>>,
         prefixes: impl IntoIterator<Item = P>,
     ) -> Specification {
-        let mut old_fws = net.get_forwarding_state();
-        let mut new_fws = if let Some(command) = command {
-            let mut new_net = net.clone();
-            new_net.apply_modifier(command).unwrap();
-            new_net.get_forwarding_state()
-        } else {
-            old_fws.clone()
-        };
+        let (mut old_fws, mut new_fws) = Self::fw_states(net, command);
 
         prefixes
             .into_iter()
@@ -487,6 +611,42 @@ impl SpecificationBuilder {
             .collect()
     }
 
+    /// Build all invariants for all nodes in the network, using a different builder for each
+    /// prefix.
+    pub fn build_per_prefix<Q: EventQueue<P> + Clone>(
+        net: &Network<P, Q>,
+        command: Option<&ConfigModifier<P>>,
+        specs: HashMap<P, SpecificationBuilder>,
+    ) -> Specification {
+        let (mut old_fws, mut new_fws) = Self::fw_states(net, command);
+
+        specs
+            .into_iter()
+            .map(|(p, builder)| {
+                (
+                    p,
+                    builder.build(&mut old_fws, &mut new_fws, net.get_routers(), p),
+                )
+            })
+            .collect()
+    }
+
+    /// Get the forwarding state before and after applying the command.
+    fn fw_states<Q: EventQueue<P> + Clone>(
+        net: &Network<P, Q>,
+        command: Option<&ConfigModifier<P>>,
+    ) -> (ForwardingState<P>, ForwardingState<P>) {
+        let old_fws = net.get_forwarding_state();
+        let new_fws = if let Some(command) = command {
+            let mut new_net = net.clone();
+            new_net.apply_modifier(command).unwrap();
+            new_net.get_forwarding_state()
+        } else {
+            old_fws.clone()
+        };
+        (old_fws, new_fws)
+    }
+
     /// Build the invariant for a given router and prefix.
     pub fn build(
         self,
@@ -722,7 +882,7 @@ impl Property {
                 .into_iter()
                 .flat_map(|x| x.as_fw_policies(net, router, prefix))
                 .collect(),
-            Property::Any(_) | Property::Not(_) => {
+            Property::Any(_) | Property::Not(_) | Property::MaxPathLength(_) => {
                 log::warn!(
                     "Cannot interpret {} as a set of forwarding policies!",
                     self.fmt(net)
@@ -736,6 +896,137 @@ impl Property {
                 bgpsim::policies::PathCondition::Node(wp),
             )],
             Property::Reachability => vec![FwPolicy::Reachable(router, prefix)],
+            Property::Isolation => vec![FwPolicy::NotReachable(router, prefix)],
+            Property::LoopFree => vec![FwPolicy::LoopFree(router, prefix)],
+        }
+    }
+}
+
+/// Deserialization of [`SpecExpr`] and [`Property`]. Both are serialized with an explicit `type`
+/// and `value` field, but specifications stored before that change use serde's default (externally
+/// tagged) representation, e.g., `{"Globally":{"Invariant":{"router":0,"prop":"Reachability"}}}`.
+/// Deserialization accepts both formats.
+#[cfg(feature = "serde")]
+mod compat {
+    use bgpsim::types::RouterId;
+    use serde::Deserialize;
+
+    use super::{Invariant, Property, SpecExpr};
+
+    /// Generate an enum that mirrors the variants of `$target` and convert it into `$target`.
+    macro_rules! mirror {
+        (
+            $(#[$attr:meta])* $name:ident => $target:ident {
+                $($var:ident $(($($field:ident: $ty:ty),*))?),* $(,)?
+            }
+        ) => {
+            #[derive(Deserialize)]
+            $(#[$attr])*
+            pub(super) enum $name {
+                $($var $(($($ty),*))?),*
+            }
+
+            impl From<$name> for $target {
+                fn from(x: $name) -> Self {
+                    match x {
+                        $($name::$var $(($($field),*))? => $target::$var $(($($field),*))?),*
+                    }
+                }
+            }
+        };
+    }
+
+    mirror! {
+        #[serde(tag = "type", content = "value")]
+        TaggedSpecExpr => SpecExpr {
+            True,
+            Not(x: Box<SpecExpr>),
+            All(x: Vec<SpecExpr>),
+            Any(x: Vec<SpecExpr>),
+            Next(x: Box<SpecExpr>),
+            Finally(x: Box<SpecExpr>),
+            Globally(x: Box<SpecExpr>),
+            Until(a: Box<SpecExpr>, b: Box<SpecExpr>),
+            WeakUntil(a: Box<SpecExpr>, b: Box<SpecExpr>),
+            Invariant(x: Invariant),
+        }
+    }
+
+    mirror! {
+        LegacySpecExpr => SpecExpr {
+            True,
+            Not(x: Box<SpecExpr>),
+            All(x: Vec<SpecExpr>),
+            Any(x: Vec<SpecExpr>),
+            Next(x: Box<SpecExpr>),
+            Finally(x: Box<SpecExpr>),
+            Globally(x: Box<SpecExpr>),
+            Until(a: Box<SpecExpr>, b: Box<SpecExpr>),
+            WeakUntil(a: Box<SpecExpr>, b: Box<SpecExpr>),
+            Invariant(x: Invariant),
+        }
+    }
+
+    mirror! {
+        #[serde(tag = "type", content = "value")]
+        TaggedProperty => Property {
+            All(x: Vec<Property>),
+            Any(x: Vec<Property>),
+            Not(x: Box<Property>),
+            Waypoint(x: RouterId),
+            MaxPathLength(x: usize),
+            LoopFree,
+            Reachability,
+            Isolation,
+            True,
+        }
+    }
+
+    mirror! {
+        LegacyProperty => Property {
+            All(x: Vec<Property>),
+            Any(x: Vec<Property>),
+            Not(x: Box<Property>),
+            Waypoint(x: RouterId),
+            MaxPathLength(x: usize),
+            LoopFree,
+            Reachability,
+            Isolation,
+            True,
+        }
+    }
+
+    /// Either the current or the legacy representation of a [`SpecExpr`].
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub(super) enum SpecExprRepr {
+        Tagged(TaggedSpecExpr),
+        Legacy(LegacySpecExpr),
+    }
+
+    impl From<SpecExprRepr> for SpecExpr {
+        fn from(x: SpecExprRepr) -> Self {
+            match x {
+                SpecExprRepr::Tagged(x) => x.into(),
+                SpecExprRepr::Legacy(x) => x.into(),
+            }
+        }
+    }
+
+    /// Either the current or the legacy representation of a [`Property`].
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub(super) enum PropertyRepr {
+        Tagged(TaggedProperty),
+        Legacy(LegacyProperty),
+    }
+
+    impl From<PropertyRepr> for Property {
+        fn from(x: PropertyRepr) -> Self {
+            match x {
+                PropertyRepr::Tagged(x) => x.into(),
+                PropertyRepr::Legacy(x) => x.into(),
+            }
         }
     }
 }
@@ -24,10 +24,12 @@ use bgpsim::{
     route_map::{RouteMapBuilder, RouteMapDirection::Incoming},
     topology_zoo::TopologyZoo,
 };
+use std::time::Duration;
+
 use test_log::test;
 
 use crate::{
-    decomposition::decompose,
+    decomposition::{decompose, decompose_with_budget, BudgetedDecomposition, DecompositionError},
     runtime::sim::run,
     specification::{Specification, SpecificationBuilder},
     P,
@@ -211,6 +213,35 @@ fn remove_session() {
     run(net, decomposition, &spec).unwrap();
 }
 
+#[test]
+fn remove_session_with_budget() {
+    let (net, p, spec) = get_net();
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: hs(),
+        target: hs_ext(),
+        session_type: BgpSessionType::EBgp,
+    });
+
+    // with enough time, the decomposition is complete.
+    match decompose_with_budget(&net, command.clone(), &spec, Duration::from_secs(600)).unwrap() {
+        BudgetedDecomposition::Complete(decomposition) => {
+            run(net.clone(), decomposition, &spec).unwrap()
+        }
+        BudgetedDecomposition::BestEffort(_, e) => panic!("Decomposition is incomplete: {e}"),
+    }
+
+    // without any time, the prefix cannot be scheduled, but a decomposition is still returned.
+    match decompose_with_budget(&net, command, &spec, Duration::ZERO).unwrap() {
+        BudgetedDecomposition::Complete(_) => panic!("Decomposition is complete without any time"),
+        BudgetedDecomposition::BestEffort(decomposition, e) => {
+            assert!(matches!(e, DecompositionError::Unscheduled(ps) if ps == vec![p]));
+            assert!(decomposition.atomic_before.get(&p).is_none());
+            assert!(decomposition.atomic_after.get(&p).is_none());
+            assert!(decomposition.schedule.get(&p).is_none());
+        }
+    }
+}
+
 #[test]
 fn remove_route_map() {
     let (net, _p, spec) = get_net();
@@ -323,6 +354,8 @@ fn empty_command() {
     assert!(decomposition.atomic_after[&p].is_empty());
     assert_eq!(decomposition.cleanup_commands.len(), 1);
     assert!(decomposition.cleanup_commands[0].is_empty());
+    assert_eq!(decomposition.step_count(), 3);
+    assert_eq!(decomposition.temp_session_count(), 0);
 
     run(net, decomposition, &spec).unwrap();
 }
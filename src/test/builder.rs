@@ -15,9 +15,23 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-use bgpsim::{prelude::BasicEventQueue, topology_zoo::TopologyZoo};
+use std::collections::HashSet;
 
-use crate::experiment::Scenario;
+use bgpsim::{
+    config::{ConfigExpr, ConfigModifier, NetworkConfig},
+    forwarding_state::ForwardingState,
+    prelude::{BasicEventQueue, BgpSessionType},
+    topology_zoo::TopologyZoo,
+};
+use itertools::Itertools;
+
+use crate::{
+    decompose,
+    experiment::{DecompositionExport, Experiment, Scenario, ScenarioBuildError},
+    runtime::sim::{replay, run_and_export, SimExport},
+    specification::SpecificationBuilder,
+    RouterId, P,
+};
 
 #[test]
 fn deterministic_builder_abilene() {
@@ -40,3 +54,265 @@ fn deterministic_builder_uninett() {
         assert_eq!(net_a, net_b);
     }
 }
+
+#[test]
+fn change_link_weight_abilene() {
+    let topo = TopologyZoo::Abilene;
+    let (net, p, cmd) = Scenario::ChangeLinkWeight
+        .build(topo, BasicEventQueue::new(), false)
+        .unwrap();
+    match &cmd {
+        ConfigModifier::Update {
+            from:
+                ConfigExpr::IgpLinkWeight {
+                    source,
+                    target,
+                    weight: old,
+                },
+            to: ConfigExpr::IgpLinkWeight { weight: new, .. },
+        } => {
+            assert!(net.get_device(*source).is_internal());
+            assert!(net.get_device(*target).is_internal());
+            assert_eq!(net.get_link_weigth(*source, *target).unwrap(), *old);
+            assert!(new > old);
+        }
+        c => panic!("Expected a link weight update, found {c:?}"),
+    }
+
+    // the command changes the egress of at least one router
+    let mut final_net = net.clone();
+    final_net.apply_modifier(&cmd).unwrap();
+    let mut initial_fw = net.get_forwarding_state();
+    let mut final_fw = final_net.get_forwarding_state();
+    let egress = |fw: &mut ForwardingState<P>, r: RouterId| {
+        fw.get_paths(r, p)
+            .unwrap()
+            .into_iter()
+            .map(|path| *path.last().unwrap())
+            .collect::<HashSet<_>>()
+    };
+    assert!(net
+        .get_routers()
+        .into_iter()
+        .any(|r| egress(&mut initial_fw, r) != egress(&mut final_fw, r)));
+
+    // the scenario is deterministic
+    let (_, _, cmd_b) = Scenario::ChangeLinkWeight
+        .build(topo, BasicEventQueue::new(), false)
+        .unwrap();
+    assert_eq!(cmd, cmd_b);
+}
+
+#[test]
+fn move_route_reflector() {
+    let topo = TopologyZoo::Epoch;
+    assert_eq!(topo.num_internals(), 6);
+    let (net, _, cmds) = Scenario::MoveRouteReflector
+        .build_commands(topo, BasicEventQueue::new(), false)
+        .unwrap();
+    assert!(!cmds.is_empty());
+
+    // all commands change iBGP sessions between internal routers
+    for c in cmds.iter() {
+        let exprs = match c {
+            ConfigModifier::Insert(e) | ConfigModifier::Remove(e) => vec![e],
+            ConfigModifier::Update { from, to } => vec![from, to],
+            c => panic!("Expected a modification of BGP sessions, found {c:?}"),
+        };
+        for e in exprs {
+            match e {
+                ConfigExpr::BgpSession {
+                    source,
+                    target,
+                    session_type,
+                } => {
+                    assert!(net.get_device(*source).is_internal());
+                    assert!(net.get_device(*target).is_internal());
+                    assert_ne!(*session_type, BgpSessionType::EBgp);
+                }
+                e => panic!("Expected a BGP session, found {e:?}"),
+            }
+        }
+    }
+
+    // a single command cannot express the scenario
+    assert!(matches!(
+        Scenario::MoveRouteReflector.build(topo, BasicEventQueue::new(), false),
+        Err(ScenarioBuildError::MultipleCommands(_))
+    ));
+}
+
+#[test]
+fn multi_prefix_abilene() {
+    let topo = TopologyZoo::Abilene;
+    let (net, prefixes, _) = Scenario::DelBestRoute
+        .build_multi(topo, BasicEventQueue::new(), 4, false)
+        .unwrap();
+    assert_eq!(prefixes.len(), 4);
+    assert_eq!(prefixes.iter().unique().count(), 4);
+
+    let mut fw_state = net.get_forwarding_state();
+    let mut best_egresses = HashSet::new();
+    for p in prefixes {
+        assert!(net.get_external_routers().into_iter().any(|e| net
+            .get_device(e)
+            .unwrap_external()
+            .get_advertised_route(p)
+            .is_some()));
+        for r in net.get_routers() {
+            assert!(fw_state.get_paths(r, p).is_ok());
+        }
+        best_egresses.insert(fw_state.get_paths(net.get_routers()[0], p).unwrap()[0].clone());
+    }
+    // the prefixes have different preferences.
+    assert!(best_egresses.len() > 1);
+}
+
+#[test]
+fn seeded_builder_abilene() {
+    let topo = TopologyZoo::Abilene;
+    for scenario in [Scenario::DelBestRoute, Scenario::NewBestRoute] {
+        let (net_a, _, cmd_a) = scenario
+            .build_seeded(topo, BasicEventQueue::new(), 42)
+            .unwrap();
+        let (net_b, _, cmd_b) = scenario
+            .build_seeded(topo, BasicEventQueue::new(), 42)
+            .unwrap();
+        assert_eq!(cmd_a, cmd_b);
+        assert_eq!(net_a, net_b);
+    }
+}
+
+#[test]
+fn export_decomposition() {
+    let (net, p, command) = Scenario::DelBestRoute
+        .build(TopologyZoo::Abilene, BasicEventQueue::new(), false)
+        .unwrap();
+    let spec = SpecificationBuilder::Reachability.build_all(&net, Some(&command), [p]);
+    let decomp = decompose(&net, command.clone(), &spec).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("decomposition.json");
+    DecompositionExport { spec, decomp }
+        .write_json(dir.path(), "decomposition.json")
+        .unwrap();
+    assert!(file.exists());
+
+    let export = DecompositionExport::read_json(&file).unwrap();
+    assert_eq!(export.decomp.original_command, command);
+    assert_eq!(
+        export.spec,
+        SpecificationBuilder::Reachability.build_all(&net, Some(&command), [p])
+    );
+}
+
+#[test]
+fn replay_experiment() {
+    let topo = TopologyZoo::Abilene;
+    let scenario = Scenario::DelBestRoute;
+    let (net, p, command) = scenario.build(topo, BasicEventQueue::new(), false).unwrap();
+    let spec = SpecificationBuilder::Reachability.build_all(&net, Some(&command), [p]);
+    let decomp = decompose(&net, command.clone(), &spec).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("scenario.json");
+    Experiment {
+        net: &net,
+        topo: Some(topo),
+        scenario: Some(scenario),
+        spec_builder: Some(SpecificationBuilder::Reachability),
+        spec: &spec,
+        decomp: Some(&decomp),
+        rand: false,
+        data: (),
+    }
+    .write_json(dir.path(), "scenario.json")
+    .unwrap();
+
+    let (final_net, _) = replay(&file).unwrap();
+
+    let mut exp_net = net.clone();
+    exp_net.apply_modifier(&command).unwrap();
+    assert_eq!(final_net, exp_net);
+}
+
+#[test]
+fn sim_run_and_export() {
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct ExportedExperiment {
+        topo: Option<TopologyZoo>,
+        scenario: Option<Scenario>,
+        spec_builder: Option<SpecificationBuilder>,
+        spec: crate::specification::Specification,
+        decomp: Option<crate::decomposition::Decomposition>,
+        data: SimExport<String>,
+        net: serde_json::Value,
+    }
+
+    let (net, p, command) = Scenario::DelBestRoute
+        .build(TopologyZoo::Abilene, BasicEventQueue::new(), false)
+        .unwrap();
+    let spec = SpecificationBuilder::Reachability.build_all(&net, Some(&command), [p]);
+    let decomp = decompose(&net, command.clone(), &spec).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let (_, stats) =
+        run_and_export(net.clone(), decomp, &spec, dir.path(), String::from("sim")).unwrap();
+
+    let content = std::fs::read_to_string(dir.path().join("scenario.json")).unwrap();
+    let exp: ExportedExperiment = serde_json::from_str(&content).unwrap();
+
+    assert_eq!(exp.spec, spec);
+    assert_eq!(exp.decomp.unwrap().original_command, command);
+    assert_eq!(exp.data.data, "sim");
+    assert_eq!(exp.data.num_steps, stats.fw_deltas.len());
+    assert_eq!(exp.data.stats, stats);
+    assert_eq!(
+        exp.data.num_fw_updates,
+        stats.fw_deltas.iter().map(Vec::len).sum::<usize>()
+    );
+    assert!(exp.net.is_object());
+}
+
+#[test]
+fn build_from_graphml() {
+    let graphml = r#"<?xml version="1.0" encoding="utf-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key attr.name="label" attr.type="string" for="node" id="d34" />
+  <key attr.name="Internal" attr.type="int" for="node" id="d29" />
+  <graph edgedefault="undirected">
+    <node id="0"><data key="d29">1</data><data key="d34">A</data></node>
+    <node id="1"><data key="d29">1</data><data key="d34">B</data></node>
+    <node id="2"><data key="d29">1</data><data key="d34">C</data></node>
+    <node id="3"><data key="d29">1</data><data key="d34">D</data></node>
+    <node id="4"><data key="d29">1</data><data key="d34">E</data></node>
+    <node id="5"><data key="d29">1</data><data key="d34">F</data></node>
+    <edge source="0" target="1" />
+    <edge source="1" target="2" />
+    <edge source="2" target="3" />
+    <edge source="3" target="4" />
+    <edge source="4" target="5" />
+    <edge source="5" target="0" />
+    <edge source="0" target="3" />
+  </graph>
+</graphml>"#;
+
+    for scenario in [Scenario::DelBestRoute, Scenario::NewBestRoute] {
+        let (net_a, _, cmd_a) = scenario
+            .build_from_graphml(graphml, BasicEventQueue::new(), false)
+            .unwrap();
+        let (net_b, _, cmd_b) = scenario
+            .build_from_graphml(graphml, BasicEventQueue::new(), false)
+            .unwrap();
+        assert_eq!(cmd_a, cmd_b);
+        assert_eq!(net_a, net_b);
+        assert_eq!(net_a.get_routers().len(), 6);
+        assert_eq!(net_a.get_external_routers().len(), 3);
+    }
+
+    assert!(matches!(
+        Scenario::DelBestRoute.build_from_graphml("<graph />", BasicEventQueue::new(), false),
+        Err(ScenarioBuildError::TopologyZoo(_))
+    ));
+}
@@ -0,0 +1,310 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the passes of the compiler.
+
+use atomic_command::{AtomicCommand, AtomicCondition, AtomicModifier};
+use bgpsim::{
+    config::{ConfigExpr, ConfigModifier},
+    route_map::{RouteMapBuilder, RouteMapDirection},
+    types::RouterId,
+};
+use test_log::test;
+
+use crate::{decomposition::compiler::coalesce_commands, Decomposition, P};
+
+/// Route-map on `router` that sets the weight of routes for `p` from `neighbor`.
+fn weight_rm(router: RouterId, neighbor: RouterId, p: P, weight: u32) -> ConfigExpr<P> {
+    ConfigExpr::BgpRouteMap {
+        router,
+        neighbor,
+        direction: RouteMapDirection::Incoming,
+        map: RouteMapBuilder::new()
+            .allow()
+            .order(10)
+            .match_prefix(p)
+            .set_weight(weight)
+            .build(),
+    }
+}
+
+#[test]
+fn coalesce_same_router() {
+    let r0: RouterId = 0.into();
+    let r1: RouterId = 1.into();
+    let r2: RouterId = 2.into();
+    let p = P::from(0);
+
+    let change = AtomicCommand {
+        command: AtomicModifier::ChangePreference {
+            router: r0,
+            prefix: p,
+            neighbor: r1,
+            raw: vec![ConfigModifier::Insert(weight_rm(r0, r1, p, 200))],
+        },
+        precondition: AtomicCondition::None,
+        postcondition: AtomicCondition::SelectedRoute {
+            router: r0,
+            prefix: p,
+            neighbor: Some(r1),
+            weight: None,
+            next_hop: None,
+            originator_id: None,
+        },
+    };
+    let clear = AtomicCommand {
+        command: AtomicModifier::ClearPreference {
+            router: r0,
+            prefix: p,
+            raw: vec![ConfigModifier::Remove(weight_rm(r0, r2, p, 100))],
+        },
+        precondition: AtomicCondition::None,
+        postcondition: AtomicCondition::None,
+    };
+    let other = AtomicCommand {
+        command: AtomicModifier::ClearPreference {
+            router: r1,
+            prefix: p,
+            raw: vec![ConfigModifier::Remove(weight_rm(r1, r2, p, 100))],
+        },
+        precondition: AtomicCondition::None,
+        postcondition: AtomicCondition::None,
+    };
+
+    let mut decomp = Decomposition::baseline(ConfigModifier::Insert(weight_rm(r0, r1, p, 200)));
+    decomp
+        .atomic_before
+        .insert(p, vec![vec![change.clone(), clear.clone(), other.clone()]]);
+    coalesce_commands(&mut decomp);
+
+    let step = &decomp.atomic_before[&p][0];
+    assert_eq!(step.len(), 2);
+    assert_eq!(
+        step[0],
+        AtomicCommand {
+            command: AtomicModifier::ChangePreference {
+                router: r0,
+                prefix: p,
+                neighbor: r1,
+                raw: vec![
+                    ConfigModifier::Insert(weight_rm(r0, r1, p, 200)),
+                    ConfigModifier::Remove(weight_rm(r0, r2, p, 100)),
+                ],
+            },
+            precondition: AtomicCondition::None,
+            postcondition: change.postcondition,
+        }
+    );
+    assert_eq!(step[1], other);
+
+    // the main command is left unchanged
+    assert_eq!(decomp.main_commands.len(), 1);
+    assert_eq!(decomp.main_commands[0].len(), 1);
+
+    // commands in different rounds are never merged
+    let mut decomp = Decomposition::baseline(ConfigModifier::Insert(weight_rm(r0, r1, p, 200)));
+    decomp
+        .atomic_before
+        .insert(p, vec![vec![change.clone()], vec![clear.clone()]]);
+    coalesce_commands(&mut decomp);
+    assert_eq!(decomp.atomic_before[&p], vec![vec![change], vec![clear]]);
+}
+
+#[test]
+fn coalesce_keeps_temp_sessions() {
+    let r0: RouterId = 0.into();
+    let r1: RouterId = 1.into();
+    let r2: RouterId = 2.into();
+    let p = P::from(0);
+
+    let change = AtomicCommand {
+        command: AtomicModifier::ChangePreference {
+            router: r0,
+            prefix: p,
+            neighbor: r1,
+            raw: vec![ConfigModifier::Insert(weight_rm(r0, r1, p, 200))],
+        },
+        precondition: AtomicCondition::None,
+        postcondition: AtomicCondition::None,
+    };
+    let use_temp = AtomicCommand {
+        command: AtomicModifier::UseTempSession {
+            router: r0,
+            neighbor: r2,
+            prefix: p,
+            raw: ConfigModifier::Insert(weight_rm(r0, r2, p, 300)),
+        },
+        precondition: AtomicCondition::None,
+        postcondition: AtomicCondition::None,
+    };
+
+    let mut decomp = Decomposition::baseline(ConfigModifier::Insert(weight_rm(r0, r1, p, 200)));
+    decomp
+        .atomic_before
+        .insert(p, vec![vec![change.clone(), use_temp.clone()]]);
+    assert_eq!(decomp.temp_session_count(), 1);
+    coalesce_commands(&mut decomp);
+
+    // the temporary session is not hidden inside the `ChangePreference` command.
+    assert_eq!(decomp.atomic_before[&p], vec![vec![change, use_temp]]);
+    assert_eq!(decomp.temp_session_count(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn compile_hand_written_schedule() {
+    use bgpsim::{
+        builder::{constant_link_weight, unique_preferences, NetworkBuilder},
+        prelude::*,
+    };
+
+    use crate::{
+        decomposition::{
+            compiler::compile_from_schedule, ilp_scheduler::schedule_from_json, CommandInfo,
+        },
+        specification::SpecificationBuilder,
+    };
+
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+    net.build_external_routers(|_, _| vec![RouterId::from(0), RouterId::from(2)], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_route_reflection(|_, _| vec![RouterId::from(3)], ())
+        .unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+    let ads = net.build_advertisements(p, unique_preferences, 2).unwrap();
+    let spec = SpecificationBuilder::Reachability.build_all(&net, None, [p]);
+
+    let e = ads[0][0];
+    let r = *net
+        .get_device(e)
+        .unwrap_external()
+        .get_bgp_sessions()
+        .iter()
+        .next()
+        .unwrap();
+
+    // every router switches in a separate round, without temporary sessions. The router on which
+    // the session is removed switches last.
+    let order: Vec<RouterId> = net
+        .get_routers()
+        .into_iter()
+        .filter(|x| *x != r)
+        .chain(std::iter::once(r))
+        .collect();
+    let json = format!(
+        "{{{}}}",
+        order
+            .iter()
+            .enumerate()
+            .map(|(round, router)| format!(
+                r#""{}": {{"fw_state": {round}, "old_route": {round}, "new_route": {round}}}"#,
+                router.index()
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let schedule = schedule_from_json(&json).unwrap();
+    assert_eq!(schedule.len(), order.len());
+    assert_eq!(schedule[&r].fw_state, order.len() - 1);
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+    let info = CommandInfo::new(&net, command.clone(), &spec).unwrap();
+    let decomp = compile_from_schedule(&info, schedule, p).unwrap();
+
+    assert_eq!(decomp.original_command, command);
+    assert_eq!(decomp.temp_session_count(), 0);
+    // the trace contains one step for each round, even if no router changes its next-hop.
+    assert_eq!(decomp.fw_state_trace[&p].len(), order.len());
+    // each router prefers its old route during setup.
+    assert_eq!(decomp.setup_commands.len(), 1);
+    assert_eq!(decomp.setup_commands[0].len(), order.len());
+    // each router changes its preference in its own round, all before the session is removed.
+    let before = &decomp.atomic_before[&p];
+    assert_eq!(before.len(), order.len());
+    for (round, router) in order.iter().enumerate() {
+        assert_eq!(before[round].len(), 1);
+        assert!(matches!(
+            before[round][0].command,
+            AtomicModifier::ChangePreference { router: x, prefix, .. } if x == *router && prefix == p
+        ));
+    }
+    assert!(decomp.atomic_after[&p].is_empty());
+}
+
+#[test]
+fn decompose_with_coalescing() {
+    use bgpsim::{
+        builder::{constant_link_weight, unique_preferences, NetworkBuilder},
+        prelude::*,
+    };
+
+    use crate::{decompose_with_options, specification::SpecificationBuilder, CompilerOptions};
+
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+    net.build_external_routers(|_, _| vec![RouterId::from(0), RouterId::from(2)], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_route_reflection(|_, _| vec![RouterId::from(3)], ())
+        .unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+    let ads = net.build_advertisements(p, unique_preferences, 2).unwrap();
+    let spec = SpecificationBuilder::Reachability.build_all(&net, None, [p]);
+
+    let e = ads[0][0];
+    let r = *net
+        .get_device(e)
+        .unwrap_external()
+        .get_bgp_sessions()
+        .iter()
+        .next()
+        .unwrap();
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let num_commands = |d: &Decomposition| {
+        d.setup_commands
+            .iter()
+            .chain(d.atomic_before.values().flatten())
+            .chain(d.main_commands.iter())
+            .chain(d.atomic_after.values().flatten())
+            .chain(d.cleanup_commands.iter())
+            .map(Vec::len)
+            .sum::<usize>()
+    };
+
+    let (plain, _) =
+        decompose_with_options(&net, command.clone(), &spec, CompilerOptions::default()).unwrap();
+    let (coalesced, _) =
+        decompose_with_options(&net, command, &spec, CompilerOptions { coalesce: true }).unwrap();
+
+    coalesced.verify_endpoints(&net, &spec).unwrap();
+    assert!(num_commands(&coalesced) <= num_commands(&plain));
+    assert_eq!(coalesced.temp_session_count(), plain.temp_session_count());
+    assert_eq!(coalesced.step_count(), plain.step_count());
+}
@@ -0,0 +1,128 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test exporting migrations for `bgpsim-web`.
+
+use bgpsim::{
+    builder::{constant_link_weight, unique_preferences, NetworkBuilder},
+    config::{ConfigExpr, ConfigModifier},
+    prelude::*,
+};
+use itertools::Itertools;
+use test_log::test;
+
+use crate::{
+    decomposition::decompose,
+    export_web, export_web_bundle,
+    specification::{Specification, SpecificationBuilder},
+    web_bundle, WebBundle, P,
+};
+
+/// Clique of 4 routers with two external routers, removing the session to the preferred one.
+fn prepare() -> (
+    Network<P, BasicEventQueue<P>>,
+    Specification,
+    ConfigModifier<P>,
+) {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 4);
+    net.build_external_routers(|_, _| vec![RouterId::from(0), RouterId::from(2)], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+    let ads = net.build_advertisements(p, unique_preferences, 2).unwrap();
+    let spec = SpecificationBuilder::Reachability.build_all(&net, None, [p]);
+
+    let e = ads[0][0];
+    let r = net
+        .get_device(e)
+        .unwrap_external()
+        .get_bgp_sessions()
+        .iter()
+        .next()
+        .copied()
+        .unwrap();
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    (net, spec, command)
+}
+
+#[test]
+fn bundle() {
+    let (net, spec, command) = prepare();
+    let decomposition = decompose(&net, command.clone(), &spec).unwrap();
+    let json = serde_json::to_string(&web_bundle(&net, &spec, decomposition).unwrap()).unwrap();
+
+    let bundle: WebBundle = serde_json::from_str(&json).unwrap();
+    assert_eq!(bundle.atomic.len(), 5);
+    assert_eq!(bundle.naive.len(), 1);
+    assert_eq!(
+        bundle.naive[0][0][0].command,
+        atomic_command::AtomicModifier::Raw(command)
+    );
+    assert!(!bundle.spec.is_empty());
+    // one policy status per phase
+    assert_eq!(bundle.atomic_spec.len(), bundle.atomic.len());
+    assert_eq!(bundle.naive_spec.len(), bundle.naive.len());
+    for status in bundle.atomic_spec.iter().chain(bundle.naive_spec.iter()) {
+        assert_eq!(
+            status.keys().sorted().collect_vec(),
+            bundle.spec.keys().sorted().collect_vec()
+        );
+    }
+    // the atomic migration satisfies the specification after every phase.
+    assert!(bundle
+        .atomic_spec
+        .iter()
+        .flat_map(|status| status.values().flatten())
+        .all(|(_, result)| result.is_ok()));
+    assert!(Network::from_json_str(&bundle.net.to_string(), BasicEventQueue::new).is_ok());
+}
+
+#[test]
+fn export_to_output_dir() {
+    let (net, spec, command) = prepare();
+    let decomposition = decompose(&net, command, &spec).unwrap();
+
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().join("web");
+    export_web(&net, &spec, decomposition.clone(), &dir, "test").unwrap();
+    export_web_bundle(&net, &spec, decomposition, &dir, "test").unwrap();
+
+    let files: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|f| f.unwrap().file_name().to_string_lossy().to_string())
+        .sorted()
+        .collect();
+    let atomic: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("test_atomic.json")).unwrap())
+            .unwrap();
+    assert_eq!(atomic["phase_spec"].as_array().unwrap().len(), 5);
+    assert_eq!(
+        files,
+        ["test_atomic.json", "test_bundle.json", "test_naive.json"]
+    );
+    for file in files {
+        assert!(!std::path::Path::new(&file).exists());
+    }
+}
@@ -0,0 +1,299 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the commands generated for the cisco lab.
+
+use std::collections::HashMap;
+
+use atomic_command::{AtomicCommand, AtomicCondition, AtomicModifier};
+use bgpsim::{
+    builder::{constant_link_weight, NetworkBuilder},
+    config::{ConfigExpr, ConfigModifier},
+    export::{cisco_frr_generators::Target, CiscoFrrCfgGen, DefaultAddressorBuilder},
+    prelude::*,
+    route_map::{RouteMapBuilder, RouteMapDirection},
+};
+use test_log::test;
+
+use crate::{
+    decomposition::Decomposition,
+    runtime::lab::{command_jobs, generate_commands, ConfiguredJobs},
+    P,
+};
+
+/// Route-map on `r0` that prefers routes for `p` learned from `r2`.
+fn temp_session_rm(r: [RouterId; 3], p: P) -> ConfigExpr<P> {
+    ConfigExpr::BgpRouteMap {
+        router: r[0],
+        neighbor: r[2],
+        direction: RouteMapDirection::Incoming,
+        map: RouteMapBuilder::new()
+            .allow()
+            .order(10)
+            .match_prefix(p)
+            .set_weight(200)
+            .exit()
+            .build(),
+    }
+}
+
+#[test]
+fn temp_session() {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 3);
+    let r = [0.into(), 1.into(), 2.into()];
+    net.build_external_routers(|_, _| vec![r[0], r[2]], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+    net.build_advertisements(p, |_, _| vec![vec![3.into()], vec![4.into()]], ())
+        .unwrap();
+
+    let mut addressor = DefaultAddressorBuilder::default().build(&net).unwrap();
+    let ifaces = (1..=8).map(|i| format!("Ethernet8/{i}")).collect();
+    let mut gen = CiscoFrrCfgGen::new(&net, r[0], Target::CiscoNexus7000, ifaces).unwrap();
+
+    let use_temp = AtomicModifier::UseTempSession {
+        router: r[0],
+        neighbor: r[2],
+        prefix: p,
+        raw: ConfigModifier::Insert(temp_session_rm(r, p)),
+    };
+    let ignore_temp = AtomicModifier::IgnoreTempSession {
+        router: r[0],
+        neighbor: r[2],
+        prefix: p,
+        raw: ConfigModifier::Remove(temp_session_rm(r, p)),
+    };
+
+    // using the temporary session installs the route-map item on the temporary session.
+    let cmd = generate_commands(&net, &mut gen, &mut addressor, r[0], &use_temp).unwrap();
+    let lines = cmd.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("ip prefix-list neighbor-R2-in-32778-pl "));
+    assert!(lines.contains(&"route-map neighbor-R2-in permit 32778"));
+    assert!(lines.contains(&"  match ip address prefix-list neighbor-R2-in-32778-pl"));
+    assert!(lines.contains(&"  set weight 200"));
+    assert!(!cmd.contains("ip route"));
+
+    // ignoring the temporary session removes the route-map item again.
+    let cmd = generate_commands(&net, &mut gen, &mut addressor, r[0], &ignore_temp).unwrap();
+    assert_eq!(
+        cmd,
+        "\
+no ip prefix-list neighbor-R2-in-32778-pl
+no route-map neighbor-R2-in permit 32778
+"
+    );
+
+    // routers that are not affected by the modifier get no commands.
+    let mut gen = CiscoFrrCfgGen::new(&net, r[1], Target::CiscoNexus7000, vec![]).unwrap();
+    assert_eq!(
+        generate_commands(&net, &mut gen, &mut addressor, r[1], &use_temp).unwrap(),
+        ""
+    );
+}
+
+/// Atomic command without any pre- or postcondition.
+fn unconditional(command: AtomicModifier<P>) -> AtomicCommand<P> {
+    AtomicCommand {
+        command,
+        precondition: AtomicCondition::None,
+        postcondition: AtomicCondition::None,
+    }
+}
+
+#[test]
+fn cli_script() {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 3);
+    let r = [0.into(), 1.into(), 2.into()];
+    net.build_external_routers(|_, _| vec![r[0], r[2]], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.set_bgp_session(r[1], r[0], Some(BgpSessionType::IBgpClient))
+        .unwrap();
+    net.set_bgp_session(r[1], r[2], Some(BgpSessionType::IBgpClient))
+        .unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+
+    let mut addressor = DefaultAddressorBuilder::default().build(&net).unwrap();
+    let mut cfg_gens = r
+        .iter()
+        .map(|r| {
+            let ifaces = (1..=8).map(|i| format!("Ethernet8/{i}")).collect();
+            let gen = CiscoFrrCfgGen::new(&net, *r, Target::CiscoNexus7000, ifaces).unwrap();
+            (*r, gen)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let session = ConfigExpr::BgpSession {
+        source: r[2],
+        target: r[0],
+        session_type: BgpSessionType::IBgpPeer,
+    };
+    let mut decomp = Decomposition::baseline(ConfigModifier::Update {
+        from: ConfigExpr::IgpLinkWeight {
+            source: r[1],
+            target: r[2],
+            weight: 1.0,
+        },
+        to: ConfigExpr::IgpLinkWeight {
+            source: r[1],
+            target: r[2],
+            weight: 5.0,
+        },
+    });
+    decomp.setup_commands = vec![vec![AtomicCommand {
+        command: AtomicModifier::AddTempSession {
+            router: r[0],
+            neighbor: r[2],
+            raw: vec![ConfigModifier::Insert(session.clone())],
+        },
+        precondition: AtomicCondition::BgpSessionEstablished {
+            router: r[1],
+            neighbor: r[0],
+        },
+        postcondition: AtomicCondition::BgpSessionEstablished {
+            router: r[0],
+            neighbor: r[2],
+        },
+    }]];
+    decomp.atomic_before.insert(
+        p,
+        vec![vec![unconditional(AtomicModifier::UseTempSession {
+            router: r[0],
+            neighbor: r[2],
+            prefix: p,
+            raw: ConfigModifier::Insert(temp_session_rm(r, p)),
+        })]],
+    );
+    decomp.atomic_after.insert(
+        p,
+        vec![vec![unconditional(AtomicModifier::IgnoreTempSession {
+            router: r[0],
+            neighbor: r[2],
+            prefix: p,
+            raw: ConfigModifier::Remove(temp_session_rm(r, p)),
+        })]],
+    );
+    decomp.cleanup_commands = vec![vec![unconditional(AtomicModifier::RemoveTempSession {
+        router: r[0],
+        neighbor: r[2],
+        raw: vec![ConfigModifier::Remove(session)],
+    })]];
+
+    let script = decomp
+        .to_cli_script(&net, &mut cfg_gens, &mut addressor)
+        .unwrap();
+
+    // one header per stage and step
+    let stages = script
+        .lines()
+        .filter(|l| l.starts_with("# ====="))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stages,
+        vec![
+            "# ===== setup: step 1 =====",
+            "# ===== before, 0: step 1 =====",
+            "# ===== main: step 1 =====",
+            "# ===== after, 0: step 1 =====",
+            "# ===== cleanup: step 1 =====",
+        ]
+    );
+
+    // one block per command and router, where the neighbor of a temporary session is configured
+    // first when adding it, and last when removing it.
+    let routers = script
+        .lines()
+        .filter_map(|l| l.strip_prefix("# router "))
+        .map(|l| l.split(':').next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(routers, vec!["R2", "R0", "R0", "R1", "R0", "R0", "R2"]);
+
+    // the conditions of adding the temporary session are given once: the precondition before the
+    // commands of the first router, and the postcondition after the commands of the last router.
+    let lines = script.lines().collect::<Vec<_>>();
+    let position = |prefix: &str| {
+        let pos = (0..lines.len())
+            .filter(|i| lines[*i].starts_with(prefix))
+            .collect::<Vec<_>>();
+        assert_eq!(pos.len(), 1, "{prefix:?} must appear exactly once");
+        pos[0]
+    };
+    let is_router = |i: &usize| lines[*i].starts_with("# router ");
+    let pre = position("# wait until: ");
+    let post = position("# then wait until: ");
+    let first = (0..lines.len()).find(is_router).unwrap();
+    let last = (0..post).rev().find(is_router).unwrap();
+    assert!(lines[first].starts_with("# router R2: "));
+    assert!(lines[last].starts_with("# router R0: "));
+    assert_eq!(pre, first + 1);
+    assert!(last > pre);
+    assert!(!lines[post - 1].starts_with('#'));
+    assert!(lines[post + 1].is_empty());
+}
+
+#[test]
+fn temp_session_job_order() {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 2);
+    let r: [RouterId; 2] = [0.into(), 1.into()];
+    let e = net.add_external_router("E", AsId(100));
+    net.add_link(r[0], e);
+    let p = P::from(0);
+
+    // the neighbor is configured before the router, and the router must wait for the neighbor.
+    let cmd = unconditional(AtomicModifier::AddTempSession {
+        router: r[0],
+        neighbor: r[1],
+        raw: vec![],
+    });
+    let mut idx = 0;
+    let jobs = command_jobs(&net, &cmd, Some(p), &mut idx);
+    assert_eq!(idx, 2);
+    assert_eq!(
+        jobs,
+        vec![
+            ((r[1], Some(p), 1), None),
+            ((r[0], Some(p), 2), Some((r[1], Some(p), 1)))
+        ]
+    );
+
+    let configured = ConfiguredJobs::default();
+    let (first, _) = jobs[0];
+    let (_, second_after) = jobs[1];
+    assert!(configured.may_configure(None));
+    assert!(!configured.may_configure(second_after));
+    configured.insert(first);
+    assert!(configured.may_configure(second_after));
+
+    // the configured jobs are not shared with other executions.
+    assert!(!ConfiguredJobs::default().may_configure(second_after));
+
+    // external routers do not get any job.
+    let cmd = unconditional(AtomicModifier::AddTempSession {
+        router: r[0],
+        neighbor: e,
+        raw: vec![],
+    });
+    let jobs = command_jobs(&net, &cmd, None, &mut idx);
+    assert_eq!(jobs, vec![((r[0], None, 3), None)]);
+}
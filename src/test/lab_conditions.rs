@@ -0,0 +1,211 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test that conditions evaluated on the routes of the cisco lab match the simulated ones.
+
+use std::collections::{BTreeSet, HashMap};
+
+use atomic_command::{AtomicCondition, RibCond};
+use bgpsim::{
+    bgp::BgpRibEntry,
+    builder::{constant_link_weight, NetworkBuilder},
+    export::{Addressor, DefaultAddressor, DefaultAddressorBuilder},
+    prelude::*,
+};
+use cisco_lab::router::{BgpPathType, BgpRoute};
+use ipnet::Ipv4Net;
+use test_log::test;
+
+use crate::{
+    runtime::lab::{LabCondition, LabError},
+    P,
+};
+
+/// Address of `router` as seen from `from`, the same as used when translating conditions.
+fn addr(
+    net: &Network<P, BasicEventQueue<P>>,
+    addressor: &mut DefaultAddressor<'_, P, BasicEventQueue<P>>,
+    from: RouterId,
+    router: RouterId,
+) -> std::net::Ipv4Addr {
+    if net.get_device(router).is_internal() {
+        addressor.router_address(router).unwrap()
+    } else {
+        addressor.iface_address(router, from).unwrap()
+    }
+}
+
+/// Translate a RIB entry of the simulation into the route as it is parsed in the lab.
+fn lab_route(
+    net: &Network<P, BasicEventQueue<P>>,
+    addressor: &mut DefaultAddressor<'_, P, BasicEventQueue<P>>,
+    router: RouterId,
+    entry: &BgpRibEntry<P>,
+    selected: bool,
+) -> BgpRoute {
+    let neighbor = addr(net, addressor, router, entry.from_id);
+    BgpRoute {
+        net: match addressor.prefix(entry.route.prefix).unwrap() {
+            bgpsim::export::MaybePec::Single(net) => net,
+            bgpsim::export::MaybePec::Pec(_, _) => unreachable!(),
+        },
+        next_hop: addr(net, addressor, router, entry.route.next_hop),
+        med: entry.route.med,
+        local_pref: entry.route.local_pref,
+        weight: entry.weight,
+        igp_cost: entry
+            .igp_cost
+            .map(|x| x.into_inner() as u32)
+            .unwrap_or_default(),
+        path: entry.route.as_path.clone(),
+        communities: Default::default(),
+        neighbor,
+        neighbor_id: neighbor,
+        valid: true,
+        selected,
+        path_type: if entry.from_type == BgpSessionType::EBgp {
+            BgpPathType::External
+        } else {
+            BgpPathType::Internal
+        },
+    }
+}
+
+#[test]
+fn routes_less_preferred() {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 3);
+    let r = [0.into(), 1.into(), 2.into()];
+    net.build_external_routers(|_, _| vec![r[0], r[2]], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+    net.build_advertisements(p, |_, _| vec![vec![3.into()], vec![4.into()]], ())
+        .unwrap();
+
+    let mut addressor = DefaultAddressorBuilder::default().build(&net).unwrap();
+
+    let rib_in = net
+        .get_device(r[0])
+        .unwrap_internal()
+        .get_processed_bgp_rib()
+        .get(&p)
+        .cloned()
+        .unwrap();
+    assert!(rib_in.len() > 1);
+    let (best, _) = rib_in.iter().find(|(_, s)| *s).cloned().unwrap();
+    let (worse, _) = rib_in.iter().find(|(_, s)| !*s).cloned().unwrap();
+
+    let lab_net = match addressor.prefix(p).unwrap() {
+        bgpsim::export::MaybePec::Single(net) => net,
+        bgpsim::export::MaybePec::Pec(_, _) => unreachable!(),
+    };
+    let routes: HashMap<Ipv4Net, Vec<BgpRoute>> = HashMap::from([(
+        lab_net,
+        rib_in
+            .iter()
+            .map(|(e, s)| lab_route(&net, &mut addressor, r[0], e, *s))
+            .collect(),
+    )]);
+
+    // the selected route is most preferred, but a less preferred route is not.
+    for (good, route, exp) in [(&best, &best, true), (&worse, &worse, false)] {
+        let cond = AtomicCondition::RoutesLessPreferred {
+            router: r[0],
+            prefix: p,
+            good_neighbors: BTreeSet::from([good.from_id]),
+            route: route.clone(),
+        };
+        assert_eq!(cond.check(&net).unwrap(), exp);
+        let lab_cond =
+            LabCondition::translate(&cond, r[0], &net, &mut addressor, &HashMap::new()).unwrap();
+        assert_eq!(lab_cond.check_raw(&routes), Some(exp));
+    }
+
+    // without any routes, the condition is trivially satisfied.
+    let cond = AtomicCondition::RoutesLessPreferred {
+        router: r[0],
+        prefix: p,
+        good_neighbors: BTreeSet::new(),
+        route: best,
+    };
+    let lab_cond =
+        LabCondition::translate(&cond, r[0], &net, &mut addressor, &HashMap::new()).unwrap();
+    assert_eq!(lab_cond.check_raw(&HashMap::new()), Some(true));
+
+    // session conditions cannot be evaluated on routes.
+    let cond = AtomicCondition::BgpSessionEstablished {
+        router: r[0],
+        neighbor: r[1],
+    };
+    let lab_cond =
+        LabCondition::translate(&cond, r[0], &net, &mut addressor, &HashMap::new()).unwrap();
+    assert_eq!(lab_cond.check_raw(&routes), None);
+}
+
+#[test]
+fn selected_route_originator_id() {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 2);
+    let r: [RouterId; 2] = [0.into(), 1.into()];
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    let p = P::from(0);
+
+    let mut addressor = DefaultAddressorBuilder::default().build(&net).unwrap();
+
+    let cond = |originator_id| AtomicCondition::SelectedRoute {
+        router: r[0],
+        prefix: p,
+        neighbor: Some(r[1]),
+        weight: None,
+        next_hop: None,
+        originator_id,
+    };
+
+    // without an originator ID, the condition is translated.
+    let lab_cond =
+        LabCondition::translate(&cond(None), r[0], &net, &mut addressor, &HashMap::new()).unwrap();
+    assert!(matches!(lab_cond, LabCondition::SelectedRoute { .. }));
+
+    // the originator ID cannot be checked in the lab, so the condition must be rejected.
+    let cond = cond(Some(r[1]));
+    let result = LabCondition::translate(&cond, r[0], &net, &mut addressor, &HashMap::new());
+    assert!(matches!(result, Err(LabError::UnsupportedCondition(_))));
+}
+
+#[test]
+fn all_routes_match_unsupported() {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 2);
+    let r: [RouterId; 2] = [0.into(), 1.into()];
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    let p = P::from(0);
+
+    let mut addressor = DefaultAddressorBuilder::default().build(&net).unwrap();
+
+    let cond = AtomicCondition::AllRoutesMatch {
+        router: r[0],
+        prefix: p,
+        cond: RibCond::LearnedFrom(r[1]),
+    };
+    let result = LabCondition::translate(&cond, r[0], &net, &mut addressor, &HashMap::new());
+    assert!(matches!(result, Err(LabError::UnsupportedCondition(_))));
+}
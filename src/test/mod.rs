@@ -20,7 +20,17 @@
 mod abilene;
 #[cfg(feature = "experiment")]
 mod builder;
+mod compiler;
+#[cfg(feature = "export-web")]
+mod export_web;
+#[cfg(feature = "cisco-lab")]
+mod lab_commands;
+#[cfg(feature = "cisco-lab")]
+mod lab_conditions;
 mod route_reflection_dep;
+#[cfg(feature = "undo")]
+mod sim_runner;
 mod simple_no_dependencies;
 mod simple_route_reflection;
 mod single_fw_dependency;
+mod specification;
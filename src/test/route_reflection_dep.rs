@@ -17,15 +17,23 @@
 
 //! Test the system with a scenario that is simple and has no dependencies whatsoever.
 
+use std::{collections::HashMap, time::Duration};
+
 use bgpsim::{
     builder::{constant_link_weight, unique_preferences, NetworkBuilder},
     config::{ConfigExpr, ConfigModifier},
     prelude::*,
 };
+use good_lp::ResolutionError;
 use test_log::test;
 
 use crate::{
-    decomposition::decompose,
+    decomposition::{
+        bgp_dependencies::find_dependencies,
+        decompose, decompose_with_metrics, decompose_with_prefix_weights,
+        ilp_scheduler::{schedule_weighted, schedule_with_max_steps, ScheduleOptions},
+        min_temp_sessions, BudgetedDecomposition, CommandInfo,
+    },
     runtime::sim::run,
     specification::{Specification, SpecificationBuilder},
     P,
@@ -94,6 +102,117 @@ fn remove_session() {
     run(net, decomposition, &spec).unwrap();
 }
 
+/// Router 1 learns both the old and the new route from the route reflector `r3`, so it cannot
+/// migrate without a temporary session.
+#[test]
+fn remove_session_without_temp_sessions() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+
+    let (with_temp, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    let (schedule, _) = with_temp.unwrap();
+    assert!(schedule[&RouterId::from(1)].cost() > 0);
+
+    let options = ScheduleOptions {
+        allow_temp_sessions: false,
+        ..Default::default()
+    };
+    let (without_temp, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    assert!(matches!(without_temp, Err(ResolutionError::Infeasible)));
+}
+
+/// Router 1 learns both the old and the new route from the route reflector `r3`, so it must know
+/// the old route before `r3` loses it, and the new route only after `r3` learns it. Hence, router 1
+/// participates in at least three rounds. Limiting it to two rounds is infeasible, while three
+/// rounds are enough.
+#[test]
+fn remove_session_max_rounds_per_router() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    let r1 = RouterId::from(1);
+
+    let options = ScheduleOptions {
+        max_rounds_per_router: HashMap::from([(r1, 2)]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    assert!(matches!(result, Err(ResolutionError::Infeasible)));
+
+    let options = ScheduleOptions {
+        max_rounds_per_router: HashMap::from([(r1, 3)]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    let (schedule, _) = result.unwrap();
+    assert_eq!(schedule[&r1].new_route - schedule[&r1].old_route, 2);
+}
+
+/// Router 1 learns both routes from the route reflector `r3`, so `r_old[r1] < r_old[r3]` and
+/// `r_new[r1] > r_new[r3]`. Limiting router 1 to three rounds forces the schedule to work around it:
+/// `r3` must switch from the old to the new route in a single round, the old egress must learn the
+/// new route after `r3`, and the new egress must stop using the old route before `r3`.
+#[test]
+fn remove_session_max_rounds_schedules_around_router() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    let r1 = RouterId::from(1);
+    let r3 = RouterId::from(3);
+    let old_egress = r;
+    let new_egress = if r == RouterId::from(0) {
+        RouterId::from(2)
+    } else {
+        RouterId::from(0)
+    };
+
+    let options = ScheduleOptions {
+        max_rounds_per_router: HashMap::from([(r1, 3)]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    let (schedule, _) = result.unwrap();
+
+    // router 1 respects the limit
+    assert!(schedule[&r1].new_route - schedule[&r1].old_route < 3);
+    // the route reflector changes everything in a single round, without a temporary session.
+    let rr = schedule[&r3];
+    assert_eq!(rr.old_route, rr.new_route);
+    assert_eq!(rr.cost(), 0);
+    // router 1 knows the old route until before and the new route only after that round.
+    assert!(schedule[&r1].old_route < rr.old_route);
+    assert!(schedule[&r1].new_route > rr.new_route);
+    // both egress routers are scheduled around the route reflector.
+    assert!(schedule[&old_egress].new_route > rr.new_route);
+    assert!(schedule[&new_egress].old_route < rr.old_route);
+}
+
 #[test]
 fn add_session() {
     let (mut net, r, e, spec, _) = prepare();
@@ -168,3 +287,119 @@ fn add_session_2_prefixes() {
     let decomposition = decompose(&net, command, &spec).unwrap();
     run(net, decomposition, &spec).unwrap();
 }
+
+/// With the fewest steps (three), router 1 changes its forwarding in the round between losing the
+/// old route and learning the new one, so it needs two temporary sessions. With four steps, router
+/// 1 can change its forwarding while it still knows the old route, requiring only one temporary
+/// session. Hence, a heavy prefix uses fewer steps, while a light prefix uses fewer temporary
+/// sessions.
+#[test]
+fn remove_session_2_prefixes_weighted() {
+    let (net, r, e, spec, ps) = prepare_2_prefixes();
+    let (heavy, light) = (ps[0], ps[1]);
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let (_, unweighted) = decompose_with_metrics(&net, command.clone(), &spec).unwrap();
+
+    let weights = HashMap::from([(heavy, 1000.0), (light, 0.0)]);
+    let budget = Duration::from_secs(60);
+    let (decomposition, weighted) =
+        decompose_with_prefix_weights(&net, command, &spec, budget, &weights).unwrap();
+    let decomposition = match decomposition {
+        BudgetedDecomposition::Complete(d) => d,
+        BudgetedDecomposition::BestEffort(_, e) => panic!("Not all prefixes scheduled: {e}"),
+    };
+
+    assert_eq!(weighted.num_steps[&heavy], unweighted.num_steps[&heavy]);
+    assert!(weighted.num_steps[&heavy] < weighted.num_steps[&light]);
+    assert!(weighted.temp_sessions[&heavy] > weighted.temp_sessions[&light]);
+
+    run(net, decomposition, &spec).unwrap();
+}
+
+/// The weight of a prefix must be finite and not negative.
+#[test]
+fn schedule_weighted_invalid_weight() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let budget = Duration::from_secs(60);
+    for weight in [-1.0, f64::NAN, f64::INFINITY] {
+        let (result, _) =
+            schedule_weighted(&info, &bgp_deps, p, budget, weight, &Default::default());
+        assert!(matches!(result, Err(ResolutionError::Str(_))));
+    }
+}
+
+/// The weighted scheduler respects the schedule options. Router 1 needs a temporary session, so
+/// forbidding them makes the problem infeasible for any weight.
+#[test]
+fn schedule_weighted_options() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let budget = Duration::from_secs(60);
+    let options = ScheduleOptions {
+        allow_temp_sessions: false,
+        ..Default::default()
+    };
+    for weight in [0.0, 1.0, 100.0] {
+        let (result, _) = schedule_weighted(&info, &bgp_deps, p, budget, weight, &options);
+        assert!(matches!(result, Err(ResolutionError::Infeasible)));
+    }
+
+    let r1 = RouterId::from(1);
+    let options = ScheduleOptions {
+        max_rounds_per_router: HashMap::from([(r1, 3)]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_weighted(&info, &bgp_deps, p, budget, 0.0, &options);
+    let (schedule, _) = result.unwrap();
+    assert!(schedule[&r1].new_route - schedule[&r1].old_route < 3);
+}
+
+/// Router 1 learns both the old and the new route from the route reflector, so it must use a
+/// temporary session in any schedule.
+#[test]
+fn remove_session_min_temp_sessions() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let forced = min_temp_sessions(&net, command.clone(), &spec).unwrap();
+    assert!(forced[&p].contains(&RouterId::from(1)));
+
+    // the optimal schedule must use temporary sessions on all those routers.
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    let (schedule, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    let (schedule, _) = schedule.unwrap();
+    for router in &forced[&p] {
+        assert!(schedule[router].cost() > 0);
+    }
+}
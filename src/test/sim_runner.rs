@@ -0,0 +1,104 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the interactive runner of the simulated system, and undoing atomic modifiers.
+
+use atomic_command::AtomicModifier;
+use bgpsim::{
+    builder::{constant_link_weight, NetworkBuilder},
+    config::{ConfigExpr, ConfigModifier},
+    prelude::*,
+    types::LinkWeight,
+};
+use test_log::test;
+
+use crate::{runtime::sim::SimRunner, P};
+
+/// Triangle of `r0`, `r1`, and `r2` with an external router attached to both `r0` and `r2`. All
+/// routers prefer the route from `r0`, and `r1` initially forwards directly to `r0`.
+fn get_net() -> (Network<P, BasicEventQueue<P>>, [RouterId; 3], P) {
+    let mut net: Network<P, BasicEventQueue<P>> =
+        NetworkBuilder::build_complete_graph(BasicEventQueue::new(), 3);
+    let r = [0.into(), 1.into(), 2.into()];
+    net.build_external_routers(|_, _| vec![r[0], r[2]], ())
+        .unwrap();
+    net.build_link_weights(constant_link_weight, 1.0).unwrap();
+    net.build_ibgp_full_mesh().unwrap();
+    net.build_ebgp_sessions().unwrap();
+    let p = P::from(0);
+    net.build_advertisements(p, |_, _| vec![vec![3.into()], vec![4.into()]], ())
+        .unwrap();
+    (net, r, p)
+}
+
+/// Atomic modifier that changes the link weight from `a` to `b` from 1 to `w`.
+fn set_weight(a: RouterId, b: RouterId, w: LinkWeight) -> AtomicModifier<P> {
+    AtomicModifier::Raw(ConfigModifier::Update {
+        from: ConfigExpr::IgpLinkWeight {
+            source: a,
+            target: b,
+            weight: 1.0,
+        },
+        to: ConfigExpr::IgpLinkWeight {
+            source: a,
+            target: b,
+            weight: w,
+        },
+    })
+}
+
+#[test]
+fn undo() {
+    let (net, r, p) = get_net();
+    let cmd_a = set_weight(r[1], r[0], 10.0);
+    let cmd_b = set_weight(r[1], r[2], 10.0);
+
+    let mut runner = SimRunner::new(net.clone());
+    runner.apply(&cmd_a).unwrap();
+    let mut fw_a = runner.net().get_forwarding_state();
+    runner.apply(&cmd_b).unwrap();
+    assert_eq!(runner.num_applied(), 2);
+    let mut fw_b = runner.net().get_forwarding_state();
+    assert_ne!(
+        fw_a.get_paths(r[1], p).unwrap(),
+        fw_b.get_paths(r[1], p).unwrap()
+    );
+
+    // undo the second command
+    runner.undo().unwrap();
+    assert_eq!(runner.num_applied(), 1);
+    let mut fw = runner.net().get_forwarding_state();
+    for router in r {
+        assert_eq!(
+            fw.get_paths(router, p).unwrap(),
+            fw_a.get_paths(router, p).unwrap()
+        );
+    }
+
+    // undo the first command
+    runner.undo().unwrap();
+    assert_eq!(runner.num_applied(), 0);
+    let mut fw = runner.net().get_forwarding_state();
+    let mut fw_init = net.get_forwarding_state();
+    for router in r {
+        assert_eq!(
+            fw.get_paths(router, p).unwrap(),
+            fw_init.get_paths(router, p).unwrap()
+        );
+    }
+    assert!(runner.undo().is_err());
+}
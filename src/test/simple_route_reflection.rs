@@ -17,18 +17,26 @@
 
 //! Test the system with a scenario that is simple and has no dependencies whatsoever.
 
+use std::collections::HashMap;
+
 use bgpsim::{
     builder::{constant_link_weight, unique_preferences, NetworkBuilder},
-    config::{ConfigExpr, ConfigModifier},
+    config::{ConfigExpr, ConfigModifier, NetworkConfig},
     prelude::*,
 };
 use test_log::test;
 
 use crate::{
-    decomposition::decompose,
-    runtime::sim::run,
-    specification::{Specification, SpecificationBuilder},
-    P,
+    decomposition::{
+        bgp_dependencies::find_dependencies, decompose, decompose_with_metrics,
+        ilp_scheduler::enumerate_schedules, CommandInfo, EndpointMismatch,
+    },
+    runtime::{
+        controller::Phase,
+        sim::{naive_transient_states, resume_from, run, run_until},
+    },
+    specification::{Checker, Specification, SpecificationBuilder},
+    Decomposition, P,
 };
 
 fn get_net() -> Network<P, BasicEventQueue<P>> {
@@ -84,6 +92,133 @@ fn remove_session() {
     run(net, decomposition, &spec).unwrap();
 }
 
+#[test]
+fn fmt_plan() {
+    let (net, r, e, spec, _) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let decomposition = decompose(&net, command, &spec).unwrap();
+    let plan = decomposition.fmt_plan(&net);
+
+    let all_commands = decomposition
+        .setup_commands
+        .iter()
+        .chain(decomposition.atomic_before.values().flatten())
+        .chain(decomposition.main_commands.iter())
+        .chain(decomposition.atomic_after.values().flatten())
+        .chain(decomposition.cleanup_commands.iter())
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(plan.lines().count(), all_commands.len());
+    for c in all_commands {
+        assert!(plan.contains(&c.command.fmt(&net)));
+    }
+    assert!(plan.contains("Phase main, step 1: "));
+}
+
+#[cfg(feature = "latex-export")]
+#[test]
+fn to_tikz() {
+    let (net, r, e, spec, _) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let decomposition = decompose(&net, command, &spec).unwrap();
+    let tikz = decomposition.to_tikz(&net);
+
+    assert!(tikz.contains("\\begin{tikzpicture}"));
+    assert!(tikz.contains("\\end{tikzpicture}"));
+    let num_scheduled: usize = decomposition.schedule.values().map(|s| s.len()).sum();
+    assert_eq!(tikz.matches("\\fill[fw bar]").count(), num_scheduled);
+}
+
+#[test]
+fn metrics() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let (decomposition, metrics) = decompose_with_metrics(&net, command, &spec).unwrap();
+    assert!(metrics.solve_time.contains_key(&p));
+    // The border router without the session must switch last, after the route reflector, which
+    // must switch after the new border router. The remaining client learns both the old and the
+    // new route from the route reflector, and thus needs two temporary sessions.
+    assert_eq!(metrics.num_steps[&p], 3);
+    assert_eq!(metrics.temp_sessions[&p], 2);
+    assert_eq!(metrics.problem_size[&p].steps, 3);
+    assert!(metrics.problem_size[&p].rows > 0);
+    assert_eq!(decomposition.schedule[&p].len(), 4);
+
+    // The client changes its forwarding state strictly between the old and the new route, so it
+    // needs a temporary session to both the old and the new border router, and uses each once.
+    assert_eq!(decomposition.temp_session_count(), 4);
+    // The session is removed after the last round, so all three rounds happen before the main
+    // command. Setup, main command, and cleanup take one round each.
+    assert_eq!(decomposition.atomic_before[&p].len(), 3);
+    assert!(decomposition.atomic_after[&p].is_empty());
+    assert_eq!(decomposition.step_count(), 6);
+}
+
+#[test]
+fn pause_and_resume() {
+    let (net, r, e, spec, _) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+    let mut exp_net = net.clone();
+    exp_net.apply_modifier(&command).unwrap();
+
+    let decomposition = decompose(&net, command, &spec).unwrap();
+
+    // pause right before the main command
+    let (net, _) = run_until(net, decomposition.clone(), &spec, Phase::UpdateBefore).unwrap();
+    assert!(Checker::new(&spec).step(&mut net.get_forwarding_state()));
+    assert_ne!(net, exp_net);
+
+    let (net, _) = resume_from(net, decomposition, &spec, Phase::UpdateBefore).unwrap();
+    assert_eq!(net, exp_net);
+}
+
+#[test]
+fn verify_endpoints() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let decomposition = decompose(&net, command.clone(), &spec).unwrap();
+    decomposition.verify_endpoints(&net, &spec).unwrap();
+
+    // apply the original command during cleanup, and then drop that cleanup command.
+    let mut decomposition = Decomposition::baseline(command);
+    decomposition.cleanup_commands = std::mem::take(&mut decomposition.main_commands);
+    decomposition.verify_endpoints(&net, &spec).unwrap();
+    decomposition.cleanup_commands.clear();
+    assert!(matches!(
+        decomposition.verify_endpoints(&net, &spec),
+        Err(EndpointMismatch::ForwardingState(x, _)) if x == p
+    ));
+}
+
 #[test]
 fn add_session() {
     let (mut net, r, e, spec, _) = prepare();
@@ -158,3 +293,75 @@ fn add_session_2_prefixes() {
     let decomposition = decompose(&net, command, &spec).unwrap();
     run(net, decomposition, &spec).unwrap();
 }
+
+#[test]
+fn enumerate_distinct_schedules() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0) + 1;
+
+    let schedules = enumerate_schedules(&info, &bgp_deps, p, 5, max_steps);
+    assert!(schedules.len() >= 2);
+
+    let fw_rounds: Vec<HashMap<RouterId, usize>> = schedules
+        .iter()
+        .map(|(s, _)| s.iter().map(|(r, s)| (*r, s.fw_state)).collect())
+        .collect();
+    for (i, a) in fw_rounds.iter().enumerate() {
+        for b in fw_rounds.iter().skip(i + 1) {
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[test]
+fn naive_transient_states_violate_reachability() {
+    let (net, r, e, _, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let mut states = naive_transient_states(&net, &command, p).unwrap();
+    assert!(states.len() >= 2);
+
+    // the final state reaches the prefix from everywhere
+    let mut net_after = net.clone();
+    net_after.apply_modifier(&command).unwrap();
+    let mut fw_after = net_after.get_forwarding_state();
+    let routers = net.get_routers();
+    assert!(routers.iter().all(|x| fw_after.get_paths(*x, p).is_ok()));
+
+    // but at least one transient state violates reachability.
+    assert!(states
+        .iter_mut()
+        .any(|fw| routers.iter().any(|x| fw.get_paths(*x, p).is_err())));
+}
+
+#[test]
+fn naive_transient_states_not_converged() {
+    let (mut net, r, e, _, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    net.manual_simulation();
+    net.apply_modifier(&command).unwrap();
+    assert!(matches!(
+        naive_transient_states(&net, &command, p),
+        Err(NetworkError::QueueNotEmpty(_))
+    ));
+}
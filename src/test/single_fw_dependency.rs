@@ -21,13 +21,20 @@ use bgpsim::{
     builder::{constant_link_weight, NetworkBuilder},
     config::{ConfigExpr, ConfigModifier},
     prelude::*,
+    route_map::{RouteMapBuilder, RouteMapDirection::Incoming},
 };
+use good_lp::ResolutionError;
 use test_log::test;
 
 use crate::{
-    decomposition::decompose,
-    runtime::sim::run,
-    specification::{Specification, SpecificationBuilder},
+    decomposition::{
+        bgp_dependencies::find_dependencies,
+        decompose,
+        ilp_scheduler::{schedule_with_max_steps, NodeSchedule, ScheduleOptions},
+        CommandInfo,
+    },
+    runtime::sim::{compare, run},
+    specification::{Invariant, Property, SpecExpr, Specification, SpecificationBuilder},
     P,
 };
 
@@ -69,6 +76,43 @@ fn prepare() -> (
     (net, r, e, spec, p)
 }
 
+/// Prepare the network where only `e4` advertises the prefix. Removing the session between `r0`
+/// and `e4` turns every router into a black hole. Returns the network, the command, and the
+/// prefix.
+fn prepare_black_hole() -> (Network<P, BasicEventQueue<P>>, ConfigModifier<P>, P) {
+    let mut net = get_net();
+    let p = P::from(0);
+    net.build_advertisements(p, |_, _| vec![vec![4.into()]], ())
+        .unwrap();
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: 0.into(),
+        target: 4.into(),
+        session_type: BgpSessionType::EBgp,
+    });
+    (net, command, p)
+}
+
+/// Build the expression that requires `prop` on all internal routers.
+fn all_routers(net: &Network<P, BasicEventQueue<P>>, prop: Property) -> SpecExpr {
+    SpecExpr::All(
+        net.get_routers()
+            .into_iter()
+            .map(|router| {
+                SpecExpr::Invariant(Invariant {
+                    router,
+                    prop: prop.clone(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Build the specification that requires `prop` on all internal routers in every step.
+fn globally_all(net: &Network<P, BasicEventQueue<P>>, p: P, prop: Property) -> Specification {
+    let expr = SpecExpr::Globally(Box::new(all_routers(net, prop)));
+    [(p, expr)].into_iter().collect()
+}
+
 /// Clique with 4 nodes, and two external nodes, changing from the old to the new one.
 #[test]
 fn remove_session() {
@@ -84,6 +128,95 @@ fn remove_session() {
     run(net, decomposition, &spec).unwrap();
 }
 
+/// The naive migration causes `r0` to drop packets until it learns the route from `r2`, while the
+/// atomic migration never violates reachability.
+#[test]
+fn compare_remove_session() {
+    let (net, r, e, spec, _) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let decomposition = decompose(&net, command, &spec).unwrap();
+    let report = compare(&net, decomposition, &spec).unwrap();
+    assert_eq!(report.atomic_global_violations, 0);
+    assert!(report.naive_global_violations > 0);
+    assert_eq!(
+        report.avoided_global_violations(),
+        report.naive_global_violations as isize
+    );
+}
+
+/// Router 3 must switch before router 0 to avoid a forwarding loop, so only the order `[3, 0]` is
+/// feasible. An order with an unknown router is rejected.
+#[test]
+fn remove_session_fixed_order() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    let (r0, r3) = (RouterId::from(0), RouterId::from(3));
+
+    let options = ScheduleOptions {
+        fixed_order: Some(vec![r3, r0]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    let (schedule, _) = result.unwrap();
+    assert!(schedule[&r3].fw_state <= schedule[&r0].fw_state);
+
+    let options = ScheduleOptions {
+        fixed_order: Some(vec![r0, r3]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    assert!(matches!(result, Err(ResolutionError::Infeasible)));
+
+    // routers that do not exist are rejected.
+    let options = ScheduleOptions {
+        fixed_order: Some(vec![r3, RouterId::from(42), r0]),
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    assert!(matches!(result, Err(ResolutionError::Str(_))));
+}
+
+/// In a full mesh, all routers learn the new route directly from `r2`, and they only need to switch
+/// before `r0` loses the old route. Hence, the migration needs no temporary session.
+#[test]
+fn remove_session_without_temp_sessions() {
+    let (net, r, e, spec, p) = prepare();
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+
+    let options = ScheduleOptions {
+        allow_temp_sessions: false,
+        ..Default::default()
+    };
+    let (result, _) = schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &options);
+    let (schedule, _) = result.unwrap();
+    assert!(!schedule.is_empty());
+    assert_eq!(schedule.values().map(NodeSchedule::cost).sum::<usize>(), 0);
+}
+
 #[test]
 fn add_session() {
     let (mut net, r, e, spec, _) = prepare();
@@ -153,3 +286,123 @@ fn add_session_2_prefixes() {
     let decomposition = decompose(&net, command, &spec).unwrap();
     run(net, decomposition, &spec).unwrap();
 }
+
+/// Removing the last session turns all routers into black holes, which satisfy any bound on the
+/// path length (as in [`Property::check`]). The longest path initially is `r2 -> r1 -> r3 -> r0 ->
+/// e4`.
+#[test]
+fn remove_last_session_max_path_length() {
+    let (net, command, p) = prepare_black_hole();
+    let spec = globally_all(&net, p, Property::MaxPathLength(4));
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    assert!(max_steps > 0);
+
+    let (result, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    result.unwrap();
+}
+
+/// Removing the last session turns all routers into black holes, which are free of loops. The
+/// scheduler must not require reachability for loop freedom.
+#[test]
+fn remove_last_session_loop_free() {
+    let (net, command, p) = prepare_black_hole();
+    let spec = globally_all(&net, p, Property::LoopFree);
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    assert!(max_steps > 0);
+
+    let (result, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    result.unwrap();
+}
+
+/// Router 0 must not switch before router 3, as this would create a forwarding loop. The scheduler
+/// validates the resulting trace with the [`crate::specification::Checker`], which would panic if
+/// the schedule contained a loop.
+#[test]
+fn remove_session_loop_free() {
+    let (net, r, e, _, p) = prepare();
+    let spec = globally_all(&net, p, Property::LoopFree);
+
+    let command = ConfigModifier::Remove(ConfigExpr::BgpSession {
+        source: r,
+        target: e,
+        session_type: BgpSessionType::EBgp,
+    });
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    let (r0, r3) = (RouterId::from(0), RouterId::from(3));
+
+    let (result, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    let (schedule, _) = result.unwrap();
+    assert!(schedule[&r3].fw_state <= schedule[&r0].fw_state);
+}
+
+/// Removing the last session isolates all routers. The scheduler must handle routers that end up
+/// in a black hole without a next-hop.
+#[test]
+fn remove_last_session_isolation() {
+    let (net, command, p) = prepare_black_hole();
+    let expr = all_routers(&net, Property::Isolation);
+    let expr = SpecExpr::Finally(Box::new(SpecExpr::Globally(Box::new(expr))));
+    let spec: Specification = [(p, expr)].into_iter().collect();
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    assert!(max_steps > 0);
+
+    let (result, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    result.unwrap();
+}
+
+/// Router 1 drops the route from `r0`, and is therefore a black hole. Router 2 forwards its traffic
+/// towards `r0` via `r1`, and is thus isolated before removing the last session, and a black hole
+/// afterwards. Both routers must remain isolated during the entire migration.
+#[test]
+fn remove_last_session_globally_isolated() {
+    let (mut net, command, p) = prepare_black_hole();
+    let (r1, r2) = (RouterId::from(1), RouterId::from(2));
+    net.set_bgp_route_map(
+        r1,
+        0.into(),
+        Incoming,
+        RouteMapBuilder::new().order(10).deny().build(),
+    )
+    .unwrap();
+    assert!(matches!(
+        net.get_forwarding_state().get_paths(r2, p),
+        Err(NetworkError::ForwardingBlackHole(path)) if path == vec![r2, r1]
+    ));
+
+    let invariants = [r1, r2]
+        .into_iter()
+        .map(|router| {
+            SpecExpr::Invariant(Invariant {
+                router,
+                prop: Property::Isolation,
+            })
+        })
+        .collect();
+    let expr = SpecExpr::Globally(Box::new(SpecExpr::All(invariants)));
+    let spec: Specification = [(p, expr)].into_iter().collect();
+
+    let info = CommandInfo::new(&net, command, &spec).unwrap();
+    let bgp_deps = find_dependencies(&info);
+    let max_steps = info.fw_diff.get(&p).map(|x| x.len()).unwrap_or(0);
+    assert!(max_steps > 0);
+
+    let (result, _) =
+        schedule_with_max_steps(&info, &bgp_deps, p, max_steps, None, &Default::default());
+    result.unwrap();
+}
@@ -0,0 +1,470 @@
+// Chameleon: Taming the transient while reconfiguring BGP
+// Copyright (C) 2023 Tibor Schneider <sctibor@ethz.ch>
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Test the specification checker on handcrafted sequences of forwarding states.
+
+use bgpsim::{forwarding_state::ForwardingState, prelude::*, router::StaticRoute};
+use test_log::test;
+
+use crate::{
+    specification::{
+        Checker, Invariant, Property, SpecDiff, SpecExpr, Specification, SpecificationBuilder,
+        SpecificationExt,
+    },
+    P,
+};
+
+/// Ring network `r0 - r1 - r2 - r3 - r4 - r0`, with an external router attached to `r2`.
+/// Initially, `r0` reaches the prefix via `r1`. Returns the network, the internal routers, and the
+/// prefix.
+fn get_net() -> (Network<P, BasicEventQueue<P>>, [RouterId; 5], P) {
+    let mut net: Network<P, BasicEventQueue<P>> = Network::default();
+    let r = [
+        net.add_router("r0"),
+        net.add_router("r1"),
+        net.add_router("r2"),
+        net.add_router("r3"),
+        net.add_router("r4"),
+    ];
+    let e = net.add_external_router("e", AsId(100));
+    for (a, b, w) in [
+        (r[0], r[1], 1.0),
+        (r[1], r[2], 1.0),
+        (r[2], r[3], 1.0),
+        (r[3], r[4], 1.0),
+        (r[4], r[0], 1.0),
+    ] {
+        net.add_link(a, b);
+        net.set_link_weight(a, b, w).unwrap();
+        net.set_link_weight(b, a, w).unwrap();
+    }
+    net.add_link(r[2], e);
+    net.set_bgp_session(r[2], e, Some(BgpSessionType::EBgp))
+        .unwrap();
+    for a in r {
+        for b in r {
+            if a < b {
+                net.set_bgp_session(a, b, Some(BgpSessionType::IBgpPeer))
+                    .unwrap();
+            }
+        }
+    }
+    let p = P::from(0);
+    net.advertise_external_route(e, p, [100], None, None)
+        .unwrap();
+    (net, r, p)
+}
+
+/// Get the forwarding state after `r0` no longer forwards via `r1`, but via `r4` and `r3`.
+fn detour_fw_state(net: &Network<P, BasicEventQueue<P>>, r: [RouterId; 5]) -> ForwardingState<P> {
+    let mut net = net.clone();
+    net.set_link_weight(r[0], r[1], 100.0).unwrap();
+    net.set_link_weight(r[1], r[0], 100.0).unwrap();
+    net.get_forwarding_state()
+}
+
+/// Get a forwarding state in which `r1` forwards traffic back to `r0`, creating a forwarding
+/// loop.
+fn loop_fw_state(
+    net: &Network<P, BasicEventQueue<P>>,
+    r: [RouterId; 5],
+    p: P,
+) -> ForwardingState<P> {
+    let mut net = net.clone();
+    net.set_static_route(r[1], p, Some(StaticRoute::Direct(r[0])))
+        .unwrap();
+    net.get_forwarding_state()
+}
+
+#[test]
+fn waypoint() {
+    let (net, r, p) = get_net();
+    let spec: Specification = [(
+        p,
+        SpecExpr::Globally(Box::new(SpecExpr::All(vec![SpecExpr::Invariant(
+            Invariant {
+                router: r[0],
+                prop: Property::Waypoint(r[1]),
+            },
+        )]))),
+    )]
+    .into_iter()
+    .collect();
+
+    let mut fw_wp = net.get_forwarding_state();
+    let mut fw_detour = detour_fw_state(&net, r);
+
+    // schedule that always traverses the waypoint
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_wp));
+    assert!(checker.step(&mut fw_wp));
+    assert!(checker.check());
+
+    // schedule that skips the waypoint in the second step
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_wp));
+    assert!(!checker.step(&mut fw_detour));
+    assert!(!checker.step(&mut fw_wp));
+    assert!(!checker.check());
+}
+
+#[test]
+fn max_path_length() {
+    let (net, r, p) = get_net();
+    let spec: Specification = [(
+        p,
+        SpecExpr::Globally(Box::new(SpecExpr::All(vec![SpecExpr::Invariant(
+            Invariant {
+                router: r[0],
+                prop: Property::MaxPathLength(3),
+            },
+        )]))),
+    )]
+    .into_iter()
+    .collect();
+
+    let mut fw_short = net.get_forwarding_state();
+    let mut fw_detour = detour_fw_state(&net, r);
+
+    // r0 -> r1 -> r2 -> e
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_short));
+    assert!(checker.step(&mut fw_short));
+    assert!(checker.check());
+
+    // r0 -> r4 -> r3 -> r2 -> e
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_short));
+    assert!(!checker.step(&mut fw_detour));
+    assert!(!checker.check());
+}
+
+#[test]
+fn max_path_length_subprops() {
+    assert_eq!(
+        Property::MaxPathLength(2).get_subprops(),
+        [
+            Property::MaxPathLength(0),
+            Property::MaxPathLength(1),
+            Property::MaxPathLength(2)
+        ]
+        .into_iter()
+        .collect()
+    );
+}
+
+#[test]
+fn loop_free() {
+    let (net, r, p) = get_net();
+    let spec: Specification = [(
+        p,
+        SpecExpr::Globally(Box::new(SpecExpr::All(vec![SpecExpr::Invariant(
+            Invariant {
+                router: r[0],
+                prop: Property::LoopFree,
+            },
+        )]))),
+    )]
+    .into_iter()
+    .collect();
+
+    let mut fw_ok = net.get_forwarding_state();
+    let mut fw_detour = detour_fw_state(&net, r);
+    let mut fw_loop = loop_fw_state(&net, r, p);
+    assert!(fw_loop.get_paths(r[0], p).is_err());
+
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_ok));
+    assert!(checker.step(&mut fw_detour));
+    assert!(checker.check());
+
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_ok));
+    assert!(!checker.step(&mut fw_loop));
+    assert!(!checker.step(&mut fw_detour));
+    assert!(!checker.check());
+}
+
+#[test]
+fn loop_free_allows_black_hole() {
+    assert!(Property::LoopFree.check(&[0.into(), 1.into()], false));
+    assert!(!Property::LoopFree.check(&[0.into(), 1.into(), 0.into()], false));
+}
+
+/// Get a forwarding state in which `r1` drops all traffic, creating a black hole for `r0`.
+fn black_hole_fw_state(
+    net: &Network<P, BasicEventQueue<P>>,
+    r: [RouterId; 5],
+    p: P,
+) -> ForwardingState<P> {
+    let mut net = net.clone();
+    net.set_static_route(r[1], p, Some(StaticRoute::Drop))
+        .unwrap();
+    net.get_forwarding_state()
+}
+
+#[test]
+fn isolation() {
+    let (net, r, p) = get_net();
+    let spec: Specification = [(
+        p,
+        SpecExpr::Globally(Box::new(SpecExpr::All(vec![SpecExpr::Invariant(
+            Invariant {
+                router: r[0],
+                prop: Property::Isolation,
+            },
+        )]))),
+    )]
+    .into_iter()
+    .collect();
+
+    let mut fw_reach = net.get_forwarding_state();
+    let mut fw_black_hole = black_hole_fw_state(&net, r, p);
+    let mut fw_loop = loop_fw_state(&net, r, p);
+
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_black_hole));
+    assert!(checker.step(&mut fw_loop));
+    assert!(checker.check());
+
+    // a transient state in which `r0` can reach the prefix violates the isolation.
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_black_hole));
+    assert!(!checker.step(&mut fw_reach));
+    assert!(!checker.step(&mut fw_black_hole));
+    assert!(!checker.check());
+}
+
+#[test]
+fn isolation_subprops() {
+    assert!(Property::Isolation.check(&[0.into(), 1.into()], false));
+    assert!(!Property::Isolation.check(&[0.into(), 1.into()], true));
+    assert_eq!(
+        Property::Isolation.get_subprops(),
+        [Property::Isolation, Property::Reachability]
+            .into_iter()
+            .collect()
+    );
+}
+
+#[test]
+fn build_per_prefix() {
+    let (mut net, _, p0) = get_net();
+    let p1 = P::from(1);
+    let e = net.get_router_id("e").unwrap();
+    net.advertise_external_route(e, p1, [100], None, None)
+        .unwrap();
+
+    let spec = SpecificationBuilder::build_per_prefix(
+        &net,
+        None,
+        [
+            (p0, SpecificationBuilder::Reachability),
+            (p1, SpecificationBuilder::EgressWaypoint),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    let reach = SpecificationBuilder::Reachability.build_all(&net, None, [p0, p1]);
+    let egress = SpecificationBuilder::EgressWaypoint.build_all(&net, None, [p0, p1]);
+
+    assert_eq!(spec.len(), 2);
+    assert_eq!(spec[&p0], reach[&p0]);
+    assert_eq!(spec[&p1], egress[&p1]);
+    assert_ne!(spec[&p1], reach[&p1]);
+}
+
+#[test]
+fn merge_and_intersect() {
+    let (mut net, r, p0) = get_net();
+    let p1 = P::from(1);
+    let e = net.get_router_id("e").unwrap();
+    net.advertise_external_route(e, p1, [100], None, None)
+        .unwrap();
+
+    let spec_0 = SpecificationBuilder::Reachability.build_all(&net, None, [p0]);
+    let spec_1 = SpecificationBuilder::Reachability.build_all(&net, None, [p1]);
+    let spec_both = SpecificationBuilder::Reachability.build_all(&net, None, [p0, p1]);
+
+    // disjoint prefixes
+    let merged = spec_0.clone().merge(spec_1.clone());
+    assert_eq!(merged, spec_both);
+    assert!(spec_0.clone().intersect(spec_1).is_empty());
+
+    // same prefix
+    let wp: Specification = [(
+        p0,
+        SpecExpr::Globally(Box::new(SpecExpr::Invariant(Invariant {
+            router: r[0],
+            prop: Property::Waypoint(r[1]),
+        }))),
+    )]
+    .into_iter()
+    .collect();
+    let merged = spec_0.clone().merge(wp.clone());
+    let intersected = spec_0.clone().intersect(wp.clone());
+    assert_eq!(
+        merged[&p0],
+        SpecExpr::All(vec![spec_0[&p0].clone(), wp[&p0].clone()])
+    );
+    assert_eq!(
+        intersected[&p0],
+        SpecExpr::Any(vec![spec_0[&p0].clone(), wp[&p0].clone()])
+    );
+
+    // the detour is reachable, but skips the waypoint.
+    let mut fw_ok = net.get_forwarding_state();
+    let mut fw_detour = detour_fw_state(&net, r);
+
+    let mut checker = Checker::new(&merged);
+    assert!(checker.step(&mut fw_ok));
+    assert!(!checker.step(&mut fw_detour));
+    assert!(!checker.check());
+
+    let mut checker = Checker::new(&intersected);
+    assert!(checker.step(&mut fw_ok));
+    assert!(checker.step(&mut fw_detour));
+    assert!(checker.check());
+}
+
+#[test]
+fn diff() {
+    let (net, r, p) = get_net();
+    let reach = SpecificationBuilder::Reachability.build_all(&net, None, [p]);
+    let wp = |router: RouterId| Invariant {
+        router: r[0],
+        prop: Property::Waypoint(router),
+    };
+    let with_wp = |router: RouterId| {
+        let expr = SpecExpr::Globally(Box::new(SpecExpr::Invariant(wp(router))));
+        reach.clone().merge([(p, expr)].into())
+    };
+
+    assert!(reach.diff(&reach).is_empty());
+
+    // adding a waypoint property
+    let spec = with_wp(r[1]);
+    let diff = reach.diff(&spec);
+    assert_eq!(diff.added, [(p, [wp(r[1])].into())].into());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.changed, [p].into());
+    assert_eq!(spec.diff(&reach).removed, diff.added);
+
+    // changing the waypoint
+    let diff = spec.diff(&with_wp(r[4]));
+    assert_eq!(
+        diff,
+        SpecDiff {
+            added: [(p, [wp(r[4])].into())].into(),
+            removed: [(p, [wp(r[1])].into())].into(),
+            changed: [p].into(),
+        }
+    );
+
+    // changing only the modal operator
+    let invariant = SpecExpr::Invariant(wp(r[1]));
+    let finally: Specification = [(p, SpecExpr::Finally(Box::new(invariant.clone())))].into();
+    let globally: Specification = [(p, SpecExpr::Globally(Box::new(invariant)))].into();
+    let diff = finally.diff(&globally);
+    assert!(!diff.is_empty());
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.changed, [p].into());
+}
+
+#[test]
+fn fork() {
+    let (net, r, p) = get_net();
+    let spec: Specification = [(
+        p,
+        SpecExpr::Globally(Box::new(SpecExpr::Invariant(Invariant {
+            router: r[0],
+            prop: Property::Waypoint(r[1]),
+        }))),
+    )]
+    .into_iter()
+    .collect();
+
+    let mut fw_wp = net.get_forwarding_state();
+    let mut fw_detour = detour_fw_state(&net, r);
+
+    let mut checker = Checker::new(&spec);
+    assert!(checker.step(&mut fw_wp));
+
+    let mut forked = checker.fork();
+    assert!(!forked.step(&mut fw_detour));
+    assert!(!forked.check());
+
+    // the original checker is not affected by the fork
+    assert!(checker.check());
+    assert!(checker.step(&mut fw_wp));
+    assert!(checker.check());
+
+    // the fork is not affected by the original checker
+    assert!(!forked.step(&mut fw_wp));
+    assert!(!forked.check());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_roundtrip() {
+    let (net, r, p) = get_net();
+    let mut spec = SpecificationBuilder::Reachability.build_all(&net, None, [p]);
+    spec.insert(
+        P::from(1),
+        SpecExpr::Globally(Box::new(SpecExpr::Invariant(Invariant {
+            router: r[0],
+            prop: Property::Any(vec![Property::Waypoint(r[1]), Property::Waypoint(r[4])]),
+        }))),
+    );
+
+    let json = spec.to_json().unwrap();
+    assert!(json.contains(r#""type":"Waypoint""#));
+    assert!(json.contains(r#""type":"Reachability""#));
+    assert_eq!(Specification::from_json(&json).unwrap(), spec);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_legacy_format() {
+    let expected = SpecExpr::All(vec![
+        SpecExpr::True,
+        SpecExpr::Until(
+            Box::new(SpecExpr::Invariant(Invariant {
+                router: 0.into(),
+                prop: Property::Reachability,
+            })),
+            Box::new(SpecExpr::Globally(Box::new(SpecExpr::Invariant(
+                Invariant {
+                    router: 1.into(),
+                    prop: Property::All(vec![Property::Waypoint(2.into()), Property::LoopFree]),
+                },
+            )))),
+        ),
+    ]);
+    let legacy = r#"{"All":["True",{"Until":[
+        {"Invariant":{"router":0,"prop":"Reachability"}},
+        {"Globally":{"Invariant":{"router":1,"prop":{"All":[{"Waypoint":2},"LoopFree"]}}}}
+    ]}]}"#;
+    let parsed: SpecExpr = serde_json::from_str(legacy).unwrap();
+    assert_eq!(parsed, expected);
+
+    // the current format is still accepted
+    let json = serde_json::to_string(&expected).unwrap();
+    assert!(json.contains(r#""type":"Until""#));
+    assert_eq!(serde_json::from_str::<SpecExpr>(&json).unwrap(), expected);
+}